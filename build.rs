@@ -0,0 +1,57 @@
+//! Script de build: captura metadados de reprodutibilidade
+//! (commit git, target e versões das dependências principais).
+
+use std::env;
+use std::fs;
+use std::process::Command;
+
+fn main() {
+    let git_sha = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+        .unwrap_or_else(|| "desconhecido".to_string());
+
+    let target = env::var("TARGET").unwrap_or_else(|_| "desconhecido".to_string());
+
+    println!("cargo:rustc-env=GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=BUILD_TARGET={}", target);
+    println!(
+        "cargo:rustc-env=REQWEST_VERSION={}",
+        locked_version("reqwest").unwrap_or_else(|| "desconhecida".to_string())
+    );
+
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+}
+
+/// Busca a versão resolvida de uma dependência direta no `Cargo.lock`.
+///
+/// Quando há múltiplas versões do pacote, o lock registra `"nome versão"`
+/// na lista de dependências do pacote raiz; caso contrário, a versão é única.
+fn locked_version(package: &str) -> Option<String> {
+    let lock = fs::read_to_string("Cargo.lock").ok()?;
+    let root = env::var("CARGO_PKG_NAME").ok()?;
+
+    let blocks: Vec<&str> = lock.split("[[package]]").collect();
+    let name_line = |name: &str| format!("name = \"{}\"", name);
+
+    let root_block = blocks
+        .iter()
+        .find(|b| b.lines().any(|l| l.trim() == name_line(&root)))?;
+
+    let pinned = format!("\"{} ", package);
+    if let Some(entry) = root_block.lines().find(|l| l.trim().starts_with(&pinned)) {
+        return Some(entry.trim().trim_matches([',', '"']).split(' ').nth(1)?.to_string());
+    }
+
+    blocks
+        .iter()
+        .find(|b| b.lines().any(|l| l.trim() == name_line(package)))?
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("version = "))
+        .map(|v| v.trim_matches('"').to_string())
+}
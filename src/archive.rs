@@ -0,0 +1,195 @@
+//! # Compressão Pós-Escrita
+//!
+//! Comprime o arquivo final inteiro (ex: `dados.parquet` → `dados.parquet.xz`)
+//! para destinos de arquivamento, além dos codecs internos do Parquet/Avro.
+//!
+//! ## Memória
+//! Todos os codecs operam em streaming (`std::io::copy`): o arquivo nunca é
+//! carregado por inteiro. O `xz` é delegado ao binário do sistema, alimentado
+//! diretamente pelo arquivo de origem.
+//!
+//! ## Leitura
+//! Ferramentas que leem saídas existentes (`diff`) aceitam os arquivos
+//! comprimidos, detectados pela extensão (`.xz`, `.br`, `.gz`). O Parquet
+//! exige acesso aleatório ao rodapé, então o conteúdo é descomprimido em memória.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+use crate::atomic;
+use crate::errors::ProcessorError;
+use crate::models::PostCompress;
+
+/// Qualidade do brotli (0-11); 9 equilibra taxa e tempo para arquivamento.
+const BROTLI_QUALITY: u32 = 9;
+
+/// Janela do brotli (log2 bytes).
+const BROTLI_WINDOW: u32 = 22;
+
+/// Caminho do arquivo comprimido (`{original}.{extensão}`).
+pub fn compressed_path(path: &Path, codec: PostCompress) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(codec.extension());
+    PathBuf::from(name)
+}
+
+/// Codec de um arquivo comprimido por `post_compress`, pela extensão.
+pub fn codec_of(path: &Path) -> Option<PostCompress> {
+    let extension = path.extension()?.to_str()?;
+    [PostCompress::Xz, PostCompress::Brotli, PostCompress::Gzip]
+        .into_iter()
+        .find(|codec| codec.extension() == extension)
+}
+
+/// Conteúdo descomprimido de `path`, gravado com `codec`.
+pub fn decompress_file(path: &Path, codec: PostCompress) -> Result<Vec<u8>, ProcessorError> {
+    let input = File::open(path).map_err(ProcessorError::Io)?;
+    let mut content = Vec::new();
+
+    match codec {
+        PostCompress::Gzip => {
+            GzDecoder::new(BufReader::new(input))
+                .read_to_end(&mut content)
+                .map_err(ProcessorError::Io)?;
+        }
+        PostCompress::Brotli => {
+            brotli::Decompressor::new(input, 64 * 1024)
+                .read_to_end(&mut content)
+                .map_err(ProcessorError::Io)?;
+        }
+        PostCompress::Xz => {
+            let output = Command::new("xz")
+                .args(["--decompress", "--stdout"])
+                .stdin(Stdio::from(input))
+                .stderr(Stdio::inherit())
+                .output()
+                .map_err(|e| {
+                    ProcessorError::Io(io::Error::new(
+                        e.kind(),
+                        format!("binário 'xz' indisponível: {}", e),
+                    ))
+                })?;
+
+            if !output.status.success() {
+                return Err(ProcessorError::Io(io::Error::other(format!(
+                    "xz terminou com {}",
+                    output.status
+                ))));
+            }
+            content = output.stdout;
+        }
+    }
+    Ok(content)
+}
+
+/// Comprime `path` com `codec`, removendo o original se `keep_original` for falso.
+///
+/// # Returns
+/// Caminho do arquivo comprimido.
+///
+/// # Erros
+/// Em caso de falha, o arquivo comprimido parcial é descartado e o original preservado.
+pub fn compress_file(
+    path: &Path,
+    codec: PostCompress,
+    keep_original: bool,
+) -> Result<PathBuf, ProcessorError> {
+    let target = compressed_path(path, codec);
+    atomic::write(&target, |output| write_compressed(path, output, codec))?;
+
+    if !keep_original {
+        fs::remove_file(path).map_err(ProcessorError::Io)?;
+    }
+    Ok(target)
+}
+
+/// Copia `source` para `output` através do encoder do codec.
+fn write_compressed(source: &Path, output: &mut File, codec: PostCompress) -> Result<(), ProcessorError> {
+    let input = File::open(source).map_err(ProcessorError::Io)?;
+
+    match codec {
+        PostCompress::Gzip => {
+            let mut encoder = GzEncoder::new(BufWriter::new(output), flate2::Compression::default());
+            io::copy(&mut BufReader::new(input), &mut encoder).map_err(ProcessorError::Io)?;
+            encoder
+                .finish()
+                .and_then(|mut w| w.flush())
+                .map_err(ProcessorError::Io)?;
+        }
+        PostCompress::Brotli => {
+            let mut encoder = brotli::CompressorWriter::new(
+                BufWriter::new(output),
+                64 * 1024,
+                BROTLI_QUALITY,
+                BROTLI_WINDOW,
+            );
+            io::copy(&mut BufReader::new(input), &mut encoder).map_err(ProcessorError::Io)?;
+            encoder
+                .into_inner()
+                .flush()
+                .map_err(ProcessorError::Io)?;
+        }
+        PostCompress::Xz => {
+            let status = Command::new("xz")
+                .args(["--compress", "--stdout"])
+                .stdin(Stdio::from(input))
+                .stdout(Stdio::from(output.try_clone().map_err(ProcessorError::Io)?))
+                .status()
+                .map_err(|e| {
+                    ProcessorError::Io(io::Error::new(
+                        e.kind(),
+                        format!("binário 'xz' indisponível: {}", e),
+                    ))
+                })?;
+
+            if !status.success() {
+                return Err(ProcessorError::Io(io::Error::other(format!(
+                    "xz terminou com {}",
+                    status
+                ))));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    #[test]
+    fn compressed_parquet_reads_back_after_decompression() {
+        let dir = std::env::temp_dir().join(format!("data_gov_archive_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut dataframe = df!("id" => [1i64, 2, 3], "nome" => [Some("Ana"), None, Some("Caio")]).unwrap();
+
+        let mut codecs = vec![PostCompress::Gzip, PostCompress::Brotli];
+        // O xz depende do binário do sistema
+        if Command::new("xz").arg("--version").output().is_ok() {
+            codecs.push(PostCompress::Xz);
+        }
+        for codec in codecs {
+            let path = dir.join("pedidos.parquet");
+            ParquetWriter::new(File::create(&path).unwrap()).finish(&mut dataframe).unwrap();
+
+            let target = compress_file(&path, codec, false).unwrap();
+            assert_eq!(target, dir.join(format!("pedidos.parquet.{}", codec.extension())));
+            assert!(!path.exists());
+            assert_eq!(codec_of(&target), Some(codec));
+
+            let content = decompress_file(&target, codec).unwrap();
+            let read = ParquetReader::new(io::Cursor::new(content)).finish().unwrap();
+            assert_eq!(read, dataframe, "{codec:?}");
+            fs::remove_file(&target).unwrap();
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+//! # Escrita Atômica
+//!
+//! Arquivos finais (Parquet/Avro, comprimidos e manifestos) são gravados em
+//! `{arquivo}.tmp` e renomeados para o destino apenas após o sucesso. Leitores
+//! nunca observam um arquivo parcial: se o processo morrer no meio da escrita,
+//! resta no máximo o `.tmp`.
+//!
+//! ## Novas Tentativas
+//! Falhas transitórias do sistema de arquivos (ex: NFS) podem ser repetidas
+//! com `Retry` (`[pipeline] write_retries`). Apenas erros de I/O são repetidos:
+//! erros de schema e de serialização falhariam de novo. Como `write` remove o
+//! `.tmp` ao falhar, cada tentativa recomeça do zero.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::errors::ProcessorError;
+use crate::open_files;
+use crate::warnings::{self, EndpointWarnings};
+
+/// Espera padrão entre tentativas de gravação.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Política de novas tentativas de uma gravação (ver módulo).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    /// Tentativas além da primeira.
+    pub retries: u32,
+    /// Espera antes de cada nova tentativa.
+    pub delay: Duration,
+}
+
+impl Default for Retry {
+    /// Sem novas tentativas.
+    fn default() -> Self {
+        Retry {
+            retries: 0,
+            delay: DEFAULT_RETRY_DELAY,
+        }
+    }
+}
+
+impl Retry {
+    /// Executa `write` até o sucesso, um erro que não seja de I/O ou o fim das tentativas.
+    ///
+    /// Cada nova tentativa gera um aviso em `warnings`.
+    ///
+    /// # Erros
+    /// O erro da última tentativa.
+    pub fn run<T, F>(self, path: &Path, warnings: Option<&EndpointWarnings>, mut write: F) -> Result<T, ProcessorError>
+    where
+        F: FnMut() -> Result<T, ProcessorError>,
+    {
+        let mut attempt = 0;
+        loop {
+            match write() {
+                Err(ProcessorError::Io(e)) if attempt < self.retries => {
+                    attempt += 1;
+                    warnings::warn(
+                        warnings,
+                        format!(
+                            "Aviso: falha de I/O ao gravar '{}': {}; nova tentativa ({}/{}) em {:.2?}",
+                            path.display(),
+                            e,
+                            attempt,
+                            self.retries,
+                            self.delay
+                        ),
+                    );
+                    thread::sleep(self.delay);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Caminho temporário usado durante a escrita de `path`.
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+/// Grava `path` via `write` no arquivo temporário e o publica com `rename`.
+///
+/// # Erros
+/// Em caso de falha, o temporário é removido e `path` permanece intocado.
+pub fn write<T, F>(path: &Path, write: F) -> Result<T, ProcessorError>
+where
+    F: FnOnce(&mut File) -> Result<T, ProcessorError>,
+{
+    let tmp = temp_path(path);
+    let _permit = open_files::acquire();
+    let result = File::create(&tmp)
+        .map_err(ProcessorError::Io)
+        .and_then(|mut file| {
+            let value = write(&mut file)?;
+            // Garante os bytes em disco antes de tornar o arquivo visível
+            file.sync_all().map_err(ProcessorError::Io)?;
+            Ok(value)
+        });
+
+    match result {
+        Ok(value) => {
+            commit(&tmp, path)?;
+            Ok(value)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// Publica um temporário já completo (`{arquivo}.tmp`) em seu destino.
+pub fn commit(tmp: &Path, path: &Path) -> Result<(), ProcessorError> {
+    fs::rename(tmp, path).map_err(|e| {
+        let _ = fs::remove_file(tmp);
+        ProcessorError::Io(e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("data_gov_atomic_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn failed_write_leaves_no_output_or_temp_file() {
+        let dir = temp_dir("failed_write");
+        let path = dir.join("pedidos.parquet");
+
+        let result: Result<(), _> = write(&path, |file| {
+            file.write_all(b"PAR1 parcial").map_err(ProcessorError::Io)?;
+            Err(ProcessorError::Parquet("falha no meio da escrita".to_string()))
+        });
+
+        assert!(matches!(result, Err(ProcessorError::Parquet(_))));
+        assert!(!path.exists());
+        assert!(!temp_path(&path).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_write_keeps_the_previous_output() {
+        let dir = temp_dir("keeps_previous");
+        let path = dir.join("pedidos.parquet");
+        write(&path, |file| file.write_all(b"versao 1").map_err(ProcessorError::Io)).unwrap();
+
+        let result: Result<(), _> = write(&path, |_| Err(ProcessorError::Io(std::io::Error::other("disco cheio"))));
+
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"versao 1");
+        assert!(!temp_path(&path).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn retry(retries: u32) -> Retry {
+        Retry {
+            retries,
+            delay: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn io_errors_are_retried_until_success() {
+        let warnings = crate::warnings::Warnings::default();
+        let endpoint = warnings.endpoint("compras.itens.pedidos".to_string(), crate::errors::Stage::Conversion);
+        let mut calls = 0;
+
+        let result = retry(2).run(Path::new("pedidos.parquet"), Some(&endpoint), || {
+            calls += 1;
+            match calls {
+                1 => Err(ProcessorError::Io(std::io::Error::other("NFS indisponível"))),
+                _ => Ok(calls),
+            }
+        });
+
+        assert_eq!(result.unwrap(), 2);
+        let recorded = warnings.take();
+        assert_eq!(recorded.len(), 1);
+        assert!(recorded[0].message.contains("nova tentativa (1/2)"), "{}", recorded[0].message);
+    }
+
+    #[test]
+    fn io_errors_stop_after_the_last_retry() {
+        let mut calls = 0;
+        let result: Result<(), _> = retry(2).run(Path::new("pedidos.parquet"), None, || {
+            calls += 1;
+            Err(ProcessorError::Io(std::io::Error::other("NFS indisponível")))
+        });
+
+        assert!(matches!(result, Err(ProcessorError::Io(_))));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn other_errors_are_not_retried() {
+        let mut calls = 0;
+        let result: Result<(), _> = retry(3).run(Path::new("pedidos.parquet"), None, || {
+            calls += 1;
+            Err(ProcessorError::Schema("coluna ausente".to_string()))
+        });
+
+        assert!(matches!(result, Err(ProcessorError::Schema(_))));
+        assert_eq!(calls, 1);
+    }
+}
@@ -0,0 +1,450 @@
+//! # Conversão em Lotes (Memória Limitada)
+//!
+//! Para endpoints com milhões de registros, montar um único DataFrame antes
+//! da escrita torna o pico de memória proporcional ao tamanho total.
+//!
+//! ## Estratégia
+//! - O envelope JSON é percorrido em streaming (`serde_json::Deserializer`),
+//!   sem materializar o documento inteiro.
+//! - Os registros sob `root_path` são acumulados em lotes de `batch_size`.
+//! - Cada lote vira um row group do mesmo arquivo Parquet (`BatchedWriter`),
+//!   resultando em um único dataset lógico.
+//!
+//! O schema é inferido no primeiro lote e imposto aos seguintes; campos do
+//! envelope fora de `root_path` são descartados neste modo.
+//!
+//! No modo `--sample`, a leitura é interrompida assim que N registros foram
+//! coletados, sem percorrer o restante do arquivo.
+//!
+//! Rotas paginadas entregam um arquivo por página: todos são percorridos em
+//! ordem pelo mesmo writer, formando um único Parquet.
+
+use polars::io::parquet::write::BatchedWriter;
+use polars::prelude::*;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use serde_json::Value;
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::atomic;
+use crate::errors::ProcessorError;
+use crate::models::EndpointGroup;
+use crate::open_files;
+use crate::processor::{self, CoercionReport, ConvertOptions, ConvertStats, StageTimings};
+
+/// Converte o JSON em lotes, gravando cada lote como row group.
+///
+/// # Returns
+/// Total de registros e colunas (fixadas pelo primeiro lote) gravados.
+pub fn process_json_in_batches(
+    inputs: &[PathBuf],
+    parquet_path: &Path,
+    group: &EndpointGroup,
+    batch_size: NonZeroUsize,
+    options: ConvertOptions,
+) -> Result<ConvertStats, ProcessorError> {
+    let mut sink = BatchSink {
+        batch_size: batch_size.get(),
+        buffer: Vec::with_capacity(batch_size.get()),
+        group,
+        parquet_path,
+        options,
+        state: None,
+        total: 0,
+        coercions: CoercionReport::new(),
+        nullable: BTreeSet::new(),
+        declared: None,
+        status: None,
+        status_message: None,
+        status_seen: false,
+        error: None,
+        sampled: false,
+        timings: StageTimings::default(),
+    };
+
+    // Row groups vão para `.tmp`; o destino só aparece após o `finish`
+    let tmp_path = atomic::temp_path(parquet_path);
+
+    // Páginas alimentam o mesmo writer, em ordem
+    for path in inputs {
+        if let Err(err) = extract_file(path, &mut sink) {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(err);
+        }
+        if sink.sampled {
+            break;
+        }
+    }
+
+    if let Err(err) = sink.flush() {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(err);
+    }
+    let reconciliation = match processor::reconcile(group, options, sink.declared, sink.total) {
+        Ok(reconciliation) => reconciliation,
+        Err(err) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(err);
+        }
+    };
+
+    match sink.state {
+        Some(state) => {
+            let timer = Instant::now();
+            if let Err(e) = state.writer.finish() {
+                let _ = std::fs::remove_file(&tmp_path);
+                return Err(ProcessorError::Parquet(format!("Erro ao gravar Parquet: {}", e)));
+            }
+            if group.verify_after_write
+                && let Err(e) = processor::verify_written(&tmp_path, group.output_format, sink.total)
+            {
+                let _ = std::fs::remove_file(&tmp_path);
+                return Err(e);
+            }
+            atomic::commit(&tmp_path, parquet_path)?;
+            sink.timings.write += timer.elapsed();
+            Ok(ConvertStats {
+                rows: sink.total,
+                cols: state.schema.len(),
+                coercions: sink.coercions,
+                schema: state.schema,
+                nullable: sink.nullable,
+                reconciliation,
+                // `report_cardinality` e variantes de compressão não são aceitos em lotes
+                cardinality: Default::default(),
+                variants: Vec::new(),
+                timings: sink.timings,
+                skipped: None,
+            })
+        }
+        None => processor::empty_output(parquet_path, group, options, reconciliation),
+    }
+}
+
+/// Percorre um arquivo de staging, entregando seus registros ao `sink`.
+///
+/// # Erros
+/// JSON malformado, falha de um lote ou status de negócio inesperado.
+fn extract_file(path: &Path, sink: &mut BatchSink) -> Result<(), ProcessorError> {
+    let file = File::open(path).map_err(ProcessorError::Io)?;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+    let group = sink.group;
+
+    sink.status = None;
+    sink.status_message = None;
+    sink.status_seen = false;
+
+    let extraction = Envelope {
+        root_path: group.root_path.as_deref().filter(|p| !p.is_empty()),
+        sink: &mut *sink,
+    }
+    .deserialize(&mut deserializer);
+
+    if let Some(err) = sink.error.take() {
+        return Err(err);
+    }
+    // Amostra completa interrompe a leitura de propósito: o restante é ignorado
+    match extraction {
+        _ if sink.sampled => {}
+        other => other.and_then(|_| deserializer.end())?,
+    }
+
+    // Amostras podem parar antes do campo de status: sem ele, nada a conferir
+    if !sink.sampled || sink.status_seen {
+        processor::check_status(group, sink.status.take(), sink.status_message.take())?;
+    }
+    Ok(())
+}
+
+/// Writer aberto a partir do primeiro lote, com o schema de leitura fixado.
+struct BatchState {
+    writer: BatchedWriter<File>,
+    /// Vaga de `max_open_files` do writer, liberada com ele.
+    _permit: open_files::Permit,
+    read_schema: SchemaRef,
+    /// Schema após a normalização (igual em todos os lotes).
+    schema: SchemaRef,
+}
+
+/// Acumula registros e descarrega cada lote completo no writer.
+struct BatchSink<'a> {
+    batch_size: usize,
+    buffer: Vec<Value>,
+    group: &'a EndpointGroup,
+    parquet_path: &'a Path,
+    options: ConvertOptions<'a>,
+    state: Option<BatchState>,
+    total: usize,
+    coercions: CoercionReport,
+    /// Colunas com nulos em algum lote.
+    nullable: BTreeSet<String>,
+    /// `totalRegistros` encontrado no envelope.
+    declared: Option<usize>,
+    /// Valores de `status_field` e `status_message_field` no envelope.
+    status: Option<String>,
+    status_message: Option<String>,
+    /// O campo de status foi encontrado (amostras podem interromper antes).
+    status_seen: bool,
+    error: Option<ProcessorError>,
+    /// A amostra (`options.sample`) foi completada e a leitura interrompida.
+    sampled: bool,
+    /// Tempo de transformação e gravação dos lotes.
+    timings: StageTimings,
+}
+
+impl BatchSink<'_> {
+    fn push<E: de::Error>(&mut self, record: Value) -> Result<(), E> {
+        self.buffer.push(record);
+        let sample_full = self
+            .options
+            .sample
+            .is_some_and(|rows| self.total + self.buffer.len() >= rows);
+
+        if (self.buffer.len() >= self.batch_size || sample_full)
+            && let Err(err) = self.flush()
+        {
+            // Preserva o erro tipado; o serde só transporta uma mensagem
+            let msg = err.to_string();
+            self.error = Some(err);
+            return Err(E::custom(msg));
+        }
+        if sample_full {
+            // Interrompe o streaming; `process_json_in_batches` ignora este erro
+            self.sampled = true;
+            return Err(E::custom("amostra completa"));
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), ProcessorError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let records = std::mem::take(&mut self.buffer);
+        let bytes = serde_json::to_vec(&records)?;
+        drop(records);
+
+        let reader = JsonReader::new(Cursor::new(bytes));
+        let raw = match &self.state {
+            Some(state) => reader.with_schema(state.read_schema.clone()).finish(),
+            None => reader
+                .infer_schema_len(NonZeroUsize::new(self.batch_size))
+                .finish(),
+        }
+        .map_err(|e| ProcessorError::Parquet(format!("Falha no parsing do lote: {}", e)))?;
+
+        let raw = match &self.state {
+            Some(_) => raw,
+            None => widen_null_columns(raw)?,
+        };
+        let read_schema = raw.schema().clone();
+
+        let timer = Instant::now();
+        let mut dataframe = processor::normalize(raw, self.group, self.options, &mut self.coercions)?;
+        dataframe.align_chunks();
+        self.timings.transform += timer.elapsed();
+        self.total += dataframe.height();
+        self.nullable.extend(processor::nullable_columns(&dataframe));
+
+        let timer = Instant::now();
+        if self.state.is_none() {
+            // Os lotes seguintes têm o mesmo schema: um aviso basta
+            processor::absent_first_columns(&dataframe, self.group, self.options);
+            let permit = open_files::acquire();
+            let file = File::create(atomic::temp_path(self.parquet_path)).map_err(ProcessorError::Io)?;
+            let writer = processor::parquet_writer(file, self.group)
+                .batched(dataframe.schema())
+                .map_err(|e| ProcessorError::Parquet(format!("Erro ao abrir Parquet: {}", e)))?;
+            self.state = Some(BatchState {
+                writer,
+                _permit: permit,
+                read_schema,
+                schema: dataframe.schema().clone(),
+            });
+        }
+
+        if let Some(state) = self.state.as_mut() {
+            state.writer.write_batch(&dataframe).map_err(|e| {
+                ProcessorError::Schema(format!("Lote incompatível com o schema inicial: {}", e))
+            })?;
+        }
+        self.timings.write += timer.elapsed();
+
+        if let Some(limit) = self.options.limit {
+            limit.check("lote")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Colunas totalmente nulas no primeiro lote viram `String`, evitando
+/// que lotes seguintes com valores sejam descartados pelo tipo `Null`.
+fn widen_null_columns(mut dataframe: DataFrame) -> Result<DataFrame, ProcessorError> {
+    let null_cols: Vec<PlSmallStr> = dataframe
+        .get_columns()
+        .iter()
+        .filter(|c| c.dtype() == &DataType::Null)
+        .map(|c| c.name().clone())
+        .collect();
+
+    for name in null_cols {
+        let widened = dataframe.column(&name)?.cast(&DataType::String)?;
+        dataframe.with_column(widened)?;
+    }
+    Ok(dataframe)
+}
+
+/// Total declarado como número ou texto (ex: com `big_number_columns`).
+fn declared_total(value: &Value) -> Option<usize> {
+    let total = match value {
+        Value::String(text) => text.parse().ok(),
+        other => other.as_u64(),
+    }?;
+    usize::try_from(total).ok()
+}
+
+/// Percorre o envelope até `root_path`, ignorando os demais campos
+/// (exceto `totalRegistros` e os campos de status configurados).
+struct Envelope<'s, 'a> {
+    root_path: Option<&'s str>,
+    sink: &'s mut BatchSink<'a>,
+}
+
+impl<'de> DeserializeSeed<'de> for Envelope<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Envelope<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "um envelope JSON (objeto) ou uma lista de registros")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<(), A::Error> {
+        Records { sink: self.sink }.visit_seq(seq)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let Some(root_path) = self.root_path else {
+            // Sem root_path, o próprio objeto é o único registro
+            let record = Value::deserialize(de::value::MapAccessDeserializer::new(map))?;
+            return self.sink.push(record);
+        };
+
+        while let Some(key) = map.next_key::<String>()? {
+            if key == root_path {
+                map.next_value_seed(Records {
+                    sink: &mut *self.sink,
+                })?;
+            } else if key == processor::TOTAL_KEY {
+                // Em páginas, vale o total da primeira que o declarar
+                let declared = declared_total(&map.next_value::<Value>()?);
+                self.sink.declared = self.sink.declared.or(declared);
+            } else if self.sink.group.status_field.as_deref() == Some(key.as_str()) {
+                self.sink.status = processor::envelope_text(&map.next_value::<Value>()?);
+                self.sink.status_seen = true;
+            } else if self.sink.group.status_message_field.as_deref() == Some(key.as_str()) {
+                self.sink.status_message = processor::envelope_text(&map.next_value::<Value>()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Consome a lista de registros (ou um objeto único) sob `root_path`.
+struct Records<'s, 'a> {
+    sink: &'s mut BatchSink<'a>,
+}
+
+impl<'de> DeserializeSeed<'de> for Records<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Records<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "uma lista de registros")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(record) = seq.next_element::<Value>()? {
+            self.sink.push(record)?;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<(), A::Error> {
+        let record = Value::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        self.sink.push(record)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars_parquet::parquet::read::read_metadata;
+
+    fn options() -> ConvertOptions<'static> {
+        ConvertOptions {
+            limit: None,
+            run_id: "execucao-1",
+            sample: None,
+            stdout: None,
+            write_retry: atomic::Retry::default(),
+            stream_above: None,
+            warnings: None,
+            origin: None,
+        }
+    }
+
+    #[test]
+    fn batches_cover_every_input_record() {
+        let dir = std::env::temp_dir().join(format!("data_gov_batch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let records: Vec<Value> = (0..7).map(|id| serde_json::json!({ "id": id, "nome": format!("n{}", id) })).collect();
+        let input = dir.join("pedidos.json");
+        let envelope = serde_json::json!({ "totalRegistros": 7, "resultado": records });
+        std::fs::write(&input, envelope.to_string()).unwrap();
+        let output = dir.join("pedidos.parquet");
+        let group: EndpointGroup = toml::from_str(r#"root_path = "resultado""#).unwrap();
+
+        let stats = process_json_in_batches(&[input], &output, &group, NonZeroUsize::new(3).unwrap(), options()).unwrap();
+
+        assert_eq!(stats.rows, 7);
+        let mut file = File::open(&output).unwrap();
+        // Um row group por lote: 3 + 3 + 1
+        let row_groups: Vec<usize> = read_metadata(&mut file).unwrap().row_groups.iter().map(|rg| rg.num_rows()).collect();
+        assert_eq!(row_groups.iter().sum::<usize>(), 7);
+        assert_eq!(row_groups, [3, 3, 1]);
+        let dataframe = ParquetReader::new(File::open(&output).unwrap()).finish().unwrap();
+        let ids: Vec<_> = dataframe.column("id").unwrap().i64().unwrap().into_no_null_iter().collect();
+        assert_eq!(ids, (0..7).collect::<Vec<i64>>());
+        assert!(!atomic::temp_path(&output).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+//! # Números Grandes como Texto
+//!
+//! Identificadores com 20+ dígitos (CNPJ, números de processo) perdem
+//! precisão quando inferidos como `Int64`/`Float64`, ou sequer são aceitos
+//! pelo parser. Para as chaves listadas em `big_number_columns`, os literais
+//! numéricos do staging são reescritos como strings JSON *antes* da leitura,
+//! preservando exatamente os dígitos originais.
+//!
+//! ## Escopo
+//! - A reescrita é textual e em streaming: o documento nunca é materializado.
+//! - Vale para a chave em qualquer profundidade; listas de números não são alteradas.
+//! - Fontes XML já chegam como texto; fontes CSV recebem a coluna como `String`.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::atomic;
+use crate::errors::ProcessorError;
+use crate::open_files;
+
+/// Reescreve o JSON em `path`, envolvendo em aspas os números das chaves `columns`.
+///
+/// # Erros
+/// Em caso de falha, o staging original permanece intocado.
+pub fn quote_in_place(path: &Path, columns: &[String]) -> Result<(), ProcessorError> {
+    let keys: HashSet<&[u8]> = columns.iter().map(|c| c.as_bytes()).collect();
+    let tmp = atomic::temp_path(path);
+    let _permit = open_files::acquire();
+
+    let result = File::open(path)
+        .and_then(|input| {
+            let output = File::create(&tmp)?;
+            let mut writer = BufWriter::new(output);
+            quote_numbers(BufReader::new(input), &mut writer, &keys)?;
+            writer.flush()
+        })
+        .map_err(ProcessorError::Io);
+
+    match result {
+        Ok(()) => atomic::commit(&tmp, path),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// Copia `input` para `output`, citando os números que são valor de uma chave em `keys`.
+fn quote_numbers<R: BufRead, W: Write>(
+    input: R,
+    output: &mut W,
+    keys: &HashSet<&[u8]>,
+) -> std::io::Result<()> {
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut in_number = false;
+    // Conteúdo da string atual e da última string fechada (candidata a chave)
+    let mut token: Vec<u8> = Vec::new();
+    let mut last_string: Option<Vec<u8>> = None;
+    // O próximo valor pertence a uma chave listada
+    let mut target_value = false;
+
+    for byte in input.bytes() {
+        let byte = byte?;
+
+        if in_number {
+            if byte.is_ascii_digit() || matches!(byte, b'.' | b'e' | b'E' | b'+' | b'-') {
+                output.write_all(&[byte])?;
+                continue;
+            }
+            output.write_all(b"\"")?;
+            in_number = false;
+        }
+
+        if in_string {
+            output.write_all(&[byte])?;
+            if escaped {
+                escaped = false;
+                token.push(byte);
+            } else if byte == b'\\' {
+                escaped = true;
+                token.push(byte);
+            } else if byte == b'"' {
+                in_string = false;
+                last_string = Some(std::mem::take(&mut token));
+            } else {
+                token.push(byte);
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => {
+                in_string = true;
+                target_value = false;
+                token.clear();
+            }
+            b':' => {
+                target_value = last_string
+                    .take()
+                    .is_some_and(|key| keys.contains(key.as_slice()));
+            }
+            b'-' | b'0'..=b'9' if target_value => {
+                output.write_all(b"\"")?;
+                in_number = true;
+                target_value = false;
+            }
+            b' ' | b'\t' | b'\n' | b'\r' => {}
+            _ => {
+                target_value = false;
+                last_string = None;
+            }
+        }
+        output.write_all(&[byte])?;
+    }
+
+    // Documento terminado no meio de um número (ex: valor escalar na raiz)
+    if in_number {
+        output.write_all(b"\"")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+
+    #[test]
+    fn twenty_digit_values_survive_as_strings() {
+        let path = std::env::temp_dir().join(format!("data_gov_big_numbers_{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"[{"cnpj": 12345678901234567890, "valor": 10, "nota": "cnpj: 1"},
+                {"cnpj": 98765432109876543210123, "valor": 20, "nota": "x\"cnpj\": 2"}]"#,
+        )
+        .unwrap();
+
+        quote_in_place(&path, &["cnpj".to_string()]).unwrap();
+
+        let dataframe = JsonReader::new(File::open(&path).unwrap()).finish().unwrap();
+        let cnpj: Vec<_> = dataframe.column("cnpj").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(cnpj, [Some("12345678901234567890"), Some("98765432109876543210123")]);
+        // Chaves fora da lista e conteúdo de strings não são alterados
+        assert_eq!(dataframe.column("valor").unwrap().dtype(), &DataType::Int64);
+        let notes: Vec<_> = dataframe.column("nota").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(notes, [Some("cnpj: 1"), Some("x\"cnpj\": 2")]);
+        fs::remove_file(&path).unwrap();
+    }
+}
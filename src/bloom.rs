@@ -0,0 +1,273 @@
+//! # Filtros de Bloom
+//!
+//! `bloom_filter_columns` grava, em cada row group, um filtro de Bloom das
+//! colunas listadas (split block com xxHash64, como define a especificação do
+//! Parquet). Leitores que os consultam (Spark, DuckDB, Trino, arrow-rs) pulam
+//! row groups inteiros em buscas pontuais (`WHERE cnpj = '...'`).
+//!
+//! ## Gravação
+//! O writer do Polars não emite filtros: após a escrita, o rodapé do `.tmp` é
+//! substituído pelos filtros seguidos de um novo rodapé, com
+//! `bloom_filter_offset` e `bloom_filter_length` de cada coluna preenchidos.
+//! As páginas de dados não são reescritas.
+//!
+//! ## Custo
+//! Por coluna e row group, uma contagem de valores distintos e o hash de cada
+//! valor não nulo. Cada filtro é dimensionado para 1% de falsos positivos:
+//! cerca de 1,2 byte por valor distinto, arredondado para uma potência de 2.
+
+use polars::prelude::*;
+use polars_parquet::parquet::bloom_filter::{self, hash_byte, hash_native};
+use polars_parquet_format::thrift::protocol::{TCompactInputProtocol, TCompactOutputProtocol};
+use polars_parquet_format::{
+    BloomFilterAlgorithm, BloomFilterCompression, BloomFilterHash, BloomFilterHeader, FileMetaData,
+    SplitBlockAlgorithm, Uncompressed, XxHash,
+};
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::errors::ProcessorError;
+
+/// Taxa de falsos positivos usada no dimensionamento dos filtros.
+const FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// Limites de tamanho de um filtro (os mesmos do parquet-mr).
+const MIN_BYTES: usize = 32;
+const MAX_BYTES: usize = 128 * 1024 * 1024;
+
+const MAGIC: &[u8; 4] = b"PAR1";
+
+/// Confere, antes da escrita, se as colunas existem e têm tipo suportado.
+///
+/// # Erros
+/// `ProcessorError::Schema` com a primeira coluna ausente ou de tipo sem filtro.
+pub fn check(dataframe: &DataFrame, columns: &[String]) -> Result<(), ProcessorError> {
+    for name in columns {
+        let column = dataframe.column(name).map_err(|_| {
+            ProcessorError::Schema(format!("bloom_filter_columns: coluna '{}' não existe na saída", name))
+        })?;
+        if !supported(column.dtype()) {
+            return Err(ProcessorError::Schema(format!(
+                "bloom_filter_columns: a coluna '{}' ({}) não aceita filtro de Bloom; \
+                 use inteiros, decimais, texto, binário, datas ou timestamps",
+                name,
+                column.dtype()
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn supported(dtype: &DataType) -> bool {
+    matches!(
+        dtype,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::String
+            | DataType::Binary
+            | DataType::Date
+            | DataType::Datetime(_, _)
+    )
+}
+
+/// Acrescenta ao Parquet em `path` os filtros de `columns`, calculados a partir
+/// de `dataframe` (o mesmo que acabou de ser gravado, na mesma ordem).
+///
+/// # Erros
+/// `ProcessorError::Parquet` se o rodapé não puder ser lido ou regravado.
+pub fn write(path: &Path, dataframe: &DataFrame, columns: &[String]) -> Result<(), ProcessorError> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(ProcessorError::Io)?;
+    let (footer_start, mut metadata) = read_footer(&mut file)?;
+
+    // Filtros e novo rodapé, gravados a partir do início do rodapé atual
+    let mut tail = Vec::new();
+    let mut first_row = 0;
+    for row_group in &mut metadata.row_groups {
+        let rows = usize::try_from(row_group.num_rows).unwrap_or_default();
+        for chunk in &mut row_group.columns {
+            let Some(meta) = chunk.meta_data.as_mut() else {
+                continue;
+            };
+            let [name] = meta.path_in_schema.as_slice() else {
+                continue;
+            };
+            if !columns.contains(name) {
+                continue;
+            }
+            let values = dataframe.column(name)?.as_materialized_series().slice(first_row as i64, rows);
+            let bitset = bitset(&values)?;
+
+            let offset = footer_start + tail.len() as u64;
+            let header = BloomFilterHeader::new(
+                bitset.len() as i32,
+                BloomFilterAlgorithm::BLOCK(SplitBlockAlgorithm {}),
+                BloomFilterHash::XXHASH(XxHash {}),
+                BloomFilterCompression::UNCOMPRESSED(Uncompressed {}),
+            );
+            header
+                .write_to_out_protocol(&mut TCompactOutputProtocol::new(&mut tail))
+                .map_err(footer_error)?;
+            tail.extend_from_slice(&bitset);
+            meta.bloom_filter_offset = Some(offset as i64);
+            meta.bloom_filter_length = Some((footer_start + tail.len() as u64 - offset) as i32);
+        }
+        first_row += rows;
+    }
+
+    let metadata_start = tail.len();
+    metadata
+        .write_to_out_protocol(&mut TCompactOutputProtocol::new(&mut tail))
+        .map_err(footer_error)?;
+    let metadata_len = (tail.len() - metadata_start) as u32;
+    tail.extend_from_slice(&metadata_len.to_le_bytes());
+    tail.extend_from_slice(MAGIC);
+
+    file.set_len(footer_start).map_err(ProcessorError::Io)?;
+    file.seek(SeekFrom::Start(footer_start)).map_err(ProcessorError::Io)?;
+    file.write_all(&tail).map_err(ProcessorError::Io)
+}
+
+/// Posição e conteúdo do rodapé (`FileMetaData`, tamanho e `PAR1`).
+fn read_footer(file: &mut File) -> Result<(u64, FileMetaData), ProcessorError> {
+    let len = file.seek(SeekFrom::End(0)).map_err(ProcessorError::Io)?;
+    let mut trailer = [0u8; 8];
+    if len < 12 {
+        return Err(ProcessorError::Parquet("Filtro de Bloom: arquivo Parquet truncado".to_string()));
+    }
+    file.seek(SeekFrom::End(-8)).map_err(ProcessorError::Io)?;
+    file.read_exact(&mut trailer).map_err(ProcessorError::Io)?;
+    if &trailer[4..] != MAGIC {
+        return Err(ProcessorError::Parquet("Filtro de Bloom: rodapé Parquet inválido".to_string()));
+    }
+
+    let metadata_len = u64::from(u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]));
+    let Some(footer_start) = len.checked_sub(8 + metadata_len) else {
+        return Err(ProcessorError::Parquet("Filtro de Bloom: rodapé Parquet inválido".to_string()));
+    };
+    let mut bytes = vec![0u8; metadata_len as usize];
+    file.seek(SeekFrom::Start(footer_start)).map_err(ProcessorError::Io)?;
+    file.read_exact(&mut bytes).map_err(ProcessorError::Io)?;
+
+    let metadata = FileMetaData::read_from_in_protocol(&mut TCompactInputProtocol::new(bytes.as_slice(), usize::MAX))
+        .map_err(footer_error)?;
+    Ok((footer_start, metadata))
+}
+
+fn footer_error(e: polars_parquet_format::thrift::Error) -> ProcessorError {
+    ProcessorError::Parquet(format!("Filtro de Bloom: falha no rodapé Parquet: {}", e))
+}
+
+/// Filtro de um trecho da coluna, com os valores no tipo físico gravado.
+fn bitset(values: &Series) -> Result<Vec<u8>, ProcessorError> {
+    let values = values.drop_nulls();
+    let mut bitset = vec![0u8; optimal_bytes(values.n_unique()?)];
+
+    // Inteiros curtos e datas são INT32 no Parquet; sem sinal mantêm os bits
+    let physical = values.to_physical_repr();
+    match physical.dtype() {
+        DataType::Int8 | DataType::Int16 | DataType::UInt8 | DataType::UInt16 | DataType::Int32 => {
+            let values = physical.cast(&DataType::Int32)?;
+            fill(&mut bitset, values.i32()?.into_no_null_iter().map(hash_native));
+        }
+        DataType::UInt32 => fill(&mut bitset, physical.u32()?.into_no_null_iter().map(|v| hash_native(v as i32))),
+        DataType::Int64 => fill(&mut bitset, physical.i64()?.into_no_null_iter().map(hash_native)),
+        DataType::UInt64 => fill(&mut bitset, physical.u64()?.into_no_null_iter().map(|v| hash_native(v as i64))),
+        DataType::Float32 => fill(&mut bitset, physical.f32()?.into_no_null_iter().map(hash_native)),
+        DataType::Float64 => fill(&mut bitset, physical.f64()?.into_no_null_iter().map(hash_native)),
+        DataType::String => fill(&mut bitset, physical.str()?.into_no_null_iter().map(hash_byte)),
+        DataType::Binary => fill(&mut bitset, physical.binary()?.into_no_null_iter().map(hash_byte)),
+        dtype => {
+            return Err(ProcessorError::Schema(format!(
+                "bloom_filter_columns: tipo {} sem filtro de Bloom",
+                dtype
+            )));
+        }
+    }
+    Ok(bitset)
+}
+
+fn fill(bitset: &mut [u8], hashes: impl Iterator<Item = u64>) {
+    for hash in hashes {
+        bloom_filter::insert(bitset, hash);
+    }
+}
+
+/// Bytes do filtro para `distinct` valores à taxa de `FALSE_POSITIVE_RATE`.
+fn optimal_bytes(distinct: usize) -> usize {
+    let bits = -8.0 * distinct as f64 / (1.0 - FALSE_POSITIVE_RATE.powf(1.0 / 8.0)).ln();
+    ((bits / 8.0).ceil() as usize).clamp(MIN_BYTES, MAX_BYTES).next_power_of_two()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars_parquet::parquet::read::read_metadata;
+
+    #[test]
+    fn write_sets_filter_offsets_and_keeps_rows() {
+        let path = std::env::temp_dir().join(format!("data_gov_bloom_{}.parquet", std::process::id()));
+        let mut dataframe = df!(
+            "id" => [10i64, 20, 30, 40, 50],
+            "nome" => ["a", "b", "c", "d", "e"]
+        )
+        .unwrap();
+        ParquetWriter::new(File::create(&path).unwrap())
+            .with_row_group_size(Some(2))
+            .finish(&mut dataframe)
+            .unwrap();
+
+        write(&path, &dataframe, &["id".to_string()]).unwrap();
+
+        let mut file = File::open(&path).unwrap();
+        let metadata = read_metadata(&mut file).unwrap();
+        assert!(metadata.row_groups.len() > 1);
+        let mut first_row = 0;
+        for row_group in &metadata.row_groups {
+            for chunk in row_group.parquet_columns() {
+                let name = chunk.descriptor().path_in_schema[0].as_str();
+                let mut bitset = Vec::new();
+                bloom_filter::read(chunk, &mut file, &mut bitset).unwrap();
+                if name != "id" {
+                    assert!(chunk.metadata().bloom_filter_offset.is_none(), "{name}");
+                    continue;
+                }
+                assert!(chunk.metadata().bloom_filter_offset.is_some());
+                assert!(chunk.metadata().bloom_filter_length.is_some());
+                // Cada filtro contém os valores do próprio row group
+                let ids = dataframe.column("id").unwrap().i64().unwrap().slice(first_row, row_group.num_rows());
+                for id in ids.into_no_null_iter() {
+                    assert!(bloom_filter::is_in_set(&bitset, hash_native(id)), "{id}");
+                }
+            }
+            first_row += row_group.num_rows() as i64;
+        }
+
+        let read_back = ParquetReader::new(File::open(&path).unwrap()).finish().unwrap();
+        assert_eq!(read_back, dataframe);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn check_rejects_missing_and_nested_columns() {
+        let dataframe = df!("id" => [1i64]).unwrap();
+        assert!(check(&dataframe, &["id".to_string()]).is_ok());
+        assert!(matches!(check(&dataframe, &["cnpj".to_string()]), Err(ProcessorError::Schema(_))));
+
+        let lists = DataFrame::new(vec![Series::new("tags".into(), [Series::new("".into(), [1i64])]).into_column()]).unwrap();
+        assert!(matches!(check(&lists, &["tags".to_string()]), Err(ProcessorError::Schema(_))));
+    }
+}
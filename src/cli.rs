@@ -0,0 +1,400 @@
+//! # Interface de Linha de Comando
+//!
+//! Parsing manual dos argumentos (sem dependências externas).
+//!
+//! ```text
+//! data_gov [--version] [--strict] [--quiet] [--verbose] [--interval SEGUNDOS]
+//!          [--max-memory-mb MB] [--run-id ID] [--manifest ARQUIVO]
+//!          [--outputs-csv ARQUIVO] [--manifest-every N] [--sample N] [--emit-json-schema]
+//!          [--preview] [--explain] [--timings] [--profile ARQUIVO] [--log-file ARQUIVO]
+//!          [--log-max-size MB] [--log-keep N] [--no-console-log] [--allow-host HOST]...
+//!          [--tag TAG[,TAG...]]... [--output - [--format FORMATO]]
+//!          [--config-format toml|json] [[--config] CONFIG]
+//!
+//! `CONFIG` igual a `-` lê a configuração do stdin.
+//!
+//! data_gov diff ANTIGO.parquet NOVO.parquet --key COLUNA... [--output ARQUIVO]
+//!
+//! data_gov from_openapi ARQUIVO_OU_URL [--api NOME]
+//! ```
+
+use crate::models::{ConfigFormat, STDIN_CONFIG};
+use crate::processor::StreamFormat;
+
+/// Caminho padrão do arquivo de configuração.
+const DEFAULT_CONFIG: &str = "endpoints_publicos.toml";
+
+/// Ação pedida na linha de comando.
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Execução do pipeline (padrão).
+    Run(Box<CliArgs>),
+    /// Comparação de duas saídas (`diff`).
+    Diff(DiffArgs),
+    /// Esqueleto de configuração a partir de um OpenAPI (`from_openapi`).
+    FromOpenApi(OpenApiArgs),
+}
+
+/// Interpreta os argumentos do processo (sem o nome do binário).
+///
+/// # Erros
+/// Retorna uma mensagem descritiva para flags desconhecidas ou incompletas.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    if args.next_if(|arg| arg == "diff").is_some() {
+        return DiffArgs::parse(args).map(Command::Diff);
+    }
+    if args.next_if(|arg| arg == "from_openapi").is_some() {
+        return OpenApiArgs::parse(args).map(Command::FromOpenApi);
+    }
+    CliArgs::parse(args).map(|args| Command::Run(Box::new(args)))
+}
+
+/// Argumentos do subcomando `diff`.
+#[derive(Debug, Clone)]
+pub struct DiffArgs {
+    /// Saída Parquet de referência (execução anterior).
+    pub old_path: String,
+
+    /// Saída Parquet comparada (execução nova).
+    pub new_path: String,
+
+    /// Colunas que identificam cada linha (repetível).
+    pub keys: Vec<String>,
+
+    /// Parquet que recebe as linhas divergentes.
+    pub output: Option<String>,
+}
+
+impl DiffArgs {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut paths = Vec::new();
+        let mut keys = Vec::new();
+        let mut output = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--key" => keys.push(text_value(&arg, args.next())?),
+                "--output" => output = Some(text_value(&arg, args.next())?),
+                flag if flag.starts_with("--") => {
+                    return Err(format!("Opção desconhecida para diff: {}", flag));
+                }
+                path => paths.push(path.to_string()),
+            }
+        }
+
+        let [old_path, new_path]: [String; 2] = paths
+            .try_into()
+            .map_err(|_| "diff exige dois arquivos: ANTIGO.parquet NOVO.parquet".to_string())?;
+        if keys.is_empty() {
+            return Err("diff exige ao menos uma --key".to_string());
+        }
+
+        Ok(Self {
+            old_path,
+            new_path,
+            keys,
+            output,
+        })
+    }
+}
+
+/// Argumentos do subcomando `from_openapi`.
+#[derive(Debug, Clone)]
+pub struct OpenApiArgs {
+    /// Documento OpenAPI em JSON: caminho local ou URL `http(s)://`.
+    pub source: String,
+
+    /// Nome da API no `.toml`; None usa o `info.title` do documento.
+    pub api: Option<String>,
+}
+
+impl OpenApiArgs {
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut sources = Vec::new();
+        let mut api = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--api" => api = Some(text_value(&arg, args.next())?),
+                flag if flag.starts_with("--") => {
+                    return Err(format!("Opção desconhecida para from_openapi: {}", flag));
+                }
+                source => sources.push(source.to_string()),
+            }
+        }
+
+        let [source]: [String; 1] = sources
+            .try_into()
+            .map_err(|_| "from_openapi exige um documento: ARQUIVO_OU_URL".to_string())?;
+        if let Some(name) = &api
+            && !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("--api '{}': use apenas letras, dígitos, '_' e '-'", name));
+        }
+
+        Ok(Self { source, api })
+    }
+}
+
+/// Argumentos já interpretados da execução.
+#[derive(Debug, Clone)]
+pub struct CliArgs {
+    /// Caminho do arquivo TOML de configuração (`-` para o stdin).
+    pub config_path: String,
+
+    /// Formato da configuração (`--config-format`); None deduz pela extensão.
+    pub config_format: Option<ConfigFormat>,
+
+    /// Imprime os metadados de build e encerra.
+    pub show_version: bool,
+
+    /// Aborta a execução no primeiro endpoint com falha.
+    pub strict: bool,
+
+    /// Oculta barras de progresso e o resumo dinâmico.
+    pub quiet: bool,
+
+    /// Loga requisições e respostas HTTP (nível debug).
+    pub verbose: bool,
+
+    /// Intervalo entre ciclos no modo daemon; `None` executa uma única vez.
+    pub interval: Option<u64>,
+
+    /// Teto de RSS (MB) durante a conversão de cada endpoint.
+    pub max_memory_mb: Option<u64>,
+
+    /// Identificador da execução; gerado automaticamente (um por ciclo do daemon) se ausente.
+    pub run_id: Option<String>,
+
+    /// Caminho do manifesto JSON da execução.
+    pub manifest_path: Option<String>,
+
+    /// Caminho do CSV com uma linha por arquivo produzido.
+    pub outputs_csv: Option<String>,
+
+    /// Grava manifesto e CSV parciais a cada N endpoints encerrados.
+    pub manifest_every: Option<usize>,
+
+    /// Grava apenas as primeiras N linhas de cada endpoint (validação rápida).
+    pub sample: Option<usize>,
+
+    /// Grava um JSON Schema (`{chave}.jsonschema.json`) ao lado de cada saída.
+    pub emit_json_schema: bool,
+
+    /// Lista tamanhos via HEAD e encerra, sem baixar nada.
+    pub preview: bool,
+
+    /// Lista as etapas de transformação de cada endpoint e encerra, sem I/O.
+    pub explain: bool,
+
+    /// Detalha no resumo final o tempo de cada etapa por endpoint.
+    pub timings: bool,
+
+    /// Grava os spans de cada endpoint e etapa no formato Chrome Tracing.
+    pub profile: Option<String>,
+
+    /// Arquivo que recebe os logs operacionais.
+    pub log_file: Option<String>,
+
+    /// Tamanho (MB) que dispara a rotação do arquivo de log.
+    pub log_max_size_mb: Option<u64>,
+
+    /// Cópias rotacionadas mantidas do arquivo de log.
+    pub log_keep: Option<u64>,
+
+    /// Desliga os logs operacionais no console (banners e progresso permanecem).
+    pub no_console_log: bool,
+
+    /// Hosts permitidos (repetível); substitui `allowed_hosts` da configuração.
+    pub allow_hosts: Vec<String>,
+
+    /// Filtros de tags (repetível): basta casar um; `a,b` exige as duas.
+    pub tags: Vec<String>,
+
+    /// Formato do único endpoint escrito no stdout (`--output -`).
+    pub stdout: Option<StreamFormat>,
+}
+
+impl CliArgs {
+    fn parse<I: Iterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut config_path = None;
+        let mut config_format = None;
+        let mut show_version = false;
+        let mut strict = false;
+        let mut quiet = false;
+        let mut verbose = false;
+        let mut interval = None;
+        let mut max_memory_mb = None;
+        let mut run_id = None;
+        let mut manifest_path = None;
+        let mut outputs_csv = None;
+        let mut manifest_every = None;
+        let mut sample = None;
+        let mut emit_json_schema = false;
+        let mut preview = false;
+        let mut explain = false;
+        let mut timings = false;
+        let mut profile = None;
+        let mut log_file = None;
+        let mut log_max_size_mb = None;
+        let mut log_keep = None;
+        let mut no_console_log = false;
+        let mut allow_hosts = Vec::new();
+        let mut tags = Vec::new();
+        let mut stdout = false;
+        let mut format = None;
+
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--version" | "-V" => show_version = true,
+                "--strict" | "--fail-fast" => strict = true,
+                "--quiet" | "-q" => quiet = true,
+                "--verbose" | "-v" => verbose = true,
+                "--interval" => interval = Some(positive_value(&arg, args.next())?),
+                "--max-memory-mb" => max_memory_mb = Some(positive_value(&arg, args.next())?),
+                "--run-id" => run_id = Some(text_value(&arg, args.next())?),
+                "--manifest" => manifest_path = Some(text_value(&arg, args.next())?),
+                "--outputs-csv" => outputs_csv = Some(text_value(&arg, args.next())?),
+                "--manifest-every" => manifest_every = Some(positive_value(&arg, args.next())? as usize),
+                "--emit-json-schema" => emit_json_schema = true,
+                "--preview" => preview = true,
+                "--explain" => explain = true,
+                "--timings" => timings = true,
+                "--profile" => profile = Some(text_value(&arg, args.next())?),
+                "--log-file" => log_file = Some(text_value(&arg, args.next())?),
+                "--log-max-size" => log_max_size_mb = Some(positive_value(&arg, args.next())?),
+                "--log-keep" => log_keep = Some(positive_value(&arg, args.next())?),
+                "--no-console-log" => no_console_log = true,
+                "--allow-host" => allow_hosts.push(text_value(&arg, args.next())?),
+                "--tag" => tags.push(tag_value(&arg, args.next())?),
+                "--output" => match text_value(&arg, args.next())?.as_str() {
+                    "-" => stdout = true,
+                    other => {
+                        return Err(format!(
+                            "Valor inválido para --output: '{}' (apenas '-', o stdout)",
+                            other
+                        ));
+                    }
+                },
+                "--format" => format = Some(format_value(&arg, args.next())?),
+                "--config" => set_config_path(&mut config_path, text_value(&arg, args.next())?)?,
+                "--config-format" => config_format = Some(config_format_value(&arg, args.next())?),
+                "--sample" => sample = Some(positive_value(&arg, args.next())? as usize),
+                flag if flag.starts_with("--") => {
+                    return Err(format!("Opção desconhecida: {}", flag));
+                }
+                path => set_config_path(&mut config_path, path.to_string())?,
+            }
+        }
+
+        if log_file.is_none() && (log_max_size_mb.is_some() || log_keep.is_some()) {
+            return Err("--log-max-size e --log-keep exigem --log-file".to_string());
+        }
+        if manifest_every.is_some() && manifest_path.is_none() && outputs_csv.is_none() {
+            return Err("--manifest-every exige --manifest ou --outputs-csv".to_string());
+        }
+        if format.is_some() && !stdout {
+            return Err("--format exige --output -".to_string());
+        }
+        if stdout && interval.is_some() {
+            return Err("--output - não combina com --interval".to_string());
+        }
+        if stdout && emit_json_schema {
+            return Err("--output - não combina com --emit-json-schema".to_string());
+        }
+        if config_path.as_deref() == Some(STDIN_CONFIG) && interval.is_some() {
+            return Err("--config - não combina com --interval (o stdin não pode ser relido)".to_string());
+        }
+        if explain && (preview || interval.is_some()) {
+            return Err("--explain não combina com --preview nem --interval".to_string());
+        }
+        if profile.is_some() && (explain || preview) {
+            return Err("--profile não combina com --explain nem --preview".to_string());
+        }
+
+        Ok(Self {
+            config_path: config_path.unwrap_or_else(|| DEFAULT_CONFIG.to_string()),
+            config_format,
+            show_version,
+            strict,
+            quiet,
+            verbose,
+            interval,
+            max_memory_mb,
+            run_id,
+            manifest_path,
+            outputs_csv,
+            manifest_every,
+            sample,
+            emit_json_schema,
+            preview,
+            explain,
+            timings,
+            profile,
+            log_file,
+            log_max_size_mb,
+            log_keep,
+            no_console_log,
+            allow_hosts,
+            tags,
+            stdout: stdout.then(|| format.unwrap_or_default()),
+        })
+    }
+}
+
+/// Valor textual não vazio exigido por uma flag (ex: `--run-id abc`).
+fn text_value(flag: &str, value: Option<String>) -> Result<String, String> {
+    match value {
+        Some(v) if !v.is_empty() && !v.starts_with("--") => Ok(v),
+        _ => Err(format!("{} exige um valor", flag)),
+    }
+}
+
+/// Valor inteiro positivo exigido por uma flag (ex: `--interval 3600`).
+fn positive_value(flag: &str, value: Option<String>) -> Result<u64, String> {
+    let value = value.ok_or_else(|| format!("{} exige um valor", flag))?;
+    match value.parse::<u64>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!(
+            "Valor inválido para {}: '{}' (esperado inteiro > 0)",
+            flag, value
+        )),
+    }
+}
+
+/// Filtro de `--tag`: uma ou mais tags separadas por vírgula (ex: `diario,publico`).
+fn tag_value(flag: &str, value: Option<String>) -> Result<String, String> {
+    let value = text_value(flag, value)?;
+    if value.split(',').any(|tag| tag.trim().is_empty()) {
+        return Err(format!("Valor inválido para {}: '{}' (tag vazia)", flag, value));
+    }
+    Ok(value)
+}
+
+/// Caminho posicional ou de `--config`; apenas um é aceito.
+fn set_config_path(config_path: &mut Option<String>, path: String) -> Result<(), String> {
+    if config_path.replace(path).is_some() {
+        return Err("Apenas um arquivo de configuração é aceito".to_string());
+    }
+    Ok(())
+}
+
+/// Formato de `--config-format`: `toml` ou `json`.
+fn config_format_value(flag: &str, value: Option<String>) -> Result<ConfigFormat, String> {
+    let value = text_value(flag, value)?;
+    ConfigFormat::parse(&value)
+        .ok_or_else(|| format!("Valor inválido para {}: '{}' (toml ou json)", flag, value))
+}
+
+/// Formato de `--format`: `ndjson`, `csv`, `parquet` ou `avro`.
+fn format_value(flag: &str, value: Option<String>) -> Result<StreamFormat, String> {
+    let value = text_value(flag, value)?;
+    StreamFormat::parse(&value).ok_or_else(|| {
+        format!(
+            "Valor inválido para {}: '{}' (ndjson, csv, parquet ou avro)",
+            flag, value
+        )
+    })
+}
@@ -0,0 +1,518 @@
+//! # Colunas Calculadas
+//!
+//! `computed_columns` acrescenta colunas derivadas das existentes, a partir de
+//! expressões simples convertidas em expressões do Polars:
+//!
+//! ```toml
+//! computed_columns = { total = "quantidade * preco_unitario", ano = "year(data)" }
+//! ```
+//!
+//! ## Sintaxe
+//! - colunas pelo nome (`preco`) ou entre crases (`` `preço unitário` ``);
+//! - números (`2`, `0.5`) e textos entre aspas simples (`'-'`);
+//! - `+ - * /` com a precedência usual (`/` sempre decimal), `-` unário e parênteses;
+//! - `year(coluna)`, `month(coluna)`, `day(coluna)`: datas, timestamps ou
+//!   texto ISO 8601 (`2024-03-15`, `2024-03-15T10:00:00`);
+//! - `concat(a, b, ...)`: concatena os argumentos como texto.
+//!
+//! ## Regras
+//! - Todas as expressões enxergam apenas as colunas normalizadas: uma coluna
+//!   calculada não pode citar outra.
+//! - Nulos se propagam (`concat` com um nulo resulta nulo).
+//! - Textos que não são datas ISO 8601 viram nulo e entram no relatório de
+//!   coerções, com o nome da coluna de origem.
+//! - A sintaxe é validada na carga; colunas ausentes e tipos incompatíveis
+//!   falham o endpoint na conversão.
+
+use polars::prelude::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::errors::ProcessorError;
+use crate::processor::CoercionReport;
+
+/// Expressão já interpretada.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Column(String),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Negate(Box<Node>),
+    Binary(Box<Node>, Arithmetic, Box<Node>),
+    Date(DatePart, String),
+    Concat(Vec<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Arithmetic {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DatePart {
+    Year,
+    Month,
+    Day,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Quoted(String),
+    Number(String),
+    Text(String),
+    Symbol(char),
+}
+
+/// Confere a sintaxe de uma expressão (validação da configuração).
+///
+/// # Erros
+/// Mensagem com o primeiro problema encontrado.
+pub fn check(expression: &str) -> Result<(), String> {
+    parse(expression).map(|_| ())
+}
+
+/// Acrescenta as colunas de `expressions` (nome → expressão) ao `dataframe`.
+///
+/// Textos de `year()`/`month()`/`day()` que não são datas viram nulo e são
+/// somados em `coercions`.
+///
+/// # Erros
+/// `ProcessorError::Schema` se uma expressão citar coluna inexistente, criar
+/// uma coluna que já existe ou falhar na avaliação (ex: tipos incompatíveis).
+pub fn apply(
+    dataframe: DataFrame,
+    expressions: &BTreeMap<String, String>,
+    coercions: &mut CoercionReport,
+) -> Result<DataFrame, ProcessorError> {
+    if expressions.is_empty() {
+        return Ok(dataframe);
+    }
+
+    let mut columns = Vec::with_capacity(expressions.len());
+    let mut text_dates = BTreeSet::new();
+    for (name, expression) in expressions {
+        if dataframe.column(name).is_ok() {
+            return Err(ProcessorError::Schema(format!(
+                "computed_columns: a coluna '{}' já existe na saída",
+                name
+            )));
+        }
+        let node = parse(expression)
+            .map_err(|e| ProcessorError::Schema(format!("computed_columns.{}: {}", name, e)))?;
+        columns.push(to_expr(&node, &dataframe, name)?.alias(name.as_str()));
+        node.text_dates(&dataframe, &mut text_dates);
+    }
+
+    // Cada coluna de texto conta uma vez, mesmo citada por várias expressões
+    for column in text_dates {
+        let values = dataframe.column(&column)?;
+        let parsed = dataframe
+            .clone()
+            .lazy()
+            .select([text_date(&column)])
+            .collect()
+            .map_err(|e| ProcessorError::Schema(format!("computed_columns: falha na avaliação: {}", e)))?;
+        let stats = coercions.entry(column).or_default();
+        stats.attempted += values.len() - values.null_count();
+        stats.failed += parsed[0].null_count().saturating_sub(values.null_count());
+    }
+
+    dataframe
+        .lazy()
+        .with_columns(columns)
+        .collect()
+        .map_err(|e| ProcessorError::Schema(format!("computed_columns: falha na avaliação: {}", e)))
+}
+
+/// Expressão do Polars para `node`, conferindo as colunas citadas.
+fn to_expr(node: &Node, dataframe: &DataFrame, name: &str) -> Result<Expr, ProcessorError> {
+    let column = |column: &str| {
+        dataframe.column(column).map_err(|_| {
+            ProcessorError::Schema(format!(
+                "computed_columns.{}: coluna '{}' não existe na saída",
+                name, column
+            ))
+        })
+    };
+
+    Ok(match node {
+        Node::Column(column_name) => {
+            column(column_name)?;
+            col(column_name.as_str())
+        }
+        Node::Integer(value) => lit(*value),
+        Node::Float(value) => lit(*value),
+        Node::Text(value) => lit(value.as_str()),
+        Node::Negate(inner) => lit(0) - to_expr(inner, dataframe, name)?,
+        Node::Binary(left, arithmetic, right) => {
+            let (left, right) = (to_expr(left, dataframe, name)?, to_expr(right, dataframe, name)?);
+            match arithmetic {
+                Arithmetic::Add => left + right,
+                Arithmetic::Subtract => left - right,
+                Arithmetic::Multiply => left * right,
+                // Divisão real, também entre inteiros (3 / 2 = 1.5)
+                Arithmetic::Divide => binary_expr(left, Operator::TrueDivide, right),
+            }
+        }
+        Node::Date(part, column_name) => {
+            let values = column(column_name)?;
+            let dtype = values.dtype();
+            let value = match dtype {
+                DataType::Date | DataType::Datetime(_, _) => col(column_name.as_str()),
+                DataType::String => text_date(column_name),
+                _ => {
+                    return Err(ProcessorError::Schema(format!(
+                        "computed_columns.{}: '{}' ({}) não é data, timestamp nem texto",
+                        name, column_name, dtype
+                    )));
+                }
+            };
+            match part {
+                DatePart::Year => value.dt().year(),
+                DatePart::Month => value.dt().month(),
+                DatePart::Day => value.dt().day(),
+            }
+        }
+        Node::Concat(parts) => {
+            let mut parts = parts
+                .iter()
+                .map(|part| Ok(to_expr(part, dataframe, name)?.cast(DataType::String)))
+                .collect::<Result<Vec<_>, ProcessorError>>()?
+                .into_iter();
+            let first = parts.next().unwrap_or_else(|| lit(""));
+            parts.fold(first, |text, part| text + part)
+        }
+    })
+}
+
+/// Texto ISO 8601 como timestamp, linha a linha: `2024-03-15T10:00:00` ou,
+/// na falta dele, `2024-03-15` (meia-noite). Outros valores viram nulo.
+fn text_date(column: &str) -> Expr {
+    let timestamp = DataType::Datetime(TimeUnit::Microseconds, None);
+    let datetime = col(column).cast(timestamp.clone());
+    when(datetime.clone().is_not_null())
+        .then(datetime)
+        .otherwise(col(column).cast(DataType::Date).cast(timestamp))
+}
+
+impl Node {
+    /// Colunas de texto lidas como data por `year()`/`month()`/`day()`.
+    fn text_dates(&self, dataframe: &DataFrame, out: &mut BTreeSet<String>) {
+        match self {
+            Node::Date(_, column) => {
+                if dataframe.column(column).is_ok_and(|values| values.dtype() == &DataType::String) {
+                    out.insert(column.clone());
+                }
+            }
+            Node::Negate(inner) => inner.text_dates(dataframe, out),
+            Node::Binary(left, _, right) => {
+                left.text_dates(dataframe, out);
+                right.text_dates(dataframe, out);
+            }
+            Node::Concat(parts) => parts.iter().for_each(|part| part.text_dates(dataframe, out)),
+            Node::Column(_) | Node::Integer(_) | Node::Float(_) | Node::Text(_) => {}
+        }
+    }
+}
+
+fn parse(expression: &str) -> Result<Node, String> {
+    let tokens = tokenize(expression)?;
+    if tokens.is_empty() {
+        return Err("expressão vazia".to_string());
+    }
+    let mut parser = Parser { tokens, position: 0 };
+    let node = parser.sum()?;
+    match parser.tokens.get(parser.position) {
+        None => Ok(node),
+        Some(token) => Err(format!("trecho inesperado: {}", describe(token))),
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' | '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(Token::Symbol(c));
+            }
+            '\'' | '`' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => text.push(next),
+                        None => return Err(format!("{} sem fechamento", c)),
+                    }
+                }
+                tokens.push(if c == '`' { Token::Quoted(text) } else { Token::Text(text) });
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some(&next) = chars.peek().filter(|next| next.is_ascii_digit() || **next == '.') {
+                    number.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = String::new();
+                while let Some(&next) = chars.peek().filter(|next| next.is_alphanumeric() || **next == '_') {
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Name(word));
+            }
+            c => return Err(format!("caractere inesperado '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Name(name) => format!("'{}'", name),
+        Token::Quoted(name) => format!("`{}`", name),
+        Token::Number(number) => number.clone(),
+        Token::Text(text) => format!("'{}'", text),
+        Token::Symbol(symbol) => format!("'{}'", symbol),
+    }
+}
+
+/// Descida recursiva: soma → produto → unário → primário.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.tokens.get(self.position) == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn sum(&mut self) -> Result<Node, String> {
+        let mut node = self.product()?;
+        loop {
+            let arithmetic = if self.eat('+') {
+                Arithmetic::Add
+            } else if self.eat('-') {
+                Arithmetic::Subtract
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(Box::new(node), arithmetic, Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Node, String> {
+        let mut node = self.unary()?;
+        loop {
+            let arithmetic = if self.eat('*') {
+                Arithmetic::Multiply
+            } else if self.eat('/') {
+                Arithmetic::Divide
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(Box::new(node), arithmetic, Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.eat('-') {
+            return Ok(Node::Negate(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Symbol('(')) => {
+                let node = self.sum()?;
+                if !self.eat(')') {
+                    return Err("')' esperado".to_string());
+                }
+                Ok(node)
+            }
+            Some(Token::Number(number)) => {
+                if let Ok(value) = number.parse::<i64>() {
+                    Ok(Node::Integer(value))
+                } else {
+                    number
+                        .parse::<f64>()
+                        .map(Node::Float)
+                        .map_err(|_| format!("número inválido '{}'", number))
+                }
+            }
+            Some(Token::Text(text)) => Ok(Node::Text(text)),
+            Some(Token::Quoted(name)) => Ok(Node::Column(name)),
+            Some(Token::Name(name)) if self.eat('(') => self.call(&name),
+            Some(Token::Name(name)) => Ok(Node::Column(name)),
+            Some(token) => Err(format!("trecho inesperado: {}", describe(&token))),
+            None => Err("expressão incompleta".to_string()),
+        }
+    }
+
+    /// Chamada de função, já após o `(`.
+    fn call(&mut self, function: &str) -> Result<Node, String> {
+        let mut arguments = Vec::new();
+        if !self.eat(')') {
+            loop {
+                arguments.push(self.sum()?);
+                if self.eat(')') {
+                    break;
+                }
+                if !self.eat(',') {
+                    return Err(format!("',' ou ')' esperado em {}()", function));
+                }
+            }
+        }
+
+        let part = match function {
+            "concat" if arguments.is_empty() => return Err("concat() exige ao menos um argumento".to_string()),
+            "concat" => return Ok(Node::Concat(arguments)),
+            "year" => DatePart::Year,
+            "month" => DatePart::Month,
+            "day" => DatePart::Day,
+            other => {
+                return Err(format!(
+                    "função desconhecida '{}' (use year, month, day ou concat)",
+                    other
+                ));
+            }
+        };
+        match <[Node; 1]>::try_from(arguments) {
+            Ok([Node::Column(column)]) => Ok(Node::Date(part, column)),
+            _ => Err(format!("{}() aceita uma única coluna", function)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str) -> Box<Node> {
+        Box::new(Node::Column(name.to_string()))
+    }
+
+    fn expressions(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(name, expression)| (name.to_string(), expression.to_string())).collect()
+    }
+
+    #[test]
+    fn parse_respects_precedence_and_parentheses() {
+        assert_eq!(
+            parse("a + b * 2").unwrap(),
+            Node::Binary(
+                column("a"),
+                Arithmetic::Add,
+                Box::new(Node::Binary(column("b"), Arithmetic::Multiply, Box::new(Node::Integer(2))))
+            )
+        );
+        assert_eq!(
+            parse("-(a - 0.5) / `preço unitário`").unwrap(),
+            Node::Binary(
+                Box::new(Node::Negate(Box::new(Node::Binary(
+                    column("a"),
+                    Arithmetic::Subtract,
+                    Box::new(Node::Float(0.5))
+                )))),
+                Arithmetic::Divide,
+                column("preço unitário")
+            )
+        );
+    }
+
+    #[test]
+    fn parse_reads_functions() {
+        assert_eq!(parse("year(data)").unwrap(), Node::Date(DatePart::Year, "data".to_string()));
+        assert_eq!(
+            parse("concat(uf, '-', id)").unwrap(),
+            Node::Concat(vec![Node::Column("uf".to_string()), Node::Text("-".to_string()), Node::Column("id".to_string())])
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_expressions() {
+        for expression in ["", "a +", "(a", "'aberto", "a # b", "a b", "year(a, b)", "month(1)", "concat()", "upper(a)"] {
+            assert!(check(expression).is_err(), "{expression:?} deveria falhar");
+        }
+    }
+
+    #[test]
+    fn apply_evaluates_arithmetic_and_concat() {
+        let dataframe = df!("quantidade" => [3i64, 4], "preco" => [2.5, 1.0], "uf" => ["SP", "RJ"]).unwrap();
+        let mut coercions = CoercionReport::new();
+
+        let out = apply(
+            dataframe,
+            &expressions(&[
+                ("total", "quantidade * preco"),
+                ("metade", "quantidade / 2"),
+                ("rotulo", "concat(uf, '-', quantidade)"),
+            ]),
+            &mut coercions,
+        )
+        .unwrap();
+
+        let floats = |name: &str| out.column(name).unwrap().f64().unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(floats("total"), [Some(7.5), Some(4.0)]);
+        assert_eq!(floats("metade"), [Some(1.5), Some(2.0)]);
+        let labels: Vec<_> = out.column("rotulo").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(labels, [Some("SP-3"), Some("RJ-4")]);
+        assert!(coercions.is_empty());
+    }
+
+    #[test]
+    fn apply_parses_mixed_text_dates_and_reports_failures() {
+        let dataframe = df!("data" => [Some("2024-03-15T10:00:00"), Some("2023-12-01"), Some(""), Some("ontem"), None]).unwrap();
+        let mut coercions = CoercionReport::new();
+
+        let out = apply(
+            dataframe,
+            &expressions(&[("ano", "year(data)"), ("mes", "month(data)"), ("dia", "day(data)")]),
+            &mut coercions,
+        )
+        .unwrap();
+
+        let years: Vec<_> = out.column("ano").unwrap().i32().unwrap().into_iter().collect();
+        assert_eq!(years, [Some(2024), Some(2023), None, None, None]);
+        let months: Vec<_> = out.column("mes").unwrap().cast(&DataType::Int32).unwrap().i32().unwrap().into_iter().collect();
+        assert_eq!(months, [Some(3), Some(12), None, None, None]);
+        // Três expressões sobre a mesma coluna: contada uma única vez
+        assert_eq!((coercions["data"].attempted, coercions["data"].failed), (4, 2));
+    }
+
+    #[test]
+    fn apply_rejects_missing_and_existing_columns() {
+        let dataframe = df!("id" => [1i64], "data" => [1.5]).unwrap();
+        let mut coercions = CoercionReport::new();
+
+        for pairs in [[("novo", "ausente + 1")], [("id", "1")], [("ano", "year(data)")]] {
+            let result = apply(dataframe.clone(), &expressions(&pairs), &mut coercions);
+            assert!(matches!(result, Err(ProcessorError::Schema(_))), "{pairs:?}");
+        }
+    }
+}
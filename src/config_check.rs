@@ -0,0 +1,307 @@
+//! # Versão e Chaves da Configuração
+//!
+//! Checagens sobre o TOML bruto (após `[defaults]`), antes da tipagem:
+//!
+//! - `config_version = N` declara a versão do formato usada pelo arquivo. Um
+//!   binário que só conhece versões anteriores recusa a carga, em vez de
+//!   ignorar em silêncio as opções que não entende.
+//! - `deny_unknown_keys = true` recusa chaves desconhecidas nas seções
+//!   `[http]`, `[pipeline]`, nas tabelas de API, em `basic_auth` e em `retry`.
+//!
+//! ## Rotas
+//! Nos grupos, toda chave que não é uma opção vira rota: `compresion = "zstd"`
+//! seria baixado como endpoint. No modo estrito, rotas cujo nome difere de uma
+//! opção de grupo por uma ou duas letras são recusadas como erro de digitação.
+
+use serde::Deserialize;
+use serde::de::{self, Deserializer, Visitor};
+
+use std::cell::Cell;
+
+use crate::errors::ProcessorError;
+use crate::models::{ApiConfig, BasicAuth, HTTP_SECTION, HttpSettings, PIPELINE_SECTION, PipelineSettings, RetryPolicy};
+
+/// Chave de topo com a versão do formato da configuração.
+pub const VERSION_KEY: &str = "config_version";
+
+/// Chave de topo que liga o modo estrito.
+pub const STRICT_KEY: &str = "deny_unknown_keys";
+
+/// Versão mais recente do formato que este binário entende.
+pub const SUPPORTED_VERSION: i64 = 1;
+
+/// Opções de grupo (campos de `EndpointGroup` fora de `routes`).
+///
+/// O `flatten` das rotas impede o serde de listar os campos do grupo: novas
+/// opções precisam entrar aqui para que o modo estrito aponte seus erros de digitação.
+const GROUP_OPTIONS: &[&str] = &[
+    "enabled",
+    "root_path",
+    "extract",
+    "jq_filter",
+    "json_path",
+    "on_duplicate_column",
+    "input_format",
+    "output_format",
+    "compression",
+    "zstd_workers",
+    "delta_merge_schema",
+    "parquet_version",
+    "field_ids",
+    "bloom_filter_columns",
+    "post_compress",
+    "keep_uncompressed",
+    "verify_after_write",
+    "write_success_marker",
+    "output_template",
+    "on_exists",
+    "write_empty",
+    "empty_schema",
+    "record_element",
+    "csv",
+    "reconcile",
+    "report_cardinality",
+    "approx_cardinality",
+    "status_field",
+    "expected_status",
+    "status_message_field",
+    "big_number_columns",
+    "null_values",
+    "float_special",
+    "trim_columns",
+    "trim_all_strings",
+    "collapse_whitespace",
+    "transform_script",
+    "sort_by",
+    "batch_size",
+    "max_columns",
+    "required_columns",
+    "columns_first",
+    "max_decompress_ratio",
+    "byte_array_columns",
+    "stringify_lists",
+    "list_separator",
+    "run_id_column",
+    "add_api_column",
+    "add_group_column",
+    "computed_columns",
+    "hash_columns",
+    "hash_algorithm",
+    "hash_salt",
+    "source_glob",
+    "name_template",
+    "depends_on",
+    "tags",
+    "pagination",
+    "multipart",
+    "body_file",
+    "compress_request",
+    "retry_on_empty",
+    "resume_downloads",
+    "accept_status",
+];
+
+/// Valida `config_version` e, com `deny_unknown_keys`, as chaves do arquivo.
+///
+/// # Erros
+/// `ProcessorError::Schema` com a versão não suportada ou a lista de chaves recusadas.
+pub fn check(table: &toml::Table) -> Result<(), ProcessorError> {
+    check_version(table)?;
+
+    match table.get(STRICT_KEY) {
+        None | Some(toml::Value::Boolean(false)) => return Ok(()),
+        Some(toml::Value::Boolean(true)) => {}
+        Some(_) => return Err(ProcessorError::Schema(format!("{} deve ser true ou false", STRICT_KEY))),
+    }
+
+    let mut issues = Vec::new();
+    for (name, value) in table {
+        match (name.as_str(), value) {
+            (VERSION_KEY | STRICT_KEY, _) => {}
+            (HTTP_SECTION, toml::Value::Table(section)) => {
+                unknown_keys(section, struct_fields::<HttpSettings>(), &format!("[{}]", name), &mut issues);
+            }
+            (PIPELINE_SECTION, toml::Value::Table(section)) => {
+                unknown_keys(section, struct_fields::<PipelineSettings>(), &format!("[{}]", name), &mut issues);
+            }
+            (_, toml::Value::Table(api)) => check_api(name, api, &mut issues),
+            // Tabelas de API são as únicas outras entradas de topo
+            _ => issues.push(format!("chave de topo desconhecida '{}'{}", name, suggestion(name, &[VERSION_KEY, STRICT_KEY]))),
+        }
+    }
+
+    if issues.is_empty() {
+        return Ok(());
+    }
+    Err(ProcessorError::Schema(format!(
+        "Chaves recusadas por {} ({} problema(s)):\n  - {}",
+        STRICT_KEY,
+        issues.len(),
+        issues.join("\n  - ")
+    )))
+}
+
+fn check_version(table: &toml::Table) -> Result<(), ProcessorError> {
+    match table.get(VERSION_KEY) {
+        None => Ok(()),
+        Some(toml::Value::Integer(version)) if *version > SUPPORTED_VERSION => Err(ProcessorError::Schema(format!(
+            "{} = {} exige um binário mais novo (este suporta até {})",
+            VERSION_KEY, version, SUPPORTED_VERSION
+        ))),
+        Some(toml::Value::Integer(version)) if *version >= 1 => Ok(()),
+        Some(value) => Err(ProcessorError::Schema(format!(
+            "{} deve ser um inteiro >= 1 (recebido {})",
+            VERSION_KEY, value
+        ))),
+    }
+}
+
+fn check_api(name: &str, api: &toml::Table, issues: &mut Vec<String>) {
+    unknown_keys(api, struct_fields::<ApiConfig>(), &format!("[{}]", name), issues);
+
+    if let Some(toml::Value::Table(auth)) = api.get("basic_auth") {
+        unknown_keys(auth, struct_fields::<BasicAuth>(), &format!("[{}.basic_auth]", name), issues);
+    }
+    if let Some(toml::Value::Table(retry)) = api.get("retry") {
+        unknown_keys(retry, struct_fields::<RetryPolicy>(), &format!("[{}.retry]", name), issues);
+    }
+
+    let Some(toml::Value::Table(groups)) = api.get("endpoints") else {
+        return;
+    };
+    for (group_name, group) in groups {
+        let Some(group) = group.as_table() else {
+            continue;
+        };
+        // Só valores de texto viram rota; os demais já falham na tipagem
+        for key in group.keys().filter(|k| group[*k].is_str() && !GROUP_OPTIONS.contains(&k.as_str())) {
+            let hint = suggestion(key, GROUP_OPTIONS);
+            if !hint.is_empty() {
+                issues.push(format!("'{}.{}': rota '{}'{}", name, group_name, key, hint));
+            }
+        }
+    }
+}
+
+/// Anexa a `issues` as chaves de `section` fora de `known`.
+fn unknown_keys(section: &toml::Table, known: &[&str], scope: &str, issues: &mut Vec<String>) {
+    for key in section.keys().filter(|k| !known.contains(&k.as_str())) {
+        issues.push(format!("{}: chave desconhecida '{}'{}", scope, key, suggestion(key, known)));
+    }
+}
+
+/// Sugestão ` (quis dizer 'x'?)` quando `key` está a uma ou duas edições de um nome conhecido.
+///
+/// Nomes curtos toleram uma única edição: `uf` não deve sugerir `csv`.
+fn suggestion(key: &str, known: &[&str]) -> String {
+    known
+        .iter()
+        .map(|name| (edit_distance(key, name), name))
+        .filter(|(distance, name)| *distance > 0 && distance * 4 <= name.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| format!(" (quis dizer '{}'?)", name))
+        .unwrap_or_default()
+}
+
+/// Distância de Levenshtein entre dois nomes.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Nomes dos campos de uma struct, como o `derive(Deserialize)` os declara.
+///
+/// Structs com `flatten` não declaram seus campos (ver `GROUP_OPTIONS`).
+fn struct_fields<T: for<'de> Deserialize<'de>>() -> &'static [&'static str] {
+    let fields = Cell::new(&[][..]);
+    let _ = T::deserialize(FieldNames(&fields));
+    fields.get()
+}
+
+/// Deserializer que apenas registra os campos pedidos em `deserialize_struct`.
+struct FieldNames<'c>(&'c Cell<&'static [&'static str]>);
+
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("apenas structs"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.set(fields);
+        Err(de::Error::custom("campos registrados"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_toml(content: &str) -> Result<(), String> {
+        check(&toml::from_str(content).unwrap()).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_keys_with_a_suggestion() {
+        let config = r#"
+            [http]
+            timeout_sec = 30
+
+            [compras]
+            base_url = "https://api.gov.br"
+            [compras.endpoints.itens]
+            compresion = "zstd"
+            pedidos = "/pedidos"
+        "#;
+
+        // Fora do modo estrito nada é conferido
+        assert!(check_toml(config).is_ok());
+        let message = check_toml(&format!("deny_unknown_keys = true\n{}", config)).unwrap_err();
+        assert!(message.contains("(2 problema(s))"), "{message}");
+        assert!(message.contains("[http]: chave desconhecida 'timeout_sec' (quis dizer 'timeout_secs'?)"), "{message}");
+        assert!(message.contains("'compras.itens': rota 'compresion' (quis dizer 'compression'?)"), "{message}");
+        // Rotas sem semelhança com uma opção continuam aceitas
+        assert!(!message.contains("pedidos"), "{message}");
+    }
+
+    #[test]
+    fn newer_config_versions_are_rejected() {
+        assert!(check_toml(&format!("config_version = {}", SUPPORTED_VERSION)).is_ok());
+        let message = check_toml(&format!("config_version = {}", SUPPORTED_VERSION + 1)).unwrap_err();
+        assert!(message.contains("exige um binário mais novo"), "{message}");
+        assert!(check_toml("config_version = 0").is_err());
+        assert!(check_toml("config_version = \"1\"").is_err());
+    }
+
+    #[test]
+    fn suggestion_uses_edit_distance_relative_to_the_name() {
+        assert_eq!(edit_distance("compresion", "compression"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(suggestion("root_pth", GROUP_OPTIONS), " (quis dizer 'root_path'?)");
+        // Nomes curtos toleram menos edições: `uf` não vira `csv`
+        assert_eq!(suggestion("uf", &["csv"]), "");
+        assert_eq!(suggestion("pedidos", GROUP_OPTIONS), "");
+    }
+}
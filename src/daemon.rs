@@ -0,0 +1,96 @@
+//! # Modo Daemon (Execução Periódica)
+//!
+//! Com `--interval N`, o pipeline roda em ciclos de N segundos.
+//! Antes de cada ciclo o arquivo de configuração é relido, aplicando
+//! endpoints adicionados/removidos sem reiniciar o processo.
+//!
+//! ## Resiliência
+//! Uma configuração malformada na recarga não derruba o daemon: o erro é
+//! logado e a última configuração válida continua em uso.
+
+use crate::models::{Config, ConfigFormat};
+
+/// Descreve as diferenças de endpoints entre duas configurações.
+///
+/// Cada linha indica `+ api.grupo.chave` (novo) ou `- api.grupo.chave` (removido).
+pub fn describe_changes(old: &Config, new: &Config) -> Vec<String> {
+    let before = old.endpoint_ids();
+    let after = new.endpoint_ids();
+
+    let added = after.difference(&before).map(|id| format!("+ {}", id));
+    let removed = before.difference(&after).map(|id| format!("- {}", id));
+
+    added.chain(removed).collect()
+}
+
+/// Relê a configuração, preservando a atual em caso de erro.
+pub fn reload_config(path: &str, format: Option<ConfigFormat>, current: Config) -> Config {
+    match Config::load(path, format) {
+        Ok(new) => {
+            let changes = describe_changes(&current, &new);
+            if changes.is_empty() {
+                log::info!("Configuração recarregada: sem alterações de endpoints");
+            } else {
+                log::info!("Configuração recarregada ({} alteração(ões)):", changes.len());
+                for change in &changes {
+                    log::info!("  {}", change);
+                }
+            }
+            new
+        }
+        Err(e) => {
+            log::error!(
+                "Falha ao recarregar configuração (mantendo a última válida): {}",
+                e
+            );
+            current
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const CONFIG: &str = r#"
+[compras]
+base_url = "https://api.exemplo.gov.br"
+
+[compras.endpoints.itens]
+pedidos = "/pedidos"
+"#;
+
+    #[test]
+    fn reload_picks_up_endpoints_added_between_cycles() {
+        let path = std::env::temp_dir().join(format!("data_gov_daemon_{}_reload.toml", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        fs::write(&path, CONFIG).unwrap();
+        let current = Config::load(path_str, None).unwrap();
+
+        // Entre dois ciclos, o arquivo ganha um endpoint
+        fs::write(&path, format!("{}contratos = \"/contratos\"\n", CONFIG)).unwrap();
+        let new = Config::load(path_str, None).unwrap();
+        assert_eq!(describe_changes(&current, &new), ["+ compras.itens.contratos"]);
+        assert_eq!(describe_changes(&new, &current), ["- compras.itens.contratos"]);
+
+        let reloaded = reload_config(path_str, None, current);
+        assert!(reloaded.endpoint_ids().contains("compras.itens.contratos"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn malformed_reload_keeps_last_good_config() {
+        let path = std::env::temp_dir().join(format!("data_gov_daemon_{}_malformed.toml", std::process::id()));
+        let path_str = path.to_str().unwrap();
+        fs::write(&path, CONFIG).unwrap();
+        let current = Config::load(path_str, None).unwrap();
+        let before = current.endpoint_ids();
+
+        fs::write(&path, "[compras\nbase_url = ").unwrap();
+        let reloaded = reload_config(path_str, None, current);
+
+        assert_eq!(reloaded.endpoint_ids(), before);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+//! # Dead Letter
+//!
+//! Com `[pipeline] dead_letter_dir`, o staging de uma conversão que falhou é
+//! movido para `{dir}/{api}/{grupo}/{run_id}/`, com um `{arquivo}.error.json`
+//! ao lado de cada payload (endpoint, origem, etapa e erro). O JSON bruto pode
+//! ser inspecionado ou reprocessado sem um novo download.
+//!
+//! Sem a opção, o staging permanece no diretório do grupo, como antes. Falhas
+//! após a escrita (JSON Schema, `post_compress`) não têm staging a mover: ele já
+//! foi removido com a saída gravada.
+
+use chrono::Utc;
+use serde_json::json;
+
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::errors::ProcessorError;
+
+/// Sufixo do arquivo com o erro, ao lado de cada payload.
+const SIDECAR_SUFFIX: &str = ".error.json";
+
+/// Contexto de uma conversão que falhou, gravado no sidecar.
+pub struct Failure<'a> {
+    /// Identificador `api.grupo.chave` da saída.
+    pub endpoint: &'a str,
+    /// URL (sem senha) ou arquivo de origem.
+    pub source: &'a str,
+    pub run_id: &'a str,
+    pub error: &'a ProcessorError,
+}
+
+/// Move os `inputs` ainda existentes para `dir`, cada um com o seu sidecar.
+///
+/// # Returns
+/// Os novos caminhos dos payloads.
+///
+/// # Erros
+/// `ProcessorError::Io` se um arquivo não puder ser movido ou o sidecar gravado.
+pub fn store(dir: &Path, inputs: &[PathBuf], failure: &Failure) -> Result<Vec<PathBuf>, ProcessorError> {
+    let inputs: Vec<&PathBuf> = inputs.iter().filter(|path| path.exists()).collect();
+    if inputs.is_empty() {
+        return Ok(Vec::new());
+    }
+    fs::create_dir_all(dir).map_err(ProcessorError::Io)?;
+
+    let mut stored = Vec::with_capacity(inputs.len());
+    for (page, input) in inputs.iter().enumerate() {
+        let target = dir.join(input.file_name().unwrap_or_default());
+        move_file(input, &target)?;
+
+        let sidecar = sidecar_path(&target);
+        let document = json!({
+            "endpoint": failure.endpoint,
+            "source": failure.source,
+            "run_id": failure.run_id,
+            "failed_at": Utc::now().to_rfc3339(),
+            "error": failure.error.to_string(),
+            "original_path": input.display().to_string(),
+            "page": page + 1,
+            "pages": inputs.len(),
+        });
+        atomic::write(&sidecar, |file| {
+            let mut out = BufWriter::new(file);
+            serde_json::to_writer_pretty(&mut out, &document)?;
+            out.flush().map_err(ProcessorError::Io)
+        })?;
+        stored.push(target);
+    }
+    Ok(stored)
+}
+
+/// Sidecar do payload (`dados_temp.json` → `dados_temp.json.error.json`).
+fn sidecar_path(payload: &Path) -> PathBuf {
+    let mut name = payload.as_os_str().to_owned();
+    name.push(SIDECAR_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// `rename`, com cópia e remoção quando o destino está em outro sistema de arquivos.
+fn move_file(from: &Path, to: &Path) -> Result<(), ProcessorError> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(ProcessorError::Io)?;
+    fs::remove_file(from).map_err(ProcessorError::Io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_moves_each_payload_next_to_its_error_sidecar() {
+        let root = std::env::temp_dir().join(format!("data_gov_dead_letter_{}_store", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let staging = root.join("data/compras/itens");
+        fs::create_dir_all(&staging).unwrap();
+        let pages = [staging.join("pedidos_temp_1.json"), staging.join("pedidos_temp_2.json")];
+        fs::write(&pages[0], r#"[{"id": 1}]"#).unwrap();
+        fs::write(&pages[1], "{quebrado").unwrap();
+        // Páginas já removidas não entram na contagem
+        let inputs = [pages[0].clone(), staging.join("ausente.json"), pages[1].clone()];
+        let error = ProcessorError::Schema("JSON inválido na página 2".to_string());
+        let failure = Failure {
+            endpoint: "compras.itens.pedidos",
+            source: "https://api.gov.br/pedidos",
+            run_id: "execucao-1",
+            error: &error,
+        };
+        let dir = root.join("dead/compras/itens/execucao-1");
+
+        let stored = store(&dir, &inputs, &failure).unwrap();
+
+        assert_eq!(stored, [dir.join("pedidos_temp_1.json"), dir.join("pedidos_temp_2.json")]);
+        assert!(pages.iter().all(|page| !page.exists()));
+        assert_eq!(fs::read_to_string(&stored[1]).unwrap(), "{quebrado");
+        let sidecar: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("pedidos_temp_2.json.error.json")).unwrap()).unwrap();
+        assert_eq!(sidecar["endpoint"], "compras.itens.pedidos");
+        assert_eq!(sidecar["source"], "https://api.gov.br/pedidos");
+        assert_eq!(sidecar["run_id"], "execucao-1");
+        assert_eq!(sidecar["error"], error.to_string());
+        assert_eq!(sidecar["original_path"], pages[1].display().to_string());
+        assert_eq!((sidecar["page"].as_u64(), sidecar["pages"].as_u64()), (Some(2), Some(2)));
+        assert!(dir.join("pedidos_temp_1.json.error.json").exists());
+
+        // Sem payloads restantes, nada é criado
+        let empty = root.join("dead/vazio");
+        assert!(store(&empty, &inputs, &failure).unwrap().is_empty());
+        assert!(!empty.exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,632 @@
+//! # Tabelas Delta Lake
+//!
+//! `output_format = "delta"` grava cada endpoint como uma tabela Delta
+//! (`{chave}.delta/`): arquivos Parquet de dados e o log de transações em
+//! `_delta_log/`. A primeira execução cria a tabela (versão 0); cada execução
+//! seguinte anexa uma nova versão com os dados convertidos.
+//!
+//! ## Protocolo
+//! O log é escrito diretamente (leitor 1, escritor 2), sem partições nem
+//! checkpoints. Cada commit é um `_delta_log/{versão:020}.json` publicado de
+//! forma exclusiva: se outro escritor criar a mesma versão antes, o commit
+//! falha e o arquivo de dados órfão é removido. Tabelas que exijam protocolos
+//! mais novos, partições ou checkpoints são recusadas.
+//!
+//! ## Evolução de Schema
+//! Segue as regras de append do Delta:
+//! - colunas da tabela ausentes no lote são gravadas como nulas;
+//! - colunas novas falham o endpoint, exceto com `delta_merge_schema = true`,
+//!   que as acrescenta ao final do schema da tabela;
+//! - tipos divergentes falham, exceto alargamentos seguros (Int32 → Int64/Float64,
+//!   Float32 → Float64) e colunas totalmente nulas no lote.
+//!
+//! Colunas aninhadas (listas e structs) precisam coincidir exatamente.
+
+use chrono::Utc;
+use polars::prelude::*;
+use serde_json::{Map, Value, json};
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::Path;
+
+use crate::atomic;
+use crate::errors::ProcessorError;
+use crate::models::{EndpointGroup, OutputFormat};
+use crate::open_files;
+use crate::processor;
+
+/// Diretório do log de transações dentro da tabela.
+const LOG_DIR: &str = "_delta_log";
+
+/// Versões de protocolo implementadas.
+const READER_VERSION: u64 = 1;
+const WRITER_VERSION: u64 = 2;
+
+/// Caracteres proibidos em nomes de coluna (sem column mapping).
+const INVALID_NAME_CHARS: [char; 10] = [' ', ',', ';', '{', '}', '(', ')', '\n', '\t', '='];
+
+/// Verdadeiro se `path` é uma tabela Delta (tem `_delta_log/`).
+pub fn is_table(path: &Path) -> bool {
+    path.join(LOG_DIR).is_dir()
+}
+
+/// Anexa o DataFrame como uma nova versão da tabela, criando-a se preciso.
+///
+/// # Returns
+/// A versão gravada.
+///
+/// # Erros
+/// `ProcessorError::Schema` para schemas incompatíveis (ver módulo);
+/// `ProcessorError::Parquet` para logs ilegíveis, protocolos não suportados e
+/// conflitos de commit.
+pub fn append(table: &Path, dataframe: &DataFrame, group: &EndpointGroup) -> Result<u64, ProcessorError> {
+    let snapshot = if is_table(table) {
+        let snapshot = Snapshot::load(table)?;
+        if snapshot.min_writer > WRITER_VERSION {
+            return Err(delta_error(table, format!(
+                "exige escritor versão {} (suportada: {})",
+                snapshot.min_writer, WRITER_VERSION
+            )));
+        }
+        snapshot.check_unpartitioned(table)?;
+        Some(snapshot)
+    } else {
+        None
+    };
+
+    let existing = snapshot.as_ref().map(|s| s.fields(table)).transpose()?;
+    let (mut data, fields) = conform(dataframe, existing.as_deref(), group.delta_merge_schema)
+        .map_err(|issues| {
+            ProcessorError::Schema(format!(
+                "Schema incompatível com a tabela Delta '{}':\n  - {}",
+                table.display(),
+                issues.join("\n  - ")
+            ))
+        })?;
+
+    fs::create_dir_all(table.join(LOG_DIR)).map_err(ProcessorError::Io)?;
+
+    // Dados primeiro: invisíveis aos leitores até o commit citá-los
+    let file_name = format!("part-00000-{}-c000.parquet", uuid::Uuid::new_v4());
+    let data_path = table.join(&file_name);
+    atomic::write(&data_path, |file| {
+        processor::write_format(file, &mut data, OutputFormat::Parquet, group)
+    })?;
+    let size = fs::metadata(&data_path).map_err(ProcessorError::Io)?.len();
+
+    let now = Utc::now().timestamp_millis();
+    let schema_string = json!({ "type": "struct", "fields": fields }).to_string();
+    let mut actions = vec![json!({
+        "commitInfo": {
+            "timestamp": now,
+            "operation": "WRITE",
+            "operationParameters": { "mode": "Append", "partitionBy": "[]" },
+            "engineInfo": format!("data_gov/{}", env!("CARGO_PKG_VERSION")),
+            "isBlindAppend": true,
+        }
+    })];
+    match &snapshot {
+        None => {
+            actions.push(json!({
+                "protocol": { "minReaderVersion": READER_VERSION, "minWriterVersion": WRITER_VERSION }
+            }));
+            actions.push(json!({
+                "metaData": {
+                    "id": uuid::Uuid::new_v4().to_string(),
+                    "format": { "provider": "parquet", "options": {} },
+                    "schemaString": schema_string,
+                    "partitionColumns": [],
+                    "configuration": {},
+                    "createdTime": now,
+                }
+            }));
+        }
+        // Schema alterado (delta_merge_schema): nova metaData, demais campos preservados
+        Some(snapshot) if existing.as_deref() != Some(fields.as_slice()) => {
+            let mut metadata = snapshot.metadata.clone();
+            metadata.insert("schemaString".to_string(), json!(schema_string));
+            actions.push(json!({ "metaData": metadata }));
+        }
+        Some(_) => {}
+    }
+    actions.push(json!({
+        "add": {
+            "path": file_name,
+            "partitionValues": {},
+            "size": size,
+            "modificationTime": now,
+            "dataChange": true,
+            "stats": json!({ "numRecords": data.height() }).to_string(),
+        }
+    }));
+
+    let version = snapshot.map_or(0, |s| s.version + 1);
+    if let Err(e) = commit(table, version, &actions) {
+        let _ = fs::remove_file(&data_path);
+        return Err(e);
+    }
+    log::info!("Delta: versão {} de {}", version, table.display());
+    Ok(version)
+}
+
+/// Lê a versão mais recente da tabela no schema vigente.
+///
+/// Arquivos gravados antes de uma evolução de schema recebem as colunas
+/// novas como nulas.
+pub fn read_table(table: &Path) -> Result<DataFrame, ProcessorError> {
+    let snapshot = Snapshot::load(table)?;
+    let schema = snapshot.schema(table)?;
+
+    let mut result = DataFrame::empty_with_schema(&schema);
+    for path in &snapshot.files {
+        let file = File::open(table.join(path)).map_err(ProcessorError::Io)?;
+        let frame = ParquetReader::new(file)
+            .finish()
+            .map_err(|e| ProcessorError::Parquet(format!("Falha ao ler '{}': {}", path, e)))?;
+        let columns: Vec<Column> = schema
+            .iter()
+            .map(|(name, dtype)| match frame.column(name) {
+                Ok(column) => column.cast(dtype),
+                Err(_) => Ok(Column::full_null(name.clone(), frame.height(), dtype)),
+            })
+            .collect::<PolarsResult<_>>()?;
+        result.vstack_mut(&DataFrame::new(columns)?)?;
+    }
+    result.align_chunks_par();
+    Ok(result)
+}
+
+/// Schema da versão mais recente, sem ler os arquivos de dados.
+pub fn schema(table: &Path) -> Result<Schema, ProcessorError> {
+    Snapshot::load(table)?.schema(table)
+}
+
+/// Estado da tabela na última versão, reconstruído pelo replay do log.
+struct Snapshot {
+    version: u64,
+    min_reader: u64,
+    min_writer: u64,
+    /// Ação `metaData` vigente (campos desconhecidos preservados na evolução).
+    metadata: Map<String, Value>,
+    /// Arquivos de dados ativos (`add` sem `remove` posterior).
+    files: BTreeSet<String>,
+}
+
+impl Snapshot {
+    fn load(table: &Path) -> Result<Self, ProcessorError> {
+        let log = table.join(LOG_DIR);
+        if log.join("_last_checkpoint").exists() {
+            return Err(delta_error(table, "tabelas com checkpoints não são suportadas".to_string()));
+        }
+
+        let mut commits: Vec<u64> = fs::read_dir(&log)
+            .map_err(ProcessorError::Io)?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                let stem = name.strip_suffix(".json")?;
+                (stem.len() == 20).then(|| stem.parse().ok()).flatten()
+            })
+            .collect();
+        commits.sort_unstable();
+        if let Some(gap) = (0..).zip(&commits).find(|(expected, version)| expected != *version) {
+            return Err(delta_error(table, format!("log sem a versão {}", gap.0)));
+        }
+        let Some(&version) = commits.last() else {
+            return Err(delta_error(table, "log de transações vazio".to_string()));
+        };
+
+        let (mut min_reader, mut min_writer) = (0, 0);
+        let mut metadata = None;
+        let mut files = BTreeSet::new();
+        for commit in &commits {
+            let path = log.join(format!("{:020}.json", commit));
+            let file = File::open(&path).map_err(ProcessorError::Io)?;
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(ProcessorError::Io)?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let action: Map<String, Value> = serde_json::from_str(&line)?;
+                if let Some(protocol) = action.get("protocol") {
+                    min_reader = protocol["minReaderVersion"].as_u64().unwrap_or(0);
+                    min_writer = protocol["minWriterVersion"].as_u64().unwrap_or(0);
+                }
+                if let Some(Value::Object(m)) = action.get("metaData") {
+                    metadata = Some(m.clone());
+                }
+                if let Some(path) = action.get("add").and_then(|a| a["path"].as_str()) {
+                    files.insert(path.to_string());
+                }
+                if let Some(path) = action.get("remove").and_then(|r| r["path"].as_str()) {
+                    files.remove(path);
+                }
+            }
+        }
+
+        let metadata = metadata.ok_or_else(|| delta_error(table, "log sem metaData".to_string()))?;
+        Ok(Self { version, min_reader, min_writer, metadata, files })
+    }
+
+    /// Campos do `schemaString` vigente.
+    fn fields(&self, table: &Path) -> Result<Vec<Value>, ProcessorError> {
+        let schema: Value = self.metadata["schemaString"]
+            .as_str()
+            .map(serde_json::from_str)
+            .transpose()?
+            .unwrap_or(Value::Null);
+        match schema.get("fields") {
+            Some(Value::Array(fields)) => Ok(fields.clone()),
+            _ => Err(delta_error(table, "schemaString inválido".to_string())),
+        }
+    }
+
+    /// Schema vigente em tipos Polars, checados o protocolo de leitura e a partição.
+    fn schema(&self, table: &Path) -> Result<Schema, ProcessorError> {
+        if self.min_reader > READER_VERSION {
+            return Err(delta_error(table, format!(
+                "exige leitor versão {} (suportada: {})",
+                self.min_reader, READER_VERSION
+            )));
+        }
+        self.check_unpartitioned(table)?;
+
+        self.fields(table)?
+            .iter()
+            .map(|field| {
+                let dtype = polars_type(&field["type"]).map_err(|e| delta_error(table, e))?;
+                Ok(polars::prelude::Field::new(field_name(field).into(), dtype))
+            })
+            .collect()
+    }
+
+    /// Valores de partição vêm do caminho dos arquivos: não suportados.
+    fn check_unpartitioned(&self, table: &Path) -> Result<(), ProcessorError> {
+        match self.metadata.get("partitionColumns") {
+            Some(Value::Array(columns)) if !columns.is_empty() => {
+                Err(delta_error(table, "tabelas particionadas não são suportadas".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Publica o commit `version` com as ações, uma por linha.
+///
+/// O arquivo completo é ligado ao nome final com `hard_link`, que falha se a
+/// versão já existir: dois escritores nunca gravam a mesma versão.
+fn commit(table: &Path, version: u64, actions: &[Value]) -> Result<(), ProcessorError> {
+    let path = table.join(LOG_DIR).join(format!("{:020}.json", version));
+    let tmp = atomic::temp_path(&path);
+    let _permit = open_files::acquire();
+    let written = File::create(&tmp).and_then(|mut file| {
+        for action in actions {
+            writeln!(file, "{}", action)?;
+        }
+        file.sync_all()
+    });
+    let published = written.and_then(|_| fs::hard_link(&tmp, &path));
+    let _ = fs::remove_file(&tmp);
+
+    match published {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => Err(delta_error(
+            table,
+            format!("a versão {} foi gravada por outro escritor", version),
+        )),
+        Err(e) => Err(ProcessorError::Io(e)),
+    }
+}
+
+/// Alinha o lote ao schema da tabela (ver regras no módulo).
+///
+/// # Returns
+/// O lote na ordem das colunas da tabela e o schema resultante (campos Delta).
+///
+/// # Erros
+/// Todos os problemas encontrados, reportados juntos.
+fn conform(
+    dataframe: &DataFrame,
+    existing: Option<&[Value]>,
+    merge: bool,
+) -> Result<(DataFrame, Vec<Value>), Vec<String>> {
+    let mut issues = Vec::new();
+    let mut seen = HashMap::new();
+    for name in dataframe.get_column_names() {
+        if name.contains(INVALID_NAME_CHARS) {
+            issues.push(format!("'{}': nome de coluna com caractere inválido para o Delta", name));
+        }
+        // Nomes de coluna no Delta não diferenciam maiúsculas
+        if let Some(other) = seen.insert(name.to_lowercase(), name) {
+            issues.push(format!("'{}' e '{}' diferem apenas em maiúsculas", other, name));
+        }
+    }
+
+    let mut fields = existing.map(<[Value]>::to_vec).unwrap_or_default();
+    let mut columns = Vec::with_capacity(dataframe.width());
+
+    // Colunas da tabela, na ordem do schema
+    for field in &fields {
+        let name = field_name(field);
+        let table_type = &field["type"];
+        let target = match polars_type(table_type) {
+            Ok(target) => target,
+            Err(e) => {
+                issues.push(format!("'{}': {}", name, e));
+                continue;
+            }
+        };
+        let Ok(column) = dataframe.column(name) else {
+            if field["nullable"] == json!(false) {
+                issues.push(format!("'{}': coluna não anulável ausente do lote", name));
+            }
+            columns.push(Column::full_null(name.into(), dataframe.height(), &target));
+            continue;
+        };
+
+        let accepted = column.dtype() == &DataType::Null
+            || match compatible(column.dtype()) {
+                Ok(dtype) => {
+                    type_name(&delta_type(&dtype)) == type_name(table_type)
+                        || widens(&dtype, &target) && type_name(&delta_type(&target)) == type_name(table_type)
+                }
+                Err(_) => false,
+            };
+        if !accepted {
+            let batch_type = compatible(column.dtype())
+                .map(|dtype| type_name(&delta_type(&dtype)))
+                .unwrap_or_else(|_| column.dtype().to_string());
+            issues.push(format!("'{}': {} na tabela, {} no lote", name, type_name(table_type), batch_type));
+            continue;
+        }
+        match column.strict_cast(&target) {
+            Ok(column) => columns.push(column),
+            Err(e) => issues.push(format!("'{}': {}", name, e)),
+        }
+    }
+
+    // Colunas novas, na ordem do lote
+    let mut added = Vec::new();
+    for column in dataframe.get_columns() {
+        let name = column.name().as_str();
+        if existing.is_some_and(|fields| fields.iter().any(|f| field_name(f) == name)) {
+            continue;
+        }
+        if let Some(field) = existing
+            .into_iter()
+            .flatten()
+            .find(|f| field_name(f).eq_ignore_ascii_case(name))
+        {
+            issues.push(format!(
+                "'{}' difere apenas em maiúsculas da coluna '{}' da tabela",
+                name,
+                field_name(field)
+            ));
+            continue;
+        }
+
+        let target = match compatible(column.dtype()) {
+            Ok(target) => target,
+            Err(e) => {
+                issues.push(format!("'{}': {}", name, e));
+                continue;
+            }
+        };
+        match column.strict_cast(&target) {
+            Ok(column) => columns.push(column),
+            Err(e) => {
+                issues.push(format!("'{}': {}", name, e));
+                continue;
+            }
+        }
+        fields.push(json!({ "name": name, "type": delta_type(&target), "nullable": true, "metadata": {} }));
+        added.push(name);
+    }
+    if existing.is_some() && !merge && !added.is_empty() {
+        issues.push(format!(
+            "colunas fora do schema da tabela: {} (delta_merge_schema = true as acrescenta)",
+            added.join(", ")
+        ));
+    }
+
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+    let data = DataFrame::new(columns).map_err(|e| vec![e.to_string()])?;
+    Ok((data, fields))
+}
+
+/// Tipo Polars gravável no Delta equivalente a `dtype`.
+fn compatible(dtype: &DataType) -> Result<DataType, String> {
+    match dtype {
+        DataType::Boolean
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::Float32
+        | DataType::Float64
+        | DataType::String
+        | DataType::Binary
+        | DataType::Date => Ok(dtype.clone()),
+        // Sem inteiros sem sinal no Delta; valores acima de i64 falham no cast
+        DataType::UInt32 | DataType::UInt64 => Ok(DataType::Int64),
+        DataType::Datetime(_, _) => Ok(DataType::Datetime(TimeUnit::Microseconds, None)),
+        DataType::Null | DataType::Time => Ok(DataType::String),
+        DataType::List(inner) | DataType::Array(inner, _) => {
+            Ok(DataType::List(Box::new(compatible(inner)?)))
+        }
+        DataType::Struct(fields) => fields
+            .iter()
+            .map(|f| Ok(polars::prelude::Field::new(f.name().clone(), compatible(f.dtype())?)))
+            .collect::<Result<_, String>>()
+            .map(DataType::Struct),
+        other => Err(format!("tipo {} sem equivalente no Delta", other)),
+    }
+}
+
+/// Tipo Delta (JSON do `schemaString`) de um dtype já `compatible`.
+fn delta_type(dtype: &DataType) -> Value {
+    match dtype {
+        DataType::Boolean => json!("boolean"),
+        DataType::Int32 => json!("integer"),
+        DataType::Int64 => json!("long"),
+        DataType::Float32 => json!("float"),
+        DataType::Float64 => json!("double"),
+        DataType::Binary => json!("binary"),
+        DataType::Date => json!("date"),
+        DataType::Datetime(_, _) => json!("timestamp"),
+        DataType::List(inner) => json!({
+            "type": "array",
+            "elementType": delta_type(inner),
+            "containsNull": true,
+        }),
+        DataType::Struct(fields) => {
+            let fields: Vec<Value> = fields
+                .iter()
+                .map(|f| json!({ "name": f.name().as_str(), "type": delta_type(f.dtype()), "nullable": true, "metadata": {} }))
+                .collect();
+            json!({ "type": "struct", "fields": fields })
+        }
+        // String e os demais tipos, que `compatible` já converteu em texto
+        _ => json!("string"),
+    }
+}
+
+/// Tipo Polars de leitura de um tipo Delta.
+fn polars_type(delta: &Value) -> Result<DataType, String> {
+    let dtype = match delta {
+        Value::String(name) => match name.as_str() {
+            "boolean" => DataType::Boolean,
+            // Sem Int8/Int16 habilitados no Polars: lidos como Int32
+            "byte" | "short" | "integer" => DataType::Int32,
+            "long" => DataType::Int64,
+            "float" => DataType::Float32,
+            "double" => DataType::Float64,
+            "string" => DataType::String,
+            "binary" => DataType::Binary,
+            "date" => DataType::Date,
+            "timestamp" | "timestamp_ntz" => DataType::Datetime(TimeUnit::Microseconds, None),
+            other => return Err(format!("tipo Delta '{}' não suportado", other)),
+        },
+        Value::Object(object) => match object.get("type").and_then(Value::as_str) {
+            Some("array") => DataType::List(Box::new(polars_type(&object["elementType"])?)),
+            Some("struct") => {
+                let fields = object["fields"].as_array().map(Vec::as_slice).unwrap_or_default();
+                DataType::Struct(
+                    fields
+                        .iter()
+                        .map(|f| Ok(polars::prelude::Field::new(field_name(f).into(), polars_type(&f["type"])?)))
+                        .collect::<Result<_, String>>()?,
+                )
+            }
+            _ => return Err(format!("tipo Delta {} não suportado", type_name(delta))),
+        },
+        other => return Err(format!("tipo Delta {} não suportado", other)),
+    };
+    Ok(dtype)
+}
+
+/// Alargamentos aceitos sem perda ao gravar em uma coluna existente.
+fn widens(from: &DataType, to: &DataType) -> bool {
+    matches!(
+        (from, to),
+        (DataType::Int32, DataType::Int64)
+            | (DataType::Int32, DataType::Float64)
+            | (DataType::Float32, DataType::Float64)
+    )
+}
+
+/// Forma canônica de um tipo Delta (ex: `array<struct<id:long>>`), sem
+/// nulidade nem metadados: base das comparações e das mensagens.
+fn type_name(delta: &Value) -> String {
+    match delta {
+        Value::String(name) => name.clone(),
+        Value::Object(object) => match object.get("type").and_then(Value::as_str) {
+            Some("array") => format!("array<{}>", type_name(&object["elementType"])),
+            Some("map") => format!(
+                "map<{},{}>",
+                type_name(&object["keyType"]),
+                type_name(&object["valueType"])
+            ),
+            Some("struct") => {
+                let fields: Vec<String> = object["fields"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|f| format!("{}:{}", field_name(f), type_name(&f["type"])))
+                    .collect();
+                format!("struct<{}>", fields.join(","))
+            }
+            _ => delta.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+fn field_name(field: &Value) -> &str {
+    field["name"].as_str().unwrap_or_default()
+}
+
+fn delta_error(table: &Path, message: String) -> ProcessorError {
+    ProcessorError::Parquet(format!("Tabela Delta '{}': {}", table.display(), message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(toml: &str) -> EndpointGroup {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn table(name: &str) -> std::path::PathBuf {
+        let table = std::env::temp_dir().join(format!("data_gov_delta_{}_{}/pedidos.delta", std::process::id(), name));
+        let _ = fs::remove_dir_all(table.parent().unwrap());
+        table
+    }
+
+    #[test]
+    fn append_writes_one_log_version_per_call() {
+        let table = table("append");
+        let group = group("");
+
+        let first = df!("id" => [1i64, 2], "nome" => ["a", "b"]).unwrap();
+        let second = df!("id" => [3i64], "nome" => ["c"]).unwrap();
+        assert_eq!(append(&table, &first, &group).unwrap(), 0);
+        assert_eq!(append(&table, &second, &group).unwrap(), 1);
+
+        assert!(table.join("_delta_log/00000000000000000000.json").is_file());
+        assert!(table.join("_delta_log/00000000000000000001.json").is_file());
+        assert!(!table.join("_delta_log/00000000000000000002.json").exists());
+
+        // A ordem dos arquivos de dados não é garantida
+        let out = read_table(&table).unwrap().sort(["id"], Default::default()).unwrap();
+        assert_eq!(out.height(), 3);
+        assert_eq!(out.slice(0, 2), first);
+        assert_eq!(out.slice(2, 1), second);
+        assert_eq!(schema(&table).unwrap(), first.schema().as_ref().clone());
+        fs::remove_dir_all(table.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn new_columns_need_delta_merge_schema() {
+        let table = table("merge");
+        let first = df!("id" => [1i64, 2]).unwrap();
+        let second = df!("id" => [3i64], "valor" => [9.5f64]).unwrap();
+        append(&table, &first, &group("")).unwrap();
+
+        let rejected = append(&table, &second, &group(""));
+        assert!(matches!(rejected, Err(ProcessorError::Schema(_))), "{rejected:?}");
+        assert!(!table.join("_delta_log/00000000000000000001.json").exists());
+
+        assert_eq!(append(&table, &second, &group("delta_merge_schema = true")).unwrap(), 1);
+        let out = read_table(&table).unwrap().sort(["id"], Default::default()).unwrap();
+        assert_eq!(out.get_column_names(), ["id", "valor"]);
+        let ids: Vec<_> = out.column("id").unwrap().i64().unwrap().into_iter().collect();
+        let valores: Vec<_> = out.column("valor").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(ids, [Some(1), Some(2), Some(3)]);
+        // Linhas da versão 0 recebem a coluna nova como nula
+        assert_eq!(valores, [None, None, Some(9.5)]);
+        fs::remove_dir_all(table.parent().unwrap()).unwrap();
+    }
+}
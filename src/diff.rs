@@ -0,0 +1,430 @@
+//! # Comparação de Saídas (`diff`)
+//!
+//! Compara duas saídas Parquet do pipeline (ex: antes e depois de uma mudança
+//! de configuração), casando as linhas por uma ou mais colunas-chave:
+//!
+//! - colunas adicionadas, removidas ou com tipo alterado;
+//! - linhas adicionadas (só na nova) e removidas (só na antiga);
+//! - por coluna em comum, quantas linhas casadas mudaram de valor.
+//!
+//! ```text
+//! data_gov diff ANTIGO.parquet NOVO.parquet --key id [--key ...] [--output diff.parquet]
+//! ```
+//!
+//! ## Saída em Parquet
+//! Com `--output`, as linhas divergentes são gravadas com a coluna `_diff`
+//! (`added`, `removed` ou `changed`); linhas alteradas trazem os valores novos.
+//!
+//! ## Tabelas Delta
+//! Um lado pode ser uma tabela Delta (`output_format = "delta"`): é comparada
+//! a versão mais recente.
+//!
+//! ## Saídas Comprimidas
+//! Arquivos de `post_compress` (`.parquet.xz`, `.parquet.br`, `.parquet.gz`)
+//! são descomprimidos em memória antes da leitura.
+//!
+//! ## Chaves
+//! As chaves precisam existir nos dois arquivos, sem nulos nem repetições:
+//! do contrário, o casamento das linhas seria ambíguo.
+
+use polars::prelude::*;
+
+use std::fmt;
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::archive;
+use crate::atomic;
+use crate::delta;
+use crate::errors::ProcessorError;
+
+/// Coluna com o tipo de divergência na saída em Parquet.
+pub const DIFF_COLUMN: &str = "_diff";
+
+/// Marca as linhas que encontraram par no outro arquivo (junção à esquerda).
+const MATCHED: &str = "__diff_matched";
+
+/// Prefixo das colunas do arquivo antigo na junção das linhas casadas.
+const OLD_PREFIX: &str = "__diff_old_";
+
+/// Resumo das diferenças entre duas saídas.
+#[derive(Debug, Default)]
+pub struct DiffReport {
+    pub old_rows: usize,
+    pub new_rows: usize,
+    pub added_columns: Vec<String>,
+    pub removed_columns: Vec<String>,
+    /// Colunas em comum com tipo alterado (nome, tipo antigo, tipo novo).
+    pub retyped_columns: Vec<(String, DataType, DataType)>,
+    pub added_rows: usize,
+    pub removed_rows: usize,
+    /// Linhas casadas com ao menos um valor diferente.
+    pub changed_rows: usize,
+    /// Linhas casadas com valor diferente, por coluna em comum (apenas > 0).
+    pub changed_values: Vec<(String, usize)>,
+}
+
+impl DiffReport {
+    /// Verdadeiro quando as saídas têm o mesmo schema e os mesmos valores.
+    pub fn is_empty(&self) -> bool {
+        self.added_columns.is_empty()
+            && self.removed_columns.is_empty()
+            && self.retyped_columns.is_empty()
+            && self.added_rows == 0
+            && self.removed_rows == 0
+            && self.changed_rows == 0
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Linhas: {} → {} (+{} adicionadas, -{} removidas, {} alteradas)",
+            self.old_rows, self.new_rows, self.added_rows, self.removed_rows, self.changed_rows
+        )?;
+        if !self.added_columns.is_empty() {
+            writeln!(f, "Colunas adicionadas: {}", self.added_columns.join(", "))?;
+        }
+        if !self.removed_columns.is_empty() {
+            writeln!(f, "Colunas removidas: {}", self.removed_columns.join(", "))?;
+        }
+        for (column, old, new) in &self.retyped_columns {
+            writeln!(f, "Tipo alterado: {} ({} → {})", column, old, new)?;
+        }
+        if !self.changed_values.is_empty() {
+            writeln!(f, "Valores alterados por coluna:")?;
+            for (column, count) in &self.changed_values {
+                writeln!(f, "  {}: {}", column, count)?;
+            }
+        }
+        if self.is_empty() {
+            writeln!(f, "Sem diferenças")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compara `old` e `new` casando as linhas por `keys`.
+///
+/// Com `output`, grava as linhas divergentes (ver módulo) em Parquet.
+///
+/// # Erros
+/// Arquivos ilegíveis, chaves ausentes/nulas/repetidas ou falha na gravação.
+pub fn compare(
+    old_path: &Path,
+    new_path: &Path,
+    keys: &[String],
+    output: Option<&Path>,
+) -> Result<DiffReport, ProcessorError> {
+    let old = read(old_path)?;
+    let new = read(new_path)?;
+    check_keys(&old, keys, old_path)?;
+    check_keys(&new, keys, new_path)?;
+
+    let old_schema = old.schema();
+    let new_schema = new.schema();
+    let mut report = DiffReport {
+        old_rows: old.height(),
+        new_rows: new.height(),
+        ..Default::default()
+    };
+
+    // Colunas não-chave presentes nos dois arquivos, na ordem da saída nova
+    let mut common = Vec::new();
+    for (name, dtype) in new_schema.iter() {
+        match old_schema.get(name) {
+            None => report.added_columns.push(name.to_string()),
+            Some(_) if keys.iter().any(|k| k == name.as_str()) => {}
+            Some(old_dtype) => {
+                if old_dtype != dtype {
+                    report
+                        .retyped_columns
+                        .push((name.to_string(), old_dtype.clone(), dtype.clone()));
+                }
+                common.push(name.to_string());
+            }
+        }
+    }
+    report.removed_columns = old_schema
+        .iter_names()
+        .filter(|name| !new_schema.contains(name))
+        .map(|name| name.to_string())
+        .collect();
+
+    let added = unmatched(&new, &old, keys)?;
+    let removed = unmatched(&old, &new, keys)?;
+    report.added_rows = added.height();
+    report.removed_rows = removed.height();
+
+    let changed_keys = changed(&old, &new, keys, &common, &mut report)?;
+
+    if let Some(output) = output {
+        let changed = matched(&new, &changed_keys, keys)?;
+        write_diff(output, [("added", added), ("removed", removed), ("changed", changed)])?;
+    }
+
+    Ok(report)
+}
+
+fn read(path: &Path) -> Result<DataFrame, ProcessorError> {
+    if delta::is_table(path) {
+        return delta::read_table(path);
+    }
+    let read_error = |e: PolarsError| ProcessorError::Parquet(format!("Falha ao ler '{}': {}", path.display(), e));
+    if let Some(codec) = archive::codec_of(path) {
+        let content = archive::decompress_file(path, codec)?;
+        return ParquetReader::new(Cursor::new(content)).finish().map_err(read_error);
+    }
+    let file = File::open(path).map_err(ProcessorError::Io)?;
+    ParquetReader::new(file).finish().map_err(read_error)
+}
+
+/// As chaves precisam identificar cada linha: presentes, sem nulos, sem repetição.
+fn check_keys(dataframe: &DataFrame, keys: &[String], path: &Path) -> Result<(), ProcessorError> {
+    for key in keys {
+        let column = dataframe.column(key).map_err(|_| {
+            ProcessorError::Parquet(format!("'{}': coluna-chave '{}' ausente", path.display(), key))
+        })?;
+        if column.null_count() > 0 {
+            return Err(ProcessorError::Parquet(format!(
+                "'{}': coluna-chave '{}' tem {} valor(es) nulo(s)",
+                path.display(),
+                key,
+                column.null_count()
+            )));
+        }
+    }
+
+    let distinct = dataframe
+        .select(keys.iter().map(String::as_str))?
+        .unique_stable(None, UniqueKeepStrategy::Any, None)?
+        .height();
+    if distinct != dataframe.height() {
+        return Err(ProcessorError::Parquet(format!(
+            "'{}': chave ({}) repetida em {} linha(s)",
+            path.display(),
+            keys.join(", "),
+            dataframe.height() - distinct
+        )));
+    }
+    Ok(())
+}
+
+/// Linhas de `frame` cuja chave aparece (`present`) ou não em `other`.
+fn by_presence(
+    frame: &DataFrame,
+    other: &DataFrame,
+    keys: &[String],
+    present: bool,
+) -> Result<DataFrame, ProcessorError> {
+    let on: Vec<Expr> = keys.iter().map(|k| col(k.as_str())).collect();
+    let other_keys = other
+        .select(keys.iter().map(String::as_str))?
+        .lazy()
+        .with_column(lit(true).alias(MATCHED));
+
+    let marker = col(MATCHED);
+    let filter = if present { marker.is_not_null() } else { marker.is_null() };
+    let mut rows = frame
+        .clone()
+        .lazy()
+        .join(other_keys, &on, &on, JoinArgs::new(JoinType::Left))
+        .filter(filter)
+        .collect()?;
+    rows.drop_in_place(MATCHED)?;
+    Ok(rows)
+}
+
+fn unmatched(frame: &DataFrame, other: &DataFrame, keys: &[String]) -> Result<DataFrame, ProcessorError> {
+    by_presence(frame, other, keys, false)
+}
+
+fn matched(frame: &DataFrame, other: &DataFrame, keys: &[String]) -> Result<DataFrame, ProcessorError> {
+    by_presence(frame, other, keys, true)
+}
+
+/// Conta as alterações por coluna entre as linhas casadas.
+///
+/// # Returns
+/// As chaves das linhas com ao menos um valor diferente.
+fn changed(
+    old: &DataFrame,
+    new: &DataFrame,
+    keys: &[String],
+    common: &[String],
+    report: &mut DiffReport,
+) -> Result<DataFrame, ProcessorError> {
+    let on: Vec<Expr> = keys.iter().map(|k| col(k.as_str())).collect();
+    let empty_keys = || new.select(keys.iter().map(String::as_str)).map(|df| df.clear());
+    if common.is_empty() {
+        return Ok(empty_keys()?);
+    }
+
+    let old_columns: Vec<Expr> = on
+        .iter()
+        .cloned()
+        .chain(common.iter().map(|c| col(c.as_str()).alias(format!("{}{}", OLD_PREFIX, c))))
+        .collect();
+    let new_columns: Vec<Expr> = on.iter().cloned().chain(common.iter().map(|c| col(c.as_str()))).collect();
+    let joined = new
+        .clone()
+        .lazy()
+        .select(new_columns)
+        .join(old.clone().lazy().select(old_columns), &on, &on, JoinArgs::new(JoinType::Inner));
+
+    // Tipos alterados: números comparados como Float64 (10 e 10.0 não mudam),
+    // demais tipos como texto
+    let differs: Vec<Expr> = common
+        .iter()
+        .map(|c| {
+            let (current, previous) = (col(c.as_str()), col(format!("{}{}", OLD_PREFIX, c)));
+            let retyped = report.retyped_columns.iter().find(|(name, _, _)| name == c);
+            match retyped {
+                Some((_, old, new)) => {
+                    let common_type = if old.is_primitive_numeric() && new.is_primitive_numeric() {
+                        DataType::Float64
+                    } else {
+                        DataType::String
+                    };
+                    current
+                        .cast(common_type.clone())
+                        .neq_missing(previous.cast(common_type))
+                }
+                None => current.neq_missing(previous),
+            }
+        })
+        .collect();
+
+    let counts = joined
+        .clone()
+        .select(
+            differs
+                .iter()
+                .zip(common)
+                .map(|(expr, c)| expr.clone().sum().alias(c.as_str()))
+                .collect::<Vec<_>>(),
+        )
+        .collect()?;
+    for c in common {
+        let count = counts.column(c)?.get(0)?.extract::<usize>().unwrap_or(0);
+        if count > 0 {
+            report.changed_values.push((c.clone(), count));
+        }
+    }
+
+    let changed_keys = joined
+        .filter(any_horizontal(differs)?)
+        .select(on)
+        .collect()?;
+    report.changed_rows = changed_keys.height();
+    Ok(changed_keys)
+}
+
+/// Grava as linhas divergentes com a coluna `_diff`, alinhando os schemas.
+fn write_diff<const N: usize>(
+    output: &Path,
+    parts: [(&str, DataFrame); N],
+) -> Result<(), ProcessorError> {
+    let frames: Vec<LazyFrame> = parts
+        .into_iter()
+        .map(|(kind, frame)| frame.lazy().with_column(lit(kind).alias(DIFF_COLUMN)))
+        .collect();
+    let args = UnionArgs {
+        rechunk: true,
+        to_supertypes: true,
+        diagonal: true,
+        ..Default::default()
+    };
+    let mut diff = concat(frames, args)?.collect()?;
+
+    atomic::write(output, |file| {
+        ParquetWriter::new(file)
+            .finish(&mut diff)
+            .map_err(|e| ProcessorError::Parquet(format!("Erro ao gravar Parquet: {}", e)))?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn compare_reports_known_changes() {
+        let dir = std::env::temp_dir().join(format!("data_gov_diff_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, mut dataframe: DataFrame| {
+            let path = dir.join(name);
+            ParquetWriter::new(File::create(&path).unwrap()).finish(&mut dataframe).unwrap();
+            path
+        };
+        // id 1 igual, id 2 com nome alterado, id 3 removido, id 4 adicionado;
+        // `uf` removida, `ativo` adicionada e `valor` de inteiro para float
+        let old = write(
+            "antigo.parquet",
+            df!("id" => [1i64, 2, 3], "nome" => ["Ana", "Bia", "Caio"], "valor" => [1i64, 2, 3], "uf" => ["SP", "RJ", "MG"])
+                .unwrap(),
+        );
+        let new = write(
+            "novo.parquet",
+            df!("id" => [1i64, 2, 4], "nome" => ["Ana", "Beatriz", "Duda"], "valor" => [1.0f64, 2.0, 4.0], "ativo" => [true, true, false])
+                .unwrap(),
+        );
+        let output = dir.join("diff.parquet");
+
+        let report = compare(&old, &new, &["id".to_string()], Some(&output)).unwrap();
+
+        assert_eq!((report.old_rows, report.new_rows), (3, 3));
+        assert_eq!((report.added_rows, report.removed_rows, report.changed_rows), (1, 1, 1));
+        assert_eq!(report.added_columns, ["ativo"]);
+        assert_eq!(report.removed_columns, ["uf"]);
+        assert_eq!(report.retyped_columns, [("valor".to_string(), DataType::Int64, DataType::Float64)]);
+        assert_eq!(report.changed_values, [("nome".to_string(), 1)]);
+
+        let written = ParquetReader::new(File::open(&output).unwrap()).finish().unwrap();
+        let rows: Vec<(Option<i64>, Option<&str>)> = written
+            .column("id")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_iter()
+            .zip(written.column(DIFF_COLUMN).unwrap().str().unwrap())
+            .collect();
+        assert_eq!(rows, [(Some(4), Some("added")), (Some(3), Some("removed")), (Some(2), Some("changed"))]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn post_compressed_outputs_read_back_like_the_original() {
+        use crate::models::{OutputFormat, PostCompress};
+        use crate::processor;
+
+        let dir = std::env::temp_dir().join(format!("data_gov_diff_{}_compressed", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut dataframe = df!("id" => [1i64, 2, 3], "nome" => [Some("Ana"), None, Some("Caio")]).unwrap();
+        let plain = dir.join("antigo.parquet");
+        ParquetWriter::new(File::create(&plain).unwrap()).finish(&mut dataframe).unwrap();
+
+        for codec in [PostCompress::Gzip, PostCompress::Brotli] {
+            let original = dir.join("novo.parquet");
+            fs::copy(&plain, &original).unwrap();
+            let compressed = archive::compress_file(&original, codec, false).unwrap();
+
+            assert_eq!(read(&compressed).unwrap(), dataframe, "{codec:?}");
+            let report = compare(&plain, &compressed, &["id".to_string()], None).unwrap();
+            assert!(report.is_empty(), "{codec:?}");
+
+            // O conteúdo descomprimido passa pela mesma verificação da gravação
+            let restored = dir.join("restaurado.parquet");
+            fs::write(&restored, archive::decompress_file(&compressed, codec).unwrap()).unwrap();
+            processor::verify_written(&restored, OutputFormat::Parquet, 3).unwrap();
+            fs::remove_file(&compressed).unwrap();
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,6 +6,8 @@
 //! - **Tipagem:** Enums para tratamento exaustivo.
 //! - **Extensibilidade:** Marcado como `non_exhaustive` para permitir evolução sem quebra de contrato.
 
+use serde::Serialize;
+
 /// Enumeração central de falhas do Processador.
 ///
 /// O atributo `#[non_exhaustive]` garante compatibilidade futura,
@@ -26,6 +28,47 @@ pub enum ProcessorError {
 
     /// Violações de regras de negócio ou inconsistência de formato nos dados (ex: Schema mismatch).
     Schema(String),
+
+    /// Documento XML malformado ou com codificação inválida.
+    Xml(String),
+
+    /// O envelope sinalizou falha de negócio (`status_field`) apesar do HTTP 200.
+    UpstreamStatus {
+        /// Valor recebido; None se o campo estava ausente ou nulo.
+        status: Option<String>,
+        expected: String,
+        /// Mensagem da API (`status_message_field`), se disponível.
+        message: Option<String>,
+    },
+
+    /// Erro de uma etapa do pipeline acompanhado de onde ocorreu.
+    /// Anexado ao cruzar a fronteira entre etapas (`run_pipeline`).
+    WithContext {
+        context: ErrorContext,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+/// Localização de uma falha: endpoint, etapa e arquivo/URL de entrada.
+#[derive(Debug, Clone)]
+pub struct ErrorContext {
+    /// Identificador completo `api.grupo.chave`.
+    pub endpoint: String,
+    pub stage: Stage,
+    /// Arquivo (ou URL) consumido pela etapa.
+    pub input: String,
+}
+
+/// Etapas do processamento de um endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    /// Download ou cópia para o staging.
+    Staging,
+    /// Leitura, normalização e escrita do arquivo final.
+    Conversion,
+    /// Compressão pós-escrita (`post_compress`).
+    Compression,
 }
 /// Define erros específicos da camada de API/Rede.
 #[derive(Debug)]
@@ -37,6 +80,8 @@ pub enum ApiError {
     HttpStatusError {
         status: reqwest::StatusCode,
         url: String,
+        /// Espera sugerida pelo servidor (`Retry-After` em segundos), se enviada.
+        retry_after: Option<std::time::Duration>,
     },
 
     /// Falha ao criar diretórios ou escrever no disco.
@@ -44,4 +89,22 @@ pub enum ApiError {
 
     /// O servidor respondeu com sucesso, mas nenhum byte útil foi recebido.
     EmptyResponse,
+
+    /// O corpo recebido não é um JSON válido.
+    InvalidJson(String),
+
+    /// O host da URL não está em `allowed_hosts` (nenhuma conexão é aberta).
+    HostNotAllowed(String),
+
+    /// O redirecionamento excederia `max_redirects` (0: nenhum é seguido).
+    TooManyRedirects { limit: usize, url: String },
+
+    /// Redirecionamento para outro host com `block_cross_host_redirects`.
+    CrossHostRedirect { from: String, to: String },
+
+    /// O gzip expandiu além de `max_decompress_ratio` vezes o tamanho comprimido.
+    DecompressionRatio {
+        compressed: u64,
+        ratio: u64,
+    },
 }
\ No newline at end of file
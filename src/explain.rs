@@ -0,0 +1,552 @@
+//! # Plano de Transformação (`--explain`)
+//!
+//! Lista, para cada endpoint selecionado, as etapas que a execução aplicaria,
+//! na mesma ordem do `processor` e com os parâmetros configurados: origem,
+//! leitura, limpeza, validações, ordenação e gravação. Nada é baixado, lido
+//! ou gravado; serve para entender por que uma saída tem a forma que tem.
+//!
+//! Etapas sempre presentes (remoção das colunas técnicas, decodificação de
+//! listas de bytes) também aparecem: elas alteram a saída tanto quanto as
+//! configuradas.
+
+use std::path::Path;
+
+use crate::archive;
+use crate::json_schema;
+use crate::models::{
+    CsvType, EndpointGroup, FloatSpecial, InputFormat, NullValues, NullsOrder, OnDuplicateColumn, OnExists,
+    OutputFormat, Pagination, ParquetVersion, ReconcileMode,
+};
+use crate::pagination::DEFAULT_MAX_PAGES;
+use crate::pipeline::{RunOptions, SUCCESS_MARKER};
+use crate::processor::{self, TOTAL_KEY};
+use crate::sources::Source;
+
+/// Etapa do plano: nome curto e parâmetros.
+pub struct Step {
+    pub name: &'static str,
+    pub detail: String,
+}
+
+impl Step {
+    fn new(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Etapas de um endpoint, na ordem de execução.
+///
+/// `names` traz os nomes da API e do grupo; `output_path` é o arquivo final
+/// antes de `post_compress`.
+pub fn steps(
+    source: &Source,
+    group: &EndpointGroup,
+    names: (&str, &str),
+    output_path: &Path,
+    options: &RunOptions,
+) -> Vec<Step> {
+    let mut steps = vec![Step::new("origem", origin(source, group))];
+
+    if !group.accept_status.is_empty() {
+        let accepted: Vec<String> = group.accept_status.iter().map(u16::to_string).collect();
+        steps.push(Step::new(
+            "accept_status",
+            format!("sucesso apenas com {}; demais status falham", accepted.join(", ")),
+        ));
+    }
+
+    if group.resume_downloads && matches!(source, Source::Http { .. }) {
+        steps.push(Step::new(
+            "resume_downloads",
+            "baixa em .part; um parcial com ETag é retomado com Range/If-Range",
+        ));
+    }
+
+    if group.input_format == InputFormat::Json && !group.big_number_columns.is_empty() {
+        steps.push(Step::new(
+            "big_number_columns",
+            format!("números longos como texto: {}", group.big_number_columns.join(", ")),
+        ));
+    }
+
+    if !group.extract.is_empty() {
+        let outputs: Vec<String> = group
+            .extract
+            .iter()
+            .map(|(name, pointer)| format!("{} ← '{}'", name, pointer))
+            .collect();
+        steps.push(Step::new(
+            "extract",
+            format!("um staging por array ({}); cada um segue as etapas abaixo", outputs.join(", ")),
+        ));
+    }
+
+    if let Some(filter) = &group.jq_filter {
+        steps.push(Step::new("jq_filter", format!("`{}` (a saída são os registros)", filter)));
+    }
+
+    if let Some(path) = &group.json_path {
+        steps.push(Step::new("json_path", format!("`{}` (os nós selecionados são os registros)", path)));
+    }
+
+    steps.push(Step::new("leitura", reading(group, options)));
+
+    if let Some(field) = &group.status_field {
+        let expected = group
+            .expected_status
+            .as_ref()
+            .map_or("qualquer valor".to_string(), |s| format!("'{}'", s));
+        let message = group
+            .status_message_field
+            .as_ref()
+            .map(|m| format!(", mensagem em '{}'", m))
+            .unwrap_or_default();
+        steps.push(Step::new(
+            "status",
+            format!("'{}' deve ser {}{}; campos removidos da saída", field, expected, message),
+        ));
+    }
+
+    if let Some(path) = group.root_path.as_deref().filter(|p| !p.is_empty()) {
+        let detail = match group.input_format {
+            InputFormat::Json => format!(
+                "desaninha '{}' (colunas repetidas: {})",
+                path,
+                duplicate_policy(group.on_duplicate_column)
+            ),
+            _ => format!("ignorado para input_format = \"{}\"", group.input_format.extension()),
+        };
+        steps.push(Step::new("root_path", detail));
+    }
+
+    if group.write_empty {
+        let schema = match group.empty_schema.as_slice() {
+            [] => "schema da saída anterior".to_string(),
+            columns => columns
+                .iter()
+                .map(|c| format!("{}: {}", c.column, type_name(c.dtype)))
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        steps.push(Step::new("write_empty", format!("sem registros, grava zero linhas ({})", schema)));
+    }
+
+    if let Some(rows) = options.sample {
+        steps.push(Step::new("amostra", format!("primeiras {} linha(s)", rows)));
+    }
+
+    steps.push(Step::new(
+        "colunas técnicas",
+        format!("remove {}", processor::TECHNICAL_COLUMNS.join(", ")),
+    ));
+    match group.byte_array_columns.as_deref() {
+        None => steps.push(Step::new("bytes", "listas de bytes (0-255) viram texto UTF-8")),
+        Some([]) => {}
+        Some(columns) => steps.push(Step::new(
+            "byte_array_columns",
+            format!("{}: listas de bytes (0-255) viram texto UTF-8", columns.join(", ")),
+        )),
+    }
+
+    match group.float_special {
+        FloatSpecial::Null => steps.push(Step::new("float_special", "NaN e ±Infinity viram nulo em colunas numéricas")),
+        FloatSpecial::Error => steps.push(Step::new("float_special", "NaN e ±Infinity em colunas numéricas falham o endpoint")),
+        FloatSpecial::Keep => {}
+    }
+
+    if group.trim_all_strings || !group.trim_columns.is_empty() {
+        let columns = if group.trim_all_strings {
+            "todas as colunas de texto".to_string()
+        } else {
+            group.trim_columns.join(", ")
+        };
+        let collapse = if group.collapse_whitespace { "; espaços internos repetidos viram um" } else { "" };
+        steps.push(Step::new("trim", format!("remove espaços nas bordas de {}{}", columns, collapse)));
+    }
+
+    if let Some(null_values) = &group.null_values {
+        let detail = match null_values {
+            NullValues::All(values) => format!("{} viram nulo em colunas de texto", quoted(values)),
+            NullValues::PerColumn(columns) => {
+                let mut columns: Vec<_> = columns.iter().collect();
+                columns.sort_by_key(|(column, _)| *column);
+                columns
+                    .iter()
+                    .map(|(column, values)| format!("{}: {}", column, quoted(values)))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            }
+        };
+        steps.push(Step::new("null_values", detail));
+    }
+
+    if group.stringify_lists {
+        steps.push(Step::new(
+            "stringify_lists",
+            format!("listas viram texto separado por '{}'", group.list_separator()),
+        ));
+    }
+
+    if let Some(column) = &group.run_id_column {
+        steps.push(Step::new("run_id_column", format!("'{}' = '{}'", column, options.run_id)));
+    }
+    if group.add_api_column {
+        steps.push(Step::new("add_api_column", format!("'{}' = '{}'", processor::API_COLUMN, names.0)));
+    }
+    if group.add_group_column {
+        steps.push(Step::new(
+            "add_group_column",
+            format!("'{}' = '{}'", processor::GROUP_COLUMN, names.1),
+        ));
+    }
+
+    if !group.computed_columns.is_empty() {
+        let columns: Vec<String> = group
+            .computed_columns
+            .iter()
+            .map(|(column, expression)| format!("{} = {}", column, expression))
+            .collect();
+        steps.push(Step::new("computed_columns", columns.join("; ")));
+    }
+
+    #[cfg(feature = "scripting")]
+    if let Some(script) = &group.transform_script {
+        steps.push(Step::new("transform_script", script.clone()));
+    }
+
+    if !group.hash_columns.is_empty() {
+        let salt = if group.hash_salt.is_some() { " com salt" } else { "" };
+        steps.push(Step::new(
+            "hash_columns",
+            format!(
+                "{}{} em {} (nulos mantidos)",
+                group.hash_algorithm.name(),
+                salt,
+                group.hash_columns.join(", ")
+            ),
+        ));
+    }
+
+    if let Some(max) = group.max_columns {
+        steps.push(Step::new("max_columns", format!("falha acima de {} coluna(s)", max)));
+    }
+
+    if !group.required_columns.is_empty() {
+        steps.push(Step::new(
+            "required_columns",
+            format!("exige {}", group.required_columns.join(", ")),
+        ));
+    }
+
+    if !group.columns_first.is_empty() {
+        steps.push(Step::new(
+            "columns_first",
+            format!("{} no início; as demais na ordem de origem", group.columns_first.join(", ")),
+        ));
+    }
+
+    if let Some(mode) = group.reconcile {
+        let detail = match (options.sample, mode) {
+            (Some(_), _) => "ignorada no modo amostra".to_string(),
+            (None, ReconcileMode::Warn) => format!("{} vs. linhas; divergência gera aviso", TOTAL_KEY),
+            (None, ReconcileMode::Fail) => format!("{} vs. linhas; divergência falha o endpoint", TOTAL_KEY),
+        };
+        steps.push(Step::new("reconcile", detail));
+    }
+
+    if group.report_cardinality {
+        let method = if group.approx_cardinality { "estimados (HyperLogLog)" } else { "exatos" };
+        steps.push(Step::new(
+            "report_cardinality",
+            format!("valores distintos por coluna, {}; listas e structs ficam de fora", method),
+        ));
+    }
+
+    if !group.sort_by.is_empty() {
+        let keys: Vec<String> = group
+            .sort_by
+            .iter()
+            .map(|key| {
+                let order = if key.descending { "desc" } else { "asc" };
+                let nulls = match key.nulls {
+                    NullsOrder::First => "nulos primeiro",
+                    NullsOrder::Last => "nulos por último",
+                };
+                format!("{} {} ({})", key.column, order, nulls)
+            })
+            .collect();
+        steps.push(Step::new("sort_by", keys.join(", ")));
+    }
+
+    steps.push(Step::new("gravação", writing(group, output_path, options)));
+
+    if options.stdout.is_none() {
+        if group.verify_after_write {
+            steps.push(Step::new(
+                "verify_after_write",
+                "relê a saída e confere as linhas antes de remover o staging",
+            ));
+        }
+        if options.emit_json_schema {
+            steps.push(Step::new(
+                "json_schema",
+                json_schema::schema_path(output_path).display().to_string(),
+            ));
+        }
+        if let Some(codec) = group.post_compress {
+            let keep = if group.keep_uncompressed { "; mantém o original" } else { "" };
+            steps.push(Step::new(
+                "post_compress",
+                format!(
+                    "{} em {}{}",
+                    codec.extension(),
+                    archive::compressed_path(output_path, codec).display(),
+                    keep
+                ),
+            ));
+        }
+        if group.write_success_marker {
+            let table = if group.output_format == OutputFormat::Delta { " e na tabela" } else { "" };
+            steps.push(Step::new(
+                "write_success_marker",
+                format!(
+                    "{} no diretório do grupo{} após o sucesso de todas as saídas",
+                    SUCCESS_MARKER,
+                    table
+                ),
+            ));
+        }
+    }
+
+    steps
+}
+
+/// Requisição ou arquivo de origem (URLs sem senha).
+fn origin(source: &Source, group: &EndpointGroup) -> String {
+    let location = source.location();
+    match (source, &group.pagination, &group.multipart) {
+        (Source::File(_), _, _) => format!("arquivo local {}", location),
+        (Source::Http { .. }, Some(Pagination::Cursor { next_path, cursor_param, max_pages }), _) => {
+            let param = cursor_param
+                .as_ref()
+                .map_or("a própria URL".to_string(), |p| format!("param '{}'", p));
+            format!(
+                "GET {}; cursor em '{}' ({}), até {} página(s)",
+                location,
+                next_path,
+                param,
+                max_pages.map_or(DEFAULT_MAX_PAGES, |n| n.get())
+            )
+        }
+        (Source::Http { .. }, None, Some(form)) => {
+            let names: Vec<&str> = form.fields.keys().chain(form.files.keys()).map(String::as_str).collect();
+            let gzip = if group.compress_request { ", corpo em gzip" } else { "" };
+            format!("POST multipart {} (campos: {}{})", location, names.join(", "), gzip)
+        }
+        (Source::Http { .. }, None, None) => match &group.body_file {
+            Some(path) => {
+                let gzip = if group.compress_request { ", em gzip" } else { "" };
+                format!("POST {} (corpo de {}{})", location, path, gzip)
+            }
+            None => format!("GET {}", location),
+        },
+    }
+}
+
+fn reading(group: &EndpointGroup, options: &RunOptions) -> String {
+    let format = match group.input_format {
+        InputFormat::Json => "json".to_string(),
+        InputFormat::Xml => format!(
+            "xml (registros <{}>)",
+            group.record_element.as_deref().unwrap_or_default()
+        ),
+        InputFormat::Csv => {
+            let csv = group.csv.clone().unwrap_or_default();
+            let mut types: Vec<String> = csv
+                .schema
+                .iter()
+                .map(|(column, dtype)| format!("{}: {}", column, type_name(*dtype)))
+                .collect();
+            types.sort();
+            let types = if types.is_empty() {
+                String::new()
+            } else {
+                format!(", tipos {}", types.join(", "))
+            };
+            format!(
+                "csv (separador '{}', cabeçalho {}{})",
+                csv.delimiter() as char,
+                if csv.has_header.unwrap_or(true) { "sim" } else { "não" },
+                types
+            )
+        }
+    };
+    match (group.batch_size, options.max_memory_mb) {
+        (Some(size), _) => format!("{} em lotes de {} registro(s)", format, size),
+        (None, Some(mb)) if processor::batch_fallback(group) => {
+            format!("{} (em lotes se a estimativa exceder {} MB)", format, mb)
+        }
+        (None, Some(mb)) => format!("{} (falha se a estimativa exceder {} MB)", format, mb),
+        (None, None) => format,
+    }
+}
+
+fn writing(group: &EndpointGroup, output_path: &Path, options: &RunOptions) -> String {
+    if let Some(format) = options.stdout {
+        return format!("{} no stdout", format.name());
+    }
+
+    let variants = group.compression_variants();
+    let codec = match (group.compression(), group.output_format) {
+        (Some(codec), _) => codec.name().to_string(),
+        (None, _) if !variants.is_empty() => {
+            let names: Vec<&str> = variants.iter().map(|codec| codec.name()).collect();
+            format!("{}; uma variante por codec", names.join(", "))
+        }
+        (None, OutputFormat::Avro) => "uncompressed".to_string(),
+        (None, _) => "snappy".to_string(),
+    };
+    let codec = match group.zstd_workers {
+        Some(workers) if processor::ZSTD_MULTITHREAD => format!("{}; {} threads", codec, workers),
+        Some(workers) => format!("{}; zstd_workers = {} ignorado, sem suporte no backend", codec, workers),
+        None => codec,
+    };
+    let format = match group.output_format {
+        OutputFormat::Parquet => {
+            let version = match group.parquet_version {
+                ParquetVersion::V1 => "v1",
+                ParquetVersion::V2 => "v2",
+            };
+            let field_ids = if group.field_ids.is_empty() {
+                String::new()
+            } else {
+                let ids: Vec<String> = group
+                    .field_ids
+                    .iter()
+                    .map(|(column, id)| format!("{}={}", column, id))
+                    .collect();
+                format!(", field IDs {}", ids.join(", "))
+            };
+            let bloom = if group.bloom_filter_columns.is_empty() {
+                String::new()
+            } else {
+                format!(", filtros de Bloom em {}", group.bloom_filter_columns.join(", "))
+            };
+            format!("parquet {} ({}{}{})", version, codec, field_ids, bloom)
+        }
+        OutputFormat::Avro => format!("avro ({})", codec),
+        OutputFormat::Delta => {
+            let merge = if group.delta_merge_schema { ", mergeSchema" } else { "" };
+            format!("delta ({}{}), nova versão", codec, merge)
+        }
+    };
+    let paths: Vec<String> = match variants {
+        [] => vec![output_path.display().to_string()],
+        codecs => codecs
+            .iter()
+            .map(|codec| processor::variant_path(output_path, *codec).display().to_string())
+            .collect(),
+    };
+    format!(
+        "{} em {} (on_exists = \"{}\")",
+        format,
+        paths.join(", "),
+        on_exists_name(group.on_exists)
+    )
+}
+
+fn on_exists_name(policy: OnExists) -> &'static str {
+    match policy {
+        OnExists::Overwrite => "overwrite",
+        OnExists::Error => "error",
+        OnExists::Skip => "skip",
+        OnExists::Timestamped => "timestamped",
+    }
+}
+
+fn duplicate_policy(policy: OnDuplicateColumn) -> &'static str {
+    match policy {
+        OnDuplicateColumn::Error => "error",
+        OnDuplicateColumn::Suffix => "suffix",
+        OnDuplicateColumn::First => "first",
+    }
+}
+
+fn type_name(dtype: CsvType) -> &'static str {
+    match dtype {
+        CsvType::String => "string",
+        CsvType::Int64 => "int64",
+        CsvType::Float64 => "float64",
+        CsvType::Boolean => "boolean",
+    }
+}
+
+fn quoted(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|v| format!("'{}'", v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn steps_follow_the_processor_order() {
+        let group: EndpointGroup = toml::from_str(
+            r#"
+            root_path = "resultado"
+            trim_all_strings = true
+            null_values = ["N/A"]
+            add_api_column = true
+            computed_columns = { ano = "year(data)" }
+            required_columns = ["id"]
+            columns_first = ["id"]
+            sort_by = [{ column = "id", descending = true }]
+            post_compress = "gzip"
+            write_success_marker = true
+            "#,
+        )
+        .unwrap();
+        let options = RunOptions {
+            run_id: "execucao-1".to_string(),
+            sample: Some(10),
+            ..Default::default()
+        };
+        let source = Source::File(PathBuf::from("in/pedidos.json"));
+
+        let steps = steps(&source, &group, ("compras", "itens"), Path::new("data/compras/itens/pedidos.parquet"), &options);
+
+        let names: Vec<&str> = steps.iter().map(|step| step.name).collect();
+        assert_eq!(
+            names,
+            [
+                "origem",
+                "leitura",
+                "root_path",
+                "amostra",
+                "colunas técnicas",
+                "bytes",
+                "float_special",
+                "trim",
+                "null_values",
+                "add_api_column",
+                "computed_columns",
+                "required_columns",
+                "columns_first",
+                "sort_by",
+                "gravação",
+                "post_compress",
+                "write_success_marker",
+            ]
+        );
+        assert_eq!(steps[2].detail, "desaninha 'resultado' (colunas repetidas: error)");
+        assert_eq!(steps[13].detail, "id desc (nulos primeiro)");
+    }
+}
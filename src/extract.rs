@@ -0,0 +1,93 @@
+//! # Extração de Vários Arrays
+//!
+//! Um envelope com vários arrays (ex: `compras` e `contratos`) vira várias
+//! saídas a partir de um único download: `extract` associa nomes de saída a
+//! JSON Pointers (RFC 6901, ex: `/dados/contratos`). Cada array é gravado em um
+//! staging próprio (`{chave}_temp.{nome}.json`) e segue pelas etapas de
+//! conversão como um endpoint independente, gravado em `{chave}_{nome}.{formato}`.
+//!
+//! ## Regras
+//! - Ponteiro ausente no envelope falha o endpoint; `null` vira zero registros.
+//! - O envelope de cada staging é carregado inteiro na memória (sem `batch_size`).
+
+use serde_json::{Map, Value};
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::big_numbers;
+use crate::errors::ProcessorError;
+use crate::models::EndpointGroup;
+use crate::open_files;
+
+/// Chave do envelope de cada staging extraído (usada como `root_path`).
+pub const RECORDS_KEY: &str = "registros";
+
+/// Divide os arquivos de staging em um conjunto por saída de `extract`, na
+/// ordem dos nomes. Os originais são removidos após a divisão.
+///
+/// # Erros
+/// `ProcessorError::Schema` se um ponteiro não existir em algum staging.
+pub fn split(inputs: &[PathBuf], group: &EndpointGroup) -> Result<Vec<(String, Vec<PathBuf>)>, ProcessorError> {
+    // Aplicado antes do parsing, que arredondaria os números longos
+    if !group.big_number_columns.is_empty() {
+        for path in inputs {
+            big_numbers::quote_in_place(path, &group.big_number_columns)?;
+        }
+    }
+
+    let mut outputs: Vec<(String, Vec<PathBuf>)> = group
+        .extract
+        .keys()
+        .map(|name| (name.clone(), Vec::with_capacity(inputs.len())))
+        .collect();
+
+    for path in inputs {
+        let file = File::open(path)?;
+        let mut envelope: Value = serde_json::from_reader(BufReader::new(file))?;
+
+        for ((name, pointer), (_, pages)) in group.extract.iter().zip(&mut outputs) {
+            // Ponteiros não se sobrepõem (validado na carga): `take` não afeta os demais
+            let records = match envelope.pointer_mut(pointer) {
+                Some(Value::Null) => Value::Array(Vec::new()),
+                Some(value) => value.take(),
+                None => {
+                    return Err(ProcessorError::Schema(format!(
+                        "extract.{}: '{}' não encontrado em {}",
+                        name,
+                        pointer,
+                        path.display()
+                    )));
+                }
+            };
+            let target = path.with_extension(format!("{}.json", name));
+            write_records(&target, records)?;
+            pages.push(target);
+        }
+        fs::remove_file(path)?;
+    }
+    Ok(outputs)
+}
+
+/// Grupo usado na conversão de cada saída: registros sob `RECORDS_KEY`.
+pub fn records_group(group: &EndpointGroup) -> EndpointGroup {
+    EndpointGroup {
+        root_path: Some(RECORDS_KEY.to_string()),
+        extract: Default::default(),
+        // Já aplicadas na divisão
+        big_number_columns: Vec::new(),
+        ..group.clone()
+    }
+}
+
+fn write_records(path: &Path, records: Value) -> Result<(), ProcessorError> {
+    let mut envelope = Map::new();
+    envelope.insert(RECORDS_KEY.to_string(), records);
+
+    let _permit = open_files::acquire();
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, &envelope)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -11,7 +11,7 @@ use std::error::Error as StdError;
 use std::fmt;
 
 // Importação do Enum de erros e da biblioteca Polars (necessária para o ETL)
-use crate::errors::{ApiError, ProcessorError};
+use crate::errors::{ApiError, ErrorContext, ProcessorError, Stage};
 use polars::error::PolarsError;
 
 // Display
@@ -22,6 +22,58 @@ impl fmt::Display for ProcessorError {
             ProcessorError::Json(err) => write!(f, "Falha de parsing do .json: {}", err),
             ProcessorError::Parquet(msg) => write!(f, "Erro de processamento em parquet: {}", msg),
             ProcessorError::Schema(msg) => write!(f, "Violação de regra no .toml: {}", msg),
+            ProcessorError::Xml(msg) => write!(f, "Falha de parsing do .xml: {}", msg),
+            ProcessorError::UpstreamStatus { status, expected, message } => {
+                match status {
+                    Some(status) => write!(
+                        f,
+                        "API sinalizou falha no envelope: status '{}' (esperado '{}')",
+                        status, expected
+                    )?,
+                    None => write!(
+                        f,
+                        "API sinalizou falha no envelope: status ausente (esperado '{}')",
+                        expected
+                    )?,
+                }
+                match message {
+                    Some(message) => write!(f, ": {}", message),
+                    None => Ok(()),
+                }
+            }
+            ProcessorError::WithContext { context, source } => {
+                write!(f, "{}: {}", context, source)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {} de '{}'", self.endpoint, self.stage, self.input)
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::Staging => "staging",
+            Stage::Conversion => "conversão",
+            Stage::Compression => "compressão",
+        };
+        f.write_str(name)
+    }
+}
+
+impl ProcessorError {
+    /// Envolve o erro de uma etapa com endpoint, etapa e entrada.
+    pub fn with_context<E>(source: E, context: ErrorContext) -> Self
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        ProcessorError::WithContext {
+            context,
+            source: source.into(),
         }
     }
 }
@@ -36,6 +88,9 @@ impl StdError for ProcessorError {
             ProcessorError::Json(err) => Some(err),
             ProcessorError::Parquet(_) => None,
             ProcessorError::Schema(_) => None,
+            ProcessorError::Xml(_) => None,
+            ProcessorError::UpstreamStatus { .. } => None,
+            ProcessorError::WithContext { source, .. } => Some(source.as_ref()),
         }
     }
 }
@@ -65,11 +120,30 @@ impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::NetworkError(e) => write!(f, "Falha de conexão: {}", e),
-            Self::HttpStatusError { status, url } => {
+            Self::HttpStatusError { status, url, .. } => {
                 write!(f, "Erro {} em: {}", status, url)
             }
             Self::FileSystemError(e) => write!(f, "Erro de disco: {}", e),
             Self::EmptyResponse => write!(f, "A resposta do servidor estava vazia."),
+            Self::InvalidJson(msg) => write!(f, "Resposta JSON inválida: {}", msg),
+            Self::HostNotAllowed(host) => {
+                write!(f, "Host '{}' não está em allowed_hosts", host)
+            }
+            Self::TooManyRedirects { limit, url } => write!(
+                f,
+                "Redirecionamento para {} recusado: limite de {} (max_redirects)",
+                url, limit
+            ),
+            Self::CrossHostRedirect { from, to } => write!(
+                f,
+                "Redirecionamento de '{}' para outro host ('{}') bloqueado (block_cross_host_redirects)",
+                from, to
+            ),
+            Self::DecompressionRatio { compressed, ratio } => write!(
+                f,
+                "gzip de {} bytes expandiu além de {}x (max_decompress_ratio); descompressão abortada",
+                compressed, ratio
+            ),
         }
     }
 }
@@ -82,4 +156,27 @@ impl std::error::Error for ApiError {
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_context_display_names_endpoint_stage_and_input() {
+        let context = ErrorContext {
+            endpoint: "compras.itens.pedidos".to_string(),
+            stage: Stage::Conversion,
+            input: "data/compras/itens/pedidos_temp.json".to_string(),
+        };
+        let error = ProcessorError::with_context(ProcessorError::Schema("coluna ausente".to_string()), context);
+
+        assert_eq!(
+            error.to_string(),
+            "[compras.itens.pedidos] conversão de 'data/compras/itens/pedidos_temp.json': \
+             Violação de regra no .toml: coluna ausente"
+        );
+        // O erro original segue acessível pela cadeia de `source`
+        let source = error.source().unwrap().downcast_ref::<ProcessorError>();
+        assert!(matches!(source, Some(ProcessorError::Schema(_))));
+    }
+}
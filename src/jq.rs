@@ -0,0 +1,1063 @@
+//! # Filtro jq
+//!
+//! `jq_filter` remodela a resposta JSON antes da leitura pelo Polars, com a
+//! sintaxe do [jq](https://jqlang.org): extrair registros de envelopes
+//! aninhados, renomear e achatar campos sem configuração extra.
+//!
+//! ```text
+//! .dados.itens[] | {id, nome: .pessoa.nome, uf: .endereco.uf}
+//! ```
+//!
+//! ## Contrato
+//! A saída precisa ser um array de objetos (`[.x[] | {...}]`) ou uma sequência
+//! de objetos (`.x[] | {...}`); cada objeto vira uma linha.
+//!
+//! ## Subconjunto suportado
+//! - Caminhos: `.`, `..`, `.a`, `."a b"`, `.[i]`, `.[]`, `?` e `//`.
+//! - Construção: `[...]`, `{a, b: .x, "c": .y, (.k): .v}`, literais.
+//! - Operadores: `|`, `,`, `+ - * / %`, `== != < <= > >=`, `and`, `or`,
+//!   `if ... then ... elif ... else ... end`.
+//! - Funções: `empty`, `not`, `length`, `keys`, `has(k)`, `type`, `map(f)`,
+//!   `select(f)`, `add`, `to_entries`, `from_entries`, `with_entries(f)`,
+//!   `tostring`, `tonumber`, `ascii_downcase`, `ascii_upcase`, `split(s)`,
+//!   `join(s)`, `flatten`, `sort` e `unique`.
+//!
+//! Variáveis (`as $x`), `reduce`, atribuições e definições de função não são
+//! aceitas: o filtro é recusado na validação da configuração.
+//!
+//! ## Custo
+//! A resposta inteira é materializada como `serde_json::Value`: o filtro não
+//! combina com `batch_size` nem com a conversão em lotes do limite de memória.
+
+use serde_json::{Map, Number, Value};
+
+use std::cmp::Ordering;
+
+/// Valida a sintaxe do filtro e as funções usadas.
+///
+/// Usado na validação da configuração; erros trazem a posição no filtro.
+pub fn check(source: &str) -> Result<(), String> {
+    parse(source).map(|_| ())
+}
+
+/// Aplica o filtro a `input` e devolve os registros produzidos.
+///
+/// # Erros
+/// Erro de sintaxe, de execução (ex: indexar um número) ou saída que não seja
+/// um array de objetos.
+pub fn records(source: &str, input: &Value) -> Result<Vec<Value>, String> {
+    let filter = parse(source)?;
+    let outputs = eval(&filter, input)?;
+
+    // Um único array é a lista de registros; caso contrário, cada saída é um registro
+    let records = match <[Value; 1]>::try_from(outputs) {
+        Ok([Value::Array(items)]) => items,
+        Ok([single]) => vec![single],
+        Err(outputs) => outputs,
+    };
+    if let Some((index, value)) = records.iter().enumerate().find(|(_, value)| !value.is_object()) {
+        return Err(format!(
+            "a saída deve ser um array de objetos; o elemento {} é {}",
+            index,
+            type_name(value)
+        ));
+    }
+    Ok(records)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    DotDot,
+    Field(String),
+    Ident(String),
+    Number(f64),
+    Str(String),
+    Punct(&'static str),
+}
+
+#[derive(Debug)]
+enum Expr {
+    Identity,
+    Recurse,
+    Literal(Value),
+    Index(Box<Expr>, Box<Expr>),
+    Iterate(Box<Expr>),
+    Try(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Alternative(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Array(Option<Box<Expr>>),
+    Object(Vec<(Expr, Expr)>),
+    If(Vec<(Expr, Expr)>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+/// Funções aceitas e sua aridade.
+const FUNCTIONS: &[(&str, usize)] = &[
+    ("empty", 0),
+    ("not", 0),
+    ("length", 0),
+    ("keys", 0),
+    ("has", 1),
+    ("type", 0),
+    ("map", 1),
+    ("select", 1),
+    ("add", 0),
+    ("to_entries", 0),
+    ("from_entries", 0),
+    ("with_entries", 1),
+    ("tostring", 0),
+    ("tonumber", 0),
+    ("ascii_downcase", 0),
+    ("ascii_upcase", 0),
+    ("split", 1),
+    ("join", 1),
+    ("flatten", 0),
+    ("sort", 0),
+    ("unique", 0),
+];
+
+/// Operadores de dois caracteres antes dos de um (`//` antes de `/`).
+const PUNCTUATION: &[&str] = &[
+    "//", "==", "!=", "<=", ">=", "|", ",", ":", ";", "?", "(", ")", "[", "]", "{", "}", "+", "-", "*", "/", "%",
+    "<", ">",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        let token = if c == '.' {
+            i += 1;
+            if chars.get(i) == Some(&'.') {
+                i += 1;
+                Token::DotDot
+            } else if chars.get(i).is_some_and(|c| c.is_ascii_alphabetic() || *c == '_') {
+                while i < chars.len() && ident(chars[i]) {
+                    i += 1;
+                }
+                Token::Field(chars[start + 1..i].iter().collect())
+            } else {
+                Token::Dot
+            }
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            Token::Number(
+                text.parse()
+                    .map_err(|_| format!("posição {}: número inválido '{}'", start, text))?,
+            )
+        } else if c == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                if chars[i] == '\\' {
+                    i += 1;
+                    if chars.get(i) == Some(&'(') {
+                        return Err(format!("posição {}: interpolação de strings não é suportada", i - 1));
+                    }
+                }
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(format!("posição {}: string sem aspas de fechamento", start));
+            }
+            i += 1;
+            let literal: String = chars[start..i].iter().collect();
+            Token::Str(
+                serde_json::from_str(&literal)
+                    .map_err(|e| format!("posição {}: string inválida: {}", start, e))?,
+            )
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            while i < chars.len() && ident(chars[i]) {
+                i += 1;
+            }
+            Token::Ident(chars[start..i].iter().collect())
+        } else if c == '$' {
+            return Err(format!("posição {}: variáveis não são suportadas", start));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let Some(punct) = PUNCTUATION.iter().find(|p| rest.starts_with(**p)) else {
+                return Err(format!("posição {}: caractere inesperado '{}'", start, c));
+            };
+            i += punct.chars().count();
+            Token::Punct(punct)
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+fn parse(source: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+        end: source.chars().count(),
+    };
+    if parser.tokens.is_empty() {
+        return Err("filtro vazio".to_string());
+    }
+    let expr = parser.pipe()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(parser.error(&format!("'{}' inesperado", describe(token)))),
+    }
+}
+
+/// Analisador descendente; cada método cobre um nível de precedência, do menor ao maior.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, message: &str) -> String {
+        let offset = self.tokens.get(self.position).map_or(self.end, |(offset, _)| *offset);
+        format!("posição {}: {}", offset, message)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(p)) if *p == punct) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(name)) if name == keyword) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            return Ok(());
+        }
+        Err(self.error(&format!("esperado '{}'", punct)))
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.eat_keyword(keyword) {
+            return Ok(());
+        }
+        Err(self.error(&format!("esperado '{}'", keyword)))
+    }
+
+    fn pipe(&mut self) -> Result<Expr, String> {
+        let left = self.comma()?;
+        if self.eat("|") {
+            return Ok(Expr::Pipe(Box::new(left), Box::new(self.pipe()?)));
+        }
+        Ok(left)
+    }
+
+    fn comma(&mut self) -> Result<Expr, String> {
+        let mut left = self.alternative()?;
+        while self.eat(",") {
+            left = Expr::Comma(Box::new(left), Box::new(self.alternative()?));
+        }
+        Ok(left)
+    }
+
+    /// Valor de um objeto: pipes sem vírgula (`{a: .x | .y, b: .z}`).
+    fn object_value(&mut self) -> Result<Expr, String> {
+        let left = self.alternative()?;
+        if self.eat("|") {
+            return Ok(Expr::Pipe(Box::new(left), Box::new(self.object_value()?)));
+        }
+        Ok(left)
+    }
+
+    fn alternative(&mut self) -> Result<Expr, String> {
+        let left = self.or()?;
+        if self.eat("//") {
+            return Ok(Expr::Alternative(Box::new(left), Box::new(self.alternative()?)));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat_keyword("or") {
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.comparison()?;
+        while self.eat_keyword("and") {
+            left = Expr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.additive()?;
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat(op) {
+                return Ok(Expr::Binary(op, Box::new(left), Box::new(self.additive()?)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let Some(op) = ["+", "-"].into_iter().find(|op| self.eat(op)) else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.multiplicative()?));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let Some(op) = ["*", "/", "%"].into_iter().find(|op| self.eat(op)) else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("-") {
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            expr = match self.peek() {
+                Some(Token::Field(name)) => {
+                    let key = Expr::Literal(Value::String(name.clone()));
+                    self.position += 1;
+                    Expr::Index(Box::new(expr), Box::new(key))
+                }
+                // `.a."b c"` e `.a.[0]`
+                Some(Token::Dot) => {
+                    self.position += 1;
+                    match self.next() {
+                        Some(Token::Str(name)) => Expr::Index(Box::new(expr), Box::new(Expr::Literal(Value::String(name)))),
+                        Some(Token::Punct("[")) => self.bracket(expr)?,
+                        _ => {
+                            self.position -= 1;
+                            return Err(self.error("esperado um campo após '.'"));
+                        }
+                    }
+                }
+                Some(Token::Punct("[")) => {
+                    self.position += 1;
+                    self.bracket(expr)?
+                }
+                Some(Token::Punct("?")) => {
+                    self.position += 1;
+                    Expr::Try(Box::new(expr))
+                }
+                _ => return Ok(expr),
+            };
+        }
+    }
+
+    /// `[]` ou `[expr]` após um termo (o `[` já foi consumido).
+    fn bracket(&mut self, target: Expr) -> Result<Expr, String> {
+        if self.eat("]") {
+            return Ok(Expr::Iterate(Box::new(target)));
+        }
+        let index = self.pipe()?;
+        self.expect("]")?;
+        Ok(Expr::Index(Box::new(target), Box::new(index)))
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.next() else {
+            return Err(self.error("fim inesperado do filtro"));
+        };
+        match token {
+            Token::Dot => match self.peek() {
+                Some(Token::Str(name)) => {
+                    let key = Expr::Literal(Value::String(name.clone()));
+                    self.position += 1;
+                    Ok(Expr::Index(Box::new(Expr::Identity), Box::new(key)))
+                }
+                _ => Ok(Expr::Identity),
+            },
+            Token::DotDot => Ok(Expr::Recurse),
+            Token::Field(name) => Ok(Expr::Index(
+                Box::new(Expr::Identity),
+                Box::new(Expr::Literal(Value::String(name))),
+            )),
+            Token::Number(number) => Ok(Expr::Literal(number_value(number))),
+            Token::Str(text) => Ok(Expr::Literal(Value::String(text))),
+            Token::Punct("(") => {
+                let expr = self.pipe()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Punct("[") => {
+                if self.eat("]") {
+                    return Ok(Expr::Array(None));
+                }
+                let items = self.pipe()?;
+                self.expect("]")?;
+                Ok(Expr::Array(Some(Box::new(items))))
+            }
+            Token::Punct("{") => self.object(),
+            Token::Ident(name) => self.keyword_or_call(name),
+            token => {
+                self.position -= 1;
+                Err(self.error(&format!("'{}' inesperado", describe(&token))))
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Expr, String> {
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Ok(Expr::Object(entries));
+        }
+        loop {
+            let (key, shorthand) = match self.next() {
+                Some(Token::Ident(name)) => (Expr::Literal(Value::String(name.clone())), Some(name)),
+                Some(Token::Str(name)) => (Expr::Literal(Value::String(name.clone())), Some(name)),
+                Some(Token::Punct("(")) => {
+                    let key = self.pipe()?;
+                    self.expect(")")?;
+                    (key, None)
+                }
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("esperada uma chave de objeto"));
+                }
+            };
+            let value = if self.eat(":") {
+                self.object_value()?
+            } else if let Some(name) = shorthand {
+                // `{a}` equivale a `{a: .a}`
+                Expr::Index(Box::new(Expr::Identity), Box::new(Expr::Literal(Value::String(name))))
+            } else {
+                return Err(self.error("esperado ':' após a chave calculada"));
+            };
+            entries.push((key, value));
+            if self.eat("}") {
+                return Ok(Expr::Object(entries));
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn keyword_or_call(&mut self, name: String) -> Result<Expr, String> {
+        match name.as_str() {
+            "true" => return Ok(Expr::Literal(Value::Bool(true))),
+            "false" => return Ok(Expr::Literal(Value::Bool(false))),
+            "null" => return Ok(Expr::Literal(Value::Null)),
+            "if" => return self.conditional(),
+            "reduce" | "foreach" | "def" | "as" | "import" | "include" | "label" | "try" => {
+                self.position -= 1;
+                return Err(self.error(&format!("'{}' não é suportado", name)));
+            }
+            _ => {}
+        }
+
+        let mut args = Vec::new();
+        if self.eat("(") {
+            loop {
+                args.push(self.pipe()?);
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(";")?;
+            }
+        }
+        match FUNCTIONS.iter().find(|(function, _)| *function == name) {
+            Some((_, arity)) if *arity == args.len() => Ok(Expr::Call(name, args)),
+            Some((_, arity)) => Err(self.error(&format!(
+                "'{}' espera {} argumento(s), recebeu {}",
+                name,
+                arity,
+                args.len()
+            ))),
+            None => Err(self.error(&format!("função desconhecida '{}/{}'", name, args.len()))),
+        }
+    }
+
+    /// `if c then a (elif c then a)* (else b)? end`; sem `else`, a entrada segue intacta.
+    fn conditional(&mut self) -> Result<Expr, String> {
+        let mut branches = Vec::new();
+        loop {
+            let condition = self.pipe()?;
+            self.expect_keyword("then")?;
+            branches.push((condition, self.pipe()?));
+            if !self.eat_keyword("elif") {
+                break;
+            }
+        }
+        let otherwise = if self.eat_keyword("else") {
+            self.pipe()?
+        } else {
+            Expr::Identity
+        };
+        self.expect_keyword("end")?;
+        Ok(Expr::If(branches, Box::new(otherwise)))
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Dot => ".".to_string(),
+        Token::DotDot => "..".to_string(),
+        Token::Field(name) => format!(".{}", name),
+        Token::Ident(name) => name.clone(),
+        Token::Number(number) => number.to_string(),
+        Token::Str(text) => format!("\"{}\"", text),
+        Token::Punct(punct) => punct.to_string(),
+    }
+}
+
+/// Avalia `expr` sobre `input`; cada expressão produz zero ou mais saídas.
+fn eval(expr: &Expr, input: &Value) -> Result<Vec<Value>, String> {
+    match expr {
+        Expr::Identity => Ok(vec![input.clone()]),
+        Expr::Recurse => {
+            let mut outputs = Vec::new();
+            descend(input, &mut outputs);
+            Ok(outputs)
+        }
+        Expr::Literal(value) => Ok(vec![value.clone()]),
+        Expr::Index(target, key) => {
+            let keys = eval(key, input)?;
+            let mut outputs = Vec::new();
+            for value in eval(target, input)? {
+                for key in &keys {
+                    outputs.push(index(&value, key)?);
+                }
+            }
+            Ok(outputs)
+        }
+        Expr::Iterate(target) => {
+            let mut outputs = Vec::new();
+            for value in eval(target, input)? {
+                outputs.extend(iterate(&value)?);
+            }
+            Ok(outputs)
+        }
+        Expr::Try(inner) => Ok(eval(inner, input).unwrap_or_default()),
+        Expr::Pipe(left, right) => {
+            let mut outputs = Vec::new();
+            for value in eval(left, input)? {
+                outputs.extend(eval(right, &value)?);
+            }
+            Ok(outputs)
+        }
+        Expr::Comma(left, right) => {
+            let mut outputs = eval(left, input)?;
+            outputs.extend(eval(right, input)?);
+            Ok(outputs)
+        }
+        Expr::Alternative(left, right) => {
+            let truthy: Vec<Value> = eval(left, input)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(is_truthy)
+                .collect();
+            if truthy.is_empty() {
+                return eval(right, input);
+            }
+            Ok(truthy)
+        }
+        Expr::And(left, right) => logical(left, right, input, false),
+        Expr::Or(left, right) => logical(left, right, input, true),
+        Expr::Binary(op, left, right) => {
+            let rights = eval(right, input)?;
+            let mut outputs = Vec::new();
+            for left in eval(left, input)? {
+                for right in &rights {
+                    outputs.push(binary(op, &left, right)?);
+                }
+            }
+            Ok(outputs)
+        }
+        Expr::Negate(inner) => eval(inner, input)?
+            .iter()
+            .map(|value| match value {
+                Value::Number(_) => Ok(number_value(-as_f64(value))),
+                other => Err(format!("não é possível negar {}", type_name(other))),
+            })
+            .collect(),
+        Expr::Array(None) => Ok(vec![Value::Array(Vec::new())]),
+        Expr::Array(Some(items)) => Ok(vec![Value::Array(eval(items, input)?)]),
+        Expr::Object(entries) => {
+            // Chaves ou valores com várias saídas geram o produto cartesiano
+            let mut objects = vec![Map::new()];
+            for (key, value) in entries {
+                let keys = eval(key, input)?;
+                let values = eval(value, input)?;
+                let mut next = Vec::with_capacity(objects.len() * keys.len() * values.len());
+                for object in &objects {
+                    for key in &keys {
+                        let Value::String(key) = key else {
+                            return Err(format!("chaves de objeto devem ser strings, não {}", type_name(key)));
+                        };
+                        for value in &values {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            Ok(objects.into_iter().map(Value::Object).collect())
+        }
+        Expr::If(branches, otherwise) => conditional(branches, otherwise, input),
+        Expr::Call(name, args) => call(name, args, input),
+    }
+}
+
+fn descend(value: &Value, outputs: &mut Vec<Value>) {
+    outputs.push(value.clone());
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| descend(item, outputs)),
+        Value::Object(map) => map.values().for_each(|item| descend(item, outputs)),
+        _ => {}
+    }
+}
+
+fn index(value: &Value, key: &Value) -> Result<Value, String> {
+    match (value, key) {
+        (Value::Object(map), Value::String(name)) => Ok(map.get(name).cloned().unwrap_or(Value::Null)),
+        (Value::Array(items), Value::Number(_)) => {
+            let position = as_f64(key).floor() as i64;
+            let position = if position < 0 { position + items.len() as i64 } else { position };
+            Ok(usize::try_from(position)
+                .ok()
+                .and_then(|position| items.get(position))
+                .cloned()
+                .unwrap_or(Value::Null))
+        }
+        (Value::Null, Value::String(_) | Value::Number(_)) => Ok(Value::Null),
+        (value, Value::String(name)) => Err(format!("não é possível indexar {} com \"{}\"", type_name(value), name)),
+        (value, key) => Err(format!("não é possível indexar {} com {}", type_name(value), type_name(key))),
+    }
+}
+
+fn iterate(value: &Value) -> Result<Vec<Value>, String> {
+    match value {
+        Value::Array(items) => Ok(items.clone()),
+        Value::Object(map) => Ok(map.values().cloned().collect()),
+        other => Err(format!("não é possível iterar sobre {}", type_name(other))),
+    }
+}
+
+fn logical(left: &Expr, right: &Expr, input: &Value, is_or: bool) -> Result<Vec<Value>, String> {
+    let mut outputs = Vec::new();
+    for left in eval(left, input)? {
+        // `or` decide com o lado esquerdo verdadeiro; `and`, com ele falso
+        if is_truthy(&left) == is_or {
+            outputs.push(Value::Bool(is_or));
+            continue;
+        }
+        outputs.extend(eval(right, input)?.iter().map(|right| Value::Bool(is_truthy(right))));
+    }
+    Ok(outputs)
+}
+
+fn conditional(branches: &[(Expr, Expr)], otherwise: &Expr, input: &Value) -> Result<Vec<Value>, String> {
+    let Some(((condition, then), rest)) = branches.split_first() else {
+        return eval(otherwise, input);
+    };
+    let mut outputs = Vec::new();
+    for value in eval(condition, input)? {
+        if is_truthy(&value) {
+            outputs.extend(eval(then, input)?);
+        } else {
+            outputs.extend(conditional(rest, otherwise, input)?);
+        }
+    }
+    Ok(outputs)
+}
+
+fn binary(op: &str, left: &Value, right: &Value) -> Result<Value, String> {
+    let ordering = || compare(left, right);
+    match op {
+        "==" => return Ok(Value::Bool(ordering() == Ordering::Equal)),
+        "!=" => return Ok(Value::Bool(ordering() != Ordering::Equal)),
+        "<" => return Ok(Value::Bool(ordering() == Ordering::Less)),
+        "<=" => return Ok(Value::Bool(ordering() != Ordering::Greater)),
+        ">" => return Ok(Value::Bool(ordering() == Ordering::Greater)),
+        ">=" => return Ok(Value::Bool(ordering() != Ordering::Less)),
+        _ => {}
+    }
+
+    match (op, left, right) {
+        ("+", Value::Null, other) | ("+", other, Value::Null) => Ok(other.clone()),
+        ("+", Value::String(a), Value::String(b)) => Ok(Value::String(format!("{}{}", a, b))),
+        ("+", Value::Array(a), Value::Array(b)) => Ok(Value::Array(a.iter().chain(b).cloned().collect())),
+        ("+", Value::Object(a), Value::Object(b)) => {
+            let mut merged = a.clone();
+            merged.extend(b.clone());
+            Ok(Value::Object(merged))
+        }
+        ("-", Value::Array(a), Value::Array(b)) => {
+            Ok(Value::Array(a.iter().filter(|item| !b.contains(item)).cloned().collect()))
+        }
+        ("/", Value::String(a), Value::String(b)) => Ok(split(a, b)),
+        (_, Value::Number(_), Value::Number(_)) => {
+            let (a, b) = (as_f64(left), as_f64(right));
+            let result = match op {
+                "+" => a + b,
+                "-" => a - b,
+                "*" => a * b,
+                "/" if b == 0.0 => return Err("divisão por zero".to_string()),
+                "/" => a / b,
+                "%" if b as i64 == 0 => return Err("resto de divisão por zero".to_string()),
+                _ => (a as i64 % b as i64) as f64,
+            };
+            Ok(number_value(result))
+        }
+        _ => Err(format!(
+            "{} e {} não podem ser combinados com '{}'",
+            type_name(left),
+            type_name(right),
+            op
+        )),
+    }
+}
+
+fn call(name: &str, args: &[Expr], input: &Value) -> Result<Vec<Value>, String> {
+    let one = |value: Value| Ok(vec![value]);
+    match (name, args) {
+        ("empty", _) => Ok(Vec::new()),
+        ("not", _) => one(Value::Bool(!is_truthy(input))),
+        ("length", _) => one(match input {
+            Value::Null => Value::from(0),
+            Value::Number(_) => number_value(as_f64(input).abs()),
+            Value::String(text) => Value::from(text.chars().count()),
+            Value::Array(items) => Value::from(items.len()),
+            Value::Object(map) => Value::from(map.len()),
+            Value::Bool(_) => return Err("boolean não tem length".to_string()),
+        }),
+        ("keys", _) => one(match input {
+            Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                Value::Array(keys.into_iter().map(|key| Value::String(key.clone())).collect())
+            }
+            Value::Array(items) => Value::Array((0..items.len()).map(Value::from).collect()),
+            other => return Err(format!("{} não tem keys", type_name(other))),
+        }),
+        ("has", [key]) => eval(key, input)?
+            .iter()
+            .map(|key| match (input, key) {
+                (Value::Object(map), Value::String(name)) => Ok(Value::Bool(map.contains_key(name))),
+                (Value::Array(items), Value::Number(_)) => {
+                    let position = as_f64(key);
+                    Ok(Value::Bool(position >= 0.0 && position < items.len() as f64))
+                }
+                (value, key) => Err(format!(
+                    "não é possível verificar se {} tem uma chave {}",
+                    type_name(value),
+                    type_name(key)
+                )),
+            })
+            .collect(),
+        ("type", _) => one(Value::String(type_name(input).to_string())),
+        ("map", [f]) => {
+            let mut items = Vec::new();
+            for item in iterate(input)? {
+                items.extend(eval(f, &item)?);
+            }
+            one(Value::Array(items))
+        }
+        ("select", [f]) => Ok(eval(f, input)?
+            .iter()
+            .filter(|value| is_truthy(value))
+            .map(|_| input.clone())
+            .collect()),
+        ("add", _) => {
+            let items = iterate(input)?;
+            let mut total = Value::Null;
+            for item in &items {
+                total = binary("+", &total, item)?;
+            }
+            one(total)
+        }
+        ("to_entries", _) => one(to_entries(input)?),
+        ("from_entries", _) => one(from_entries(input)?),
+        ("with_entries", [f]) => {
+            let mut entries = Vec::new();
+            for entry in iterate(&to_entries(input)?)? {
+                entries.extend(eval(f, &entry)?);
+            }
+            one(from_entries(&Value::Array(entries))?)
+        }
+        ("tostring", _) => one(Value::String(to_text(input))),
+        ("tonumber", _) => one(match input {
+            Value::Number(_) => input.clone(),
+            Value::String(text) => number_value(
+                text.trim()
+                    .parse()
+                    .map_err(|_| format!("\"{}\" não pode ser convertido em número", text))?,
+            ),
+            other => return Err(format!("{} não pode ser convertido em número", type_name(other))),
+        }),
+        ("ascii_downcase" | "ascii_upcase", _) => {
+            let Value::String(text) = input else {
+                return Err(format!("{} exige uma string, não {}", name, type_name(input)));
+            };
+            one(Value::String(if name == "ascii_downcase" {
+                text.to_ascii_lowercase()
+            } else {
+                text.to_ascii_uppercase()
+            }))
+        }
+        ("split", [separator]) => eval(separator, input)?
+            .iter()
+            .map(|separator| match (input, separator) {
+                (Value::String(text), Value::String(separator)) => Ok(split(text, separator)),
+                _ => Err("split exige uma string e um separador textual".to_string()),
+            })
+            .collect(),
+        ("join", [separator]) => eval(separator, input)?
+            .iter()
+            .map(|separator| {
+                let Value::String(separator) = separator else {
+                    return Err("join exige um separador textual".to_string());
+                };
+                let parts: Result<Vec<String>, String> = iterate(input)?
+                    .iter()
+                    .map(|item| match item {
+                        Value::Null => Ok(String::new()),
+                        Value::Array(_) | Value::Object(_) => Err(format!("join não aceita {}", type_name(item))),
+                        other => Ok(to_text(other)),
+                    })
+                    .collect();
+                Ok(Value::String(parts?.join(separator)))
+            })
+            .collect(),
+        ("flatten", _) => {
+            let Value::Array(items) = input else {
+                return Err(format!("flatten exige um array, não {}", type_name(input)));
+            };
+            let mut flat = Vec::new();
+            flatten(items, &mut flat);
+            one(Value::Array(flat))
+        }
+        ("sort" | "unique", _) => {
+            let Value::Array(items) = input else {
+                return Err(format!("{} exige um array, não {}", name, type_name(input)));
+            };
+            let mut items = items.clone();
+            items.sort_by(compare);
+            if name == "unique" {
+                items.dedup_by(|a, b| compare(a, b) == Ordering::Equal);
+            }
+            one(Value::Array(items))
+        }
+        _ => Err(format!("função desconhecida '{}/{}'", name, args.len())),
+    }
+}
+
+fn to_entries(value: &Value) -> Result<Value, String> {
+    let Value::Object(map) = value else {
+        return Err(format!("to_entries exige um objeto, não {}", type_name(value)));
+    };
+    Ok(Value::Array(
+        map.iter()
+            .map(|(key, value)| {
+                let mut entry = Map::new();
+                entry.insert("key".to_string(), Value::String(key.clone()));
+                entry.insert("value".to_string(), value.clone());
+                Value::Object(entry)
+            })
+            .collect(),
+    ))
+}
+
+fn from_entries(value: &Value) -> Result<Value, String> {
+    let mut object = Map::new();
+    for entry in iterate(value)? {
+        let field = |names: &[&str]| names.iter().find_map(|name| entry.get(*name).filter(|v| !v.is_null())).cloned();
+        let key = match field(&["key", "k", "name", "Name", "Key", "K"]) {
+            Some(Value::String(key)) => key,
+            Some(key @ (Value::Number(_) | Value::Bool(_))) => to_text(&key),
+            _ => return Err("from_entries exige entradas com uma chave textual".to_string()),
+        };
+        object.insert(key, field(&["value", "v", "Value", "V"]).unwrap_or(Value::Null));
+    }
+    Ok(Value::Object(object))
+}
+
+fn flatten(items: &[Value], flat: &mut Vec<Value>) {
+    for item in items {
+        match item {
+            Value::Array(inner) => flatten(inner, flat),
+            other => flat.push(other.clone()),
+        }
+    }
+}
+
+fn split(text: &str, separator: &str) -> Value {
+    if text.is_empty() {
+        return Value::Array(Vec::new());
+    }
+    Value::Array(text.split(separator).map(|part| Value::String(part.to_string())).collect())
+}
+
+fn is_truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+/// Texto de `tostring`: strings como estão, o resto em JSON.
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn as_f64(value: &Value) -> f64 {
+    value.as_f64().unwrap_or_default()
+}
+
+/// Números inteiros voltam como inteiros (`1`, não `1.0`).
+fn number_value(number: f64) -> Value {
+    if number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+        return Value::from(number as i64);
+    }
+    Number::from_f64(number).map_or(Value::Null, Value::Number)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Ordem total do jq: null < false < true < números < strings < arrays < objetos.
+fn compare(left: &Value, right: &Value) -> Ordering {
+    let rank = |value: &Value| match value {
+        Value::Null => 0,
+        Value::Bool(false) => 1,
+        Value::Bool(true) => 2,
+        Value::Number(_) => 3,
+        Value::String(_) => 4,
+        Value::Array(_) => 5,
+        Value::Object(_) => 6,
+    };
+    match (left, right) {
+        (Value::Number(_), Value::Number(_)) => as_f64(left).total_cmp(&as_f64(right)),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => {
+            // Como no jq: primeiro o conjunto de chaves, depois os valores chave a chave
+            let mut keys_a: Vec<&String> = a.keys().collect();
+            let mut keys_b: Vec<&String> = b.keys().collect();
+            keys_a.sort();
+            keys_b.sort();
+            keys_a.cmp(&keys_b).then_with(|| {
+                keys_a
+                    .iter()
+                    .map(|key| compare(&a[*key], &b[*key]))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn flattens_nested_envelope_into_records() {
+        let input = json!({
+            "data": {
+                "total": 2,
+                "items": [
+                    { "id": 1, "pessoa": { "nome": "Ana", "cpf": "1" } },
+                    { "id": 2, "pessoa": { "nome": "Bruno" } }
+                ]
+            }
+        });
+
+        let filter = ".data.items[] | {id, nome: .pessoa.nome}";
+        assert_eq!(check(filter), Ok(()));
+        assert_eq!(
+            records(filter, &input).unwrap(),
+            [json!({ "id": 1, "nome": "Ana" }), json!({ "id": 2, "nome": "Bruno" })]
+        );
+        // Um único array produzido também é a lista de registros
+        assert_eq!(records("[.data.items[] | {id}]", &input).unwrap(), [json!({ "id": 1 }), json!({ "id": 2 })]);
+    }
+
+    #[test]
+    fn select_filters_records() {
+        let input = json!([{ "id": 1, "uf": "SP" }, { "id": 2, "uf": "RJ" }]);
+        assert_eq!(records(r#".[] | select(.uf == "SP") | {id}"#, &input).unwrap(), [json!({ "id": 1 })]);
+    }
+
+    #[test]
+    fn check_rejects_malformed_filters() {
+        for filter in [".data.items[", "{id, nome: }", ".a |", "map(.x", ".[] as $x | $x", "desconhecida(1)"] {
+            assert!(check(filter).is_err(), "{filter}");
+        }
+    }
+
+    #[test]
+    fn records_must_be_objects() {
+        let error = records(".data.total", &json!({ "data": { "total": 2 } })).unwrap_err();
+        assert!(error.contains("array de objetos"), "{error}");
+    }
+}
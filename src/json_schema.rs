@@ -0,0 +1,178 @@
+//! # Contrato de Dados (JSON Schema)
+//!
+//! Gera um documento JSON Schema (draft 2020-12) descrevendo cada tabela de
+//! saída, a partir dos dtypes do DataFrame gravado e da nulidade observada.
+//!
+//! ## Uso
+//! Habilitado por `--emit-json-schema`: ao lado de cada saída é gravado
+//! `{chave}.jsonschema.json`, que consumidores podem usar para validar registros.
+//!
+//! ## Mapeamento
+//! - Inteiros → `integer`; floats → `number`; booleanos → `boolean`.
+//! - Texto → `string`; datas e horários → `string` com `format`.
+//! - Listas → `array` com `items`; structs → `object` com `properties`.
+//! - Colunas sem nulos viram `required`; as demais aceitam também `null`.
+
+use polars::prelude::*;
+use serde_json::{Map, Value, json};
+
+use std::collections::BTreeSet;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::atomic;
+use crate::errors::ProcessorError;
+
+/// Dialeto declarado em `$schema`.
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Caminho do contrato ao lado da saída (`dados.parquet` → `dados.jsonschema.json`).
+pub fn schema_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension("jsonschema.json")
+}
+
+/// Monta o documento para a tabela `title`.
+///
+/// `nullable` lista as colunas em que ao menos um valor nulo foi gravado.
+pub fn build(title: &str, schema: &Schema, nullable: &BTreeSet<String>) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    for (name, dtype) in schema.iter() {
+        let name = name.to_string();
+        let is_nullable = nullable.contains(&name);
+        properties.insert(name.clone(), property(dtype, is_nullable));
+        if !is_nullable {
+            required.push(Value::String(name));
+        }
+    }
+
+    json!({
+        "$schema": DRAFT,
+        "title": title,
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// Grava o documento de forma atômica.
+pub fn write(path: &Path, document: &Value) -> Result<(), ProcessorError> {
+    atomic::write(path, |file| {
+        let mut out = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut out, document)?;
+        out.flush().map_err(ProcessorError::Io)
+    })
+}
+
+/// Schema de um valor com o dtype informado, opcionalmente aceitando `null`.
+fn property(dtype: &DataType, nullable: bool) -> Value {
+    let mut value = match dtype {
+        DataType::Boolean => json!({ "type": "boolean" }),
+        dtype if dtype.is_integer() => json!({ "type": "integer" }),
+        dtype if dtype.is_float() => json!({ "type": "number" }),
+        DataType::String => json!({ "type": "string" }),
+        DataType::Binary => json!({ "type": "string", "contentEncoding": "base64" }),
+        DataType::Date => json!({ "type": "string", "format": "date" }),
+        DataType::Datetime(_, _) => json!({ "type": "string", "format": "date-time" }),
+        DataType::Time => json!({ "type": "string", "format": "time" }),
+        DataType::Duration(_) => json!({ "type": "string", "format": "duration" }),
+        DataType::Null => return json!({ "type": "null" }),
+        // Itens e campos aninhados não têm nulidade observada: aceitam `null`
+        DataType::List(inner) | DataType::Array(inner, _) => {
+            json!({ "type": "array", "items": property(inner, true) })
+        }
+        DataType::Struct(fields) => {
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|f| (f.name().to_string(), property(f.dtype(), true)))
+                .collect();
+            json!({ "type": "object", "properties": properties })
+        }
+        // Tipos sem equivalente direto aceitam qualquer valor
+        _ => return json!({}),
+    };
+
+    if nullable && let Some(kind) = value.get("type").cloned() {
+        value["type"] = json!([kind, "null"]);
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Validador mínimo do subconjunto emitido por `build` (`type`,
+    /// `properties`, `required`, `additionalProperties` e `items`).
+    fn validates(schema: &Value, value: &Value) -> bool {
+        let type_matches = |kind: &Value| match kind.as_str() {
+            Some("object") => value.is_object(),
+            Some("array") => value.is_array(),
+            Some("string") => value.is_string(),
+            Some("integer") => value.is_i64() || value.is_u64(),
+            Some("number") => value.is_number(),
+            Some("boolean") => value.is_boolean(),
+            Some("null") => value.is_null(),
+            _ => false,
+        };
+        let type_ok = match &schema["type"] {
+            Value::Array(kinds) => kinds.iter().any(type_matches),
+            Value::Null => true,
+            kind => type_matches(kind),
+        };
+        if !type_ok {
+            return false;
+        }
+
+        match value {
+            Value::Object(fields) => {
+                let properties = schema["properties"].as_object().cloned().unwrap_or_default();
+                let required = schema["required"].as_array().cloned().unwrap_or_default();
+                required.iter().all(|name| fields.contains_key(name.as_str().unwrap()))
+                    && fields.iter().all(|(name, field)| match properties.get(name) {
+                        Some(property) => validates(property, field),
+                        None => schema["additionalProperties"] != false,
+                    })
+            }
+            Value::Array(items) => items.iter().all(|item| validates(&schema["items"], item)),
+            _ => true,
+        }
+    }
+
+    #[test]
+    fn built_schema_validates_a_sample_record() {
+        let dataframe = df!(
+            "id" => [1i64, 2],
+            "nome" => [Some("Ana"), None],
+            "valor" => [1.5f64, 2.0],
+            "ativo" => [true, false]
+        )
+        .unwrap();
+        let nullable: BTreeSet<String> = dataframe
+            .get_columns()
+            .iter()
+            .filter(|c| c.null_count() > 0)
+            .map(|c| c.name().to_string())
+            .collect();
+
+        let schema = build("pedidos", dataframe.schema(), &nullable);
+
+        assert_eq!(schema["required"], json!(["id", "valor", "ativo"]));
+        assert_eq!(schema["properties"]["nome"]["type"], json!(["string", "null"]));
+        for record in [
+            json!({"id": 1, "nome": "Ana", "valor": 1.5, "ativo": true}),
+            json!({"id": 2, "nome": null, "valor": 2.0, "ativo": false}),
+        ] {
+            assert!(validates(&schema, &record), "{record}");
+        }
+        for record in [
+            json!({"id": 3, "valor": 1.0}),
+            json!({"id": "3", "nome": "Caio", "valor": 1.0, "ativo": true}),
+            json!({"id": 3, "nome": "Caio", "valor": 1.0, "ativo": true, "extra": 1}),
+        ] {
+            assert!(!validates(&schema, &record), "{record}");
+        }
+    }
+}
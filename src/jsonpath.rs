@@ -0,0 +1,657 @@
+//! # Seleção por JSONPath
+//!
+//! `json_path` seleciona os registros da resposta com uma expressão
+//! [JSONPath](https://www.rfc-editor.org/rfc/rfc9535) antes da leitura pelo
+//! Polars, para envelopes que o `root_path` (uma única chave) não alcança:
+//!
+//! ```text
+//! $.data[?(@.active == true)].records
+//! ```
+//!
+//! ## Contrato
+//! Cada nó selecionado que seja um array contribui com os seus elementos; um
+//! objeto é um registro; `null` não contribui. Qualquer outro valor falha a
+//! conversão, assim como elementos que não sejam objetos.
+//!
+//! ## Subconjunto suportado
+//! - Raiz `$`, nomes (`.a`, `['a b']`), curinga (`.*`, `[*]`), índices
+//!   (`[0]`, `[-1]`), listas (`[0,2]`, `['a','b']`), fatias (`[1:3]`) e
+//!   descendentes (`..a`, `..[0]`).
+//! - Filtros `[?(...)]` (ou `[?...]`) sobre os filhos de arrays e objetos, com
+//!   caminhos relativos (`@.a.b`) ou absolutos (`$.x`), literais (números,
+//!   textos entre aspas, `true`, `false`, `null`), `== != < <= > >=`, `&&`,
+//!   `||`, `!` e parênteses. Um caminho sozinho testa a existência (`?(@.id)`).
+//!
+//! Comparações seguem a RFC 9535: `<` e `>` só entre números ou entre textos;
+//! um caminho sem resultado só é igual a outro sem resultado. Funções
+//! (`length()`, `match()`) e expressões de script não são aceitas.
+//!
+//! ## Custo
+//! Como o `jq_filter`, a resposta inteira é materializada como
+//! `serde_json::Value`: não combina com `batch_size` nem com a conversão em
+//! lotes do limite de memória.
+
+use serde_json::Value;
+
+use std::cmp::Ordering;
+
+/// Valida a sintaxe da expressão (validação da configuração).
+///
+/// # Erros
+/// Mensagem com a posição do primeiro problema na expressão.
+pub fn check(source: &str) -> Result<(), String> {
+    parse(source).map(|_| ())
+}
+
+/// Aplica a expressão a `input` e devolve os registros selecionados.
+///
+/// # Erros
+/// Erro de sintaxe ou seleção que não resulte em objetos.
+pub fn records(source: &str, input: &Value) -> Result<Vec<Value>, String> {
+    let query = parse(source)?;
+    let mut records = Vec::new();
+    for node in select(&query, input, input) {
+        match node {
+            Value::Array(items) => records.extend(items.iter().cloned()),
+            Value::Object(_) => records.push(node.clone()),
+            Value::Null => {}
+            other => {
+                return Err(format!(
+                    "a seleção deve resultar em objetos ou arrays de objetos; encontrado {}",
+                    type_name(other)
+                ));
+            }
+        }
+    }
+    if let Some((index, value)) = records.iter().enumerate().find(|(_, value)| !value.is_object()) {
+        return Err(format!(
+            "a seleção deve resultar em objetos; o registro {} é {}",
+            index,
+            type_name(value)
+        ));
+    }
+    Ok(records)
+}
+
+/// Caminho a partir da raiz (`$`) ou do nó do filtro (`@`).
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    relative: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// Filhos do nó (`.a`, `[0]`, `[*]`).
+    Child(Vec<Selector>),
+    /// O nó e todos os seus descendentes (`..a`).
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    Filter(Box<Filter>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Or(Box<Filter>, Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Compare(Operand, Comparison, Operand),
+    Exists(Query),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Literal(Value),
+    Query(Query),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Root,
+    Current,
+    Dot,
+    DotDot,
+    Name(String),
+    Text(String),
+    Number(f64),
+    Punct(&'static str),
+}
+
+/// Pontuação, das mais longas para as mais curtas.
+const PUNCTUATION: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "[", "]", "(", ")", ",", ":", "*", "?",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let name_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let token = if c == '$' {
+            i += 1;
+            Token::Root
+        } else if c == '@' {
+            i += 1;
+            Token::Current
+        } else if c == '.' {
+            i += 1;
+            if chars.get(i) == Some(&'.') {
+                i += 1;
+                Token::DotDot
+            } else {
+                Token::Dot
+            }
+        } else if c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) {
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            Token::Number(
+                text.parse()
+                    .map_err(|_| format!("posição {}: número inválido '{}'", start, text))?,
+            )
+        } else if c == '\'' || c == '"' {
+            i += 1;
+            let mut text = String::new();
+            loop {
+                match chars.get(i) {
+                    None => return Err(format!("posição {}: texto sem aspas de fechamento", start)),
+                    Some(&quote) if quote == c => break,
+                    Some('\\') => {
+                        let escaped = chars
+                            .get(i + 1)
+                            .ok_or_else(|| format!("posição {}: texto sem aspas de fechamento", start))?;
+                        text.push(match escaped {
+                            'n' => '\n',
+                            't' => '\t',
+                            other => *other,
+                        });
+                        i += 2;
+                        continue;
+                    }
+                    Some(other) => text.push(*other),
+                }
+                i += 1;
+            }
+            i += 1;
+            Token::Text(text)
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && name_char(chars[i]) {
+                i += 1;
+            }
+            Token::Name(chars[start..i].iter().collect())
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let Some(punct) = PUNCTUATION.iter().find(|p| rest.starts_with(**p)) else {
+                return Err(format!("posição {}: caractere inesperado '{}'", start, c));
+            };
+            i += punct.chars().count();
+            Token::Punct(punct)
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+fn parse(source: &str) -> Result<Query, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+        end: source.chars().count(),
+    };
+    if parser.tokens.is_empty() {
+        return Err("expressão vazia".to_string());
+    }
+    if parser.next() != Some(Token::Root) {
+        return Err("a expressão deve começar pela raiz '$'".to_string());
+    }
+    let query = parser.query(false)?;
+    match parser.peek() {
+        None => Ok(query),
+        Some(token) => Err(parser.error(&format!("{} inesperado", describe(token)))),
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Root => "'$'".to_string(),
+        Token::Current => "'@'".to_string(),
+        Token::Dot => "'.'".to_string(),
+        Token::DotDot => "'..'".to_string(),
+        Token::Name(name) => format!("'{}'", name),
+        Token::Text(text) => format!("'{}'", text),
+        Token::Number(number) => number.to_string(),
+        Token::Punct(punct) => format!("'{}'", punct),
+    }
+}
+
+/// Analisador descendente: caminho → segmentos → seletores → filtros.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, message: &str) -> String {
+        let offset = self.tokens.get(self.position).map_or(self.end, |(offset, _)| *offset);
+        format!("posição {}: {}", offset, message)
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(p)) if *p == punct) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            return Ok(());
+        }
+        Err(self.error(&format!("'{}' esperado", punct)))
+    }
+
+    /// Segmentos após `$` ou `@`.
+    fn query(&mut self, relative: bool) -> Result<Query, String> {
+        let mut segments = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Token::Dot) => {
+                    self.position += 1;
+                    segments.push(Segment::Child(vec![self.dotted()?]));
+                }
+                Some(Token::DotDot) => {
+                    self.position += 1;
+                    let selectors = if self.eat("[") {
+                        self.bracket()?
+                    } else {
+                        vec![self.dotted()?]
+                    };
+                    segments.push(Segment::Descendant(selectors));
+                }
+                Some(Token::Punct("[")) => {
+                    self.position += 1;
+                    segments.push(Segment::Child(self.bracket()?));
+                }
+                _ => return Ok(Query { relative, segments }),
+            }
+        }
+    }
+
+    /// Nome ou curinga após `.` ou `..`.
+    fn dotted(&mut self) -> Result<Selector, String> {
+        if self.eat("*") {
+            return Ok(Selector::Wildcard);
+        }
+        match self.peek().cloned() {
+            Some(Token::Name(name)) => {
+                self.position += 1;
+                Ok(Selector::Name(name))
+            }
+            _ => Err(self.error("nome ou '*' esperado")),
+        }
+    }
+
+    /// Seletores entre colchetes, já após o `[`.
+    fn bracket(&mut self) -> Result<Vec<Selector>, String> {
+        let mut selectors = vec![self.selector()?];
+        while self.eat(",") {
+            selectors.push(self.selector()?);
+        }
+        self.expect("]")?;
+        Ok(selectors)
+    }
+
+    fn selector(&mut self) -> Result<Selector, String> {
+        if self.eat("*") {
+            return Ok(Selector::Wildcard);
+        }
+        if self.eat("?") {
+            return Ok(Selector::Filter(Box::new(self.or()?)));
+        }
+        if matches!(self.peek(), Some(Token::Punct(":"))) {
+            return self.slice(None);
+        }
+        match self.peek().cloned() {
+            Some(Token::Text(name)) => {
+                self.position += 1;
+                Ok(Selector::Name(name))
+            }
+            Some(Token::Number(number)) => {
+                let index = self.integer(number)?;
+                self.position += 1;
+                if matches!(self.peek(), Some(Token::Punct(":"))) {
+                    return self.slice(Some(index));
+                }
+                Ok(Selector::Index(index))
+            }
+            _ => Err(self.error("nome entre aspas, índice, fatia, '*' ou filtro '?' esperado")),
+        }
+    }
+
+    /// Fatia `início:fim`, já após o início (se houver).
+    fn slice(&mut self, start: Option<i64>) -> Result<Selector, String> {
+        self.expect(":")?;
+        let end = match self.peek().cloned() {
+            Some(Token::Number(number)) => {
+                let end = self.integer(number)?;
+                self.position += 1;
+                Some(end)
+            }
+            _ => None,
+        };
+        if matches!(self.peek(), Some(Token::Punct(":"))) {
+            return Err(self.error("fatias com passo não são suportadas"));
+        }
+        Ok(Selector::Slice(start, end))
+    }
+
+    fn integer(&self, number: f64) -> Result<i64, String> {
+        if number.fract() != 0.0 {
+            return Err(self.error(&format!("índice inteiro esperado, encontrado {}", number)));
+        }
+        Ok(number as i64)
+    }
+
+    fn or(&mut self) -> Result<Filter, String> {
+        let mut filter = self.and()?;
+        while self.eat("||") {
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter, String> {
+        let mut filter = self.not()?;
+        while self.eat("&&") {
+            filter = Filter::And(Box::new(filter), Box::new(self.not()?));
+        }
+        Ok(filter)
+    }
+
+    fn not(&mut self) -> Result<Filter, String> {
+        if self.eat("!") {
+            return Ok(Filter::Not(Box::new(self.not()?)));
+        }
+        if self.eat("(") {
+            let filter = self.or()?;
+            self.expect(")")?;
+            return Ok(filter);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Filter, String> {
+        let left = self.operand()?;
+        let comparison = match self.peek() {
+            Some(Token::Punct("==")) => Comparison::Equal,
+            Some(Token::Punct("!=")) => Comparison::NotEqual,
+            Some(Token::Punct("<")) => Comparison::Less,
+            Some(Token::Punct("<=")) => Comparison::LessOrEqual,
+            Some(Token::Punct(">")) => Comparison::Greater,
+            Some(Token::Punct(">=")) => Comparison::GreaterOrEqual,
+            _ => {
+                return match left {
+                    Operand::Query(query) => Ok(Filter::Exists(query)),
+                    Operand::Literal(_) => Err(self.error("comparação esperada após o literal")),
+                };
+            }
+        };
+        self.position += 1;
+        Ok(Filter::Compare(left, comparison, self.operand()?))
+    }
+
+    fn operand(&mut self) -> Result<Operand, String> {
+        match self.next() {
+            Some(Token::Current) => Ok(Operand::Query(self.query(true)?)),
+            Some(Token::Root) => Ok(Operand::Query(self.query(false)?)),
+            Some(Token::Number(number)) => Ok(Operand::Literal(
+                serde_json::Number::from_f64(number).map_or(Value::Null, Value::Number),
+            )),
+            Some(Token::Text(text)) => Ok(Operand::Literal(Value::String(text))),
+            Some(Token::Name(name)) => match name.as_str() {
+                "true" => Ok(Operand::Literal(Value::Bool(true))),
+                "false" => Ok(Operand::Literal(Value::Bool(false))),
+                "null" => Ok(Operand::Literal(Value::Null)),
+                _ => {
+                    self.position -= 1;
+                    Err(self.error(&format!("'{}' não é suportado (funções e nomes soltos)", name)))
+                }
+            },
+            _ => {
+                self.position -= 1;
+                Err(self.error("caminho (@ ou $) ou literal esperado"))
+            }
+        }
+    }
+}
+
+/// Nós selecionados por `query`, a partir de `current` (`@`) ou de `root` (`$`).
+fn select<'a>(query: &Query, root: &'a Value, current: &'a Value) -> Vec<&'a Value> {
+    let mut nodes = vec![if query.relative { current } else { root }];
+    for segment in &query.segments {
+        let mut next = Vec::new();
+        for node in nodes {
+            match segment {
+                Segment::Child(selectors) => apply(selectors, node, root, &mut next),
+                Segment::Descendant(selectors) => {
+                    for descendant in descendants(node) {
+                        apply(selectors, descendant, root, &mut next);
+                    }
+                }
+            }
+        }
+        nodes = next;
+    }
+    nodes
+}
+
+fn apply<'a>(selectors: &[Selector], node: &'a Value, root: &'a Value, out: &mut Vec<&'a Value>) {
+    for selector in selectors {
+        match (selector, node) {
+            (Selector::Name(name), Value::Object(fields)) => out.extend(fields.get(name)),
+            (Selector::Wildcard, _) => out.extend(children(node)),
+            (Selector::Index(index), Value::Array(items)) => {
+                out.extend(normalize(*index, items.len()).and_then(|i| items.get(i)));
+            }
+            (Selector::Slice(start, end), Value::Array(items)) => {
+                let len = items.len() as i64;
+                let bound = |value: i64| (if value < 0 { len + value } else { value }).clamp(0, len) as usize;
+                let (start, end) = (start.map_or(0, bound), end.map_or(items.len(), bound));
+                if start < end {
+                    out.extend(&items[start..end]);
+                }
+            }
+            (Selector::Filter(filter), _) => {
+                out.extend(children(node).into_iter().filter(|child| test(filter, root, child)));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Elementos de um array ou valores de um objeto.
+fn children(node: &Value) -> Vec<&Value> {
+    match node {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(fields) => fields.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// O nó e os seus descendentes, em pré-ordem.
+fn descendants(node: &Value) -> Vec<&Value> {
+    let mut out = vec![node];
+    for child in children(node) {
+        out.extend(descendants(child));
+    }
+    out
+}
+
+fn normalize(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    usize::try_from(index).ok().filter(|i| *i < len)
+}
+
+fn test(filter: &Filter, root: &Value, current: &Value) -> bool {
+    match filter {
+        Filter::Or(left, right) => test(left, root, current) || test(right, root, current),
+        Filter::And(left, right) => test(left, root, current) && test(right, root, current),
+        Filter::Not(inner) => !test(inner, root, current),
+        Filter::Exists(query) => !select(query, root, current).is_empty(),
+        Filter::Compare(left, comparison, right) => {
+            let (left, right) = (resolve(left, root, current), resolve(right, root, current));
+            compare(left, *comparison, right)
+        }
+    }
+}
+
+/// Valor de um operando; caminhos com zero ou vários resultados não têm valor.
+fn resolve<'a>(operand: &'a Operand, root: &'a Value, current: &'a Value) -> Option<&'a Value> {
+    match operand {
+        Operand::Literal(value) => Some(value),
+        Operand::Query(query) => match select(query, root, current).as_slice() {
+            [value] => Some(value),
+            _ => None,
+        },
+    }
+}
+
+fn compare(left: Option<&Value>, comparison: Comparison, right: Option<&Value>) -> bool {
+    let equal = match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => equal(left, right),
+        _ => false,
+    };
+    let order = match (left, right) {
+        (Some(Value::Number(left)), Some(Value::Number(right))) => {
+            left.as_f64().zip(right.as_f64()).and_then(|(l, r)| l.partial_cmp(&r))
+        }
+        (Some(Value::String(left)), Some(Value::String(right))) => Some(left.cmp(right)),
+        _ => None,
+    };
+    match comparison {
+        Comparison::Equal => equal,
+        Comparison::NotEqual => !equal,
+        Comparison::Less => order == Some(Ordering::Less),
+        Comparison::LessOrEqual => equal || order == Some(Ordering::Less),
+        Comparison::Greater => order == Some(Ordering::Greater),
+        Comparison::GreaterOrEqual => equal || order == Some(Ordering::Greater),
+    }
+}
+
+/// Igualdade com números comparados pelo valor (`1 == 1.0`).
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(l, r)| equal(l, r))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len() && left.iter().all(|(key, l)| right.get(key).is_some_and(|r| equal(l, r)))
+        }
+        _ => left == right,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fixture() -> Value {
+        json!({
+            "data": [
+                { "id": 1, "valor": 5, "active": true, "records": [{ "n": 1 }] },
+                { "id": 2, "valor": 10, "active": false, "records": [{ "n": 2 }] },
+                { "id": 3, "valor": 42.5, "active": true, "records": [{ "n": 3 }, { "n": 4 }] },
+                { "id": 4, "valor": "11", "active": true }
+            ]
+        })
+    }
+
+    fn ids(records: &[Value]) -> Vec<&Value> {
+        records.iter().map(|record| &record["id"]).collect()
+    }
+
+    #[test]
+    fn filter_keeps_only_matching_records() {
+        let path = "$.data[?(@.valor > 10)]";
+        assert_eq!(check(path), Ok(()));
+        // 10 não é maior que 10; o texto "11" não se compara com números
+        assert_eq!(ids(&records(path, &fixture()).unwrap()), [&json!(3)]);
+
+        let both = records("$.data[?(@.valor >= 10 && @.active == true)]", &fixture()).unwrap();
+        assert_eq!(ids(&both), [&json!(3)]);
+        let either = records("$.data[?(@.valor < 10 || !(@.active == true))]", &fixture()).unwrap();
+        assert_eq!(ids(&either), [&json!(1), &json!(2)]);
+    }
+
+    #[test]
+    fn filter_selects_nested_arrays() {
+        let nested = records("$.data[?(@.active == true)].records", &fixture()).unwrap();
+        assert_eq!(nested, [json!({ "n": 1 }), json!({ "n": 3 }), json!({ "n": 4 })]);
+        // Existência: só registros com `records`
+        assert_eq!(records("$.data[?(@.records)]", &fixture()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn check_rejects_malformed_paths() {
+        for path in ["data[0]", "$.data[?(@.valor > )]", "$.data[", "$.data[?(length(@) > 1)]", "$..", "$.data[1:x]"] {
+            assert!(check(path).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn scalars_are_not_records() {
+        assert!(records("$.data[*].valor", &fixture()).is_err());
+    }
+}
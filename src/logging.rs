@@ -0,0 +1,267 @@
+//! # Logs Operacionais
+//!
+//! Implementação do `log::Log`: mensagens operacionais (endpoints processados,
+//! avisos e falhas) vão para o console e/ou para um arquivo, de forma
+//! independente. Banners e barras de progresso seguem apenas no console.
+//!
+//! ## Destinos
+//! - **Console** (padrão; desligável com `--no-console-log`): `info` no stdout,
+//!   `warn`/`error` no stderr, sem prefixos. Com `--output -`, tudo vai para o
+//!   stderr: o stdout fica reservado aos dados.
+//! - **Arquivo** (`--log-file`): uma linha por mensagem com horário UTC e nível.
+//!
+//! ## Depuração
+//! Com `--verbose`, mensagens `debug` deste crate (ex: requisições e respostas
+//! HTTP) seguem para os mesmos destinos; as das dependências continuam de fora.
+//!
+//! ## Rotação
+//! Ao exceder `--log-max-size` (MB), o arquivo vira `{arquivo}.1` (os anteriores
+//! deslocam para `.2`, `.3`...), mantendo no máximo `--log-keep` cópias.
+
+use chrono::{SecondsFormat, Utc};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Tamanho padrão (MB) que dispara a rotação.
+pub const DEFAULT_MAX_SIZE_MB: u64 = 10;
+
+/// Cópias rotacionadas mantidas por padrão.
+pub const DEFAULT_KEEP: usize = 5;
+
+/// Destinos e rotação escolhidos na linha de comando.
+#[derive(Debug, Clone)]
+pub struct LogOptions {
+    pub console: bool,
+    /// Envia também `info` ao stderr (stdout ocupado por `--output -`).
+    pub stderr_only: bool,
+    /// Inclui as mensagens `debug` do próprio crate.
+    pub verbose: bool,
+    pub file: Option<PathBuf>,
+    pub max_bytes: u64,
+    pub keep: usize,
+}
+
+/// Instala o logger global.
+///
+/// # Erros
+/// Falha se o arquivo de log não puder ser aberto.
+pub fn init(options: &LogOptions) -> io::Result<()> {
+    let file = match &options.file {
+        Some(path) => Some(Mutex::new(RotatingFile::open(path, options.max_bytes, options.keep)?)),
+        None => None,
+    };
+
+    let logger = Logger {
+        console: options.console,
+        stderr_only: options.stderr_only,
+        verbose: options.verbose,
+        file,
+    };
+    log::set_boxed_logger(Box::new(logger)).map_err(io::Error::other)?;
+    log::set_max_level(if options.verbose { LevelFilter::Debug } else { LevelFilter::Info });
+    Ok(())
+}
+
+struct Logger {
+    console: bool,
+    stderr_only: bool,
+    verbose: bool,
+    file: Option<Mutex<RotatingFile>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info
+            || self.verbose
+                && metadata.level() == Level::Debug
+                && metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let message = record.args().to_string();
+
+        if self.console {
+            match record.level() {
+                Level::Error | Level::Warn => eprintln!("{}", message),
+                _ if self.stderr_only => eprintln!("{}", message),
+                _ => println!("{}", message),
+            }
+        }
+
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let line = format!(
+                "{} {:<5} {}\n",
+                Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+                record.level(),
+                // Quebras decorativas do console não fazem sentido no arquivo
+                message.trim_start_matches('\n')
+            );
+            // Falhas de escrita do log não devem derrubar o pipeline
+            let _ = file.write_line(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+/// Arquivo de log com rotação por tamanho.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_bytes,
+            keep,
+        })
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// `{arquivo}.N-1` → `{arquivo}.N`, ..., `{arquivo}` → `{arquivo}.1`.
+    fn rotate(&mut self) -> io::Result<()> {
+        let _ = fs::remove_file(rotated_path(&self.path, self.keep));
+        for index in (1..self.keep).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        if self.keep > 0 {
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Caminho da cópia rotacionada `index` (`app.log` → `app.log.1`).
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Logger global dos testes, instalado uma única vez: `debug` incluído,
+/// apenas em arquivo.
+///
+/// # Returns
+/// O arquivo de log compartilhado pelos testes do processo.
+#[cfg(test)]
+pub(crate) fn capture() -> &'static Path {
+    use std::sync::OnceLock;
+
+    static FILE: OnceLock<PathBuf> = OnceLock::new();
+    FILE.get_or_init(|| {
+        let path = std::env::temp_dir().join(format!("data_gov_logging_{}.log", std::process::id()));
+        let _ = fs::remove_file(&path);
+        init(&LogOptions {
+            console: false,
+            stderr_only: false,
+            verbose: true,
+            file: Some(path.clone()),
+            max_bytes: u64::MAX,
+            keep: 0,
+        })
+        .unwrap();
+        path
+    })
+}
+
+/// Linhas do log de `capture` que contêm `marker`.
+#[cfg(test)]
+pub(crate) fn captured(marker: &str) -> Vec<String> {
+    log::logger().flush();
+    fs::read_to_string(capture())
+        .unwrap()
+        .lines()
+        .filter(|line| line.contains(marker))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_writes_timestamped_lines_to_the_file() {
+        capture();
+        log::info!("\nProcessando: marcador-init");
+        log::warn!("Aviso: marcador-init");
+
+        let lines = captured("marcador-init");
+
+        assert_eq!(lines.len(), 2, "{lines:?}");
+        // Horário UTC, nível alinhado e sem a quebra decorativa do console
+        assert!(lines[0].ends_with("Z INFO  Processando: marcador-init"), "{}", lines[0]);
+        assert!(lines[1].ends_with("Z WARN  Aviso: marcador-init"), "{}", lines[1]);
+    }
+
+    #[test]
+    fn rotation_keeps_the_configured_copies() {
+        let path = std::env::temp_dir().join(format!("data_gov_logging_{}_rotation.log", std::process::id()));
+        for index in 0..=3 {
+            let _ = fs::remove_file(rotated_path(&path, index));
+        }
+        let _ = fs::remove_file(&path);
+
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        for line in ["primeira\n", "segunda\n", "terceira\n", "quarta\n"] {
+            file.write_line(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "quarta\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 1)).unwrap(), "terceira\n");
+        assert_eq!(fs::read_to_string(rotated_path(&path, 2)).unwrap(), "segunda\n");
+        assert!(!rotated_path(&path, 3).exists());
+        for index in 1..=2 {
+            fs::remove_file(rotated_path(&path, index)).unwrap();
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
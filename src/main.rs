@@ -5,115 +5,262 @@
 //! transformações normalizadas via Polars com base em configurações dinâmicas.
 //!
 //! ## Princípios de Engenharia
-//! - **Resiliência (Fail-Soft)**: Erros individuais em endpoints não abortam o pipeline.
+//! - **Resiliência (Fail-Soft)**: Erros individuais em endpoints não abortam o pipeline
+//!   (exceto com `--strict`).
 //! - **Observabilidade**: Logs detalhados com tempos de execução por etapa.
 //! - **Atomização**: Garantia de que arquivos temporários sejam limpos apenas após o sucesso.
 
 mod api;
+mod archive;
+mod atomic;
+mod batch;
+mod big_numbers;
+mod bloom;
+mod cli;
+mod computed;
+mod config_check;
+mod daemon;
+mod dead_letter;
+mod delta;
+mod diff;
 mod errors;
+mod explain;
+mod extract;
 mod impl_errors;
+mod jq;
+mod jsonpath;
+mod json_schema;
+mod logging;
+mod manifest;
+mod memory;
 mod models;
+mod multipart;
+mod open_files;
+mod openapi;
+mod pagination;
+mod pipeline;
 mod processor;
+mod profile;
+mod progress;
+mod pseudonymize;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod sources;
+mod version;
+mod warnings;
+mod xml;
 
 use std::env;
 use std::fs;
-use std::path::Path;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::cli::{CliArgs, Command, DiffArgs, OpenApiArgs};
+use crate::logging::LogOptions;
 use crate::models::Config;
+use crate::pipeline::RunOptions;
+use crate::version::BuildInfo;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let global_timer = Instant::now();
+    let args = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Run(a)) => *a,
+        Ok(Command::Diff(d)) => std::process::exit(run_diff(&d)),
+        Ok(Command::FromOpenApi(o)) => std::process::exit(run_from_openapi(&o)),
+        Err(e) => {
+            eprintln!("Erro nos argumentos: {}", e);
+            std::process::exit(2);
+        }
+    };
 
-    println!("--- INICIANDO ETL PIPELINE ---");
+    if args.show_version {
+        println!("{}", BuildInfo::current());
+        return Ok(());
+    }
+
+    let log_options = LogOptions {
+        console: !args.no_console_log,
+        stderr_only: args.stdout.is_some(),
+        verbose: args.verbose,
+        file: args.log_file.as_ref().map(PathBuf::from),
+        max_bytes: args.log_max_size_mb.unwrap_or(logging::DEFAULT_MAX_SIZE_MB) * 1024 * 1024,
+        keep: args.log_keep.map_or(logging::DEFAULT_KEEP, |n| n as usize),
+    };
+    if let Err(e) = logging::init(&log_options) {
+        eprintln!("Erro ao abrir o arquivo de log: {}", e);
+        std::process::exit(2);
+    }
+
+    let run_id = args
+        .run_id
+        .clone()
+        .unwrap_or_else(manifest::generate_run_id);
+
+    banner(&args, "--- INICIANDO ETL PIPELINE ---");
+    log::info!("Run ID: {}", run_id);
+    if let Some(format) = args.stdout.filter(|f| f.is_binary()) {
+        log::warn!(
+            "Aviso: {} é binário; redirecione o stdout para um arquivo ou programa",
+            format.name()
+        );
+    }
 
     // Define o diretório base para armazenamento físico
     let data_root = Path::new("data");
-    if !data_root.exists() {
+    if !args.explain && !data_root.exists() {
         fs::create_dir_all(data_root)?;
     }
 
-    // Carrega configuração TOML (permite passar caminho via CLI)
-    let config_path = env::args()
-        .nth(1)
-        .unwrap_or_else(|| "endpoints_publicos.toml".to_string());
-
-    let config = match Config::load_from_file(&config_path) {
+    // Carrega a configuração (caminho via CLI; `-` lê o stdin)
+    let mut config = match Config::load(&args.config_path, args.config_format) {
         Ok(c) => c,
         Err(e) => {
-            eprintln!("Erro na carga de configuração: {}", e);
+            log::error!("Erro na carga de configuração: {}", e);
             std::process::exit(1);
         }
     };
 
-    // Reuso de conexões/Keep-alive para performance
-    let client = api::create_http_client()?;
-
-    for (api_name, api_config) in &config.apis {
-        println!("\n Domínio: {}", api_name.to_uppercase());
-
-        for (group_name, group_config) in &api_config.endpoints {
-            println!("Grupo: {}", group_name);
-
-            // Resgata metadado de normalização (root_path) do TOML
-            let root_path = group_config.root_path.as_deref();
-
-            // Garante estrutura de pastas: data/{api}/{grupo}
-            let group_dir = data_root.join(api_name).join(group_name);
-            fs::create_dir_all(&group_dir)?;
-
-            // Itera sobre as rotas dinâmicas capturadas pelo flatten
-            for key in group_config.routes.keys() {
-                let step_timer = Instant::now();
-
-                // Resolve URL completa
-                let url = match config.resolve_endpoint_url(api_name, group_name, key) {
-                    Ok(u) => u,
-                    Err(e) => {
-                        eprintln!("Erro ao resolver URL para '{}': {}", key, e);
-                        continue;
-                    }
-                };
-
-                // Pula endpoints que exigem substituição manual de ID {id}
-                if url.contains('{') {
-                    continue;
-                }
-
-                // Definição de caminhos físicos
-                let path_json = group_dir.join(format!("{}_temp.json", key));
-                let path_parquet = group_dir.join(format!("{}.parquet", key));
-
-                println!("Processando: {}", key);
-
-                if let Err(e) = api::fetch_data_to_disk(&client, &url, &path_json) {
-                    eprintln!("Falha no Download: {}", e);
-                    continue;
-                }
-
-                // Injeta o root_path específico de cada órgão/grupo
-                match processor::process_json_to_parquet(&path_json, &path_parquet, root_path) {
-                    Ok(_) => {
-                        println!(
-                            "Sucesso: Parquet gerado ({:.2?})",
-                            step_timer.elapsed()
-                        );
-                    }
-                    Err(e) => {
-                        eprintln!("Falha na Transformação: {}", e);
-                    }
-                }
+    apply_allowed_hosts(&mut config, &args.allow_hosts);
+
+    let mut options = RunOptions {
+        strict: args.strict,
+        // Barras de progresso não disputam o terminal com os dados no stdout
+        quiet: args.quiet || args.stdout.is_some(),
+        max_memory_mb: args.max_memory_mb,
+        run_id,
+        manifest_path: args.manifest_path.as_ref().map(PathBuf::from),
+        outputs_csv: args.outputs_csv.as_ref().map(PathBuf::from),
+        manifest_every: args.manifest_every,
+        sample: args.sample,
+        emit_json_schema: args.emit_json_schema,
+        preview: args.preview,
+        explain: args.explain,
+        timings: args.timings,
+        tags: args.tags.clone(),
+        stdout: args.stdout,
+    };
+
+    // Plano: sem rede nem disco
+    if options.explain {
+        return pipeline::run_explain(&config, data_root, &options);
+    }
+
+    // Prévia: apenas HEADs, sem download nem ciclos do daemon
+    if options.preview {
+        let client = api::create_http_client(&config.http)?;
+        return pipeline::run_preview(&config, &client, data_root, &options);
+    }
+
+    if args.profile.is_some() {
+        profile::enable();
+    }
+
+    loop {
+        let global_timer = Instant::now();
+
+        // Reuso de conexões/Keep-alive para performance
+        let client = api::create_http_client(&config.http)?;
+
+        if let Err(e) = pipeline::run_pipeline(&config, &client, data_root, &options) {
+            log::error!("Execução abortada: {}", e);
+            // Fora do modo daemon, o abort encerra o processo
+            if args.interval.is_none() {
+                std::process::exit(1);
+            }
+        }
+
+        if let Some(path) = &args.profile {
+            profile::record("run", None, global_timer, global_timer.elapsed(), &[]);
+            match profile::write(Path::new(path)) {
+                Ok(()) => log::info!("Perfil gravado em: {}", path),
+                Err(e) => log::warn!("Aviso: falha ao gravar o perfil '{}': {}", path, e),
             }
         }
+
+        banner(&args, "\n==========================================");
+        banner(&args, "Fim da extração e conversão de dados");
+        log::info!(
+            "Tempo de execução: {:.2?}",
+            global_timer.elapsed()
+        );
+        banner(&args, "==========================================");
+
+        let Some(interval) = args.interval else {
+            break;
+        };
+
+        log::info!("Próximo ciclo em {}s", interval);
+        thread::sleep(Duration::from_secs(interval));
+
+        // Aplica endpoints adicionados/removidos sem reiniciar o processo
+        config = daemon::reload_config(&args.config_path, args.config_format, config);
+        apply_allowed_hosts(&mut config, &args.allow_hosts);
+
+        // Cada ciclo é uma nova execução, salvo um `--run-id` explícito
+        if args.run_id.is_none() {
+            options.run_id = manifest::generate_run_id();
+            log::info!("Run ID: {}", options.run_id);
+        }
     }
 
-    println!("\n==========================================");
-    println!("Fim da extração e conversão de dados");
-    println!(
-        "Tempo de execução: {:.2?}",
-        global_timer.elapsed()
+    Ok(())
+}
+
+/// Banners do console; com `--output -`, vão para o stderr junto com os logs.
+fn banner(args: &CliArgs, text: &str) {
+    if args.stdout.is_some() {
+        eprintln!("{}", text);
+    } else {
+        println!("{}", text);
+    }
+}
+
+/// `--allow-host` substitui a allowlist do TOML: a própria configuração pode
+/// ser a parte não confiável, então não deve poder ampliá-la.
+fn apply_allowed_hosts(config: &mut Config, hosts: &[String]) {
+    if !hosts.is_empty() {
+        config.http.allowed_hosts = Some(hosts.to_vec());
+    }
+}
+
+/// Executa `diff` e imprime o resumo.
+///
+/// Códigos de saída como os do `diff`: 0 sem diferenças, 1 com diferenças, 2 em erro.
+fn run_diff(args: &DiffArgs) -> i32 {
+    let report = diff::compare(
+        Path::new(&args.old_path),
+        Path::new(&args.new_path),
+        &args.keys,
+        args.output.as_deref().map(Path::new),
     );
-    println!("==========================================");
+    match report {
+        Ok(report) => {
+            print!("{}", report);
+            if let Some(output) = &args.output {
+                println!("Linhas divergentes gravadas em: {}", output);
+            }
+            if report.is_empty() { 0 } else { 1 }
+        }
+        Err(e) => {
+            eprintln!("Erro no diff: {}", e);
+            2
+        }
+    }
+}
 
-    Ok(())
+/// Executa `from_openapi`: o `.toml` vai para o stdout e os erros para o stderr.
+fn run_from_openapi(args: &OpenApiArgs) -> i32 {
+    let generated = openapi::load(&args.source)
+        .map_err(|e| e.to_string())
+        .and_then(|spec| openapi::skeleton(&spec, &args.source, args.api.as_deref()));
+    match generated {
+        Ok(toml) => {
+            print!("{}", toml);
+            0
+        }
+        Err(e) => {
+            eprintln!("Erro no from_openapi: {}", e);
+            1
+        }
+    }
 }
@@ -0,0 +1,361 @@
+//! # Manifesto da Execução
+//!
+//! Registro em JSON de tudo que uma invocação produziu, ligado pelo `run_id`:
+//! metadados de build, horários e o resultado de cada endpoint.
+//!
+//! ## Uso
+//! Opcional, habilitado por `--manifest <caminho>`. É gravado ao final da
+//! execução, inclusive quando o modo strict aborta no primeiro erro.
+//!
+//! Com `--manifest-every N`, uma versão parcial (`finished_at` nulo) é gravada
+//! a cada N endpoints encerrados: se o processo morrer, o manifesto lista
+//! exatamente os endpoints que terminaram. Cada gravação é atômica.
+//! `--outputs-csv <caminho>` grava os mesmos registros em CSV (uma linha por
+//! endpoint), para consumo em planilhas.
+//!
+//! No modo `--sample`, o campo `sample` registra o limite de linhas, e os
+//! caminhos das saídas levam o marcador `.sample`.
+//!
+//! `warnings` lista os avisos da execução (endpoint, etapa e mensagem), para
+//! que o CI possa contá-los.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::atomic;
+use crate::errors::ProcessorError;
+use crate::processor::{CoercionReport, StageTimings};
+use crate::version::BuildInfo;
+use crate::warnings::Warning;
+
+/// Gera um identificador de execução (UUID v4).
+pub fn generate_run_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Cabeçalho do CSV de saídas.
+const CSV_HEADER: [&str; 9] = [
+    "endpoint",
+    "path",
+    "format",
+    "rows",
+    "cols",
+    "bytes",
+    "sha256",
+    "duration_ms",
+    "status",
+];
+
+/// Situação final de um endpoint.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Success,
+    Failed,
+    /// Não executado porque uma dependência falhou.
+    Skipped,
+    /// Grupo desativado na configuração (`enabled = false`).
+    Disabled,
+}
+
+impl Status {
+    /// Nome como gravado no manifesto.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Success => "success",
+            Status::Failed => "failed",
+            Status::Skipped => "skipped",
+            Status::Disabled => "disabled",
+        }
+    }
+}
+
+/// Resultado de um endpoint no manifesto.
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointRecord {
+    /// Identificador completo `api.grupo.chave`.
+    pub endpoint: String,
+    /// Tags do endpoint (`tags` do grupo).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub status: Status,
+    /// Caminho do arquivo final (apenas em caso de sucesso).
+    pub path: Option<String>,
+    pub format: &'static str,
+    pub rows: Option<usize>,
+    pub cols: Option<usize>,
+    /// `totalRegistros` do envelope, quando `reconcile` está configurado.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared_rows: Option<usize>,
+    /// Coerções de tipo por coluna (tentados/anulados).
+    #[serde(skip_serializing_if = "CoercionReport::is_empty")]
+    pub coercions: CoercionReport,
+    /// Valores distintos por coluna (`report_cardinality`).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub cardinality: BTreeMap<String, usize>,
+    /// Uma entrada por codec quando `compression` é uma lista; `path`,
+    /// `bytes` e `sha256` do registro são os da primeira.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<VariantRecord>,
+    /// Tamanho do arquivo final em disco.
+    pub bytes: Option<u64>,
+    /// SHA-256 (hex) do arquivo final.
+    pub sha256: Option<String>,
+    pub duration_ms: u64,
+    /// Tempo por etapa (apenas em caso de sucesso).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Timings>,
+    pub error: Option<String>,
+}
+
+/// Variante de uma saída gravada com um dos codecs de `compression`.
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantRecord {
+    pub compression: &'static str,
+    pub path: String,
+    /// Tamanho em disco, para comparar o custo de armazenamento dos codecs.
+    pub bytes: Option<u64>,
+    pub sha256: Option<String>,
+}
+
+/// Tempo por etapa de um endpoint, em milissegundos.
+///
+/// As etapas somam aproximadamente `duration_ms`; a diferença são logs e medições.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Timings {
+    /// Até os cabeçalhos das respostas: DNS, conexão, TLS e espera do servidor.
+    pub connect_ms: u64,
+    pub download_ms: u64,
+    pub parse_ms: u64,
+    pub transform_ms: u64,
+    /// Gravação da saída, JSON Schema e `post_compress`.
+    pub write_ms: u64,
+}
+
+impl From<StageTimings> for Timings {
+    fn from(timings: StageTimings) -> Self {
+        let ms = |d: std::time::Duration| d.as_millis() as u64;
+        Self {
+            connect_ms: ms(timings.connect),
+            download_ms: ms(timings.download),
+            parse_ms: ms(timings.parse),
+            transform_ms: ms(timings.transform),
+            write_ms: ms(timings.write),
+        }
+    }
+}
+
+/// Documento completo gravado em disco.
+#[derive(Debug, Serialize)]
+pub struct Manifest {
+    pub run_id: String,
+    pub build: BuildInfo,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Linhas por endpoint no modo `--sample` (saídas incompletas).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample: Option<usize>,
+    pub endpoints: Vec<EndpointRecord>,
+    /// Avisos da execução, na ordem de registro (vazio quando não houve).
+    pub warnings: Vec<Warning>,
+}
+
+impl Manifest {
+    /// Inicia o manifesto no começo da execução.
+    pub fn new(run_id: &str, sample: Option<usize>) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            build: BuildInfo::current(),
+            started_at: Utc::now(),
+            finished_at: None,
+            sample,
+            endpoints: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Total de valores anulados por coerções e colunas afetadas.
+    pub fn coercion_failures(&self) -> (usize, usize) {
+        self.endpoints
+            .iter()
+            .flat_map(|record| record.coercions.values())
+            .filter(|c| c.failed > 0)
+            .fold((0, 0), |(values, columns), c| (values + c.failed, columns + 1))
+    }
+
+    /// Endpoints cujo total declarado diverge das linhas gravadas.
+    pub fn reconciliation_mismatches(&self) -> usize {
+        self.endpoints
+            .iter()
+            .filter(|r| r.declared_rows.is_some() && r.declared_rows != r.rows)
+            .count()
+    }
+
+    /// Endpoints bem-sucedidos e totais por tag, em ordem alfabética.
+    pub fn tag_summary(&self) -> BTreeMap<&str, (usize, usize)> {
+        let mut summary = BTreeMap::new();
+        for record in &self.endpoints {
+            for tag in &record.tags {
+                let (succeeded, total) = summary.entry(tag.as_str()).or_insert((0, 0));
+                *total += 1;
+                if record.status == Status::Success {
+                    *succeeded += 1;
+                }
+            }
+        }
+        summary
+    }
+
+    /// Anexa o resultado de um endpoint.
+    pub fn record(&mut self, record: EndpointRecord) {
+        self.endpoints.push(record);
+    }
+
+    /// Marca o término e grava o JSON formatado em `path`.
+    pub fn write(&mut self, path: &Path) -> Result<(), ProcessorError> {
+        self.finished_at = Some(Utc::now());
+        self.checkpoint(path)
+    }
+
+    /// Grava o estado atual em `path`, sem marcar o término (`--manifest-every`).
+    pub fn checkpoint(&self, path: &Path) -> Result<(), ProcessorError> {
+        create_parent(path)?;
+        atomic::write(path, |file| {
+            let mut out = BufWriter::new(file);
+            serde_json::to_writer_pretty(&mut out, self)?;
+            out.flush().map_err(ProcessorError::Io)
+        })
+    }
+
+    /// Grava o CSV de saídas (uma linha por endpoint) em `path`.
+    pub fn write_csv(&self, path: &Path) -> Result<(), ProcessorError> {
+        create_parent(path)?;
+        atomic::write(path, |file| self.write_csv_rows(BufWriter::new(file)))
+    }
+
+    /// Cabeçalho e uma linha por endpoint.
+    fn write_csv_rows<W: Write>(&self, mut out: W) -> Result<(), ProcessorError> {
+        writeln!(out, "{}", CSV_HEADER.join(",")).map_err(ProcessorError::Io)?;
+        for record in &self.endpoints {
+            let fields = [
+                csv_field(&record.endpoint),
+                csv_field(record.path.as_deref().unwrap_or("")),
+                record.format.to_string(),
+                optional(record.rows),
+                optional(record.cols),
+                optional(record.bytes),
+                record.sha256.clone().unwrap_or_default(),
+                record.duration_ms.to_string(),
+                record.status.name().to_string(),
+            ];
+            writeln!(out, "{}", fields.join(",")).map_err(ProcessorError::Io)?;
+        }
+        out.flush().map_err(ProcessorError::Io)
+    }
+}
+
+/// Cria os diretórios pais de `path`.
+fn create_parent(path: &Path) -> Result<(), ProcessorError> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent).map_err(ProcessorError::Io)?;
+    }
+    Ok(())
+}
+
+/// Tamanho e SHA-256 (hex) de um arquivo, lido em streaming.
+pub fn file_digest(path: &Path) -> Result<(u64, String), ProcessorError> {
+    let mut reader = BufReader::new(File::open(path).map_err(ProcessorError::Io)?);
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buffer = [0u8; 64 * 1024];
+    let mut bytes = 0u64;
+
+    loop {
+        let read = reader.read(&mut buffer).map_err(ProcessorError::Io)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+        bytes += read as u64;
+    }
+
+    let hex = context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok((bytes, hex))
+}
+
+/// Campo CSV com aspas quando contém separador, aspas ou quebra de linha.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Valor numérico opcional (vazio quando ausente).
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(endpoint: &str, status: Status) -> EndpointRecord {
+        EndpointRecord {
+            endpoint: endpoint.to_string(),
+            tags: Vec::new(),
+            status,
+            path: None,
+            format: "parquet",
+            rows: None,
+            cols: None,
+            declared_rows: None,
+            coercions: CoercionReport::new(),
+            cardinality: BTreeMap::new(),
+            variants: Vec::new(),
+            bytes: None,
+            sha256: None,
+            duration_ms: 0,
+            timings: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn csv_has_the_header_and_one_row_per_endpoint() {
+        let mut manifest = Manifest::new("execucao-1", None);
+        manifest.record(EndpointRecord {
+            path: Some("data/compras/itens/pedidos, 2024.parquet".to_string()),
+            rows: Some(10),
+            cols: Some(3),
+            bytes: Some(2048),
+            sha256: Some("ab12".to_string()),
+            duration_ms: 150,
+            ..record("compras.itens.pedidos", Status::Success)
+        });
+        manifest.record(record("compras.itens.contratos", Status::Failed));
+
+        let mut out = Vec::new();
+        manifest.write_csv_rows(&mut out).unwrap();
+
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines, [
+            "endpoint,path,format,rows,cols,bytes,sha256,duration_ms,status",
+            "compras.itens.pedidos,\"data/compras/itens/pedidos, 2024.parquet\",parquet,10,3,2048,ab12,150,success",
+            "compras.itens.contratos,,parquet,,,,,0,failed",
+        ]);
+    }
+}
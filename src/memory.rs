@@ -0,0 +1,142 @@
+//! # Controle de Memória (RSS)
+//!
+//! Em runners compartilhados, um OOM-kill derruba o processo inteiro sem
+//! deixar rastro. Com `--max-memory-mb`, a conversão verifica o RSS do
+//! processo em pontos de controle e aborta apenas o endpoint atual com um
+//! erro de recurso (`ErrorKind::OutOfMemory`) antes de o sistema intervir.
+//!
+//! ## Estratégia
+//! - **Pré-checagem**: o custo da leitura é estimado pelo tamanho do staging;
+//!   se exceder o limite, o endpoint migra para a conversão em lotes (quando
+//!   elegível) ou é abortado antes de carregar o arquivo.
+//! - **Pontos de controle**: após a leitura e a cada lote gravado.
+//! - **Pico**: uma thread amostra o RSS durante a conversão; o pico é
+//!   logado por endpoint mesmo sem limite configurado.
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::errors::ProcessorError;
+
+/// Fator empírico entre o tamanho do JSON em disco e o pico de um DataFrame completo.
+const JSON_EXPANSION_FACTOR: u64 = 4;
+
+/// Intervalo de amostragem do monitor de pico.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// RSS atual do processo, em bytes (`None` se a plataforma não expuser).
+pub fn current_rss() -> Option<u64> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = System::new();
+    process_rss(&mut system, pid)
+}
+
+fn process_rss(system: &mut System, pid: Pid) -> Option<u64> {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        false,
+        ProcessRefreshKind::nothing().with_memory(),
+    );
+    system.process(pid).map(|p| p.memory())
+}
+
+/// Estimativa do RSS após carregar um JSON de `input_bytes` em um único DataFrame.
+pub fn estimated_load(input_bytes: u64) -> u64 {
+    current_rss()
+        .unwrap_or(0)
+        .saturating_add(input_bytes.saturating_mul(JSON_EXPANSION_FACTOR))
+}
+
+/// Converte bytes para MB (apenas para exibição).
+pub fn to_mb(bytes: u64) -> u64 {
+    bytes / (1024 * 1024)
+}
+
+/// Teto de RSS configurado via `--max-memory-mb`.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryLimit {
+    bytes: u64,
+}
+
+impl MemoryLimit {
+    /// Limite a partir do valor em MB da linha de comando.
+    pub fn from_mb(mb: u64) -> Self {
+        Self {
+            bytes: mb * 1024 * 1024,
+        }
+    }
+
+    /// Verifica se um consumo (em bytes) cabe no limite.
+    pub fn allows(&self, bytes: u64) -> bool {
+        bytes <= self.bytes
+    }
+
+    /// Ponto de controle: erro de recurso se o RSS atual já excede o limite.
+    pub fn check(&self, stage: &str) -> Result<(), ProcessorError> {
+        match current_rss() {
+            Some(rss) if rss > self.bytes => Err(self.exceeded(stage, rss)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Erro de recurso padronizado para o estágio informado.
+    pub fn exceeded(&self, stage: &str, bytes: u64) -> ProcessorError {
+        ProcessorError::Io(io::Error::new(
+            io::ErrorKind::OutOfMemory,
+            format!(
+                "limite de memória excedido em {} ({} MB de {} MB)",
+                stage,
+                to_mb(bytes),
+                to_mb(self.bytes)
+            ),
+        ))
+    }
+}
+
+/// Amostra o RSS em segundo plano, registrando o pico.
+pub struct PeakMonitor {
+    peak: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl PeakMonitor {
+    /// Inicia a amostragem (no-op silencioso se o PID não puder ser obtido).
+    pub fn start() -> Self {
+        let peak = Arc::new(AtomicU64::new(current_rss().unwrap_or(0)));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handle = sysinfo::get_current_pid().ok().map(|pid| {
+            let peak = Arc::clone(&peak);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                let mut system = System::new();
+                while !stop.load(Ordering::Relaxed) {
+                    if let Some(rss) = process_rss(&mut system, pid) {
+                        peak.fetch_max(rss, Ordering::Relaxed);
+                    }
+                    thread::sleep(SAMPLE_INTERVAL);
+                }
+            })
+        });
+
+        Self { peak, stop, handle }
+    }
+
+    /// Encerra a amostragem e retorna o pico observado, em bytes.
+    pub fn finish(mut self) -> u64 {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        if let Some(rss) = current_rss() {
+            self.peak.fetch_max(rss, Ordering::Relaxed);
+        }
+        self.peak.load(Ordering::Relaxed)
+    }
+}
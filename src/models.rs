@@ -9,76 +9,2014 @@
 //! - **Encapsulamento**: Validações de integridade ocorrem no momento da carga.
 //! - **Extensibilidade**: O uso de `flatten` permite adicionar novos metadados ao TOML
 //!   sem quebrar a compatibilidade de tipos.
+//!
+//! ## Fragmentos
+//! A configuração pode ser um diretório (ex: `conf.d/`): os `*.toml` são lidos
+//! em ordem de nome e mesclados. Uma mesma tabela pode se repetir entre
+//! fragmentos desde que as chaves sejam disjuntas; redefinir uma chave é erro.
+//!
+//! ## Stdin e JSON
+//! O caminho `-` lê a configuração do stdin (ex: gerada por um orquestrador).
+//! Sem extensão, o formato vem de `--config-format`; arquivos `.json` são lidos
+//! como JSON, com a mesma estrutura do TOML.
+
+use crate::api;
+use crate::atomic::{self, Retry};
+use crate::computed;
+use crate::config_check;
+use crate::errors::ProcessorError;
+use crate::jq;
+use crate::jsonpath;
+use crate::processor;
+use crate::sources;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::{Component, Path};
+use std::time::Duration;
+
+/// Configuração.
+// Mapeia o namespace da API (ex: "compras_federal") para as suas configurações.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Config {
+    /// Ajustes globais do cliente HTTP (seção reservada `[http]`).
+    #[serde(default)]
+    pub http: HttpSettings,
+
+    /// Paralelismo de downloads e conversões (seção reservada `[pipeline]`).
+    #[serde(default)]
+    pub pipeline: PipelineSettings,
+
+    /// Versão do formato da configuração (ver `config_check`).
+    #[serde(default)]
+    pub config_version: Option<u32>,
+
+    /// Modo estrito: recusa chaves desconhecidas (ver `config_check`).
+    #[serde(default)]
+    pub deny_unknown_keys: bool,
+
+    #[serde(flatten)]
+    pub apis: HashMap<String, ApiConfig>,
+}
+
+/// Paralelismo do pipeline, separado por etapa, novas tentativas da gravação
+/// e o limite de leitura em memória.
+///
+/// Downloads são limitados pela rede e conversões pela CPU: cada etapa tem seu
+/// próprio pool, ligados por uma fila limitada (ver `pipeline`).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PipelineSettings {
+    /// Endpoints baixados ao mesmo tempo (padrão: 1).
+    pub download_concurrency: Option<NonZeroUsize>,
+
+    /// Endpoints convertidos ao mesmo tempo (padrão: 1).
+    pub transform_concurrency: Option<NonZeroUsize>,
+
+    /// Downloads simultâneos de uma mesma origem (padrão: sem limite além de
+    /// `download_concurrency`).
+    pub max_per_host: Option<NonZeroUsize>,
+
+    /// Writers (staging e temporários das saídas) abertos ao mesmo tempo
+    /// (padrão: sem limite); sem vaga, o download ou a conversão espera.
+    pub max_open_files: Option<NonZeroUsize>,
+
+    /// Novas tentativas da gravação da saída em erros de I/O (padrão: 0).
+    #[serde(default)]
+    pub write_retries: u32,
+
+    /// Espera (ms) antes de cada nova tentativa de gravação (padrão: 500).
+    pub write_retry_delay_ms: Option<u64>,
+
+    /// Staging acima deste tamanho (MB) é convertido em lotes, sem carregar
+    /// a resposta inteira (padrão: sem limite).
+    pub stream_above_mb: Option<NonZeroU64>,
+
+    /// Diretório que recebe o staging das conversões que falharam, com o erro
+    /// ao lado (padrão: o staging fica no diretório do grupo).
+    pub dead_letter_dir: Option<String>,
+}
+
+impl PipelineSettings {
+    pub fn downloads(&self) -> NonZeroUsize {
+        self.download_concurrency.unwrap_or(NonZeroUsize::MIN)
+    }
+
+    pub fn transforms(&self) -> NonZeroUsize {
+        self.transform_concurrency.unwrap_or(NonZeroUsize::MIN)
+    }
+
+    /// Diretório de `dead_letter_dir`, se configurado.
+    pub fn dead_letter_dir(&self) -> Option<&Path> {
+        self.dead_letter_dir.as_deref().map(Path::new)
+    }
+
+    /// Limite de `stream_above_mb` em bytes.
+    pub fn stream_above(&self) -> Option<u64> {
+        self.stream_above_mb.map(|mb| mb.get().saturating_mul(1024 * 1024))
+    }
+
+    pub fn write_retry(&self) -> Retry {
+        Retry {
+            retries: self.write_retries,
+            delay: self
+                .write_retry_delay_ms
+                .map_or(atomic::DEFAULT_RETRY_DELAY, Duration::from_millis),
+        }
+    }
+}
+
+/// Ajustes do cliente HTTP compartilhado.
+///
+/// Todos os campos são opcionais; ausentes preservam o comportamento padrão do `reqwest`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct HttpSettings {
+    /// Máximo de conexões ociosas mantidas por host no pool.
+    pub pool_max_idle_per_host: Option<usize>,
+
+    /// Tempo (s) que uma conexão ociosa permanece no pool.
+    pub pool_idle_timeout_secs: Option<u64>,
+
+    /// Usa HTTP/2 direto, sem negociação (apenas para servidores que o suportam).
+    #[serde(default)]
+    pub http2_prior_knowledge: bool,
+
+    /// Intervalo (s) de TCP keepalive nos sockets.
+    pub tcp_keepalive_secs: Option<u64>,
+
+    /// Limite (s) para estabelecer a conexão (padrão: 10).
+    pub connect_timeout_secs: Option<u64>,
+
+    /// Limite (s) da requisição inteira, incluindo o corpo (padrão: 300).
+    pub timeout_secs: Option<u64>,
+
+    /// Versão mínima de TLS aceita na negociação (padrão: "1.2").
+    #[serde(default)]
+    pub min_tls_version: TlsVersion,
+
+    /// Únicos hosts que o cliente pode contatar (proteção contra SSRF).
+    /// Se None, qualquer host é aceito. `--allow-host` substitui esta lista.
+    pub allowed_hosts: Option<Vec<String>>,
+
+    /// Abre uma conexão por host (DNS + TLS) antes de despachar os endpoints.
+    #[serde(default)]
+    pub warm_up: bool,
+
+    /// Redirecionamentos seguidos por requisição (padrão: 10); 0 não segue nenhum.
+    pub max_redirects: Option<usize>,
+
+    /// Recusa redirecionamentos para um host diferente do da requisição original.
+    #[serde(default)]
+    pub block_cross_host_redirects: bool,
+
+    /// Novas tentativas de um download após falhas transitórias (padrão: 0).
+    #[serde(default)]
+    pub retries: u32,
+
+    /// Espera (ms) antes da primeira nova tentativa, dobrada a cada uma (padrão: 1000).
+    pub retry_delay_ms: Option<u64>,
+}
+
+/// Versões de TLS aceitas em `min_tls_version`.
+///
+/// Versões anteriores a 1.2 não são suportadas pelo backend (rustls) e são
+/// rejeitadas na carga.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TlsVersion {
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl TlsVersion {
+    /// Versão correspondente do `reqwest`.
+    pub fn to_reqwest(self) -> reqwest::tls::Version {
+        match self {
+            TlsVersion::Tls12 => reqwest::tls::Version::TLS_1_2,
+            TlsVersion::Tls13 => reqwest::tls::Version::TLS_1_3,
+        }
+    }
+}
+
+impl HttpSettings {
+    /// Timeout de conexão efetivo: hosts mortos falham rápido.
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs.unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS))
+    }
+
+    /// Timeout total efetivo: comporta downloads longos.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+
+}
+
+/// Espera padrão antes da primeira nova tentativa de download (ms).
+const DEFAULT_RETRY_DELAY_MS: u64 = 1000;
+
+/// Timeout de conexão padrão (s).
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Timeout total padrão (s), histórico do cliente.
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Configuração de uma Unidade de API.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ApiConfig {
+    /// Ponto de entrada base da API (ex: https://api.gov.br)
+    #[serde(default)]
+    pub base_url: String,
+
+    /// Credenciais HTTP Basic enviadas em todas as rotas da API.
+    pub basic_auth: Option<BasicAuth>,
+
+    /// Login que abre uma sessão (cookies) antes dos downloads da API.
+    pub login: Option<Login>,
+
+    /// Estratégia de espera entre novas tentativas dos downloads da API.
+    pub retry: Option<RetryPolicy>,
+
+    /// Dicionário de grupos de endpoints.
+    pub endpoints: HashMap<String, EndpointGroup>,
+}
+
+impl ApiConfig {
+    /// Novas tentativas dos downloads da API: `[http] retries` com o `retry` da API.
+    pub fn backoff(&self, http: &HttpSettings) -> api::Backoff {
+        let policy = self.retry.clone().unwrap_or_default();
+        let delay_ms = policy.delay_ms.or(http.retry_delay_ms).unwrap_or(DEFAULT_RETRY_DELAY_MS);
+        api::Backoff {
+            retries: http.retries,
+            strategy: policy.backoff,
+            base_delay: Duration::from_millis(delay_ms),
+            max_delay: policy.max_delay_ms.map(Duration::from_millis),
+            jitter: policy.jitter,
+        }
+    }
+
+    /// URL completa do login (`base_url` + `login.route`), se configurado.
+    pub fn login_url(&self) -> Option<String> {
+        self.login.as_ref().map(|login| join_urls(&self.base_url, &login.route))
+    }
+}
+
+/// Texto secreto da configuração (ex: `hash_salt`): o `Debug` nunca exibe o valor.
+#[derive(Deserialize, Clone)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+/// Credenciais HTTP Basic (`basic_auth = { user = "...", password = "${VAR}" }`).
+///
+/// A senha aceita referências `${VAR}` ao ambiente, resolvidas no planejamento
+/// para não manter segredos no TOML. O `Debug` nunca exibe a senha.
+#[derive(Deserialize, Clone)]
+pub struct BasicAuth {
+    pub user: String,
+    pub password: String,
+}
+
+impl BasicAuth {
+    /// Cópia com as referências `${VAR}` da senha substituídas pelo ambiente.
+    pub fn resolved(&self) -> Result<BasicAuth, String> {
+        Ok(BasicAuth {
+            user: self.user.clone(),
+            password: interpolate_env(&self.password)?,
+        })
+    }
+}
+
+impl fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("user", &self.user)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// Login da API (`login = { route = "/auth", form = { usuario = "...", senha = "${VAR}" } }`).
+///
+/// Com `form`, é um POST `application/x-www-form-urlencoded`; sem ele, um GET.
+/// Os cookies da resposta seguem em todas as requisições de dados da API. Os
+/// valores do formulário aceitam `${VAR}` e o `Debug` nunca os exibe.
+#[derive(Deserialize, Clone)]
+pub struct Login {
+    /// Rota do login, relativa ao `base_url`.
+    pub route: String,
+
+    /// Campos do formulário, em ordem de nome.
+    #[serde(default)]
+    pub form: BTreeMap<String, String>,
+}
+
+impl Login {
+    /// Campos do formulário com as referências `${VAR}` resolvidas.
+    pub fn resolved_form(&self) -> Result<Vec<(String, String)>, String> {
+        self.form
+            .iter()
+            .map(|(name, value)| Ok((name.clone(), interpolate_env(value)?)))
+            .collect()
+    }
+}
+
+impl fmt::Debug for Login {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Login")
+            .field("route", &self.route)
+            .field("form", &self.form.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Espera entre novas tentativas de uma API
+/// (`retry = { backoff = "linear", delay_ms = 500, max_delay_ms = 30000, jitter = 0.2 }`).
+///
+/// Campos ausentes seguem `[http]`: backoff exponencial a partir de
+/// `retry_delay_ms`, sem teto e sem jitter.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RetryPolicy {
+    /// Curva das esperas (padrão: exponencial).
+    #[serde(default)]
+    pub backoff: BackoffStrategy,
+
+    /// Espera (ms) antes da primeira nova tentativa (padrão: `[http] retry_delay_ms`).
+    pub delay_ms: Option<u64>,
+
+    /// Teto (ms) de cada espera, antes do jitter.
+    pub max_delay_ms: Option<u64>,
+
+    /// Fração de variação aleatória de cada espera (0.2 = ±20%; padrão: 0).
+    #[serde(default)]
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    fn collect_issues(&self, scope: &str, retries: u32, issues: &mut Vec<String>) {
+        if retries == 0 {
+            issues.push(format!("'{}': retry exige [http] retries > 0", scope));
+        }
+        if self.delay_ms == Some(0) {
+            issues.push(format!("'{}': retry.delay_ms deve ser maior que zero", scope));
+        }
+        if let (Some(delay), Some(max)) = (self.delay_ms, self.max_delay_ms)
+            && max < delay
+        {
+            issues.push(format!(
+                "'{}': retry.max_delay_ms ({}) menor que retry.delay_ms ({})",
+                scope, max, delay
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            issues.push(format!(
+                "'{}': retry.jitter deve estar entre 0 e 1 (recebido {})",
+                scope, self.jitter
+            ));
+        }
+    }
+}
+
+/// Curvas de espera aceitas em `retry.backoff`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackoffStrategy {
+    /// `delay_ms`, depois o dobro a cada tentativa.
+    #[default]
+    Exponential,
+    /// `delay_ms` vezes o número da tentativa.
+    Linear,
+    /// Sempre `delay_ms`.
+    Constant,
+}
+
+/// Representa um grupo de recursos com metadados de processamento.
+#[derive(Debug, Deserialize, Clone)]
+pub struct EndpointGroup {
+    /// Desativa temporariamente o grupo sem removê-lo do TOML (padrão: true).
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+
+    /// Identifica a chave JSON que contém a lista de dados (ex: "resultado", "dados").
+    /// Se None, assume que a estrutura é uma lista na raiz.
+    pub root_path: Option<String>,
+
+    /// Vários arrays do mesmo envelope, cada um em uma saída: nome → JSON Pointer
+    /// (ex: `{ compras = "/compras", contratos = "/dados/contratos" }`).
+    /// Substitui `root_path`; as saídas são gravadas em `{chave}_{nome}`.
+    #[serde(default)]
+    pub extract: BTreeMap<String, String>,
+
+    /// Filtro jq aplicado à resposta antes da leitura; deve produzir um array
+    /// de objetos (ex: `.dados.itens[] | {id, nome: .pessoa.nome}`).
+    pub jq_filter: Option<String>,
+
+    /// Expressão JSONPath que seleciona os registros da resposta (ex:
+    /// `$.data[?(@.active == true)].records`); alternativa ao `root_path`.
+    pub json_path: Option<String>,
+
+    /// Política para campos de `root_path` com o nome de uma coluna do envelope
+    /// (padrão: erro).
+    #[serde(default)]
+    pub on_duplicate_column: OnDuplicateColumn,
+
+    /// Formato do conteúdo baixado (padrão: JSON).
+    #[serde(default)]
+    pub input_format: InputFormat,
+
+    /// Formato do arquivo final (padrão: Parquet).
+    #[serde(default)]
+    pub output_format: OutputFormat,
+
+    /// Codec de compressão da saída. Padrão: Snappy (Parquet) ou nenhum (Avro).
+    /// Uma lista grava uma variante da saída por codec (`{chave}.{codec}.{formato}`).
+    pub compression: Option<CompressionSetting>,
+
+    /// Threads da compressão ZSTD de cada página, onde o backend do Parquet as
+    /// suportar; sem esse suporte, a opção é ignorada com um aviso.
+    pub zstd_workers: Option<NonZeroUsize>,
+
+    /// Acrescenta ao schema da tabela Delta as colunas novas do lote
+    /// (`mergeSchema`); sem ele, colunas novas falham o endpoint.
+    #[serde(default)]
+    pub delta_merge_schema: bool,
+
+    /// Versão das páginas de dados do Parquet (padrão: "v1", a mais compatível).
+    #[serde(default)]
+    pub parquet_version: ParquetVersion,
+
+    /// Field IDs do Parquet por coluna (ex: `{ id = 1, nome = 2 }`), estáveis
+    /// entre renomeações para leitores que resolvem colunas por ID (Iceberg).
+    #[serde(default)]
+    pub field_ids: BTreeMap<String, i32>,
+
+    /// Colunas com filtro de Bloom em cada row group do Parquet (ex: `["cnpj"]`),
+    /// para leitores que pulam row groups em buscas pontuais.
+    #[serde(default)]
+    pub bloom_filter_columns: Vec<String>,
+
+    /// Compressão do arquivo final inteiro após a escrita (ex: `dados.parquet.xz`).
+    pub post_compress: Option<PostCompress>,
+
+    /// Mantém o arquivo sem compressão ao lado do comprimido (padrão: removido).
+    #[serde(default)]
+    pub keep_uncompressed: bool,
+
+    /// Relê a saída gravada e confere o número de linhas antes de remover o
+    /// staging; uma divergência falha o endpoint e mantém o arquivo bruto.
+    #[serde(default)]
+    pub verify_after_write: bool,
+
+    /// Grava um `_SUCCESS` vazio no diretório do grupo quando todas as suas
+    /// saídas da execução terminam com sucesso (e em cada tabela Delta).
+    #[serde(default)]
+    pub write_success_marker: bool,
+
+    /// Caminho da saída relativo ao diretório de dados (ex:
+    /// `"{api}/{group}/{key}_{date}.{ext}"`). Padrão: `{api}/{group}/{key}.{ext}`.
+    pub output_template: Option<String>,
+
+    /// Política quando o arquivo final já existe (padrão: sobrescrever).
+    #[serde(default)]
+    pub on_exists: OnExists,
+
+    /// Respostas sem registros gravam uma saída vazia com o schema de
+    /// `empty_schema` ou da saída anterior; sem nenhum, o endpoint é ignorado.
+    #[serde(default)]
+    pub write_empty: bool,
+
+    /// Colunas da saída vazia (ex: `[{ column = "id", type = "int64" }]`).
+    #[serde(default)]
+    pub empty_schema: Vec<ColumnType>,
+
+    /// Nome do elemento que delimita cada registro em fontes XML (ex: "item").
+    pub record_element: Option<String>,
+
+    /// Opções de leitura de fontes CSV (delimitador, cabeçalho, tipos).
+    pub csv: Option<CsvOptions>,
+
+    /// Confronta `totalRegistros` do envelope com as linhas convertidas.
+    pub reconcile: Option<ReconcileMode>,
+
+    /// Conta os valores distintos de cada coluna gravada (resumo e manifesto).
+    #[serde(default)]
+    pub report_cardinality: bool,
+
+    /// Estima a cardinalidade com HyperLogLog em vez da contagem exata.
+    #[serde(default)]
+    pub approx_cardinality: bool,
+
+    /// Campo do envelope com o status de negócio (ex: "codigoRetorno").
+    pub status_field: Option<String>,
+
+    /// Valor de `status_field` que indica sucesso; qualquer outro falha o endpoint.
+    pub expected_status: Option<StatusValue>,
+
+    /// Campo do envelope com a mensagem da API, incluída no erro (ex: "mensagem").
+    pub status_message_field: Option<String>,
+
+    /// Colunas lidas como texto para preservar números longos (ex: IDs de 20 dígitos).
+    #[serde(default)]
+    pub big_number_columns: Vec<String>,
+
+    /// Textos tratados como nulo em colunas `String`: lista para todas as colunas
+    /// (ex: `["", "N/A"]`) ou tabela por coluna (ex: `{ uf = ["XX"] }`).
+    pub null_values: Option<NullValues>,
+
+    /// NaN e ±Infinity nas colunas de ponto flutuante: viram nulo (padrão),
+    /// falham o endpoint ou são mantidos.
+    #[serde(default)]
+    pub float_special: FloatSpecial,
+
+    /// Colunas de texto com espaços nas bordas removidos (ex: `["cnpj", "nome"]`).
+    #[serde(default)]
+    pub trim_columns: Vec<String>,
+
+    /// Remove espaços nas bordas de todas as colunas de texto.
+    #[serde(default)]
+    pub trim_all_strings: bool,
+
+    /// Com o trim, também reduz espaços internos repetidos a um único espaço.
+    #[serde(default)]
+    pub collapse_whitespace: bool,
+
+    /// Script Rhai com `fn transform(row)` aplicado antes da escrita (feature `scripting`).
+    pub transform_script: Option<String>,
+
+    /// Ordenação das linhas antes da escrita (ex: `[{ column = "data", descending = true }]`).
+    #[serde(default)]
+    pub sort_by: Vec<SortKey>,
+
+    /// Converte o array em lotes deste tamanho, limitando o pico de memória.
+    /// Se None, o arquivo inteiro é carregado em um único DataFrame.
+    pub batch_size: Option<NonZeroUsize>,
+
+    /// Limite de colunas após a normalização; excedê-lo falha o endpoint.
+    /// Se None, não há limite.
+    pub max_columns: Option<NonZeroUsize>,
+
+    /// Colunas que precisam existir após a normalização (qualquer tipo);
+    /// a ausência de qualquer uma falha o endpoint.
+    #[serde(default)]
+    pub required_columns: Vec<String>,
+
+    /// Colunas movidas para o início da saída, nesta ordem (ex: `["cnpj", "ano"]`);
+    /// as demais seguem na ordem de origem.
+    #[serde(default)]
+    pub columns_first: Vec<String>,
+
+    /// Razão máxima entre os bytes descomprimidos e os comprimidos de um
+    /// staging gzip (proteção contra gzip bombs). Se None, não há limite.
+    pub max_decompress_ratio: Option<NonZeroU64>,
+
+    /// Colunas List<Int64|Float64> que são bytes (0-255) de um texto UTF-8,
+    /// decodificadas para String. Se None, todas as listas numéricas são
+    /// decodificadas; com uma lista, as demais não são alteradas.
+    pub byte_array_columns: Option<Vec<String>>,
+
+    /// Une colunas List<escalar> em uma única String (List<Struct> é preservada).
+    #[serde(default)]
+    pub stringify_lists: bool,
+
+    /// Separador usado por `stringify_lists` (padrão: ",").
+    pub list_separator: Option<String>,
+
+    /// Nome da coluna que recebe o `run_id` da execução (ex: "_run_id").
+    pub run_id_column: Option<String>,
+
+    /// Acrescenta a coluna `_api` com o nome da API de origem.
+    #[serde(default)]
+    pub add_api_column: bool,
+
+    /// Acrescenta a coluna `_group` com o nome do grupo de origem.
+    #[serde(default)]
+    pub add_group_column: bool,
+
+    /// Colunas calculadas: nome → expressão sobre as colunas existentes (ex:
+    /// `{ total = "quantidade * preco", ano = "year(data)" }`; ver `computed`).
+    #[serde(default)]
+    pub computed_columns: BTreeMap<String, String>,
+
+    /// Colunas pseudonimizadas com o digest de `hash_salt + valor` (ex: `["cpf", "email"]`).
+    #[serde(default)]
+    pub hash_columns: Vec<String>,
+
+    /// Algoritmo de `hash_columns` (padrão: "sha256").
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+
+    /// Salt de `hash_columns`; aceita referências `${VAR}` ao ambiente.
+    pub hash_salt: Option<Secret>,
+
+    /// Glob de arquivos locais (ex: "archive/**/*.json"); cada arquivo vira um endpoint.
+    pub source_glob: Option<String>,
+
+    /// Template do nome dos endpoints locais (`{stem}`, `{parent}`). Padrão: "{stem}".
+    pub name_template: Option<String>,
+
+    /// Dependências por endpoint (ex: `{ fatos = ["dimensoes"] }`), executadas antes dele.
+    /// Referências aceitas: `chave` (mesmo grupo), `grupo.chave` ou `api.grupo.chave`.
+    #[serde(default)]
+    pub depends_on: HashMap<String, Vec<String>>,
+
+    /// Tags para seleção com `--tag`: lista para todos os endpoints do grupo
+    /// (ex: `["diario", "publico"]`) ou tabela por endpoint (ex: `{ fatos = ["diario"] }`).
+    pub tags: Option<Tags>,
+
+    /// Paginação das rotas HTTP (ex: `{ type = "cursor", next_path = "paging.next" }`).
+    pub pagination: Option<Pagination>,
+
+    /// Envia as rotas HTTP via POST com um formulário `multipart/form-data`.
+    pub multipart: Option<Multipart>,
+
+    /// Envia as rotas HTTP via POST com o corpo lido deste arquivo (ex: uma
+    /// consulta GraphQL em JSON); o `Content-Type` vem da extensão.
+    pub body_file: Option<String>,
+
+    /// Conteúdo de `body_file`, lido na carga com as referências `${VAR}` resolvidas.
+    #[serde(skip)]
+    pub body: Option<String>,
+
+    /// Comprime o corpo das requisições com gzip (`Content-Encoding: gzip`).
+    #[serde(default)]
+    pub compress_request: bool,
+
+    /// Trata respostas vazias como transitórias, repetidas conforme `[http] retries`.
+    #[serde(default)]
+    pub retry_on_empty: bool,
+
+    /// Baixa em `{staging}.part` e, se uma execução anterior parou no meio,
+    /// retoma com `Range` (validado pelo ETag) em vez de recomeçar.
+    #[serde(default)]
+    pub resume_downloads: bool,
+
+    /// Status HTTP aceitos como sucesso (ex: `[200, 206]`); outros, mesmo 2xx,
+    /// falham o endpoint. Vazio aceita qualquer 2xx.
+    #[serde(default)]
+    pub accept_status: Vec<u16>,
+
+    /// Mapeamento dinâmico de chaves de identificação para caminhos relativos.
+    /// Captura todas as chaves que não sejam metadados conhecidos (como root_path).
+    #[serde(flatten)]
+    pub routes: HashMap<String, String>,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+/// Seção reservada com metadados herdados por todos os grupos.
+const DEFAULTS_SECTION: &str = "defaults";
+
+/// Seção reservada do cliente HTTP (campo `Config::http`).
+pub(crate) const HTTP_SECTION: &str = "http";
+
+/// Seção reservada do paralelismo (campo `Config::pipeline`).
+pub(crate) const PIPELINE_SECTION: &str = "pipeline";
+
+/// Metadados específicos de cada grupo, proibidos em `[defaults]`.
+const NON_INHERITABLE: [&str; 2] = ["source_glob", "depends_on"];
+
+/// Sufixo reservado aos arquivos de staging (`{chave}_temp.json`).
+const STAGING_SUFFIX: &str = "_temp";
+
+/// Placeholders aceitos em `output_template`.
+const OUTPUT_PLACEHOLDERS: [&str; 6] = ["{api}", "{group}", "{key}", "{date}", "{run_id}", "{ext}"];
+
+impl EndpointGroup {
+    /// Codec único da saída; None sem `compression` ou com uma lista.
+    pub fn compression(&self) -> Option<Compression> {
+        match &self.compression {
+            Some(CompressionSetting::One(codec)) => Some(*codec),
+            _ => None,
+        }
+    }
+
+    /// Codecs das variantes da saída (vazio sem uma lista em `compression`).
+    pub fn compression_variants(&self) -> &[Compression] {
+        match &self.compression {
+            Some(CompressionSetting::Variants(codecs)) => codecs,
+            _ => &[],
+        }
+    }
+
+    /// Grupo de uma variante: o mesmo, com apenas `codec`.
+    pub fn with_compression(&self, codec: Compression) -> EndpointGroup {
+        EndpointGroup {
+            compression: Some(CompressionSetting::One(codec)),
+            ..self.clone()
+        }
+    }
+
+    /// Validações do grupo; cada problema encontrado é anexado a `issues`.
+    fn collect_issues(&self, scope: &str, base_url: &str, issues: &mut Vec<String>) {
+        if let Some(pattern) = &self.source_glob
+            && let Err(e) = sources::validate_glob(pattern, self.name_template())
+        {
+            issues.push(format!("'{}': {}", scope, e));
+        }
+        if self.input_format == InputFormat::Xml
+            && self.record_element.as_deref().unwrap_or("").is_empty()
+        {
+            issues.push(format!(
+                "'{}' usa input_format = \"xml\" sem record_element",
+                scope
+            ));
+        }
+        if let Some(csv) = &self.csv {
+            if self.input_format != InputFormat::Csv {
+                issues.push(format!("'{}': csv só é suportado para input_format = \"csv\"", scope));
+            }
+            if let Some(delimiter) = &csv.delimiter
+                && !(delimiter.len() == 1 && delimiter.is_ascii())
+            {
+                issues.push(format!(
+                    "'{}': delimiter deve ser um único caractere ASCII ('{}')",
+                    scope, delimiter
+                ));
+            }
+        }
+        if !self.extract.is_empty() {
+            self.collect_extract_issues(scope, issues);
+        }
+        if let Some(filter) = self.jq_filter.as_deref() {
+            self.collect_jq_issues(filter, scope, issues);
+        }
+        if let Some(path) = self.json_path.as_deref() {
+            self.collect_json_path_issues(path, scope, issues);
+        }
+        if self.reconcile.is_some() && self.input_format != InputFormat::Json {
+            issues.push(format!(
+                "'{}': reconcile só é suportado para input_format = \"json\"",
+                scope
+            ));
+        }
+        if self.big_number_columns.iter().any(String::is_empty) {
+            issues.push(format!("'{}': big_number_columns não aceita nomes vazios", scope));
+        }
+        if let Some(csv) = &self.csv {
+            for column in &self.big_number_columns {
+                if csv.schema.get(column).is_some_and(|t| *t != CsvType::String) {
+                    issues.push(format!(
+                        "'{}': '{}' está em big_number_columns e em csv.schema com outro tipo",
+                        scope, column
+                    ));
+                }
+            }
+        }
+        match &self.null_values {
+            Some(NullValues::All(values)) if values.is_empty() => {
+                issues.push(format!("'{}': null_values não pode ser uma lista vazia", scope));
+            }
+            Some(NullValues::PerColumn(columns)) => {
+                for (column, values) in columns {
+                    if column.is_empty() || values.is_empty() {
+                        issues.push(format!(
+                            "'{}': null_values.'{}' exige coluna e valores não vazios",
+                            scope, column
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+        let mut trimmed = BTreeSet::new();
+        for column in &self.trim_columns {
+            if column.is_empty() {
+                issues.push(format!("'{}': trim_columns não aceita nomes vazios", scope));
+            } else if !trimmed.insert(column.as_str()) {
+                issues.push(format!("'{}': coluna '{}' repetida em trim_columns", scope, column));
+            }
+        }
+        if self.trim_all_strings && !self.trim_columns.is_empty() {
+            issues.push(format!(
+                "'{}': trim_all_strings já inclui todas as colunas; remova trim_columns",
+                scope
+            ));
+        }
+        if self.collapse_whitespace && !self.trim_all_strings && self.trim_columns.is_empty() {
+            issues.push(format!(
+                "'{}': collapse_whitespace exige trim_columns ou trim_all_strings",
+                scope
+            ));
+        }
+        if let Some(script) = self.transform_script.as_deref() {
+            #[cfg(feature = "scripting")]
+            if let Err(e) = crate::scripting::check(Path::new(script)) {
+                issues.push(format!("'{}': {}", scope, e));
+            }
+            #[cfg(not(feature = "scripting"))]
+            issues.push(format!(
+                "'{}': transform_script '{}' requer compilação com --features scripting",
+                scope, script
+            ));
+            if self.batch_size.is_some() {
+                issues.push(format!(
+                    "'{}': transform_script não é suportado com batch_size",
+                    scope
+                ));
+            }
+        }
+        match (&self.status_field, &self.expected_status) {
+            (Some(_), None) | (None, Some(_)) => issues.push(format!(
+                "'{}': status_field e expected_status devem ser informados juntos",
+                scope
+            )),
+            (Some(_), Some(_)) => {
+                if self.input_format != InputFormat::Json {
+                    issues.push(format!(
+                        "'{}': status_field só é suportado para input_format = \"json\"",
+                        scope
+                    ));
+                }
+                if self.root_path.as_deref().is_none_or(str::is_empty) {
+                    issues.push(format!(
+                        "'{}': status_field exige root_path (registros dentro de um envelope)",
+                        scope
+                    ));
+                }
+            }
+            (None, None) => {}
+        }
+        if self.status_message_field.is_some() && self.status_field.is_none() {
+            issues.push(format!("'{}': status_message_field exige status_field", scope));
+        }
+        if self.sort_by.iter().any(|key| key.column.is_empty()) {
+            issues.push(format!("'{}': sort_by não aceita colunas vazias", scope));
+        }
+        if !self.sort_by.is_empty() && self.batch_size.is_some() {
+            issues.push(format!(
+                "'{}': sort_by exige o DataFrame completo e não é suportado com batch_size",
+                scope
+            ));
+        }
+        if self.report_cardinality && self.batch_size.is_some() {
+            issues.push(format!(
+                "'{}': report_cardinality exige o DataFrame completo e não é suportado com batch_size",
+                scope
+            ));
+        }
+        if self.approx_cardinality && !self.report_cardinality {
+            issues.push(format!("'{}': approx_cardinality exige report_cardinality", scope));
+        }
+        if self.input_format != InputFormat::Json && self.batch_size.is_some() {
+            issues.push(format!(
+                "'{}': batch_size só é suportado para input_format = \"json\"",
+                scope
+            ));
+        }
+        if self.output_format != OutputFormat::Parquet && self.batch_size.is_some() {
+            issues.push(format!(
+                "'{}': batch_size só é suportado para output_format = \"parquet\"",
+                scope
+            ));
+        }
+        if self.run_id_column.as_deref() == Some("") {
+            issues.push(format!("'{}': run_id_column não pode ser vazio", scope));
+        }
+        let origin_columns = [
+            (self.add_api_column, processor::API_COLUMN),
+            (self.add_group_column, processor::GROUP_COLUMN),
+        ];
+        for (_, column) in origin_columns.iter().filter(|(enabled, _)| *enabled) {
+            if self.run_id_column.as_deref() == Some(*column) {
+                issues.push(format!(
+                    "'{}': run_id_column '{}' colide com a coluna de origem",
+                    scope, column
+                ));
+            }
+        }
+        for (column, expression) in &self.computed_columns {
+            if column.is_empty() {
+                issues.push(format!("'{}': computed_columns não aceita nomes vazios", scope));
+            } else if let Err(e) = computed::check(expression) {
+                issues.push(format!("'{}': computed_columns.{}: {}", scope, column, e));
+            }
+        }
+        let mut hashed = BTreeSet::new();
+        for column in &self.hash_columns {
+            if column.is_empty() {
+                issues.push(format!("'{}': hash_columns não aceita nomes vazios", scope));
+            } else if !hashed.insert(column.as_str()) {
+                issues.push(format!("'{}': coluna '{}' repetida em hash_columns", scope, column));
+            }
+        }
+        if self.hash_salt.is_some() && self.hash_columns.is_empty() {
+            issues.push(format!("'{}': hash_salt exige hash_columns", scope));
+        }
+        if let Err(e) = self.hash_salt() {
+            issues.push(format!("'{}': hash_salt: {}", scope, e));
+        }
+        if let Some(template) = self.output_template.as_deref() {
+            self.collect_output_template_issues(template, scope, issues);
+        }
+        if self.output_format == OutputFormat::Delta {
+            if self.post_compress.is_some() {
+                issues.push(format!("'{}': post_compress não se aplica a tabelas Delta", scope));
+            }
+            if self.verify_after_write {
+                issues.push(format!("'{}': verify_after_write não se aplica a tabelas Delta", scope));
+            }
+            if self.on_exists == OnExists::Timestamped {
+                issues.push(format!(
+                    "'{}': on_exists = \"timestamped\" não se aplica a tabelas Delta (cada execução já é uma versão)",
+                    scope
+                ));
+            }
+        } else if self.delta_merge_schema {
+            issues.push(format!("'{}': delta_merge_schema exige output_format = \"delta\"", scope));
+        }
+        if self.required_columns.iter().any(String::is_empty) {
+            issues.push(format!("'{}': required_columns não aceita nomes vazios", scope));
+        }
+        let mut first = BTreeSet::new();
+        for column in &self.columns_first {
+            if column.is_empty() {
+                issues.push(format!("'{}': columns_first não aceita nomes vazios", scope));
+            } else if !first.insert(column.as_str()) {
+                issues.push(format!("'{}': coluna '{}' repetida em columns_first", scope, column));
+            }
+        }
+        if !self.empty_schema.is_empty() {
+            if !self.write_empty {
+                issues.push(format!("'{}': empty_schema exige write_empty = true", scope));
+            }
+            let mut seen = BTreeSet::new();
+            for entry in &self.empty_schema {
+                if entry.column.is_empty() {
+                    issues.push(format!("'{}': empty_schema não aceita nomes vazios", scope));
+                } else if !seen.insert(entry.column.as_str()) {
+                    issues.push(format!("'{}': coluna '{}' repetida em empty_schema", scope, entry.column));
+                }
+            }
+        }
+        if let Some(form) = &self.multipart {
+            form.collect_issues(scope, issues);
+            if self.pagination.is_some() {
+                issues.push(format!("'{}': multipart não se aplica a rotas paginadas", scope));
+            }
+            if self.source_glob.is_some() {
+                issues.push(format!("'{}': multipart exige rotas HTTP (não se aplica a source_glob)", scope));
+            }
+        }
+        if let Some(path) = &self.body_file {
+            if let Err(e) = read_body_file(path) {
+                issues.push(format!("'{}': {}", scope, e));
+            }
+            if self.multipart.is_some() {
+                issues.push(format!("'{}': body_file e multipart são excludentes", scope));
+            }
+            if self.pagination.is_some() {
+                issues.push(format!("'{}': body_file não se aplica a rotas paginadas", scope));
+            }
+            if self.routes.is_empty() {
+                issues.push(format!("'{}': body_file exige rotas HTTP", scope));
+            }
+        }
+        if self.compress_request && self.multipart.is_none() && self.body_file.is_none() {
+            issues.push(format!("'{}': compress_request exige um corpo de requisição (multipart ou body_file)", scope));
+        }
+        if self.retry_on_empty && (self.routes.is_empty() || self.pagination.is_some()) {
+            issues.push(format!(
+                "'{}': retry_on_empty exige rotas HTTP sem pagination",
+                scope
+            ));
+        }
+        if self.resume_downloads
+            && (self.routes.is_empty()
+                || self.pagination.is_some()
+                || self.multipart.is_some()
+                || self.body_file.is_some())
+        {
+            issues.push(format!(
+                "'{}': resume_downloads exige rotas HTTP GET sem pagination (sem multipart nem body_file)",
+                scope
+            ));
+        }
+        if !self.accept_status.is_empty() && self.routes.is_empty() {
+            issues.push(format!("'{}': accept_status exige rotas HTTP", scope));
+        }
+        let mut accepted = BTreeSet::new();
+        for &status in &self.accept_status {
+            if !(200..300).contains(&status) {
+                issues.push(format!(
+                    "'{}': accept_status aceita apenas status 2xx (recebido {})",
+                    scope, status
+                ));
+            }
+            if !accepted.insert(status) {
+                issues.push(format!("'{}': status {} repetido em accept_status", scope, status));
+            }
+        }
+        if let Some(Pagination::Cursor { next_path, .. }) = &self.pagination {
+            if self.input_format != InputFormat::Json {
+                issues.push(format!(
+                    "'{}': pagination só é suportada para input_format = \"json\"",
+                    scope
+                ));
+            }
+            if next_path.split('.').any(str::is_empty) {
+                issues.push(format!("'{}': next_path inválido '{}'", scope, next_path));
+            }
+        }
+        if self.parquet_version == ParquetVersion::V2 {
+            if !matches!(self.output_format, OutputFormat::Parquet | OutputFormat::Delta) {
+                issues.push(format!(
+                    "'{}': parquet_version só se aplica a output_format = \"parquet\" ou \"delta\"",
+                    scope
+                ));
+            }
+            if self.batch_size.is_some() {
+                issues.push(format!(
+                    "'{}': parquet_version = \"v2\" não é suportado com batch_size",
+                    scope
+                ));
+            }
+        }
+        if !self.field_ids.is_empty() {
+            self.collect_field_id_issues(scope, issues);
+        }
+        if !self.bloom_filter_columns.is_empty() {
+            self.collect_bloom_issues(scope, issues);
+        }
+        let codecs = match &self.compression {
+            Some(CompressionSetting::One(codec)) => std::slice::from_ref(codec),
+            Some(CompressionSetting::Variants(codecs)) => codecs.as_slice(),
+            None => &[],
+        };
+        for codec in codecs.iter().filter(|codec| !self.output_format.supports(**codec)) {
+            issues.push(format!(
+                "'{}': compressão '{}' não suportada para {}",
+                scope,
+                codec.name(),
+                self.output_format.extension()
+            ));
+        }
+        if matches!(self.compression, Some(CompressionSetting::Variants(_))) {
+            self.collect_variant_issues(scope, issues);
+        }
+        if self.zstd_workers.is_some() && !codecs.contains(&Compression::Zstd) {
+            issues.push(format!("'{}': zstd_workers exige compression = \"zstd\"", scope));
+        }
+
+        let mut dependents: Vec<&String> = self.depends_on.keys().collect();
+        dependents.sort();
+
+        for key in dependents {
+            // Endpoints de glob só são conhecidos após a expansão
+            if self.source_glob.is_none() && !self.routes.contains_key(key) {
+                issues.push(format!("'{}': depends_on cita endpoint inexistente '{}'", scope, key));
+            }
+            for dep in &self.depends_on[key] {
+                if dep.is_empty() || dep.split('.').count() > 3 || dep.split('.').any(str::is_empty) {
+                    issues.push(format!("'{}.{}': dependência inválida '{}'", scope, key, dep));
+                }
+            }
+        }
+
+        match &self.tags {
+            Some(Tags::All(tags)) => check_tags(scope, tags, issues),
+            Some(Tags::PerEndpoint(endpoints)) => {
+                let mut tagged: Vec<&String> = endpoints.keys().collect();
+                tagged.sort();
+                for key in tagged {
+                    if self.source_glob.is_none() && !self.routes.contains_key(key) {
+                        issues.push(format!("'{}': tags cita endpoint inexistente '{}'", scope, key));
+                    }
+                    check_tags(&format!("{}.{}", scope, key), &endpoints[key], issues);
+                }
+            }
+            None => {}
+        }
+
+        let mut keys: Vec<&String> = self.routes.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            // A chave vira nome de arquivo: colisões com o staging ou caminhos são proibidas
+            if key.is_empty() || key.contains(['/', '\\']) || key == "." || key == ".." {
+                issues.push(format!("'{}': chave '{}' não é um nome de arquivo válido", scope, key));
+            } else if key.ends_with(STAGING_SUFFIX) {
+                issues.push(format!(
+                    "'{}': chave '{}' colide com o sufixo reservado '{}'",
+                    scope, key, STAGING_SUFFIX
+                ));
+            }
+
+            if !base_url.is_empty() {
+                let url = join_urls(base_url, &self.routes[key]);
+                if let Err(e) = reqwest::Url::parse(&url) {
+                    issues.push(format!("'{}.{}': URL inválida '{}' ({})", scope, key, url, e));
+                }
+            }
+        }
+    }
+
+    /// Regras de `extract`: nomes viram parte do nome do arquivo e os ponteiros
+    /// não podem se sobrepor (cada array é movido para o próprio staging).
+    fn collect_variant_issues(&self, scope: &str, issues: &mut Vec<String>) {
+        let codecs = self.compression_variants();
+        if codecs.is_empty() {
+            issues.push(format!("'{}': compression não aceita uma lista vazia", scope));
+        }
+        let mut seen = BTreeSet::new();
+        for codec in codecs {
+            if !seen.insert(codec.name()) {
+                issues.push(format!("'{}': codec '{}' repetido em compression", scope, codec.name()));
+            }
+        }
+        if self.output_format == OutputFormat::Delta {
+            issues.push(format!("'{}': várias compressões não se aplicam a output_format = \"delta\"", scope));
+        }
+        if self.batch_size.is_some() {
+            issues.push(format!("'{}': várias compressões não são suportadas com batch_size", scope));
+        }
+        if self.post_compress.is_some() {
+            issues.push(format!("'{}': várias compressões não combinam com post_compress", scope));
+        }
+    }
+
+    fn collect_field_id_issues(&self, scope: &str, issues: &mut Vec<String>) {
+        if self.output_format != OutputFormat::Parquet {
+            issues.push(format!("'{}': field_ids só se aplica a output_format = \"parquet\"", scope));
+        }
+        if self.batch_size.is_some() {
+            issues.push(format!("'{}': field_ids não é suportado com batch_size", scope));
+        }
+        let mut columns: BTreeMap<i32, &str> = BTreeMap::new();
+        for (column, &id) in &self.field_ids {
+            if column.is_empty() {
+                issues.push(format!("'{}': field_ids não aceita nomes vazios", scope));
+            }
+            if id < 0 {
+                issues.push(format!("'{}': field_ids.{} deve ser >= 0 (recebido {})", scope, column, id));
+            }
+            if let Some(other) = columns.insert(id, column) {
+                issues.push(format!(
+                    "'{}': field_ids repete o ID {} em '{}' e '{}'",
+                    scope, id, other, column
+                ));
+            }
+        }
+    }
+
+    fn collect_bloom_issues(&self, scope: &str, issues: &mut Vec<String>) {
+        if self.output_format != OutputFormat::Parquet {
+            issues.push(format!(
+                "'{}': bloom_filter_columns só se aplica a output_format = \"parquet\"",
+                scope
+            ));
+        }
+        if self.batch_size.is_some() {
+            issues.push(format!("'{}': bloom_filter_columns não é suportado com batch_size", scope));
+        }
+        let mut seen = BTreeSet::new();
+        for column in &self.bloom_filter_columns {
+            if column.is_empty() {
+                issues.push(format!("'{}': bloom_filter_columns não aceita nomes vazios", scope));
+            } else if !seen.insert(column.as_str()) {
+                issues.push(format!("'{}': coluna '{}' repetida em bloom_filter_columns", scope, column));
+            }
+        }
+    }
+
+    /// Regras de `jq_filter`: o filtro já entrega os registros, sem envelope.
+    fn collect_jq_issues(&self, filter: &str, scope: &str, issues: &mut Vec<String>) {
+        if let Err(e) = jq::check(filter) {
+            issues.push(format!("'{}': jq_filter: {}", scope, e));
+        }
+        if self.input_format != InputFormat::Json {
+            issues.push(format!("'{}': jq_filter só é suportado para input_format = \"json\"", scope));
+        }
+        if self.root_path.is_some() || !self.extract.is_empty() {
+            issues.push(format!(
+                "'{}': jq_filter já entrega os registros; não combina com root_path nem extract",
+                scope
+            ));
+        }
+        if self.batch_size.is_some() {
+            issues.push(format!("'{}': jq_filter lê a resposta inteira e não é suportado com batch_size", scope));
+        }
+        if self.reconcile.is_some() {
+            issues.push(format!(
+                "'{}': reconcile não combina com jq_filter (o envelope com o total é descartado)",
+                scope
+            ));
+        }
+    }
+
+    /// Regras de `output_template`: placeholders conhecidos, caminho relativo
+    /// ao diretório de dados e um arquivo por endpoint do grupo.
+    fn collect_output_template_issues(&self, template: &str, scope: &str, issues: &mut Vec<String>) {
+        let mut rest = template.to_string();
+        for placeholder in OUTPUT_PLACEHOLDERS {
+            rest = rest.replace(placeholder, "x");
+        }
+        if rest.contains('{') || rest.contains('}') {
+            issues.push(format!(
+                "'{}': placeholder inválido em output_template '{}' (aceitos: {})",
+                scope,
+                template,
+                OUTPUT_PLACEHOLDERS.join(", ")
+            ));
+        } else if rest.is_empty()
+            || rest.ends_with('/')
+            || !Path::new(&rest)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            issues.push(format!(
+                "'{}': output_template deve ser um caminho relativo ao diretório de dados, sem '..' ('{}')",
+                scope, template
+            ));
+        }
+        let outputs = self.routes.len() + usize::from(self.source_glob.is_some()) + self.extract.len();
+        if !template.contains("{key}") && outputs > 1 {
+            issues.push(format!(
+                "'{}': output_template sem {{key}} grava todas as saídas do grupo no mesmo arquivo",
+                scope
+            ));
+        }
+    }
+
+    /// Regras de `json_path`: como o `jq_filter`, a seleção já entrega os registros.
+    fn collect_json_path_issues(&self, path: &str, scope: &str, issues: &mut Vec<String>) {
+        if let Err(e) = jsonpath::check(path) {
+            issues.push(format!("'{}': json_path: {}", scope, e));
+        }
+        if self.input_format != InputFormat::Json {
+            issues.push(format!("'{}': json_path só é suportado para input_format = \"json\"", scope));
+        }
+        if self.root_path.is_some() || !self.extract.is_empty() || self.jq_filter.is_some() {
+            issues.push(format!(
+                "'{}': json_path já entrega os registros; não combina com root_path, extract nem jq_filter",
+                scope
+            ));
+        }
+        if self.batch_size.is_some() {
+            issues.push(format!("'{}': json_path lê a resposta inteira e não é suportado com batch_size", scope));
+        }
+        if self.reconcile.is_some() {
+            issues.push(format!(
+                "'{}': reconcile não combina com json_path (o envelope com o total é descartado)",
+                scope
+            ));
+        }
+    }
+
+    fn collect_extract_issues(&self, scope: &str, issues: &mut Vec<String>) {
+        for (name, pointer) in &self.extract {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                issues.push(format!(
+                    "'{}': extract.'{}' deve usar apenas letras, dígitos, '_' e '-'",
+                    scope, name
+                ));
+            }
+            if !pointer.starts_with('/') {
+                issues.push(format!(
+                    "'{}': extract.'{}' = '{}' não é um JSON Pointer (ex: \"/dados/contratos\")",
+                    scope, name, pointer
+                ));
+            }
+        }
+        let pointers: Vec<(&String, &String)> = self.extract.iter().collect();
+        for (index, (name, pointer)) in pointers.iter().enumerate() {
+            for (other, other_pointer) in &pointers[index + 1..] {
+                let nested = |outer: &str, inner: &str| inner == outer || inner.starts_with(&format!("{}/", outer));
+                if nested(pointer, other_pointer) || nested(other_pointer, pointer) {
+                    issues.push(format!(
+                        "'{}': extract.'{}' e extract.'{}' se sobrepõem ('{}', '{}')",
+                        scope, name, other, pointer, other_pointer
+                    ));
+                }
+            }
+        }
+
+        if self.input_format != InputFormat::Json {
+            issues.push(format!("'{}': extract só é suportado para input_format = \"json\"", scope));
+        }
+        if self.root_path.is_some() {
+            issues.push(format!("'{}': extract substitui root_path; use apenas um dos dois", scope));
+        }
+        if self.batch_size.is_some() {
+            issues.push(format!("'{}': extract não é suportado com batch_size", scope));
+        }
+        if self.reconcile.is_some() {
+            issues.push(format!(
+                "'{}': reconcile não combina com extract (um total declarado para vários arrays)",
+                scope
+            ));
+        }
+    }
+
+    /// Dependências de `key` como identificadores completos `api.grupo.chave`.
+    pub fn dependencies_of(&self, api: &str, group: &str, key: &str) -> Vec<String> {
+        self.depends_on
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|dep| match dep.split('.').count() {
+                1 => format!("{}.{}.{}", api, group, dep),
+                2 => format!("{}.{}", api, dep),
+                _ => dep.clone(),
+            })
+            .collect()
+    }
+
+    /// Tags de `key` (vazio se o grupo não define tags).
+    pub fn tags_of(&self, key: &str) -> Vec<String> {
+        match &self.tags {
+            Some(Tags::All(tags)) => tags.clone(),
+            Some(Tags::PerEndpoint(endpoints)) => endpoints.get(key).cloned().unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Separador efetivo de `stringify_lists`.
+    pub fn list_separator(&self) -> &str {
+        self.list_separator.as_deref().unwrap_or(",")
+    }
+
+    /// Salt efetivo de `hash_columns`, com as referências `${VAR}` resolvidas (vazio se ausente).
+    ///
+    /// # Erros
+    /// Referências malformadas ou variáveis indefinidas (sem o valor do salt).
+    pub fn hash_salt(&self) -> Result<String, String> {
+        self.hash_salt
+            .as_ref()
+            .map_or(Ok(String::new()), |salt| interpolate_env(&salt.0))
+    }
+
+    /// Template efetivo de nome dos endpoints locais.
+    pub fn name_template(&self) -> &str {
+        self.name_template
+            .as_deref()
+            .unwrap_or(sources::DEFAULT_NAME_TEMPLATE)
+    }
+}
 
-use crate::errors::ProcessorError;
-use serde::Deserialize;
-use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+/// Algoritmos de `hash_algorithm`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha384,
+    Sha512,
+}
 
-/// Configuração.
-// Mapeia o namespace da API (ex: "compras_federal") para as suas configurações.
+impl HashAlgorithm {
+    /// Nome canônico (como escrito no TOML).
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha384 => "sha384",
+            HashAlgorithm::Sha512 => "sha512",
+        }
+    }
+}
+
+/// Caminho de configuração que lê o stdin (`--config -`).
+pub const STDIN_CONFIG: &str = "-";
+
+/// Formatos do arquivo de configuração (`--config-format`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfigFormat {
+    #[default]
+    Toml,
+    /// Mesma estrutura do TOML; `null` não é aceito.
+    Json,
+}
+
+impl ConfigFormat {
+    /// Formato pelo nome (`toml` ou `json`).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "json" => Some(ConfigFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Formato pela extensão do arquivo; sem `.json`, TOML.
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+
+    /// Nome exibido nas mensagens de erro.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Json => "JSON",
+        }
+    }
+}
+
+/// Formatos de entrada suportados pelo conversor.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    #[default]
+    Json,
+    Xml,
+    Csv,
+}
+
+impl InputFormat {
+    /// Extensão usada no arquivo de staging.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            InputFormat::Json => "json",
+            InputFormat::Xml => "xml",
+            InputFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Formatos de saída suportados pelo writer.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Parquet,
+    Avro,
+    /// Tabela Delta Lake: diretório com dados Parquet e log de transações.
+    Delta,
+}
+
+impl OutputFormat {
+    /// Extensão do arquivo final.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Parquet => "parquet",
+            OutputFormat::Avro => "avro",
+            OutputFormat::Delta => "delta",
+        }
+    }
+
+    /// Verifica se o codec existe para o formato.
+    pub fn supports(&self, codec: Compression) -> bool {
+        match self {
+            OutputFormat::Parquet | OutputFormat::Delta => codec != Compression::Deflate,
+            OutputFormat::Avro => matches!(
+                codec,
+                Compression::Uncompressed | Compression::Snappy | Compression::Deflate
+            ),
+        }
+    }
+}
+
+/// Leitura de fontes `input_format = "csv"`.
+///
+/// Campos entre aspas (inclusive com quebras de linha) seguem o RFC 4180.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CsvOptions {
+    /// Separador de campos (padrão: ",").
+    pub delimiter: Option<String>,
+
+    /// A primeira linha contém os nomes das colunas (padrão: true).
+    pub has_header: Option<bool>,
+
+    /// Tipos fixos por coluna (ex: `{ cnpj = "string" }`); as demais são inferidas.
+    #[serde(default)]
+    pub schema: HashMap<String, CsvType>,
+}
+
+impl CsvOptions {
+    /// Separador efetivo (a validação garante um único byte ASCII).
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+            .as_deref()
+            .and_then(|d| d.bytes().next())
+            .unwrap_or(b',')
+    }
+}
+
+/// Tipos aceitos em `csv.schema` e `empty_schema`.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CsvType {
+    String,
+    Int64,
+    Float64,
+    Boolean,
+}
+
+/// Compressores aplicados ao arquivo final em `post_compress`.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PostCompress {
+    /// Delegado ao binário `xz` do sistema.
+    Xz,
+    Brotli,
+    Gzip,
+}
+
+impl PostCompress {
+    /// Extensão anexada ao arquivo final.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PostCompress::Xz => "xz",
+            PostCompress::Brotli => "br",
+            PostCompress::Gzip => "gz",
+        }
+    }
+}
+
+/// Reação a divergências entre o total declarado e o convertido.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReconcileMode {
+    /// Registra o aviso e mantém a saída.
+    Warn,
+    /// Falha o endpoint sem publicar a saída.
+    Fail,
+}
+
+/// Valor esperado em `expected_status`: texto, inteiro ou booleano do TOML.
+///
+/// A comparação é textual, então `0` e `"0"` equivalem.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum StatusValue {
+    Text(String),
+    Integer(i64),
+    Bool(bool),
+}
+
+impl fmt::Display for StatusValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusValue::Text(text) => f.write_str(text),
+            StatusValue::Integer(n) => write!(f, "{}", n),
+            StatusValue::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// Textos de `null_values`, globais ou por coluna.
 #[derive(Debug, Deserialize, Clone)]
-pub struct Config {
-    #[serde(flatten)]
-    pub apis: HashMap<String, ApiConfig>,
+#[serde(untagged)]
+pub enum NullValues {
+    /// Valem para todas as colunas `String`.
+    All(Vec<String>),
+    /// Valem apenas para as colunas listadas.
+    PerColumn(HashMap<String, Vec<String>>),
 }
 
-/// Configuração de uma Unidade de API.
+impl NullValues {
+    /// Textos tratados como nulo em `column`, se houver.
+    pub fn for_column(&self, column: &str) -> Option<&[String]> {
+        match self {
+            NullValues::All(values) => Some(values),
+            NullValues::PerColumn(columns) => columns.get(column).map(Vec::as_slice),
+        }
+    }
+}
+
+/// Tags de `tags`, do grupo inteiro ou por endpoint.
 #[derive(Debug, Deserialize, Clone)]
-pub struct ApiConfig {
-    /// Ponto de entrada base da API (ex: https://api.gov.br)
-    pub base_url: String,
+#[serde(untagged)]
+pub enum Tags {
+    /// Valem para todos os endpoints do grupo.
+    All(Vec<String>),
+    /// Valem apenas para os endpoints listados.
+    PerEndpoint(HashMap<String, Vec<String>>),
+}
 
-    /// Dicionário de grupos de endpoints.
-    pub endpoints: HashMap<String, EndpointGroup>,
+/// Política para nomes repetidos ao achatar os registros de `root_path`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnDuplicateColumn {
+    /// Falha o endpoint indicando as colunas em conflito.
+    #[default]
+    Error,
+    /// Renomeia as ocorrências seguintes para `{nome}_1`, `{nome}_2`...
+    Suffix,
+    /// Mantém a primeira ocorrência (na ordem das colunas) e descarta as demais.
+    First,
 }
 
-/// Representa um grupo de recursos com metadados de processamento.
+/// Política para NaN e ±Infinity nas colunas `Float32`/`Float64` do nível superior.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FloatSpecial {
+    /// Substitui por nulo (estatísticas do Parquet e SQL não os tratam bem).
+    #[default]
+    Null,
+    /// Falha o endpoint indicando as colunas e a contagem de valores.
+    Error,
+    /// Mantém os valores como vieram.
+    Keep,
+}
+
+/// Política aplicada quando o arquivo final de um endpoint já existe.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OnExists {
+    /// Substitui o arquivo existente (comportamento histórico).
+    #[default]
+    Overwrite,
+    /// Falha o endpoint sem baixar nem gravar nada.
+    Error,
+    /// Mantém o arquivo existente e não executa o endpoint.
+    Skip,
+    /// Grava uma cópia com o timestamp da execução no nome.
+    Timestamped,
+}
+
+/// Formulário de `multipart` (ex: `{ fields = { formato = "json" }, files = { consulta = "consulta.csv" } }`).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Multipart {
+    /// Campos de texto (nome → valor).
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+
+    /// Campos de arquivo (nome → caminho local), enviados com o nome do arquivo.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl Multipart {
+    fn collect_issues(&self, scope: &str, issues: &mut Vec<String>) {
+        if self.fields.is_empty() && self.files.is_empty() {
+            issues.push(format!("'{}': multipart sem fields nem files", scope));
+        }
+        if self.fields.keys().chain(self.files.keys()).any(String::is_empty) {
+            issues.push(format!("'{}': multipart não aceita campos sem nome", scope));
+        }
+        if let Some(name) = self.files.keys().find(|name| self.fields.contains_key(*name)) {
+            issues.push(format!("'{}': campo '{}' repetido em multipart.fields e multipart.files", scope, name));
+        }
+        for path in self.files.values() {
+            if !Path::new(path).is_file() {
+                issues.push(format!("'{}': arquivo '{}' de multipart.files não encontrado", scope, path));
+            }
+        }
+    }
+}
+
+/// Coluna declarada em `empty_schema`.
 #[derive(Debug, Deserialize, Clone)]
-pub struct EndpointGroup {
-    /// Identifica a chave JSON que contém a lista de dados (ex: "resultado", "dados").
-    /// Se None, assume que a estrutura é uma lista na raiz.
-    pub root_path: Option<String>,
+pub struct ColumnType {
+    pub column: String,
 
-    /// Mapeamento dinâmico de chaves de identificação para caminhos relativos.
-    /// Captura todas as chaves que não sejam metadados conhecidos (como root_path).
-    #[serde(flatten)]
-    pub routes: HashMap<String, String>,
+    #[serde(rename = "type")]
+    pub dtype: CsvType,
+}
+
+/// Chave de ordenação de `sort_by`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SortKey {
+    /// Coluna da saída (após a normalização).
+    pub column: String,
+
+    /// Ordem decrescente (padrão: crescente).
+    #[serde(default)]
+    pub descending: bool,
+
+    /// Posição dos nulos (padrão: "first", como no Polars).
+    #[serde(default)]
+    pub nulls: NullsOrder,
+}
+
+/// Posição dos valores nulos na ordenação.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NullsOrder {
+    #[default]
+    First,
+    Last,
+}
+
+/// Estratégias de paginação das rotas HTTP.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Pagination {
+    /// Segue o cursor (token ou URL) extraído de cada resposta até ficar nulo.
+    Cursor {
+        /// Caminho do cursor no envelope, separado por pontos (ex: "paging.next").
+        next_path: String,
+
+        /// Query param que recebe o token; se None, o cursor é a própria URL.
+        cursor_param: Option<String>,
+
+        /// Limite de páginas por endpoint (padrão: 1000).
+        max_pages: Option<NonZeroUsize>,
+    },
+}
+
+/// Versão das páginas de dados gravadas em `parquet_version`.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ParquetVersion {
+    /// `DataPageV1`: lida por qualquer engine.
+    #[default]
+    V1,
+    /// `DataPageV2`: níveis de repetição/definição fora da compressão.
+    V2,
+}
+
+/// `compression`: um codec ou uma lista (uma variante da saída por codec).
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum CompressionSetting {
+    One(Compression),
+    Variants(Vec<Compression>),
+}
+
+/// Codecs de compressão aceitos em `compression`.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[serde(alias = "none")]
+    Uncompressed,
+    Snappy,
+    Zstd,
+    Gzip,
+    Lz4,
+    /// Exclusivo do Avro.
+    Deflate,
+}
+
+impl Compression {
+    /// Nome canônico (como escrito no TOML).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::Uncompressed => "uncompressed",
+            Compression::Snappy => "snappy",
+            Compression::Zstd => "zstd",
+            Compression::Gzip => "gzip",
+            Compression::Lz4 => "lz4",
+            Compression::Deflate => "deflate",
+        }
+    }
 }
 
 impl Config {
-    /// Carrega e valida o ficheiro de configuração TOML.
+    /// Carrega e valida o ficheiro de configuração.
+    ///
+    /// Um diretório é lido como fragmentos e `-` lê o stdin (ver módulo).
+    /// `format` prevalece sobre a extensão; sem ambos, o conteúdo é TOML.
     ///
     /// # Erros
     /// Retorna `ProcessorError::Io` se o ficheiro não for encontrado ou
     /// `ProcessorError::Schema` se a estrutura for inválida.
-    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, ProcessorError> {
+    pub fn load(path: &str, format: Option<ConfigFormat>) -> Result<Self, ProcessorError> {
+        if path == STDIN_CONFIG {
+            return Self::from_reader(io::stdin().lock(), format.unwrap_or_default());
+        }
+
+        let path = Path::new(path);
+        if path.is_dir() {
+            return Self::load_from_dir(path);
+        }
+
         let content = fs::read_to_string(path).map_err(ProcessorError::Io)?;
-        let config: Config = toml::from_str(&content)
+        Self::parse(&content, format.unwrap_or_else(|| ConfigFormat::of(path)))
+    }
+
+    /// Lê e valida uma configuração completa de `reader` (ex: o stdin).
+    ///
+    /// # Erros
+    /// `ProcessorError::Io` em falhas de leitura; `ProcessorError::Schema` como em `load`.
+    pub fn from_reader(mut reader: impl Read, format: ConfigFormat) -> Result<Self, ProcessorError> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content).map_err(ProcessorError::Io)?;
+        Self::parse(&content, format)
+    }
+
+    /// Interpreta e valida o conteúdo de uma configuração completa.
+    fn parse(content: &str, format: ConfigFormat) -> Result<Self, ProcessorError> {
+        let invalid = |e: &dyn fmt::Display| ProcessorError::Schema(format!("Erro no {}: {}", format.name(), e));
+
+        let mut table: toml::Table = match format {
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| invalid(&e))?,
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| invalid(&e))?,
+        };
+
+        let has_defaults = table.contains_key(DEFAULTS_SECTION);
+        if has_defaults {
+            apply_defaults(&mut table)?;
+        }
+        config_check::check(&table)?;
+
+        let mut config: Config = if has_defaults {
+            toml::Value::Table(table).try_into().map_err(|e| invalid(&e))?
+        } else if format == ConfigFormat::Toml {
+            // Sem [defaults], preserva as posições (linha/coluna) nas mensagens
+            toml::from_str(content).map_err(|e| invalid(&e))?
+        } else {
+            toml::Value::Table(table).try_into().map_err(|e| invalid(&e))?
+        };
+
+        config.validate()?;
+        config.read_request_bodies()?;
+        Ok(config)
+    }
+
+    /// Mescla os `*.toml` do diretório, em ordem de nome, e valida o resultado.
+    ///
+    /// # Erros
+    /// `ProcessorError::Schema` se não houver fragmentos ou se dois deles
+    /// definirem a mesma chave.
+    fn load_from_dir(dir: &Path) -> Result<Self, ProcessorError> {
+        let mut fragments: Vec<_> = fs::read_dir(dir)
+            .map_err(ProcessorError::Io)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()
+            .map_err(ProcessorError::Io)?;
+        fragments.retain(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "toml"));
+        fragments.sort();
+        if fragments.is_empty() {
+            return Err(ProcessorError::Schema(format!(
+                "Nenhum fragmento *.toml em '{}'",
+                dir.display()
+            )));
+        }
+
+        let mut table = toml::Table::new();
+        // Fragmento que definiu cada tabela de topo, para a mensagem de conflito
+        let mut owners: HashMap<String, Vec<String>> = HashMap::new();
+        for path in &fragments {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let content = fs::read_to_string(path).map_err(ProcessorError::Io)?;
+            let fragment: toml::Table = toml::from_str(&content)
+                .map_err(|e| ProcessorError::Schema(format!("Erro no TOML ({}): {}", name, e)))?;
+
+            for key in fragment.keys() {
+                owners.entry(key.clone()).or_default().push(name.clone());
+            }
+            merge_disjoint(&mut table, fragment, "").map_err(|key| {
+                let top = key.split('.').next().unwrap_or_default();
+                let previous: Vec<&str> = owners[top]
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|owner| *owner != name)
+                    .collect();
+                ProcessorError::Schema(format!(
+                    "Fragmento '{}' redefine '{}' (já definida em {})",
+                    name,
+                    key,
+                    previous.join(", ")
+                ))
+            })?;
+        }
+
+        if table.contains_key(DEFAULTS_SECTION) {
+            apply_defaults(&mut table)?;
+        }
+        config_check::check(&table)?;
+        let mut config: Config = toml::Value::Table(table)
+            .try_into()
             .map_err(|e| ProcessorError::Schema(format!("Erro no TOML: {}", e)))?;
 
         config.validate()?;
+        config.read_request_bodies()?;
         Ok(config)
     }
 
     /// Validação pós-carga (Fail-Fast).
+    ///
+    /// Todos os problemas são coletados e reportados juntos em um único
+    /// `ProcessorError::Schema`, evitando correções uma a uma.
     fn validate(&self) -> Result<(), ProcessorError> {
-        for (api_name, api_cfg) in &self.apis {
-            if api_cfg.base_url.is_empty() {
-                return Err(ProcessorError::Schema(format!(
-                    "'{}' sem base_url",
-                    api_name
-                )));
+        let issues = self.collect_issues();
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        Err(ProcessorError::Schema(format!(
+            "Configuração inválida ({} problema(s)):\n  - {}",
+            issues.len(),
+            issues.join("\n  - ")
+        )))
+    }
+
+    /// Lê o `body_file` de cada grupo (já validado), guardando-o em `body`.
+    fn read_request_bodies(&mut self) -> Result<(), ProcessorError> {
+        for (api_name, api_cfg) in &mut self.apis {
+            for (group_name, group_cfg) in &mut api_cfg.endpoints {
+                if let Some(path) = &group_cfg.body_file {
+                    let body = read_body_file(path)
+                        .map_err(|e| ProcessorError::Schema(format!("'{}.{}': {}", api_name, group_name, e)))?;
+                    group_cfg.body = Some(body);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Percorre APIs e grupos em ordem alfabética (mensagens determinísticas).
+    fn collect_issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        if self.http.connect_timeout_secs == Some(0) || self.http.timeout_secs == Some(0) {
+            issues.push("[http]: timeouts devem ser maiores que zero".to_string());
+        }
+        if self.pipeline.dead_letter_dir.as_deref().is_some_and(|dir| dir.trim().is_empty()) {
+            issues.push("[pipeline]: dead_letter_dir vazio".to_string());
+        }
+        if let Some(hosts) = &self.http.allowed_hosts {
+            if hosts.is_empty() {
+                issues.push("[http]: allowed_hosts vazio bloquearia todas as requisições".to_string());
+            }
+            for host in hosts.iter().filter(|h| !is_host_name(h)) {
+                issues.push(format!(
+                    "[http]: '{}' em allowed_hosts não é um nome de host (sem esquema, porta ou caminho)",
+                    host
+                ));
+            }
+        }
+
+        let mut api_names: Vec<&String> = self.apis.keys().collect();
+        api_names.sort();
+
+        for api_name in api_names {
+            let api_cfg = &self.apis[api_name];
+
+            // Grupos apenas com arquivos locais dispensam base_url
+            let has_routes = api_cfg.endpoints.values().any(|g| !g.routes.is_empty());
+            if has_routes && api_cfg.base_url.is_empty() {
+                issues.push(format!("'{}' sem base_url", api_name));
+            } else if !api_cfg.base_url.is_empty() {
+                if let Err(e) = reqwest::Url::parse(&api_cfg.base_url) {
+                    issues.push(format!(
+                        "'{}': base_url inválida '{}' ({})",
+                        api_name, api_cfg.base_url, e
+                    ));
+                } else if !api_cfg.base_url.starts_with("https://") {
+                    issues.push(format!(
+                        "'{}': base_url deve usar https:// ('{}')",
+                        api_name,
+                        api::redact_url(&api_cfg.base_url)
+                    ));
+                }
+            }
+            if let Some(auth) = &api_cfg.basic_auth
+                && let Err(e) = auth.resolved()
+            {
+                issues.push(format!("'{}': basic_auth: {}", api_name, e));
+            }
+            if let Some(login) = &api_cfg.login {
+                if !has_routes {
+                    issues.push(format!("'{}': login exige grupos com rotas HTTP", api_name));
+                }
+                if login.route.trim().is_empty() {
+                    issues.push(format!("'{}': login.route vazia", api_name));
+                }
+                if let Err(e) = login.resolved_form() {
+                    issues.push(format!("'{}': login.form: {}", api_name, e));
+                }
+            }
+            if let Some(retry) = &api_cfg.retry {
+                retry.collect_issues(api_name, self.http.retries, &mut issues);
             }
             if api_cfg.endpoints.is_empty() {
-                return Err(ProcessorError::Schema(format!(
-                    "'{}' sem endpoints",
-                    api_name
-                )));
+                issues.push(format!("'{}' sem endpoints", api_name));
+            }
+
+            let mut group_names: Vec<&String> = api_cfg.endpoints.keys().collect();
+            group_names.sort();
+
+            for group_name in group_names {
+                let group_cfg = &api_cfg.endpoints[group_name];
+                let scope = format!("{}.{}", api_name, group_name);
+                group_cfg.collect_issues(&scope, &api_cfg.base_url, &mut issues);
+                if group_cfg.retry_on_empty && self.http.retries == 0 {
+                    issues.push(format!("'{}': retry_on_empty exige [http] retries > 0", scope));
+                }
             }
         }
-        Ok(())
+        issues
+    }
+
+    /// Identificadores `api.grupo.chave` de todas as rotas e globs configurados.
+    pub fn endpoint_ids(&self) -> BTreeSet<String> {
+        let mut ids = BTreeSet::new();
+        for (api_name, api_cfg) in &self.apis {
+            for (group_name, group_cfg) in &api_cfg.endpoints {
+                for key in group_cfg.routes.keys() {
+                    ids.insert(format!("{}.{}.{}", api_name, group_name, key));
+                }
+                if let Some(pattern) = &group_cfg.source_glob {
+                    ids.insert(format!("{}.{}.[{}]", api_name, group_name, pattern));
+                }
+            }
+        }
+        ids
     }
 
     /// Resolve a URL completa para um endpoint específico.
@@ -103,13 +2041,325 @@ impl Config {
             .get(key)
             .ok_or_else(|| ProcessorError::Schema(format!("Chave não encontrada: {}", key)))?;
 
-        Ok(self.join_urls(&api_cfg.base_url, route))
+        Ok(join_urls(&api_cfg.base_url, route))
+    }
+}
+
+/// Mescla a seção `[defaults]` em cada grupo de endpoints.
+///
+/// Precedência: valor do grupo > `[defaults]` > padrão embutido. A mescla
+/// ocorre sobre o TOML bruto, antes da tipagem, para que campos com padrão
+/// (ex: `output_format`) só herdem quando ausentes no grupo.
+fn apply_defaults(table: &mut toml::Table) -> Result<(), ProcessorError> {
+    let defaults = match table.remove(DEFAULTS_SECTION) {
+        Some(toml::Value::Table(defaults)) => defaults,
+        Some(_) => {
+            return Err(ProcessorError::Schema(format!(
+                "[{}] deve ser uma tabela",
+                DEFAULTS_SECTION
+            )));
+        }
+        None => return Ok(()),
+    };
+
+    // Tipagem dos valores; chaves desconhecidas cairiam em `routes`
+    let probe: EndpointGroup = toml::Value::Table(defaults.clone())
+        .try_into()
+        .map_err(|e| ProcessorError::Schema(format!("[{}] inválida: {}", DEFAULTS_SECTION, e)))?;
+
+    let mut rejected: Vec<&str> = probe.routes.keys().map(String::as_str).collect();
+    rejected.extend(NON_INHERITABLE.iter().filter(|k| defaults.contains_key(**k)));
+    if !rejected.is_empty() {
+        rejected.sort();
+        return Err(ProcessorError::Schema(format!(
+            "[{}] aceita apenas metadados de grupo; chaves não herdáveis: {}",
+            DEFAULTS_SECTION,
+            rejected.join(", ")
+        )));
+    }
+
+    for (name, api) in table.iter_mut() {
+        if name == HTTP_SECTION || name == PIPELINE_SECTION {
+            continue;
+        }
+        let Some(groups) = api.get_mut("endpoints").and_then(toml::Value::as_table_mut) else {
+            continue;
+        };
+        for (_, group) in groups.iter_mut() {
+            let Some(group) = group.as_table_mut() else {
+                continue;
+            };
+            for (key, value) in &defaults {
+                group.entry(key.as_str()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Mescla `source` em `target`: tabelas repetidas são unidas recursivamente.
+///
+/// # Erros
+/// O caminho pontilhado (`api.endpoints.grupo.chave`) da primeira chave
+/// presente nos dois lados que não seja tabela em ambos.
+fn merge_disjoint(target: &mut toml::Table, source: toml::Table, prefix: &str) -> Result<(), String> {
+    for (key, value) in source {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match (target.get_mut(&key), value) {
+            (None, value) => {
+                target.insert(key, value);
+            }
+            (Some(toml::Value::Table(existing)), toml::Value::Table(incoming)) => {
+                merge_disjoint(existing, incoming, &path)?;
+            }
+            (Some(_), _) => return Err(path),
+        }
+    }
+    Ok(())
+}
+
+/// Conteúdo de um `body_file`, com as referências `${VAR}` resolvidas.
+fn read_body_file(path: &str) -> Result<String, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("body_file '{}' ilegível ({})", path, e))?;
+    interpolate_env(&content).map_err(|e| format!("body_file '{}': {}", path, e))
+}
+
+/// Substitui cada `${VAR}` pelo valor da variável de ambiente.
+///
+/// As mensagens de erro nunca incluem o valor original (pode conter segredos).
+fn interpolate_env(value: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| "referência '${' sem '}'".to_string())?;
+        let name = &after[..end];
+        let resolved = env::var(name)
+            .map_err(|_| format!("variável de ambiente '{}' não definida", name))?;
+        out.push_str(&resolved);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Concatenação segura de URLs sem barras duplicadas.
+///
+/// Barras finais escritas na configuração são preservadas (APIs no estilo
+/// Django as exigem): a da rota sempre, a do `base_url` quando a rota é vazia
+/// ou só anexa uma query (`?ano=2024`), caso em que nenhuma barra é inserida.
+fn join_urls(base: &str, path: &str) -> String {
+    if path.is_empty() || path.starts_with(['?', '#']) {
+        return format!("{}{}", base, path);
+    }
+    let base_trimmed = base.trim_end_matches('/');
+    let path_trimmed = path.trim_start_matches('/');
+    format!("{}/{}", base_trimmed, path_trimmed)
+}
+
+/// Host puro, como o `Url` o normaliza (`api.gov.br`, `10.0.0.1`, `[::1]`).
+fn is_host_name(host: &str) -> bool {
+    reqwest::Url::parse(&format!("https://{}/", host))
+        .is_ok_and(|url| url.host_str() == Some(host.to_ascii_lowercase().as_str()))
+}
+
+/// Tags não vazias e sem `,` (reservada ao "e" lógico de `--tag a,b`).
+fn check_tags(scope: &str, tags: &[String], issues: &mut Vec<String>) {
+    for tag in tags {
+        if tag.trim().is_empty() || tag.contains(',') {
+            issues.push(format!("'{}': tag inválida '{}'", scope, tag));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_urls_avoids_duplicate_slashes() {
+        assert_eq!(join_urls("https://api.gov.br/v1/", "/itens"), "https://api.gov.br/v1/itens");
+        assert_eq!(join_urls("https://api.gov.br/v1", "itens"), "https://api.gov.br/v1/itens");
+    }
+
+    #[test]
+    fn join_urls_preserves_trailing_slashes() {
+        assert_eq!(join_urls("https://api.gov.br/v1", "/itens/"), "https://api.gov.br/v1/itens/");
+        assert_eq!(join_urls("https://api.gov.br/v1/", "itens/?ano=2024"), "https://api.gov.br/v1/itens/?ano=2024");
+        assert_eq!(join_urls("https://api.gov.br/v1/itens/", ""), "https://api.gov.br/v1/itens/");
+    }
+
+    #[test]
+    fn join_urls_appends_query_only_routes() {
+        assert_eq!(join_urls("https://api.gov.br/v1/itens", "?ano=2024"), "https://api.gov.br/v1/itens?ano=2024");
+        assert_eq!(join_urls("https://api.gov.br/v1/itens/", "?ano=2024"), "https://api.gov.br/v1/itens/?ano=2024");
+        assert_eq!(join_urls("https://api.gov.br/v1/itens", "#topo"), "https://api.gov.br/v1/itens#topo");
+    }
+
+    fn parse(toml: &str) -> Result<Config, ProcessorError> {
+        Config::parse(toml, ConfigFormat::Toml)
+    }
+
+    fn schema_error(result: Result<Config, ProcessorError>) -> String {
+        match result {
+            Err(ProcessorError::Schema(message)) => message,
+            Err(e) => panic!("erro inesperado: {e}"),
+            Ok(_) => panic!("configuração aceita"),
+        }
+    }
+
+    #[test]
+    fn validation_reports_every_problem_at_once() {
+        let message = schema_error(parse(
+            r#"
+            [http]
+            timeout_secs = 0
+
+            [compras]
+            base_url = "http://api.gov.br"
+            [compras.endpoints.itens]
+            pedidos = "/pedidos"
+
+            [vazia]
+            base_url = "https://api.gov.br"
+            [vazia.endpoints]
+            "#,
+        ));
+
+        assert!(message.starts_with("Configuração inválida (3 problema(s))"), "{message}");
+        assert!(message.contains("[http]: timeouts devem ser maiores que zero"), "{message}");
+        assert!(message.contains("'compras': base_url deve usar https://"), "{message}");
+        assert!(message.contains("'vazia' sem endpoints"), "{message}");
+    }
+
+    #[test]
+    fn defaults_fill_only_what_the_group_leaves_unset() {
+        let config = parse(
+            r#"
+            [defaults]
+            compression = "zstd"
+            write_empty = true
+
+            [compras]
+            base_url = "https://api.gov.br"
+            [compras.endpoints.herda]
+            pedidos = "/pedidos"
+            [compras.endpoints.proprio]
+            compression = "snappy"
+            contratos = "/contratos"
+            "#,
+        )
+        .unwrap();
+
+        let groups = &config.apis["compras"].endpoints;
+        assert_eq!(groups["herda"].compression(), Some(Compression::Zstd));
+        assert_eq!(groups["proprio"].compression(), Some(Compression::Snappy));
+        // As demais chaves de `[defaults]` seguem herdadas pelo grupo com codec próprio
+        assert!(groups["proprio"].write_empty);
+        // `[defaults]` não vira API nem rota
+        assert!(!config.apis.contains_key("defaults"));
+        assert_eq!(groups["herda"].routes.keys().collect::<Vec<_>>(), ["pedidos"]);
+    }
+
+    #[test]
+    fn defaults_reject_group_specific_keys() {
+        let message = schema_error(parse(
+            r#"
+            [defaults]
+            source_glob = "in/*.json"
+
+            [compras]
+            base_url = "https://api.gov.br"
+            [compras.endpoints.itens]
+            pedidos = "/pedidos"
+            "#,
+        ));
+        assert!(message.contains("chaves não herdáveis: source_glob"), "{message}");
     }
 
-    /// Concatenação segura de URLs sem barras duplicadas.
-    fn join_urls(&self, base: &str, path: &str) -> String {
-        let base_trimmed = base.trim_end_matches('/');
-        let path_trimmed = path.trim_start_matches('/');
-        format!("{}/{}", base_trimmed, path_trimmed)
+    #[test]
+    fn unknown_tls_versions_are_rejected_at_load() {
+        let config = |version: &str| {
+            format!(
+                "[http]\nmin_tls_version = \"{}\"\n[compras]\nbase_url = \"https://api.gov.br\"\n[compras.endpoints.itens]\npedidos = \"/pedidos\"",
+                version
+            )
+        };
+
+        assert_eq!(parse(&config("1.3")).unwrap().http.min_tls_version, TlsVersion::Tls13);
+        for version in ["1.1", "1.0", "tls13"] {
+            let message = schema_error(parse(&config(version)));
+            assert!(message.contains(&format!("unknown variant `{}`", version)), "{message}");
+            assert!(message.contains("min_tls_version"), "{message}");
+        }
+    }
+
+    #[test]
+    fn fragment_directory_merges_files_in_name_order() {
+        let dir = std::env::temp_dir().join(format!("data_gov_models_{}_fragments", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("01_base.toml"), "[http]\ntimeout_secs = 30\n[compras]\nbase_url = \"https://api.gov.br\"").unwrap();
+        fs::write(dir.join("02_itens.toml"), "[compras.endpoints.itens]\npedidos = \"/pedidos\"").unwrap();
+        // Apenas `*.toml` são fragmentos
+        fs::write(dir.join("LEIA-ME.txt"), "não é TOML").unwrap();
+
+        let config = Config::load(dir.to_str().unwrap(), None).unwrap();
+
+        assert_eq!(config.http.timeout_secs, Some(30));
+        assert_eq!(config.apis["compras"].base_url, "https://api.gov.br");
+        assert_eq!(config.apis["compras"].endpoints["itens"].routes["pedidos"], "/pedidos");
+
+        // A mesma tabela pode se repetir, mas não a mesma chave
+        fs::write(dir.join("03_conflito.toml"), "[compras]\nbase_url = \"https://outra.gov.br\"").unwrap();
+        let message = schema_error(Config::load(dir.to_str().unwrap(), None));
+        assert_eq!(message, "Fragmento '03_conflito.toml' redefine 'compras.base_url' (já definida em 01_base.toml, 02_itens.toml)");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn from_reader_loads_toml_and_json() {
+        let toml = "[compras]\nbase_url = \"https://api.gov.br\"\n[compras.endpoints.itens]\npedidos = \"/pedidos\"";
+        let json = r#"{"compras": {"base_url": "https://api.gov.br", "endpoints": {"itens": {"pedidos": "/pedidos"}}}}"#;
+
+        for (content, format) in [(toml, ConfigFormat::Toml), (json, ConfigFormat::Json)] {
+            let config = Config::from_reader(content.as_bytes(), format).unwrap();
+            assert_eq!(config.apis["compras"].endpoints["itens"].routes["pedidos"], "/pedidos");
+        }
+
+        // A mesma validação da carga por arquivo
+        let message = schema_error(Config::from_reader(json.replace("https", "http").as_bytes(), ConfigFormat::Json));
+        assert!(message.contains("base_url deve usar https://"), "{message}");
+    }
+
+    #[test]
+    fn body_file_is_read_with_env_references_resolved() {
+        let dir = std::env::temp_dir().join(format!("data_gov_models_{}_body_file", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // O cargo define CARGO_PKG_NAME ao rodar os testes
+        let body = dir.join("consulta.json");
+        fs::write(&body, r#"{"pacote": "${CARGO_PKG_NAME}", "ano": 2024}"#).unwrap();
+        let toml = format!(
+            "[compras]\nbase_url = \"https://api.gov.br\"\n[compras.endpoints.itens]\nbody_file = {:?}\npedidos = \"/pedidos\"",
+            body.to_str().unwrap()
+        );
+
+        let config = parse(&toml).unwrap();
+        assert_eq!(
+            config.apis["compras"].endpoints["itens"].body.as_deref(),
+            Some(format!(r#"{{"pacote": "{}", "ano": 2024}}"#, env!("CARGO_PKG_NAME")).as_str())
+        );
+
+        // Variável ausente: a validação aponta o nome, sem vazar conteúdo
+        fs::write(&body, r#"{"token": "${DATA_GOV_TESTE_SEM_VALOR}"}"#).unwrap();
+        let message = schema_error(parse(&toml));
+        assert!(message.contains("variável de ambiente 'DATA_GOV_TESTE_SEM_VALOR' não definida"), "{message}");
+        assert!(message.contains("'compras.itens'"), "{message}");
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
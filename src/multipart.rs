@@ -0,0 +1,131 @@
+//! # Formulários Multipart
+//!
+//! Alguns serviços só disparam uma exportação via POST de um formulário
+//! `multipart/form-data` (campos de texto e arquivos) e devolvem o JSON na
+//! resposta. O grupo declara o formulário em `multipart`; as demais rotas
+//! continuam em GET.
+//!
+//! ## Codificação
+//! O corpo é montado aqui (RFC 7578), sem dependências extras: cada campo vira
+//! uma parte delimitada por um boundary aleatório. Arquivos são lidos inteiros
+//! para a memória, adequado a formulários de consulta, não a uploads grandes.
+//! Aspas e quebras de linha em nomes são escapadas como nos navegadores
+//! (`%22`, `%0D`, `%0A`).
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::models::Multipart;
+
+/// Corpo codificado e o `Content-Type` correspondente (com o boundary).
+pub struct Encoded {
+    pub content_type: String,
+    pub body: Vec<u8>,
+}
+
+/// Codifica os campos de texto seguidos dos arquivos, em ordem de nome.
+///
+/// # Erros
+/// Arquivos de `files` ilegíveis.
+pub fn encode(form: &Multipart) -> io::Result<Encoded> {
+    let boundary = format!("data-gov-{}", uuid::Uuid::new_v4().simple());
+    let mut body = Vec::new();
+
+    for (name, value) in &form.fields {
+        part_header(&mut body, &boundary, name, None);
+        body.extend_from_slice(value.as_bytes());
+        body.extend_from_slice(b"\r\n");
+    }
+
+    for (name, path) in &form.files {
+        let path = Path::new(path);
+        let content = fs::read(path)?;
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        part_header(&mut body, &boundary, name, Some((&file_name, content_type(path))));
+        body.extend_from_slice(&content);
+        body.extend_from_slice(b"\r\n");
+    }
+
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    Ok(Encoded {
+        content_type: format!("multipart/form-data; boundary={}", boundary),
+        body,
+    })
+}
+
+/// Delimitador e cabeçalhos de uma parte; `file` traz nome e tipo do arquivo.
+fn part_header(body: &mut Vec<u8>, boundary: &str, name: &str, file: Option<(&str, &str)>) {
+    let mut header = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+        boundary,
+        escape(name)
+    );
+    if let Some((file_name, content_type)) = file {
+        header.push_str(&format!(
+            "; filename=\"{}\"\r\nContent-Type: {}",
+            escape(file_name),
+            content_type
+        ));
+    }
+    header.push_str("\r\n\r\n");
+    body.extend_from_slice(header.as_bytes());
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Tipo do arquivo pela extensão; desconhecidas seguem como binário.
+pub fn content_type(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "json" => "application/json",
+        "csv" => "text/csv",
+        "xml" => "application/xml",
+        "txt" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_writes_fields_then_files_between_boundaries() {
+        let dir = std::env::temp_dir().join(format!("data_gov_multipart_{}_encode", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("consulta.csv");
+        fs::write(&file, "ano\n2024\n").unwrap();
+        let form = Multipart {
+            fields: [("formato", "json"), ("nome \"x\"", "a\r\nb")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            files: [("consulta".to_string(), file.display().to_string())].into(),
+        };
+
+        let encoded = encode(&form).unwrap();
+
+        let boundary = encoded.content_type.strip_prefix("multipart/form-data; boundary=").unwrap();
+        assert!(boundary.starts_with("data-gov-"));
+        let expected = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"formato\"\r\n\r\njson\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"nome %22x%22\"\r\n\r\na\r\nb\r\n\
+             --{b}\r\nContent-Disposition: form-data; name=\"consulta\"; filename=\"consulta.csv\"\r\n\
+             Content-Type: text/csv\r\n\r\nano\n2024\n\r\n\
+             --{b}--\r\n",
+            b = boundary
+        );
+        assert_eq!(String::from_utf8(encoded.body).unwrap(), expected);
+
+        // Cada chamada sorteia o seu boundary
+        assert_ne!(encode(&form).unwrap().content_type, encoded.content_type);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
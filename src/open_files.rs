@@ -0,0 +1,138 @@
+//! # Limite de Arquivos Abertos
+//!
+//! `[pipeline] max_open_files` limita os writers abertos ao mesmo tempo pelo
+//! processo: o staging dos downloads (páginas, descompressão e `extract`
+//! inclusive) e os temporários das saídas. Sem vaga, o download ou a conversão
+//! espera a liberação de outra, em vez de falhar com "too many open files" em
+//! ulimits baixos (1024 é o padrão de muitas distribuições).
+//!
+//! ## Escopo
+//! Apenas os writers contam: leituras são curtas e sockets, log e stdio têm
+//! número fixo por worker. Escolha um valor com folga abaixo do `ulimit -n`.
+//!
+//! ## Aninhamento
+//! Uma thread que já ocupa uma vaga não espera por outra (ex: o temporário
+//! gravado enquanto o staging está aberto): a mesma thread nunca bloqueia à
+//! espera de si mesma.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, PoisonError};
+
+static LIMIT: Limit = Limit {
+    max: AtomicUsize::new(0),
+    open: Mutex::new(0),
+    released: Condvar::new(),
+};
+
+thread_local! {
+    /// Vagas (aninhadas) ocupadas pela thread atual.
+    static HELD: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Semáforo global; `max = 0` desliga o limite.
+struct Limit {
+    max: AtomicUsize,
+    open: Mutex<usize>,
+    released: Condvar,
+}
+
+/// Vaga de um writer, liberada no drop (na mesma thread que a ocupou).
+pub struct Permit {
+    limit: &'static Limit,
+    counted: bool,
+    _thread: PhantomData<*const ()>,
+}
+
+/// Aplica `max_open_files` (None desliga); chamado a cada execução, para que
+/// o daemon acompanhe a configuração recarregada.
+pub fn configure(max: Option<NonZeroUsize>) {
+    LIMIT.max.store(max.map_or(0, NonZeroUsize::get), Ordering::Relaxed);
+    LIMIT.released.notify_all();
+}
+
+/// Ocupa uma vaga, esperando enquanto o limite estiver atingido.
+pub fn acquire() -> Permit {
+    LIMIT.acquire()
+}
+
+impl Limit {
+    fn acquire(&'static self) -> Permit {
+        let nested = HELD.with(|held| held.get() > 0);
+        let counted = !nested && self.max.load(Ordering::Relaxed) > 0;
+        if counted {
+            let mut open = self.open.lock().unwrap_or_else(PoisonError::into_inner);
+            let full = |open: usize| {
+                let max = self.max.load(Ordering::Relaxed);
+                max > 0 && open >= max
+            };
+            if full(*open) {
+                log::debug!("Aguardando vaga de arquivo (max_open_files = {})", self.max.load(Ordering::Relaxed));
+            }
+            while full(*open) {
+                open = self.released.wait(open).unwrap_or_else(PoisonError::into_inner);
+            }
+            *open += 1;
+        }
+        HELD.with(|held| held.set(held.get() + 1));
+        Permit {
+            limit: self,
+            counted,
+            _thread: PhantomData,
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        HELD.with(|held| held.set(held.get().saturating_sub(1)));
+        if self.counted {
+            let mut open = self.limit.open.lock().unwrap_or_else(PoisonError::into_inner);
+            *open = open.saturating_sub(1);
+            drop(open);
+            self.limit.released.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn permits_bound_concurrent_holders() {
+        // Semáforo próprio: o global é compartilhado com os demais testes
+        static LOCAL: Limit = Limit {
+            max: AtomicUsize::new(2),
+            open: Mutex::new(0),
+            released: Condvar::new(),
+        };
+        let holders = Mutex::new((0usize, 0usize));
+
+        thread::scope(|scope| {
+            for _ in 0..6 {
+                scope.spawn(|| {
+                    let _permit = LOCAL.acquire();
+                    {
+                        let mut holders = holders.lock().unwrap();
+                        holders.0 += 1;
+                        holders.1 = holders.1.max(holders.0);
+                    }
+                    // Aninhada: a mesma thread não espera por si mesma
+                    let nested = LOCAL.acquire();
+                    assert!(!nested.counted);
+                    drop(nested);
+                    thread::sleep(Duration::from_millis(20));
+                    holders.lock().unwrap().0 -= 1;
+                });
+            }
+        });
+
+        assert_eq!(holders.into_inner().unwrap().1, 2);
+        assert_eq!(*LOCAL.open.lock().unwrap(), 0);
+    }
+}
@@ -0,0 +1,388 @@
+//! # Configuração a partir de OpenAPI (`from_openapi`)
+//!
+//! Gera o esqueleto de um `.toml` a partir do documento OpenAPI publicado pela
+//! API, para ser revisado e enxugado antes do uso. Apenas geração: o pipeline
+//! não lê o documento em tempo de execução.
+//!
+//! ```text
+//! data_gov from_openapi ARQUIVO_OU_URL [--api NOME] > endpoints.toml
+//! ```
+//!
+//! ## Mapeamento
+//! - `base_url`: primeiro item de `servers` (variáveis com o valor `default`;
+//!   URLs relativas resolvidas contra a URL do documento) ou, no Swagger 2,
+//!   `schemes` + `host` + `basePath`;
+//! - grupos: primeira tag de cada operação (`geral` sem tags);
+//! - endpoints: cada operação GET de `paths`, com a chave tirada do
+//!   `operationId` ou, na falta dele, do próprio caminho.
+//!
+//! Rotas com parâmetros de caminho (`/itens/{id}`) saem comentadas, pois o
+//! pipeline não os preenche; parâmetros de query obrigatórios são listados no
+//! comentário acima da rota. Apenas documentos em JSON são aceitos.
+
+use reqwest::Url;
+use serde_json::{Map, Value};
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+
+use crate::errors::ApiError;
+
+/// Grupo das operações sem tags.
+const DEFAULT_GROUP: &str = "geral";
+
+/// Nome da API quando nem `--api` nem `info.title` o definem.
+const DEFAULT_API: &str = "api";
+
+/// Operação GET de `paths`, já com grupo e chave.
+struct Operation {
+    key: String,
+    route: String,
+    summary: Option<String>,
+    required_query: Vec<String>,
+}
+
+/// Lê o documento de um arquivo ou de uma URL `http(s)://`.
+///
+/// # Erros
+/// `ApiError` se o download falhar, o arquivo não puder ser lido ou o conteúdo
+/// não for JSON.
+pub fn load(source: &str) -> Result<Value, ApiError> {
+    let content = if source.starts_with("https://") || source.starts_with("http://") {
+        let response = reqwest::blocking::get(source).map_err(ApiError::NetworkError)?;
+        if !response.status().is_success() {
+            return Err(ApiError::HttpStatusError {
+                status: response.status(),
+                url: source.to_string(),
+                retry_after: None,
+            });
+        }
+        response.text().map_err(ApiError::NetworkError)?
+    } else {
+        fs::read_to_string(source).map_err(ApiError::FileSystemError)?
+    };
+
+    serde_json::from_str(&content).map_err(|e| {
+        if source.ends_with(".yaml") || source.ends_with(".yml") {
+            ApiError::InvalidJson(format!("{} (converta o documento YAML para JSON)", e))
+        } else {
+            ApiError::InvalidJson(e.to_string())
+        }
+    })
+}
+
+/// Esqueleto do `.toml` para o documento `spec`, lido de `source`.
+///
+/// # Erros
+/// Mensagem descritiva se o documento não for OpenAPI/Swagger, não tiver
+/// operações GET ou não permitir deduzir o `base_url`.
+pub fn skeleton(spec: &Value, source: &str, api_name: Option<&str>) -> Result<String, String> {
+    if spec.get("openapi").is_none() && spec.get("swagger").is_none() {
+        return Err(format!("'{}' não é um documento OpenAPI (sem 'openapi' nem 'swagger')", source));
+    }
+
+    let title = spec.pointer("/info/title").and_then(Value::as_str);
+    let name = api_name
+        .map(str::to_string)
+        .or_else(|| title.and_then(identifier))
+        .unwrap_or_else(|| DEFAULT_API.to_string());
+    let base_url = base_url(spec, source)?;
+    let groups = operations(spec);
+    if groups.is_empty() {
+        return Err(format!("'{}' não tem operações GET em paths", source));
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Gerado por `data_gov from_openapi {}`", source);
+    if let Some(title) = title {
+        match spec.pointer("/info/version").and_then(Value::as_str) {
+            Some(version) => {
+                let _ = writeln!(out, "# {} {}", first_line(title), version);
+            }
+            None => {
+                let _ = writeln!(out, "# {}", first_line(title));
+            }
+        }
+    }
+    let _ = writeln!(out, "# Revise os grupos e remova os endpoints desnecessários.");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "[{}]", name);
+    if base_url.starts_with("http://") {
+        let _ = writeln!(out, "# O pipeline exige https://: ajuste o base_url.");
+    }
+    let _ = writeln!(out, "base_url = {}", quoted(&base_url));
+
+    for (group, operations) in &groups {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "[{}.endpoints.{}]", name, group);
+        for operation in operations {
+            match &operation.summary {
+                Some(summary) => {
+                    let _ = writeln!(out, "# GET {} - {}", operation.route, summary);
+                }
+                None => {
+                    let _ = writeln!(out, "# GET {}", operation.route);
+                }
+            }
+            if !operation.required_query.is_empty() {
+                let _ = writeln!(out, "# query obrigatória: {}", operation.required_query.join(", "));
+            }
+            // Parâmetros de caminho não são preenchidos pelo pipeline
+            let comment = if operation.route.contains('{') {
+                "# "
+            } else {
+                ""
+            };
+            let _ = writeln!(out, "{}{} = {}", comment, operation.key, quoted(&operation.route));
+        }
+    }
+    Ok(out)
+}
+
+/// `base_url` do primeiro `servers` (OpenAPI 3) ou de `host` (Swagger 2).
+fn base_url(spec: &Value, source: &str) -> Result<String, String> {
+    let url = if let Some(host) = spec.get("host").and_then(Value::as_str) {
+        let schemes: Vec<&str> = spec
+            .get("schemes")
+            .and_then(Value::as_array)
+            .map(|schemes| schemes.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let scheme = if schemes.is_empty() || schemes.contains(&"https") {
+            "https"
+        } else {
+            schemes[0]
+        };
+        let base_path = spec.get("basePath").and_then(Value::as_str).unwrap_or("");
+        format!("{}://{}{}", scheme, host, base_path)
+    } else {
+        // Sem `servers`, o OpenAPI 3 considera o servidor `/`
+        let server = spec.pointer("/servers/0");
+        let mut url = server
+            .and_then(|server| server.get("url"))
+            .and_then(Value::as_str)
+            .unwrap_or("/")
+            .to_string();
+        if let Some(variables) = server.and_then(|server| server.get("variables")).and_then(Value::as_object) {
+            for (variable, definition) in variables {
+                if let Some(default) = definition.get("default").and_then(Value::as_str) {
+                    url = url.replace(&format!("{{{}}}", variable), default);
+                }
+            }
+        }
+        url
+    };
+
+    if Url::parse(&url).is_ok() {
+        return Ok(url.trim_end_matches('/').to_string());
+    }
+    // Relativa: resolvida contra a URL do documento
+    match Url::parse(source).and_then(|origin| origin.join(&url)) {
+        Ok(resolved) => Ok(resolved.as_str().trim_end_matches('/').to_string()),
+        Err(_) => Err(format!(
+            "servidor relativo '{}' em um documento local: leia o documento pela URL da API",
+            url
+        )),
+    }
+}
+
+/// Operações GET por grupo, na ordem de `paths` (ordem alfabética das rotas).
+fn operations(spec: &Value) -> BTreeMap<String, Vec<Operation>> {
+    let mut groups: BTreeMap<String, Vec<Operation>> = BTreeMap::new();
+    let Some(paths) = spec.get("paths").and_then(Value::as_object) else {
+        return groups;
+    };
+
+    for (route, item) in paths {
+        let Some(get) = item.get("get") else {
+            continue;
+        };
+        let group = get
+            .pointer("/tags/0")
+            .and_then(Value::as_str)
+            .and_then(identifier)
+            .unwrap_or_else(|| DEFAULT_GROUP.to_string());
+        let base_key = get
+            .get("operationId")
+            .and_then(Value::as_str)
+            .and_then(identifier)
+            .or_else(|| path_key(route));
+
+        let operations = groups.entry(group).or_default();
+        let key = unique_key(base_key.as_deref().unwrap_or("raiz"), operations);
+        let summary = get
+            .get("summary")
+            .or_else(|| get.get("description"))
+            .and_then(Value::as_str)
+            .map(first_line)
+            .filter(|summary| !summary.is_empty());
+
+        // Parâmetros do caminho e da operação (a operação prevalece)
+        let mut parameters: BTreeMap<(String, String), bool> = BTreeMap::new();
+        for list in [item.get("parameters"), get.get("parameters")] {
+            for parameter in list.and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_object) {
+                if let (Some(name), Some(location)) = (text(parameter, "name"), text(parameter, "in")) {
+                    let required = parameter.get("required").and_then(Value::as_bool).unwrap_or(false);
+                    parameters.insert((location.to_string(), name.to_string()), required);
+                }
+            }
+        }
+        let required_query = parameters
+            .into_iter()
+            .filter(|((location, _), required)| location == "query" && *required)
+            .map(|((_, name), _)| name)
+            .collect();
+
+        operations.push(Operation {
+            key,
+            route: route.clone(),
+            summary,
+            required_query,
+        });
+    }
+    groups
+}
+
+fn text<'a>(object: &'a Map<String, Value>, field: &str) -> Option<&'a str> {
+    object.get(field).and_then(Value::as_str)
+}
+
+/// Chave a partir dos segmentos fixos do caminho (`/v1/orgaos/{id}` → `v1_orgaos`).
+fn path_key(route: &str) -> Option<String> {
+    let segments: Vec<&str> = route
+        .split('/')
+        .filter(|segment| !segment.is_empty() && !segment.starts_with('{'))
+        .collect();
+    identifier(&segments.join("_"))
+}
+
+/// `base`, ou `base_2`, `base_3`... se já usada no grupo.
+fn unique_key(base: &str, operations: &[Operation]) -> String {
+    let taken = |key: &str| operations.iter().any(|operation| operation.key == key);
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}_{}", base, n))
+        .find(|key| !taken(key))
+        .unwrap_or_default()
+}
+
+/// Identificador TOML sem aspas: minúsculas, dígitos e `_` (camelCase separado,
+/// acentos removidos: `Órgãos` → `orgaos`).
+fn identifier(text: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut previous_lower = false;
+    for c in text.chars().map(unaccent) {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && previous_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            previous_lower = false;
+        }
+    }
+    let out = out.trim_end_matches('_');
+    (!out.is_empty()).then(|| out.to_string())
+}
+
+fn unaccent(c: char) -> char {
+    match c {
+        'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+        'Á' | 'À' | 'Â' | 'Ã' | 'Ä' => 'A',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'É' | 'È' | 'Ê' | 'Ë' => 'E',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'Í' | 'Ì' | 'Î' | 'Ï' => 'I',
+        'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+        'Ó' | 'Ò' | 'Ô' | 'Õ' | 'Ö' => 'O',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'Ú' | 'Ù' | 'Û' | 'Ü' => 'U',
+        'ç' => 'c',
+        'Ç' => 'C',
+        c => c,
+    }
+}
+
+fn first_line(text: &str) -> String {
+    text.lines().next().unwrap_or("").trim().to_string()
+}
+
+/// String TOML com as aspas e escapes necessários.
+fn quoted(text: &str) -> String {
+    toml::Value::String(text.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Config, ConfigFormat};
+
+    #[test]
+    fn fixture_spec_yields_the_expected_endpoints() {
+        let spec: Value = serde_json::from_str(
+            r#"{
+                "openapi": "3.0.1",
+                "info": {"title": "Portal de Compras", "version": "2.1"},
+                "servers": [{"url": "https://api.compras.gov.br/{versao}/", "variables": {"versao": {"default": "v2"}}}],
+                "paths": {
+                    "/itens": {
+                        "parameters": [{"name": "ano", "in": "query", "required": true}],
+                        "get": {"summary": "Itens do catálogo\nPaginado", "parameters": [{"name": "pagina", "in": "query"}]}
+                    },
+                    "/orgaos": {"get": {"tags": ["Órgãos"], "operationId": "listarOrgaos", "summary": "Lista os órgãos"}},
+                    "/orgaos/{id}": {"get": {"tags": ["Órgãos"], "operationId": "listarOrgaos"}},
+                    "/pedidos": {"post": {"operationId": "criarPedido"}}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let out = skeleton(&spec, "spec.json", None).unwrap();
+
+        assert_eq!(
+            out,
+            "# Gerado por `data_gov from_openapi spec.json`\n\
+             # Portal de Compras 2.1\n\
+             # Revise os grupos e remova os endpoints desnecessários.\n\
+             \n\
+             [portal_de_compras]\n\
+             base_url = \"https://api.compras.gov.br/v2\"\n\
+             \n\
+             [portal_de_compras.endpoints.geral]\n\
+             # GET /itens - Itens do catálogo\n\
+             # query obrigatória: ano\n\
+             itens = \"/itens\"\n\
+             \n\
+             [portal_de_compras.endpoints.orgaos]\n\
+             # GET /orgaos - Lista os órgãos\n\
+             listar_orgaos = \"/orgaos\"\n\
+             # GET /orgaos/{id}\n\
+             # listar_orgaos_2 = \"/orgaos/{id}\"\n"
+        );
+        // O esqueleto é uma configuração válida
+        let config = Config::from_reader(out.as_bytes(), ConfigFormat::Toml).unwrap();
+        assert_eq!(config.apis["portal_de_compras"].endpoints["orgaos"].routes.len(), 1);
+    }
+
+    #[test]
+    fn swagger_two_prefers_https_and_non_specs_are_rejected() {
+        let spec: Value = serde_json::from_str(
+            r#"{"swagger": "2.0", "host": "dados.gov.br", "basePath": "/api", "schemes": ["http", "https"],
+                "paths": {"/contratos": {"get": {}}}}"#,
+        )
+        .unwrap();
+
+        let out = skeleton(&spec, "swagger.json", Some("dados")).unwrap();
+        assert!(out.contains("[dados]\nbase_url = \"https://dados.gov.br/api\"\n"), "{out}");
+        assert!(out.contains("[dados.endpoints.geral]\n# GET /contratos\ncontratos = \"/contratos\"\n"), "{out}");
+
+        let error = skeleton(&serde_json::json!({"paths": {}}), "outro.json", None).unwrap_err();
+        assert_eq!(error, "'outro.json' não é um documento OpenAPI (sem 'openapi' nem 'swagger')");
+    }
+}
@@ -0,0 +1,299 @@
+//! # Paginação por Cursor
+//!
+//! APIs que devolvem o próximo cursor (token ou URL) dentro do envelope
+//! (ex: `paging.next`) são percorridas página a página até o cursor
+//! ficar nulo/ausente. Cada página vai para o seu próprio arquivo de staging
+//! (sem acumular registros em memória), e a conversão concatena todas em uma
+//! única tabela. O `totalRegistros` da primeira página é confrontado pela
+//! reconciliação (`reconcile`) com a soma dos registros de todas as páginas.
+//!
+//! Com `status_field`, cada página tem o status de negócio conferido: a primeira
+//! com falha encerra a paginação e segue para o staging, falhando o endpoint
+//! na conversão com o status/mensagem recebidos.
+//!
+//! ## Proteções
+//! - **Limite de páginas** (`max_pages`): evita laços infinitos em APIs defeituosas.
+//! - **Cursor repetido**: uma URL já visitada encerra a paginação.
+
+use reqwest::Url;
+use serde_json::Value;
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::api::{self, Credentials, HttpClient};
+use crate::errors::ApiError;
+use crate::models::{EndpointGroup, Pagination};
+use crate::open_files;
+use crate::processor;
+use crate::progress::{self, DownloadBar};
+use crate::warnings::EndpointWarnings;
+
+/// Limite padrão de páginas quando `max_pages` não é informado.
+pub const DEFAULT_MAX_PAGES: usize = 1000;
+
+/// Percorre a cadeia de cursores a partir de `first_url`, gravando cada página.
+///
+/// Páginas sem registros não geram arquivo, exceto a que sinalizar falha de
+/// negócio: seu status precisa chegar à conversão. Com `write_empty`, a
+/// primeira página é sempre gravada, para que uma resposta vazia chegue à
+/// conversão.
+///
+/// # Returns
+/// Arquivos de staging das páginas, em ordem (`{staging}.p0001.json`, ...).
+#[allow(clippy::too_many_arguments)]
+pub fn fetch_pages(
+    client: &HttpClient,
+    first_url: &str,
+    auth: &Credentials,
+    pagination: &Pagination,
+    group: &EndpointGroup,
+    destination: &Path,
+    pb: &DownloadBar,
+    warnings: &EndpointWarnings,
+) -> Result<Vec<PathBuf>, ApiError> {
+    let Pagination::Cursor {
+        next_path,
+        cursor_param,
+        max_pages,
+    } = pagination;
+    let max_pages = max_pages.map_or(DEFAULT_MAX_PAGES, |n| n.get());
+    let root_path = group.root_path.as_deref();
+
+    pb.set_style(progress::style("{spinner:.green} [{elapsed_precise}] {msg}"));
+
+    let mut url = first_url.to_string();
+    let mut visited = HashSet::new();
+    let mut files = Vec::new();
+    let mut records = 0;
+    let mut pages = 0;
+
+    loop {
+        visited.insert(url.clone());
+
+        let page = api::fetch_json(client, &url, auth, &group.accept_status, pb)?;
+        pages += 1;
+
+        let failed = page_failed(&page, group);
+        let count = record_count(&page, root_path);
+        let next = lookup(&page, next_path)
+            .and_then(cursor_text)
+            .and_then(|cursor| next_url(first_url, &url, &cursor, cursor_param.as_deref()));
+        if count > 0 || failed || (group.write_empty && pages == 1) {
+            let path = page_path(destination, pages);
+            write_page(&path, &envelope(page, group))?;
+            files.push(path);
+            records += count;
+        }
+
+        pb.set_message(format!("Página {} ({} registros)", pages, records));
+        pb.tick();
+
+        if failed {
+            warnings.warn(format!(
+                "Aviso: página {} sinalizou falha no envelope; paginação encerrada",
+                pages
+            ));
+            break;
+        }
+
+        let Some(next) = next else {
+            break;
+        };
+        if visited.contains(&next) {
+            warnings.warn(format!(
+                "Aviso: cursor repetido em '{}'; paginação encerrada",
+                api::redact_url(&next)
+            ));
+            break;
+        }
+        if pages >= max_pages {
+            warnings.warn(format!(
+                "Aviso: limite de {} páginas atingido; registros seguintes ignorados",
+                max_pages
+            ));
+            break;
+        }
+        url = next;
+    }
+
+    if files.is_empty() {
+        pb.finish_with_message("Conteúdo Vazio");
+        return Err(ApiError::EmptyResponse);
+    }
+
+    pb.finish_with_message(format!("Paginação completa: {} página(s)", pages));
+    Ok(files)
+}
+
+/// Caminho do staging da página `page` (`x_temp.json` → `x_temp.p0001.json`).
+fn page_path(destination: &Path, page: usize) -> PathBuf {
+    destination.with_extension(format!("p{:04}.json", page))
+}
+
+/// Envelope reduzido da página: registros, `totalRegistros` e campos de status.
+///
+/// Demais chaves (ex: o próprio cursor) não viram colunas na conversão.
+fn envelope(page: Value, group: &EndpointGroup) -> Value {
+    let mut fields = match (group.root_path.as_deref(), page) {
+        (Some(_), Value::Object(fields)) => fields,
+        (_, page) => return page,
+    };
+    let root = group.root_path.as_deref();
+    let keep = [
+        root,
+        Some(processor::TOTAL_KEY),
+        group.status_field.as_deref(),
+        group.status_message_field.as_deref(),
+    ];
+    fields.retain(|key, _| keep.contains(&Some(key.as_str())));
+    Value::Object(fields)
+}
+
+/// Grava a página no staging.
+fn write_page(path: &Path, page: &Value) -> Result<(), ApiError> {
+    let _permit = open_files::acquire();
+    let file = File::create(path).map_err(ApiError::FileSystemError)?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer(&mut writer, page).map_err(|e| ApiError::FileSystemError(e.into()))?;
+    writer.flush().map_err(ApiError::FileSystemError)
+}
+
+/// A página traz um status de negócio diferente de `expected_status`.
+fn page_failed(page: &Value, group: &EndpointGroup) -> bool {
+    let Some(field) = group.status_field.as_deref() else {
+        return false;
+    };
+    let status = page.get(field).and_then(processor::envelope_text);
+    processor::check_status(group, status, None).is_err()
+}
+
+/// Navega por um caminho separado por pontos (ex: "paging.next").
+fn lookup<'v>(value: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.')
+        .try_fold(value, |current, segment| current.get(segment))
+}
+
+/// Texto do cursor; nulo, vazio ou tipos compostos encerram a paginação.
+fn cursor_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Monta a URL da próxima página.
+///
+/// Com `cursor_param`, o token é aplicado como query param sobre a URL inicial;
+/// sem ele, o cursor é uma URL (absoluta ou relativa à página atual).
+fn next_url(
+    first_url: &str,
+    current_url: &str,
+    cursor: &str,
+    cursor_param: Option<&str>,
+) -> Option<String> {
+    match cursor_param {
+        Some(param) => {
+            let mut url = Url::parse(first_url).ok()?;
+            let pairs: Vec<(String, String)> = url
+                .query_pairs()
+                .filter(|(k, _)| k != param)
+                .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                .collect();
+            url.query_pairs_mut()
+                .clear()
+                .extend_pairs(pairs)
+                .append_pair(param, cursor);
+            Some(url.into())
+        }
+        None => Url::parse(current_url).ok()?.join(cursor).ok().map(Into::into),
+    }
+}
+
+/// Registros de uma página sob `root_path` (lista ou objeto único).
+fn record_count(page: &Value, root_path: Option<&str>) -> usize {
+    let data = match root_path {
+        Some(key) => page.get(key),
+        None => Some(page),
+    };
+    match data {
+        Some(Value::Array(items)) => items.len(),
+        Some(Value::Null) | None => 0,
+        Some(_) => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_server;
+    use crate::errors::Stage;
+    use crate::models::HttpSettings;
+    use crate::progress::RunProgress;
+    use crate::warnings::Warnings;
+    use std::fs;
+
+    #[test]
+    fn next_url_applies_token_or_follows_link() {
+        let first = "https://api.exemplo.gov.br/itens?uf=SP&cursor=velho";
+        assert_eq!(
+            next_url(first, "https://api.exemplo.gov.br/outra", "abc", Some("cursor")).as_deref(),
+            Some("https://api.exemplo.gov.br/itens?uf=SP&cursor=abc")
+        );
+        assert_eq!(
+            next_url(first, "https://api.exemplo.gov.br/itens/p1", "p2?x=1", None).as_deref(),
+            Some("https://api.exemplo.gov.br/itens/p2?x=1")
+        );
+        assert_eq!(cursor_text(&Value::Null), None);
+        assert_eq!(cursor_text(&Value::String(String::new())), None);
+        assert_eq!(cursor_text(&serde_json::json!(7)).as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn follows_a_three_link_cursor_chain() {
+        let page = |ids: &str, next: &str| {
+            let body = format!(r#"{{"resultado": [{}], "paging": {{"next": {}}}}}"#, ids, next);
+            test_server::response("200 OK", &[("Content-Type", "application/json")], body.as_bytes())
+        };
+        let (base, server) = test_server::serve(vec![
+            page(r#"{"id": 1}, {"id": 2}"#, r#""/itens?pagina=2""#),
+            page(r#"{"id": 3}"#, r#""/itens?pagina=3""#),
+            page(r#"{"id": 4}"#, "null"),
+        ]);
+
+        let dir = std::env::temp_dir().join(format!("data_gov_pagination_{}_chain", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let client = api::create_http_client(&toml::from_str::<HttpSettings>("").unwrap()).unwrap();
+        let pagination: Pagination = toml::from_str("type = \"cursor\"\nnext_path = \"paging.next\"").unwrap();
+        let group: EndpointGroup = toml::from_str(r#"root_path = "resultado""#).unwrap();
+        let collector = Warnings::default();
+        let warnings = collector.endpoint("compras.itens.pedidos".to_string(), Stage::Staging);
+        let pb = RunProgress::new(1, true).download_bar();
+
+        let files = fetch_pages(
+            &client,
+            &format!("{}/itens", base),
+            &Credentials::default(),
+            &pagination,
+            &group,
+            &dir.join("pedidos_temp.json"),
+            &pb,
+            &warnings,
+        )
+        .unwrap();
+
+        let lines: Vec<String> = server.join().unwrap().into_iter().map(|r| r.line).collect();
+        assert_eq!(lines, ["GET /itens HTTP/1.1", "GET /itens?pagina=2 HTTP/1.1", "GET /itens?pagina=3 HTTP/1.1"]);
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[2], dir.join("pedidos_temp.p0003.json"));
+        // O envelope gravado guarda só os registros: o cursor não vira coluna
+        let last: Value = serde_json::from_slice(&fs::read(&files[2]).unwrap()).unwrap();
+        assert_eq!(last, serde_json::json!({"resultado": [{"id": 4}]}));
+        assert!(collector.take().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,2711 @@
+//! # Orquestração do Pipeline
+//!
+//! Percorre APIs, grupos e endpoints, encadeando as etapas de
+//! staging (download/cópia) e transformação (Parquet).
+//!
+//! ## Fases
+//! 1. **Planejamento**: resolve URLs e expande globs em uma lista de `Job`s,
+//!    conhecendo o total de endpoints antes de iniciar (base do ETA).
+//! 2. **Execução**: processa cada `Job`, atualizando o progresso global.
+//!
+//! ## Paralelismo
+//! Staging (rede) e conversão (CPU) rodam em pools separados
+//! (`[pipeline] download_concurrency` e `transform_concurrency`, padrão 1),
+//! ligados por uma fila limitada: com os conversores atrasados, os downloads
+//! param de avançar em vez de acumular arquivos de staging. A thread principal
+//! só despacha um job quando suas dependências terminaram.
+//!
+//! ## Grupos Desativados
+//! Grupos com `enabled = false` continuam planejados (referências em
+//! `depends_on` seguem válidas), mas não executam: ficam como `disabled` no
+//! manifesto e no resumo, nunca como falha. Seus dependentes executam.
+//!
+//! ## Dependências
+//! `depends_on` reordena os jobs topologicamente (ciclos são erro fatal).
+//! Dependentes de um endpoint que falhou são ignorados no modo Fail-Soft.
+//!
+//! ## Seleção por Tags
+//! `--tag` mantém apenas os endpoints com as tags indicadas (`tags` do grupo);
+//! filtros repetidos somam seleções e `a,b` exige as duas tags.
+//!
+//! ## Prévia
+//! `--preview` planeja os mesmos jobs, mas apenas emite um HEAD por URL
+//! (ou lê o tamanho do arquivo local), estimando o volume a baixar sem
+//! gravar nada em disco.
+//!
+//! ## Amostragem
+//! Com `--sample N`, cada endpoint grava apenas as primeiras N linhas em
+//! `{chave}.sample.{formato}`, nunca sobrescrevendo a saída completa.
+//!
+//! ## Saída no Stdout
+//! Com `--output -`, o único endpoint selecionado é escrito no stdout
+//! (`--format`, padrão NDJSON) em vez de `data/`; a checagem de `on_exists`,
+//! o `post_compress` e o hash do manifesto não se aplicam.
+//!
+//! ## Saídas Existentes
+//! `on_exists` decide o que fazer quando o arquivo final já existe: sobrescrever
+//! (padrão), falhar, ignorar o endpoint ou gravar `{chave}_{timestamp}.{formato}`.
+//! A checagem ocorre antes do staging, evitando downloads desnecessários.
+//!
+//! ## Marcadores `_SUCCESS`
+//! Com `write_success_marker`, o `_SUCCESS` do diretório do grupo é removido
+//! no início da execução e regravado só depois que todos os endpoints
+//! selecionados do grupo terminam com sucesso (após o rename das saídas).
+//! Uma falha ou um abort deixam o diretório sem marcador; `--sample` e
+//! `--output -` não gravam marcadores.
+//!
+//! ## Modos de Falha
+//! - **Fail-Soft (padrão)**: o erro é logado e o pipeline avança.
+//! - **Strict (`--strict`)**: o primeiro erro de endpoint aborta a execução,
+//!   propagando-o como causa de saída do processo. Endpoints em andamento
+//!   terminam e são registrados; os ainda na fila são descartados.
+
+
+use chrono::Utc;
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TryRecvError};
+use std::sync::{Condvar, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::api::{self, Credentials, HttpClient};
+use crate::archive;
+use crate::dead_letter;
+use crate::errors::{ApiError, ErrorContext, ProcessorError, Stage};
+use crate::explain;
+use crate::extract;
+use crate::json_schema;
+use crate::manifest::{self, EndpointRecord, Manifest, Status, Timings, VariantRecord};
+use crate::memory::{self, MemoryLimit, PeakMonitor};
+use crate::models::{BasicAuth, Compression, Config, EndpointGroup, OnExists, OutputFormat};
+use crate::open_files;
+use crate::processor::{self, ConvertOptions, ConvertStats, StageTimings, StreamFormat};
+use crate::profile;
+use crate::progress::RunProgress;
+use crate::sources::{self, Source};
+use crate::warnings::{self, Warnings};
+
+/// Formato do timestamp anexado às saídas com `on_exists = "timestamped"`.
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Marcador de saída completa (`write_success_marker`), no padrão do Hadoop/Spark.
+pub const SUCCESS_MARKER: &str = "_SUCCESS";
+
+/// Opções de execução vindas da linha de comando.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Aborta no primeiro endpoint com falha.
+    pub strict: bool,
+
+    /// Suprime as barras de progresso e o resumo dinâmico.
+    pub quiet: bool,
+
+    /// Teto de RSS (MB) por conversão; excedê-lo falha apenas o endpoint.
+    pub max_memory_mb: Option<u64>,
+
+    /// Identificador desta execução, compartilhado por manifesto e colunas `run_id_column`.
+    pub run_id: String,
+
+    /// Caminho do manifesto JSON; se None, nenhum manifesto é gravado.
+    pub manifest_path: Option<PathBuf>,
+
+    /// Caminho do CSV de saídas (uma linha por endpoint); se None, não é gravado.
+    pub outputs_csv: Option<PathBuf>,
+
+    /// Grava manifesto e CSV parciais a cada N endpoints encerrados; se None,
+    /// apenas ao final.
+    pub manifest_every: Option<usize>,
+
+    /// Limite de linhas por endpoint (modo amostra); se None, grava tudo.
+    pub sample: Option<usize>,
+
+    /// Grava o contrato JSON Schema de cada saída.
+    pub emit_json_schema: bool,
+
+    /// Apenas consulta tamanhos (HEAD), sem baixar nem converter.
+    pub preview: bool,
+
+    /// Apenas lista as etapas de transformação, sem rede nem disco.
+    pub explain: bool,
+
+    /// Registra no resumo final o tempo de cada etapa por endpoint.
+    pub timings: bool,
+
+    /// Filtros de `--tag`; vazio executa todos os endpoints.
+    pub tags: Vec<String>,
+
+    /// Escreve o único endpoint selecionado no stdout (`--output -`).
+    pub stdout: Option<StreamFormat>,
+}
+
+/// Unidade de trabalho: um endpoint pronto para staging e conversão.
+#[derive(Clone)]
+struct Job<'a> {
+    api_name: &'a str,
+    group_name: &'a str,
+    group: &'a EndpointGroup,
+    key: String,
+    source: Source,
+    group_dir: PathBuf,
+    /// Identificadores completos (`api.grupo.chave`) que precisam rodar antes.
+    depends_on: Vec<String>,
+    /// Tags do endpoint, usadas por `--tag`.
+    tags: Vec<String>,
+    /// Grava apenas uma amostra (nome de saída marcado com `.sample`).
+    sample: bool,
+    /// Grava `{saída}.jsonschema.json` após a conversão.
+    emit_json_schema: bool,
+    /// Timestamp anexado ao nome da saída (`on_exists = "timestamped"`).
+    stamp: Option<String>,
+    /// Diretório de dados, base do `output_template`.
+    data_root: PathBuf,
+    /// Data (UTC) e identificador da execução, para `{date}` e `{run_id}`.
+    run_date: String,
+    run_id: String,
+}
+
+impl Job<'_> {
+    /// Identificador completo `api.grupo.chave`.
+    fn id(&self) -> String {
+        format!("{}.{}.{}", self.api_name, self.group_name, self.key)
+    }
+
+    /// Caminho do arquivo final (`{chave}[_{timestamp}][.sample].{formato}`),
+    /// ou o `output_template` com o timestamp e a amostra antes da extensão.
+    fn output_path(&self) -> PathBuf {
+        let stamp = self.stamp.as_deref().map(|s| format!("_{}", s)).unwrap_or_default();
+        let marker = if self.sample { ".sample" } else { "" };
+        let extension = self.group.output_format.extension();
+        let Some(template) = &self.group.output_template else {
+            return self.group_dir.join(format!("{}{}{}.{}", self.key, stamp, marker, extension));
+        };
+
+        let rendered = self
+            .template_values()
+            .iter()
+            .fold(template.clone(), |rendered, (placeholder, value)| rendered.replace(placeholder, value));
+        let dotted = format!(".{}", extension);
+        let name = match rendered.strip_suffix(&dotted) {
+            Some(base) => format!("{}{}{}{}", base, stamp, marker, dotted),
+            None => format!("{}{}{}", rendered, stamp, marker),
+        };
+        self.data_root.join(name)
+    }
+
+    /// Valores dos placeholders de `output_template`.
+    fn template_values(&self) -> [(&'static str, &str); 6] {
+        [
+            ("{api}", self.api_name),
+            ("{group}", self.group_name),
+            ("{key}", &self.key),
+            ("{date}", &self.run_date),
+            ("{run_id}", &self.run_id),
+            ("{ext}", self.group.output_format.extension()),
+        ]
+    }
+
+    /// Confere que os valores do `output_template` (chaves vindas de nomes de
+    /// arquivo, `--run-id`...) não levam a saída para fora do diretório de dados.
+    fn check_template(&self) -> Result<(), String> {
+        if self.group.output_template.is_none() {
+            return Ok(());
+        }
+        for (placeholder, value) in self.template_values() {
+            if value.contains(['/', '\\']) || value == "." || value == ".." {
+                return Err(format!(
+                    "'{}': valor '{}' de {} em output_template não pode conter separadores nem ser '.' ou '..'",
+                    self.id(),
+                    value,
+                    placeholder
+                ));
+            }
+        }
+        let path = self.output_path();
+        let relative = path.strip_prefix(&self.data_root).unwrap_or(&path);
+        if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(format!(
+                "'{}': output_template resulta em '{}', fora do diretório de dados",
+                self.id(),
+                path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Cria o diretório do arquivo final (subdiretórios do `output_template`).
+    fn create_output_dir(&self) -> io::Result<()> {
+        match self.output_path().parent() {
+            Some(parent) => fs::create_dir_all(parent),
+            None => Ok(()),
+        }
+    }
+
+    /// Saída `{chave}_{nome}` de uma entrada de `extract`, com a mesma origem.
+    fn extracted(&self, name: &str) -> Self {
+        Self {
+            key: format!("{}_{}", self.key, name),
+            ..self.clone()
+        }
+    }
+
+    /// Saídas do endpoint: uma por entrada de `extract` ou ele mesmo.
+    fn outputs(&self) -> Vec<Self> {
+        if self.group.extract.is_empty() {
+            return vec![self.clone()];
+        }
+        self.group.extract.keys().map(|name| self.extracted(name)).collect()
+    }
+
+    /// Envolve o erro de uma etapa com o contexto deste endpoint.
+    fn fail<E>(&self, stage: Stage, input: impl fmt::Display, source: E) -> ProcessorError
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let context = ErrorContext {
+            endpoint: self.id(),
+            stage,
+            input: input.to_string(),
+        };
+        ProcessorError::with_context(source, context)
+    }
+
+    /// Caminho do artefato entregue (com a extensão de `post_compress`, se
+    /// houver); com variantes de compressão, o do primeiro codec.
+    fn artifact_path(&self) -> PathBuf {
+        match (self.group.post_compress, self.group.compression_variants()) {
+            (Some(codec), _) => archive::compressed_path(&self.output_path(), codec),
+            (None, [first, ..]) => processor::variant_path(&self.output_path(), *first),
+            (None, []) => self.output_path(),
+        }
+    }
+
+    /// Arquivos entregues: um por variante de compressão ou o próprio artefato.
+    fn artifact_paths(&self) -> Vec<PathBuf> {
+        match self.group.compression_variants() {
+            [] => vec![self.artifact_path()],
+            codecs => codecs
+                .iter()
+                .map(|codec| processor::variant_path(&self.output_path(), *codec))
+                .collect(),
+        }
+    }
+}
+
+/// Arquivos de staging de um endpoint, a caminho dos conversores.
+struct Staged {
+    index: usize,
+    inputs: Vec<PathBuf>,
+    /// Tempo gasto no staging (conexão e download).
+    timings: StageTimings,
+}
+
+/// Endpoint encerrado por um worker (com sucesso ou falha em qualquer etapa).
+struct Finished {
+    index: usize,
+    outcome: Result<Vec<Converted>, ProcessorError>,
+    elapsed: Duration,
+}
+
+/// Saída de um endpoint (uma por entrada de `extract`), convertida ou não.
+struct Converted {
+    /// Identificador `api.grupo.chave` da saída.
+    endpoint: String,
+    /// Arquivo entregue (com a extensão de `post_compress`, se houver).
+    artifact: PathBuf,
+    outcome: Result<ConvertStats, ProcessorError>,
+}
+
+/// Contexto compartilhado pelos workers de download e de conversão.
+#[derive(Clone, Copy)]
+struct Workers<'s, 'a> {
+    jobs: &'s [Job<'a>],
+    client: &'s HttpClient,
+    progress: &'s RunProgress,
+    convert: ConvertOptions<'s>,
+    warnings: &'s Warnings,
+    /// Ligado no abort: itens ainda na fila são descartados.
+    stop: &'s AtomicBool,
+    /// Vagas por origem (`max_per_host`); None sem limite.
+    hosts: Option<&'s HostLimits>,
+    /// Destino do staging de conversões que falharam (`dead_letter_dir`).
+    dead_letter: Option<&'s Path>,
+}
+
+impl Workers<'_, '_> {
+    /// Faz o staging dos jobs da fila e entrega os arquivos aos conversores.
+    fn download(self, queue: &Mutex<Receiver<usize>>, staged: SyncSender<Staged>, done: Sender<Finished>) {
+        let next = || match self.hosts {
+            Some(hosts) => hosts.next(queue),
+            None => receive(queue),
+        };
+        while let Some(index) = next() {
+            if self.stop.load(Ordering::Relaxed) {
+                if let Some(hosts) = self.hosts {
+                    hosts.release(index);
+                }
+                continue;
+            }
+            let timer = Instant::now();
+            let outcome = stage_job(&self.jobs[index], self.client, self.progress, self.warnings);
+            let elapsed = timer.elapsed();
+            // A vaga da origem é do download, não da conversão
+            if let Some(hosts) = self.hosts {
+                hosts.release(index);
+            }
+            if profile::enabled() {
+                let stages = match &outcome {
+                    Ok((_, connect)) => vec![("connect", *connect), ("download", elapsed.saturating_sub(*connect))],
+                    Err(_) => Vec::new(),
+                };
+                profile::record("staging", Some(&self.jobs[index].id()), timer, elapsed, &stages);
+            }
+            let sent = match outcome {
+                // Bloqueia enquanto a fila estiver cheia (conversores atrasados)
+                Ok((inputs, connect)) => {
+                    let timings = StageTimings {
+                        connect,
+                        download: elapsed.saturating_sub(connect),
+                        ..Default::default()
+                    };
+                    staged.send(Staged { index, inputs, timings }).is_ok()
+                }
+                Err(e) => done
+                    .send(Finished { index, outcome: Err(e), elapsed })
+                    .is_ok(),
+            };
+            if !sent {
+                break;
+            }
+        }
+    }
+
+    /// Converte os endpoints já baixados, na ordem em que chegam.
+    fn transform(self, staged: &Mutex<Receiver<Staged>>, done: Sender<Finished>) {
+        while let Some(Staged { index, inputs, timings }) = receive(staged) {
+            // Descartado no abort: o staging não será convertido
+            if self.stop.load(Ordering::Relaxed) {
+                for path in &inputs {
+                    let _ = fs::remove_file(path);
+                }
+                continue;
+            }
+            let timer = Instant::now();
+            let outcome = convert_job(
+                &self.jobs[index],
+                &inputs,
+                self.convert,
+                timings,
+                self.warnings,
+                self.dead_letter,
+            );
+            if profile::enabled() {
+                // Uma sequência de etapas por saída convertida (várias com `extract`)
+                let stages: Vec<_> = outcome
+                    .iter()
+                    .flatten()
+                    .filter_map(|converted| converted.outcome.as_ref().ok())
+                    .flat_map(|stats| {
+                        let t = stats.timings;
+                        [("parse", t.parse), ("transform", t.transform), ("write", t.write)]
+                    })
+                    .collect();
+                profile::record("convert", Some(&self.jobs[index].id()), timer, timer.elapsed(), &stages);
+            }
+            let elapsed = timings.total() + timer.elapsed();
+            if done.send(Finished { index, outcome, elapsed }).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+/// Intervalo entre novas consultas à fila enquanto há jobs adiados.
+const DEFERRED_POLL: Duration = Duration::from_millis(50);
+
+/// Semáforos por origem (`[pipeline] max_per_host`).
+///
+/// Um job cuja origem já tem `max` downloads em andamento fica adiado, e o
+/// worker segue para o próximo da fila: uma origem saturada não bloqueia as
+/// demais. Arquivos locais não ocupam vagas.
+struct HostLimits {
+    max: usize,
+    /// Origem (`esquema://host:porta`) de cada job.
+    origins: Vec<Option<String>>,
+    state: Mutex<HostState>,
+    released: Condvar,
+}
+
+#[derive(Default)]
+struct HostState {
+    /// Downloads em andamento por origem.
+    active: HashMap<String, usize>,
+    /// Jobs retirados da fila à espera de vaga, na ordem de chegada.
+    deferred: VecDeque<usize>,
+}
+
+impl HostLimits {
+    fn new(max: usize, jobs: &[Job]) -> Self {
+        let origins = jobs
+            .iter()
+            .map(|job| match &job.source {
+                Source::Http { url, .. } => api::origin(url),
+                Source::File(_) => None,
+            })
+            .collect();
+        Self {
+            max,
+            origins,
+            state: Mutex::default(),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Próximo job com vaga na sua origem, já ocupando-a; None quando a fila
+    /// encerrou e não há jobs adiados.
+    fn next(&self, queue: &Mutex<Receiver<usize>>) -> Option<usize> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(position) = state.deferred.iter().position(|&index| self.has_room(&state, index)) {
+                let index = state.deferred.remove(position)?;
+                self.occupy(&mut state, index);
+                return Some(index);
+            }
+            // Fila ocupada por um worker bloqueado em `recv`: nada novo por ora
+            let received = match queue.try_lock() {
+                Ok(queue) => queue.try_recv(),
+                Err(_) => Err(TryRecvError::Empty),
+            };
+            match received {
+                Ok(index) if self.has_room(&state, index) => {
+                    self.occupy(&mut state, index);
+                    return Some(index);
+                }
+                Ok(index) => state.deferred.push_back(index),
+                Err(TryRecvError::Empty) if state.deferred.is_empty() => {
+                    // Sem adiados, espera a fila sem reter o estado
+                    drop(state);
+                    let index = receive(queue)?;
+                    state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+                    state.deferred.push_back(index);
+                }
+                Err(TryRecvError::Disconnected) if state.deferred.is_empty() => return None,
+                Err(_) => {
+                    state = self
+                        .released
+                        .wait_timeout(state, DEFERRED_POLL)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0;
+                }
+            }
+        }
+    }
+
+    /// Libera a vaga ocupada pelo job (se a sua origem tiver uma).
+    fn release(&self, index: usize) {
+        let Some(origin) = &self.origins[index] else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(active) = state.active.get_mut(origin) {
+            *active = active.saturating_sub(1);
+        }
+        drop(state);
+        self.released.notify_all();
+    }
+
+    fn has_room(&self, state: &HostState, index: usize) -> bool {
+        self.origins[index]
+            .as_ref()
+            .is_none_or(|origin| state.active.get(origin).copied().unwrap_or(0) < self.max)
+    }
+
+    fn occupy(&self, state: &mut HostState, index: usize) {
+        if let Some(origin) = &self.origins[index] {
+            *state.active.entry(origin.clone()).or_default() += 1;
+        }
+    }
+}
+
+/// Próximo item de uma fila compartilhada; None quando os produtores encerraram.
+fn receive<T>(queue: &Mutex<Receiver<T>>) -> Option<T> {
+    queue.lock().unwrap_or_else(PoisonError::into_inner).recv().ok()
+}
+
+/// Despacha os jobs cujas dependências terminaram e registra os resultados.
+///
+/// Roda na thread principal: decisões que não exigem trabalho (desativado,
+/// dependência falha, `on_exists`) são registradas aqui, sem passar pelos workers.
+struct Scheduler<'s, 'a> {
+    jobs: &'s [Job<'a>],
+    options: &'s RunOptions,
+    progress: &'s RunProgress,
+    manifest: &'s mut Manifest,
+    warnings: &'s Warnings,
+    digest: bool,
+    dispatched: Vec<bool>,
+    /// Jobs encerrados (qualquer status) e, entre eles, os que falharam.
+    finished: HashSet<String>,
+    failed: HashSet<String>,
+    /// Jobs encerrados desde a última gravação parcial do manifesto.
+    unsaved: usize,
+    /// Registros dos jobs em andamento nos workers.
+    running: HashMap<usize, EndpointRecord>,
+    /// Jobs pendentes por diretório de grupo com `write_success_marker`;
+    /// a primeira falha retira o diretório (sem marcador nesta execução).
+    markers: HashMap<PathBuf, usize>,
+    current_scope: (&'a str, &'a str),
+}
+
+impl<'s, 'a> Scheduler<'s, 'a> {
+    fn new(
+        jobs: &'s [Job<'a>],
+        options: &'s RunOptions,
+        progress: &'s RunProgress,
+        manifest: &'s mut Manifest,
+        warnings: &'s Warnings,
+        digest: bool,
+    ) -> Self {
+        // Amostras e o stdout não entregam a saída completa
+        let mut markers = HashMap::new();
+        if options.stdout.is_none() && options.sample.is_none() {
+            for job in jobs.iter().filter(|job| job.group.enabled && job.group.write_success_marker) {
+                *markers.entry(job.group_dir.clone()).or_default() += 1;
+            }
+        }
+        Self {
+            jobs,
+            options,
+            progress,
+            manifest,
+            warnings,
+            digest,
+            dispatched: vec![false; jobs.len()],
+            finished: HashSet::new(),
+            failed: HashSet::new(),
+            unsaved: 0,
+            running: HashMap::new(),
+            markers,
+            current_scope: ("", ""),
+        }
+    }
+
+    /// Remove os marcadores de execuções anteriores antes de qualquer escrita.
+    fn clear_markers(&self) {
+        let tables = self
+            .jobs
+            .iter()
+            .filter(|job| self.markers.contains_key(&job.group_dir) && job.group.output_format == OutputFormat::Delta)
+            .flat_map(Job::outputs)
+            .map(|output| output.artifact_path());
+        for dir in self.markers.keys().cloned().chain(tables) {
+            match fs::remove_file(dir.join(SUCCESS_MARKER)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => self.warnings.warn(format!(
+                    "Aviso: marcador '{}' não removido: {}",
+                    dir.join(SUCCESS_MARKER).display(),
+                    e
+                )),
+                _ => {}
+            }
+        }
+    }
+
+    /// Grava o `_SUCCESS` vazio em `dir`; uma falha vira aviso.
+    fn write_marker(&self, dir: &Path) {
+        let path = dir.join(SUCCESS_MARKER);
+        match fs::File::create(&path) {
+            Ok(_) => log::info!("Marcador: {}", path.display()),
+            Err(e) => self
+                .warnings
+                .warn(format!("Aviso: marcador '{}' não gravado: {}", path.display(), e)),
+        }
+    }
+
+    /// Executa até não restar job pendente.
+    ///
+    /// # Erros
+    /// Em modo strict, o erro do primeiro endpoint que falhar.
+    fn run(&mut self, queue: &Sender<usize>, done: &Receiver<Finished>) -> Result<(), ProcessorError> {
+        self.clear_markers();
+        loop {
+            self.dispatch_ready(queue)?;
+            if self.running.is_empty() {
+                return Ok(());
+            }
+            let Ok(finished) = done.recv() else {
+                return Ok(());
+            };
+            if let Err(e) = self.complete(finished)
+                && self.options.strict
+            {
+                return Err(e);
+            }
+        }
+    }
+
+    /// Envia à fila de download os jobs com todas as dependências encerradas.
+    ///
+    /// Os jobs estão em ordem topológica: uma passada libera também os
+    /// dependentes de jobs resolvidos nela mesma (ex: desativados).
+    fn dispatch_ready(&mut self, queue: &Sender<usize>) -> Result<(), ProcessorError> {
+        let jobs = self.jobs;
+        for (index, job) in jobs.iter().enumerate() {
+            if self.dispatched[index] || !job.depends_on.iter().all(|d| self.finished.contains(d)) {
+                continue;
+            }
+            self.dispatched[index] = true;
+
+            if self.current_scope.0 != job.api_name {
+                log::info!("\n Domínio: {}", job.api_name.to_uppercase());
+            }
+            if self.current_scope != (job.api_name, job.group_name) {
+                log::info!("Grupo: {}", job.group_name);
+            }
+            self.current_scope = (job.api_name, job.group_name);
+
+            let mut record = EndpointRecord {
+                endpoint: job.id(),
+                tags: job.tags.clone(),
+                status: Status::Success,
+                path: None,
+                format: self
+                    .options
+                    .stdout
+                    .map_or(job.group.output_format.extension(), |f| f.name()),
+                rows: None,
+                cols: None,
+                declared_rows: None,
+                coercions: Default::default(),
+                cardinality: Default::default(),
+                variants: Vec::new(),
+                bytes: None,
+                sha256: None,
+                duration_ms: 0,
+                timings: None,
+                error: None,
+            };
+
+            if !job.group.enabled {
+                log::info!("Desativado: {} (enabled = false)", job.key);
+                record.status = Status::Disabled;
+                self.settle(job, record, true);
+                continue;
+            }
+
+            if let Some(dep) = job.depends_on.iter().find(|d| self.failed.contains(*d)) {
+                self.warnings
+                    .endpoint(job.id(), Stage::Staging)
+                    .warn(format!("Ignorado: {} (dependência '{}' falhou)", job.key, dep));
+                record.status = Status::Skipped;
+                record.error = Some(format!("dependência '{}' falhou", dep));
+                self.settle(job, record, false);
+                continue;
+            }
+
+            // Com timestamp, o caminho já é o novo; `overwrite` segue para a conversão
+            let outputs = job.outputs();
+            let artifacts: Vec<PathBuf> = outputs.iter().flat_map(Job::artifact_paths).collect();
+            let existing: Vec<&PathBuf> = artifacts.iter().filter(|path| path.exists()).collect();
+            if self.options.stdout.is_none() && !existing.is_empty() {
+                match on_exists_action(job.group.on_exists, existing.len(), artifacts.len()) {
+                    ExistingAction::Proceed => {}
+                    ExistingAction::Skip => {
+                        for output in &outputs {
+                            let path = output.artifact_path();
+                            log::info!("Ignorado: {} ('{}' já existe)", output.key, path.display());
+                            let mut record = record.clone();
+                            record.endpoint = output.id();
+                            record.status = Status::Skipped;
+                            record.path = Some(path.display().to_string());
+                            record.error = Some("saída já existe (on_exists = \"skip\")".to_string());
+                            self.manifest.record(record);
+                        }
+                        self.finish(job, true);
+                        continue;
+                    }
+                    ExistingAction::Fail => {
+                        let e = io::Error::new(
+                            io::ErrorKind::AlreadyExists,
+                            "saída já existe (on_exists = \"error\")",
+                        );
+                        let err = job.fail(Stage::Conversion, existing[0].display(), ProcessorError::Io(e));
+                        log::error!("Falha: {}", err);
+                        record.status = Status::Failed;
+                        record.error = Some(err.to_string());
+                        self.settle(job, record, false);
+                        if self.options.strict {
+                            return Err(err);
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            // O receptor só fecha após o fim do agendamento
+            let _ = queue.send(index);
+            self.running.insert(index, record);
+        }
+        Ok(())
+    }
+
+    /// Registra um endpoint devolvido pelos workers.
+    ///
+    /// # Erros
+    /// O erro do endpoint, para o abort do modo strict.
+    fn complete(&mut self, finished: Finished) -> Result<(), ProcessorError> {
+        let Finished { index, outcome, elapsed } = finished;
+        let job = &self.jobs[index];
+        let Some(mut record) = self.running.remove(&index) else {
+            return Ok(());
+        };
+        record.duration_ms = elapsed.as_millis() as u64;
+
+        match outcome {
+            // Com `extract`, cada saída tem o próprio registro no manifesto
+            Ok(outputs) => {
+                let mut failure = None;
+                for output in outputs {
+                    // Tabelas Delta são diretórios: cada uma ganha o próprio marcador
+                    if job.group.write_success_marker
+                        && self.options.stdout.is_none()
+                        && self.options.sample.is_none()
+                        && job.group.output_format == OutputFormat::Delta
+                        && output.outcome.as_ref().is_ok_and(|stats| stats.skipped.is_none())
+                    {
+                        self.write_marker(&output.artifact);
+                    }
+                    let (record, error) = self.output_record(job, record.clone(), output);
+                    self.manifest.record(record);
+                    failure = failure.or(error);
+                }
+                self.finish(job, failure.is_none());
+                failure.map_or(Ok(()), Err)
+            }
+            Err(e) => {
+                record.status = Status::Failed;
+                record.error = Some(e.to_string());
+                self.settle(job, record, false);
+                Err(e)
+            }
+        }
+    }
+
+    /// Registro no manifesto de uma saída e o erro dela, se houver.
+    fn output_record(
+        &self,
+        job: &Job,
+        mut record: EndpointRecord,
+        output: Converted,
+    ) -> (EndpointRecord, Option<ProcessorError>) {
+        let Converted { endpoint, artifact, outcome } = output;
+        record.endpoint = endpoint;
+
+        let stats = match outcome {
+            Ok(stats) => stats,
+            Err(e) => {
+                record.status = Status::Failed;
+                record.error = Some(e.to_string());
+                return (record, Some(e));
+            }
+        };
+
+        // Resposta vazia sem schema (`write_empty`): nada foi gravado
+        if stats.skipped.is_some() {
+            record.status = Status::Skipped;
+            record.rows = Some(0);
+            record.error = stats.skipped;
+            return (record, None);
+        }
+
+        let artifact = match self.options.stdout {
+            Some(_) => PathBuf::from("-"),
+            None => artifact,
+        };
+        // Tabelas Delta são diretórios: sem hash de arquivo único
+        if self.digest && job.group.output_format != OutputFormat::Delta {
+            match manifest::file_digest(&artifact) {
+                Ok((bytes, sha256)) => {
+                    record.bytes = Some(bytes);
+                    record.sha256 = Some(sha256);
+                }
+                Err(e) => self.warnings.endpoint(record.endpoint.clone(), Stage::Conversion).warn(format!(
+                    "Aviso: hash de '{}' indisponível: {}",
+                    artifact.display(),
+                    e
+                )),
+            }
+        }
+        record.path = Some(artifact.display().to_string());
+        record.rows = Some(stats.rows);
+        record.cols = Some(stats.cols);
+        record.coercions = stats.coercions;
+        record.cardinality = stats.cardinality;
+        record.variants = stats
+            .variants
+            .into_iter()
+            .map(|(codec, path)| self.variant_record(&record.endpoint, codec, path))
+            .collect();
+        record.declared_rows = stats.reconciliation.and_then(|r| r.declared);
+        record.timings = Some(stats.timings.into());
+        (record, None)
+    }
+
+    /// Registro de uma variante de compressão, com tamanho e hash se calculados.
+    fn variant_record(&self, endpoint: &str, codec: Compression, path: PathBuf) -> VariantRecord {
+        let digest = match self.digest.then(|| manifest::file_digest(&path)) {
+            Some(Ok(digest)) => Some(digest),
+            Some(Err(e)) => {
+                self.warnings.endpoint(endpoint.to_string(), Stage::Conversion).warn(format!(
+                    "Aviso: hash de '{}' indisponível: {}",
+                    path.display(),
+                    e
+                ));
+                None
+            }
+            None => None,
+        };
+        VariantRecord {
+            compression: codec.name(),
+            path: path.display().to_string(),
+            bytes: digest.as_ref().map(|(bytes, _)| *bytes),
+            sha256: digest.map(|(_, sha256)| sha256),
+        }
+    }
+
+    /// Encerra o job: progresso, manifesto e liberação dos dependentes.
+    fn settle(&mut self, job: &Job, record: EndpointRecord, success: bool) {
+        self.manifest.record(record);
+        self.finish(job, success);
+    }
+
+    /// Progresso e liberação dos dependentes (registros já no manifesto).
+    fn finish(&mut self, job: &Job, success: bool) {
+        self.progress.record(success);
+        if !success {
+            self.failed.insert(job.id());
+        }
+        self.finished.insert(job.id());
+
+        if let Some(pending) = self.markers.get_mut(&job.group_dir) {
+            *pending -= 1;
+            if !success {
+                self.markers.remove(&job.group_dir);
+            } else if *pending == 0 {
+                self.markers.remove(&job.group_dir);
+                self.write_marker(&job.group_dir);
+            }
+        }
+
+        self.unsaved += 1;
+        if self.options.manifest_every.is_some_and(|every| self.unsaved >= every) {
+            self.unsaved = 0;
+            self.checkpoint();
+        }
+    }
+
+    /// Grava manifesto e CSV parciais; falhas viram avisos (a gravação final
+    /// ainda será tentada).
+    fn checkpoint(&self) {
+        let warn = |label: &str, path: &Path, e: ProcessorError| {
+            self.warnings
+                .warn(format!("Aviso: {} parcial não gravado em '{}': {}", label, path.display(), e));
+        };
+        if let Some(path) = &self.options.manifest_path
+            && let Err(e) = self.manifest.checkpoint(path)
+        {
+            warn("manifesto", path, e);
+        }
+        if let Some(path) = &self.options.outputs_csv
+            && let Err(e) = self.manifest.write_csv(path)
+        {
+            warn("CSV de saídas", path, e);
+        }
+    }
+}
+
+/// Executa todos os endpoints da configuração.
+///
+/// # Erros
+/// Em modo strict, retorna o erro do primeiro endpoint que falhar (o
+/// manifesto, se habilitado, é gravado antes).
+/// Erros de criação de diretórios e ciclos de dependência são sempre fatais.
+pub fn run_pipeline(
+    config: &Config,
+    client: &HttpClient,
+    data_root: &Path,
+    options: &RunOptions,
+) -> Result<(), Box<dyn Error>> {
+    let mut jobs = order_jobs(select_by_tags(plan_jobs(config, data_root, options)?, &options.tags))?;
+    if options.stdout.is_some() {
+        let enabled = jobs.iter().filter(|job| job.group.enabled).count();
+        if enabled != 1 {
+            return Err(format!(
+                "--output - exige exatamente um endpoint selecionado ({} selecionado(s)); use --tag",
+                enabled
+            )
+            .into());
+        }
+        if jobs.iter().any(|job| job.group.enabled && !job.group.extract.is_empty()) {
+            return Err("--output - não combina com extract (várias saídas)".into());
+        }
+        if jobs
+            .iter()
+            .any(|job| job.group.enabled && !job.group.compression_variants().is_empty())
+        {
+            return Err("--output - não combina com uma lista em compression (várias saídas)".into());
+        }
+    }
+    let progress = RunProgress::new(jobs.len(), options.quiet);
+
+    let convert = ConvertOptions {
+        limit: options.max_memory_mb.map(MemoryLimit::from_mb),
+        run_id: &options.run_id,
+        sample: options.sample,
+        stdout: options.stdout,
+        write_retry: config.pipeline.write_retry(),
+        stream_above: config.pipeline.stream_above(),
+        warnings: None,
+        origin: None,
+    };
+    let warnings = Warnings::default();
+    let mut manifest = Manifest::new(&options.run_id, options.sample);
+    let stamp = manifest.started_at.format(TIMESTAMP_FORMAT).to_string();
+    // Tamanho e hash só são calculados se algum manifesto for gravado
+    let digest = options.stdout.is_none()
+        && (options.manifest_path.is_some() || options.outputs_csv.is_some());
+
+    // Resolvido antes dos workers, que compartilham os jobs sem alterá-los
+    open_sessions(config, client, &mut jobs, options)?;
+    if options.stdout.is_none() {
+        for job in &mut jobs {
+            if job.group.on_exists == OnExists::Timestamped
+                && job.outputs().iter().any(|output| output.artifact_path().exists())
+            {
+                job.stamp = Some(stamp.clone());
+            }
+        }
+    }
+    let jobs = jobs;
+
+    if config.http.warm_up {
+        warm_up(client, &jobs, &warnings);
+    }
+
+    let downloads = config.pipeline.downloads().get();
+    let transforms = config.pipeline.transforms().get();
+    if downloads > 1 || transforms > 1 {
+        log::info!(
+            "Paralelismo: {} download(s) e {} conversão(ões) simultâneos",
+            downloads, transforms
+        );
+    }
+    let hosts = config.pipeline.max_per_host.map(|max| HostLimits::new(max.get(), &jobs));
+    open_files::configure(config.pipeline.max_open_files);
+    if let Some(max) = config.pipeline.max_per_host
+        && max.get() < downloads
+    {
+        log::info!("Limite por origem: {} download(s) simultâneo(s)", max);
+    }
+
+    let stop = AtomicBool::new(false);
+    let (queue_tx, queue_rx) = mpsc::channel::<usize>();
+    // Fila limitada: com os conversores ocupados, os downloads aguardam
+    let (staged_tx, staged_rx) = mpsc::sync_channel::<Staged>(transforms);
+    let (done_tx, done_rx) = mpsc::channel::<Finished>();
+    let (queue_rx, staged_rx) = (Mutex::new(queue_rx), Mutex::new(staged_rx));
+
+    let mut abort = None;
+    thread::scope(|scope| {
+        let workers = Workers {
+            jobs: &jobs,
+            client,
+            progress: &progress,
+            convert,
+            warnings: &warnings,
+            stop: &stop,
+            hosts: hosts.as_ref(),
+            dead_letter: config.pipeline.dead_letter_dir(),
+        };
+        let (queue_rx, staged_rx) = (&queue_rx, &staged_rx);
+        for _ in 0..downloads {
+            let (staged_tx, done_tx) = (staged_tx.clone(), done_tx.clone());
+            scope.spawn(move || workers.download(queue_rx, staged_tx, done_tx));
+        }
+        for _ in 0..transforms {
+            let done_tx = done_tx.clone();
+            scope.spawn(move || workers.transform(staged_rx, done_tx));
+        }
+        drop((staged_tx, done_tx));
+
+        let mut scheduler = Scheduler::new(&jobs, options, &progress, &mut manifest, &warnings, digest);
+        abort = scheduler.run(&queue_tx, &done_rx).err();
+
+        // No abort, a fila é descartada; os endpoints em andamento terminam e são registrados
+        if abort.is_some() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        drop(queue_tx);
+        for finished in done_rx.iter() {
+            let _ = scheduler.complete(finished);
+        }
+    });
+
+    progress.finish();
+    if progress.bytes_downloaded() > 0 {
+        log::info!("\nTotal baixado: {}", format_size(progress.bytes_downloaded()));
+    }
+
+    let mismatches = manifest.reconciliation_mismatches();
+    if mismatches > 0 {
+        log::warn!(
+            "Aviso: {} endpoint(s) com {} divergente das linhas convertidas",
+            mismatches,
+            processor::TOTAL_KEY
+        );
+    }
+
+    let disabled = manifest
+        .endpoints
+        .iter()
+        .filter(|r| r.status == Status::Disabled)
+        .count();
+    if disabled > 0 {
+        log::info!("Desativados: {} endpoint(s) ignorado(s) por enabled = false", disabled);
+    }
+
+    let tags = manifest.tag_summary();
+    if !tags.is_empty() {
+        let counts: Vec<String> = tags
+            .iter()
+            .map(|(tag, (succeeded, total))| format!("{} {}/{}", tag, succeeded, total))
+            .collect();
+        log::info!("Por tag (sucesso/total): {}", counts.join(", "));
+    }
+
+    if options.timings {
+        log_timings(&manifest.endpoints);
+    }
+
+    for record in manifest.endpoints.iter().filter(|r| !r.cardinality.is_empty()) {
+        let counts: Vec<String> = record
+            .cardinality
+            .iter()
+            .map(|(column, distinct)| format!("{} {}", column, distinct))
+            .collect();
+        log::info!("Cardinalidade: {}: {}", record.endpoint, counts.join(", "));
+    }
+
+    let (nulled, columns) = manifest.coercion_failures();
+    if nulled > 0 {
+        log::warn!(
+            "Aviso: coerções de tipo anularam {} valor(es) em {} coluna(s)",
+            nulled, columns
+        );
+    }
+
+    if let Some(rows) = options.sample {
+        warnings.warn(format!(
+            "Aviso: modo amostra (--sample {}); as saídas .sample não são dados completos",
+            rows
+        ));
+    }
+
+    manifest.warnings = warnings.take();
+    if !manifest.warnings.is_empty() {
+        let counts: Vec<String> = warnings::count_by_stage(&manifest.warnings)
+            .iter()
+            .map(|(stage, count)| format!("{}: {}", stage, count))
+            .collect();
+        log::info!("Avisos: {} ({})", manifest.warnings.len(), counts.join(", "));
+    }
+
+    if let Some(path) = &options.manifest_path {
+        manifest.write(path)?;
+        log::info!("Manifesto gravado: {}", path.display());
+    }
+    if let Some(path) = &options.outputs_csv {
+        manifest.write_csv(path)?;
+        log::info!("CSV de saídas gravado: {}", path.display());
+    }
+
+    match abort {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
+}
+
+/// Tempo por etapa de cada endpoint bem-sucedido (`--timings`), com a soma geral.
+///
+/// Rede (conexão e download) versus CPU (leitura, transformação e gravação)
+/// indica qual lado do paralelismo (`[pipeline]`) ajustar.
+fn log_timings(records: &[EndpointRecord]) {
+    let line = |t: &Timings| {
+        format!(
+            "conexão {}ms, download {}ms, leitura {}ms, transformação {}ms, gravação {}ms",
+            t.connect_ms, t.download_ms, t.parse_ms, t.transform_ms, t.write_ms
+        )
+    };
+
+    let mut sum = Timings::default();
+    for (endpoint, timings) in records.iter().filter_map(|r| Some((&r.endpoint, r.timings?))) {
+        log::info!("Tempos: {}: {}", endpoint, line(&timings));
+        sum.connect_ms += timings.connect_ms;
+        sum.download_ms += timings.download_ms;
+        sum.parse_ms += timings.parse_ms;
+        sum.transform_ms += timings.transform_ms;
+        sum.write_ms += timings.write_ms;
+    }
+
+    let network = sum.connect_ms + sum.download_ms;
+    let total = network + sum.parse_ms + sum.transform_ms + sum.write_ms;
+    if let Some(share) = (network * 100).checked_div(total) {
+        log::info!("Tempos somados: {}", line(&sum));
+        log::info!("Rede: {}% | CPU: {}%", share, 100 - share);
+    }
+}
+
+/// Pré-conecta uma vez a cada origem HTTP dos jobs (`[http] warm_up`).
+///
+/// As origens são aquecidas em paralelo, antes dos workers: o início de uma
+/// execução concorrente não dispara vários handshakes ao mesmo host. Falhas
+/// apenas geram aviso; o endpoint tenta conectar de novo normalmente.
+fn warm_up(client: &HttpClient, jobs: &[Job], warnings: &Warnings) {
+    let origins: BTreeSet<String> = jobs
+        .iter()
+        .filter(|job| job.group.enabled)
+        .filter_map(|job| match &job.source {
+            Source::Http { url, .. } => api::origin(url),
+            Source::File(_) => None,
+        })
+        .collect();
+    if origins.is_empty() {
+        return;
+    }
+
+    let timer = Instant::now();
+    let connected = thread::scope(|scope| {
+        let probes: Vec<_> = origins
+            .iter()
+            .map(|origin| scope.spawn(move || (origin, api::warm_up(client, origin))))
+            .collect();
+        probes
+            .into_iter()
+            .filter_map(|probe| probe.join().ok())
+            .filter(|(origin, result)| match result {
+                Ok(()) => true,
+                Err(e) => {
+                    warnings.warn(format!("Aviso: warm-up de '{}' falhou: {}", origin, e));
+                    false
+                }
+            })
+            .count()
+    });
+    log::info!(
+        "Warm-up: {} de {} host(s) conectado(s) ({:.2?})",
+        connected,
+        origins.len(),
+        timer.elapsed()
+    );
+}
+
+/// Lista tamanho, tipo e data de modificação de cada endpoint sem baixá-lo.
+///
+/// # Erros
+/// Apenas erros de planejamento; falhas de HEAD são reportadas por endpoint.
+pub fn run_preview(
+    config: &Config,
+    client: &HttpClient,
+    data_root: &Path,
+    options: &RunOptions,
+) -> Result<(), Box<dyn Error>> {
+    let mut jobs = select_by_tags(plan_jobs(config, data_root, options)?, &options.tags);
+    open_sessions(config, client, &mut jobs, options)?;
+    log::info!("Prévia de {} endpoint(s) (HEAD, sem download)", jobs.len());
+
+    let mut known_bytes = 0u64;
+    let mut unknown = 0usize;
+
+    for job in &jobs {
+        if !job.group.enabled {
+            log::info!("  {}: desativado", job.id());
+            continue;
+        }
+        let info = match &job.source {
+            Source::Http { url, auth, .. } => api::head(client, url, auth),
+            Source::File(path) => fs::metadata(path)
+                .map(|m| api::HeadInfo {
+                    content_length: Some(m.len()),
+                    ..Default::default()
+                })
+                .map_err(ApiError::FileSystemError),
+        };
+
+        match info {
+            Ok(info) => {
+                let size = match info.content_length {
+                    Some(bytes) => {
+                        known_bytes += bytes;
+                        format_size(bytes)
+                    }
+                    None => {
+                        unknown += 1;
+                        "tamanho desconhecido".to_string()
+                    }
+                };
+                let paged = if job.group.pagination.is_some() { " [1ª página]" } else { "" };
+                log::info!(
+                    "  {}: {}{} | {} | {}",
+                    job.id(),
+                    size,
+                    paged,
+                    info.content_type.as_deref().unwrap_or("-"),
+                    info.last_modified.as_deref().unwrap_or("-")
+                );
+            }
+            Err(e) if api::head_unsupported(&e) => {
+                unknown += 1;
+                log::info!("  {}: HEAD não suportado pelo servidor", job.id());
+            }
+            Err(e) => {
+                unknown += 1;
+                log::warn!("  {}: falha no HEAD: {}", job.id(), e);
+            }
+        }
+    }
+
+    log::info!(
+        "Total conhecido: {}; {} endpoint(s) sem tamanho",
+        format_size(known_bytes),
+        unknown
+    );
+    Ok(())
+}
+
+/// Lista as etapas de transformação de cada endpoint selecionado (`--explain`).
+///
+/// Os endpoints seguem a ordem da execução (dependências primeiro); nada é
+/// baixado nem gravado.
+///
+/// # Erros
+/// Apenas erros de planejamento (ex: ciclos de dependência).
+pub fn run_explain(config: &Config, data_root: &Path, options: &RunOptions) -> Result<(), Box<dyn Error>> {
+    let jobs = order_jobs(select_by_tags(plan_jobs(config, data_root, options)?, &options.tags))?;
+    log::info!("Plano de {} endpoint(s) (sem download nem gravação)", jobs.len());
+    if let Some(version) = config.config_version {
+        log::info!("Formato da configuração: versão {}", version);
+    }
+    if config.deny_unknown_keys {
+        log::info!("Modo estrito: chaves desconhecidas recusadas na carga");
+    }
+    if let Some(mb) = config.pipeline.stream_above_mb {
+        log::info!("Staging JSON acima de {} MB: convertido em lotes (falha se o endpoint exigir a leitura completa)", mb);
+    }
+    if let Some(max) = config.pipeline.max_open_files {
+        log::info!("Arquivos abertos: até {} writer(s) de staging e saída ao mesmo tempo", max);
+    }
+    if let Some(dir) = config.pipeline.dead_letter_dir() {
+        log::info!("Dead letter: staging de conversões com falha movido para {}", dir.display());
+    }
+    let apis: BTreeSet<&str> = jobs.iter().map(|job| job.api_name).collect();
+    for api_name in apis {
+        let api_config = &config.apis[api_name];
+        if let (Some(login), Some(url)) = (&api_config.login, api_config.login_url()) {
+            let request = if login.form.is_empty() {
+                format!("GET {}", api::redact_url(&url))
+            } else {
+                let fields: Vec<&str> = login.form.keys().map(String::as_str).collect();
+                format!("POST {} (campos: {})", api::redact_url(&url), fields.join(", "))
+            };
+            log::info!("Login de '{}' antes dos downloads: {}", api_name, request);
+        }
+    }
+
+    for job in &jobs {
+        if !job.group.enabled {
+            log::info!("\n{}: desativado", job.id());
+            continue;
+        }
+        log::info!("\n{}:", job.id());
+        if !job.depends_on.is_empty() {
+            log::info!("  depois de: {}", job.depends_on.join(", "));
+        }
+        // Com `extract`, o caminho mostra o modelo `{chave}_{nome}`
+        let output_path = if job.group.extract.is_empty() {
+            job.output_path()
+        } else {
+            job.extracted("{nome}").output_path()
+        };
+        let steps = explain::steps(&job.source, job.group, (job.api_name, job.group_name), &output_path, options);
+        for (index, step) in steps.iter().enumerate() {
+            log::info!("  {}. {}: {}", index + 1, step.name, step.detail);
+        }
+    }
+    Ok(())
+}
+
+/// Tamanho legível (KB abaixo de 1 MB, GB a partir de 1 GB).
+fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let bytes = bytes as f64;
+    if bytes < KB * KB {
+        format!("{:.1} KB", bytes / KB)
+    } else if bytes < KB * KB * KB {
+        format!("{:.1} MB", bytes / (KB * KB))
+    } else {
+        format!("{:.2} GB", bytes / (KB * KB * KB))
+    }
+}
+
+/// Envia o `login` de cada API com endpoints HTTP ativos, em sequência e antes
+/// de qualquer download, e anexa o cookie da sessão às credenciais dos seus jobs.
+///
+/// # Erros
+/// Em modo strict, a falha de um login aborta a execução; fora dele, os
+/// endpoints da API seguem sem sessão (e falham com o status da própria API).
+fn open_sessions(
+    config: &Config,
+    client: &HttpClient,
+    jobs: &mut [Job],
+    options: &RunOptions,
+) -> Result<(), Box<dyn Error>> {
+    let apis: BTreeSet<&str> = jobs
+        .iter()
+        .filter(|job| job.group.enabled && matches!(job.source, Source::Http { .. }))
+        .map(|job| job.api_name)
+        .collect();
+
+    for api_name in apis {
+        let api_config = &config.apis[api_name];
+        let (Some(login), Some(url)) = (&api_config.login, api_config.login_url()) else {
+            continue;
+        };
+        let basic = jobs.iter().find_map(|job| match &job.source {
+            Source::Http { auth, .. } if job.api_name == api_name => auth.basic.clone(),
+            _ => None,
+        });
+        let session = login
+            .resolved_form()
+            .map_err(|e| Box::<dyn Error>::from(ProcessorError::Schema(e)))
+            .and_then(|form| Ok(api::login(client, &url, basic.as_ref(), &form)?));
+
+        let cookie = match session {
+            Ok(Some(cookie)) => {
+                log::info!("Sessão de '{}' aberta via login", api_name);
+                cookie
+            }
+            Ok(None) => {
+                log::warn!("Aviso: login de '{}' não definiu cookies", api_name);
+                continue;
+            }
+            Err(e) => {
+                log::error!("Falha no login de '{}': {}", api_name, e);
+                if options.strict {
+                    return Err(e);
+                }
+                continue;
+            }
+        };
+        for job in jobs.iter_mut().filter(|job| job.api_name == api_name) {
+            if let Source::Http { auth, .. } = &mut job.source {
+                auth.cookie = Some(cookie.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Destino de um endpoint cujas saídas já existem, conforme `on_exists`.
+#[derive(Debug, PartialEq, Eq)]
+enum ExistingAction {
+    /// Segue para o download e a conversão (sobrescrevendo ou com timestamp).
+    Proceed,
+    /// Mantém as saídas e não executa o endpoint.
+    Skip,
+    /// Falha o endpoint sem baixar nem gravar nada.
+    Fail,
+}
+
+/// Decide o que fazer com `existing` de `total` saídas já gravadas.
+fn on_exists_action(policy: OnExists, existing: usize, total: usize) -> ExistingAction {
+    if existing == 0 {
+        return ExistingAction::Proceed;
+    }
+    match policy {
+        OnExists::Overwrite | OnExists::Timestamped => ExistingAction::Proceed,
+        // Com `extract` ou variantes, só é ignorado se todas as saídas existirem;
+        // saídas parciais (falha anterior) são todas refeitas
+        OnExists::Skip if existing == total => ExistingAction::Skip,
+        OnExists::Skip => ExistingAction::Proceed,
+        OnExists::Error => ExistingAction::Fail,
+    }
+}
+
+/// Resolve URLs e arquivos locais de todos os grupos em uma lista de jobs.
+fn plan_jobs<'a>(
+    config: &'a Config,
+    data_root: &Path,
+    options: &RunOptions,
+) -> Result<Vec<Job<'a>>, Box<dyn Error>> {
+    let mut jobs = Vec::new();
+    let run_date = Utc::now().format("%Y-%m-%d").to_string();
+
+    for (api_name, api_config) in &config.apis {
+        // Senha resolvida do ambiente uma vez por API
+        let auth = match api_config.basic_auth.as_ref().map(BasicAuth::resolved).transpose() {
+            Ok(auth) => auth,
+            Err(e) => {
+                log::error!("Erro nas credenciais de '{}': {}", api_name, e);
+                if options.strict {
+                    return Err(ProcessorError::Schema(e).into());
+                }
+                continue;
+            }
+        };
+
+        for (group_name, group_config) in &api_config.endpoints {
+            // Garante estrutura de pastas: data/{api}/{grupo}
+            let group_dir = data_root.join(api_name).join(group_name);
+            if !options.preview && !options.explain {
+                fs::create_dir_all(&group_dir)?;
+            }
+
+            let mut group_sources: Vec<(String, Source)> = Vec::new();
+
+            // Itera sobre as rotas dinâmicas capturadas pelo flatten
+            for key in group_config.routes.keys() {
+                // Resolve URL completa
+                let url = match config.resolve_endpoint_url(api_name, group_name, key) {
+                    Ok(u) => u,
+                    Err(e) => {
+                        log::error!("Erro ao resolver URL para '{}': {}", key, e);
+                        if options.strict {
+                            return Err(e.into());
+                        }
+                        continue;
+                    }
+                };
+
+                // Pula endpoints que exigem substituição manual de ID {id}
+                if url.contains('{') {
+                    continue;
+                }
+
+                group_sources.push((
+                    key.clone(),
+                    Source::Http {
+                        url,
+                        auth: Credentials {
+                            basic: auth.clone(),
+                            cookie: None,
+                        },
+                        backoff: api_config.backoff(&config.http),
+                    },
+                ));
+            }
+
+            // Arquivos locais casados pelo glob viram endpoints adicionais
+            if let Some(pattern) = &group_config.source_glob {
+                match sources::expand_glob(pattern, group_config.name_template()) {
+                    Ok(files) => group_sources.extend(
+                        files.into_iter().map(|(name, path)| (name, Source::File(path))),
+                    ),
+                    Err(e) => {
+                        log::error!("Erro ao expandir glob '{}': {}", pattern, e);
+                        if options.strict {
+                            return Err(e.into());
+                        }
+                    }
+                }
+            }
+
+            jobs.extend(group_sources.into_iter().map(|(key, source)| Job {
+                api_name,
+                group_name,
+                group: group_config,
+                depends_on: group_config.dependencies_of(api_name, group_name, &key),
+                tags: group_config.tags_of(&key),
+                key,
+                source,
+                group_dir: group_dir.clone(),
+                sample: options.sample.is_some(),
+                emit_json_schema: options.emit_json_schema,
+                stamp: None,
+                data_root: data_root.to_path_buf(),
+                run_date: run_date.clone(),
+                run_id: options.run_id.clone(),
+            }));
+        }
+    }
+
+    // Com `output_template`, os valores substituídos podem sair do diretório
+    // de dados e endpoints de grupos diferentes podem coincidir
+    if jobs.iter().any(|job| job.group.output_template.is_some()) {
+        let mut destinations: HashMap<PathBuf, String> = HashMap::new();
+        for output in jobs.iter().flat_map(Job::outputs) {
+            output.check_template().map_err(ProcessorError::Schema)?;
+            let path = output.output_path();
+            if let Some(previous) = destinations.insert(path.clone(), output.id()) {
+                return Err(ProcessorError::Schema(format!(
+                    "'{}' e '{}' gravariam o mesmo arquivo '{}'; ajuste output_template",
+                    previous,
+                    output.id(),
+                    path.display()
+                ))
+                .into());
+            }
+        }
+    }
+
+    Ok(jobs)
+}
+
+/// Mantém apenas os jobs que casam com algum filtro de `--tag`.
+///
+/// Cada filtro (`a,b`) exige todas as suas tags; filtros repetidos somam
+/// seleções. Dependências planejadas mas fora da seleção são tratadas como
+/// satisfeitas: quem executa um subconjunto assume a ordem entre execuções.
+fn select_by_tags<'a>(jobs: Vec<Job<'a>>, filters: &[String]) -> Vec<Job<'a>> {
+    if filters.is_empty() {
+        return jobs;
+    }
+
+    let planned: HashSet<String> = jobs.iter().map(Job::id).collect();
+    let mut selected: Vec<Job> = jobs
+        .into_iter()
+        .filter(|job| {
+            filters.iter().any(|filter| {
+                filter
+                    .split(',')
+                    .all(|tag| job.tags.iter().any(|t| t == tag.trim()))
+            })
+        })
+        .collect();
+
+    let ids: HashSet<String> = selected.iter().map(Job::id).collect();
+    for job in &mut selected {
+        job.depends_on.retain(|dep| ids.contains(dep) || !planned.contains(dep));
+    }
+
+    log::info!(
+        "Seleção por tag ({}): {} de {} endpoint(s)",
+        filters.join(" | "),
+        selected.len(),
+        planned.len()
+    );
+    selected
+}
+
+/// Ordena os jobs respeitando `depends_on` (Kahn estável).
+///
+/// Entre jobs independentes, a ordem do planejamento é preservada.
+///
+/// # Erros
+/// `ProcessorError::Schema` para dependências inexistentes ou ciclos.
+fn order_jobs(jobs: Vec<Job<'_>>) -> Result<Vec<Job<'_>>, ProcessorError> {
+    let ids: HashSet<String> = jobs.iter().map(Job::id).collect();
+    for job in &jobs {
+        if let Some(dep) = job.depends_on.iter().find(|d| !ids.contains(*d)) {
+            return Err(ProcessorError::Schema(format!(
+                "'{}' depende de '{}', que não está entre os endpoints planejados",
+                job.id(),
+                dep
+            )));
+        }
+    }
+
+    let mut pending: Vec<Option<Job>> = jobs.into_iter().map(Some).collect();
+    let mut done: HashSet<String> = HashSet::new();
+    let mut ordered = Vec::with_capacity(pending.len());
+
+    while ordered.len() < pending.len() {
+        let ready = pending.iter().position(|slot| {
+            slot.as_ref()
+                .is_some_and(|job| job.depends_on.iter().all(|d| done.contains(d)))
+        });
+
+        let Some(job) = ready.and_then(|i| pending[i].take()) else {
+            let mut cycle: Vec<String> = pending.iter().flatten().map(Job::id).collect();
+            cycle.sort();
+            return Err(ProcessorError::Schema(format!(
+                "Ciclo de dependências entre: {}",
+                cycle.join(", ")
+            )));
+        };
+        done.insert(job.id());
+        ordered.push(job);
+    }
+
+    Ok(ordered)
+}
+
+/// Staging de um endpoint (download ou cópia), com logs da etapa.
+///
+/// # Returns
+/// Os arquivos de staging, na ordem de leitura, e a espera pelos cabeçalhos das respostas.
+///
+/// # Erros
+/// Toda falha retorna como `ProcessorError::WithContext` (endpoint, etapa e entrada).
+fn stage_job(
+    job: &Job,
+    client: &HttpClient,
+    progress: &RunProgress,
+    warnings: &Warnings,
+) -> Result<(Vec<PathBuf>, Duration), ProcessorError> {
+    // Definição de caminhos físicos
+    let path_json = job.group_dir.join(format!(
+        "{}_temp.{}",
+        job.key,
+        job.group.input_format.extension()
+    ));
+
+    log::info!("Processando: {}", job.key);
+
+    let pb = progress.download_bar();
+    let scope = warnings.endpoint(job.id(), Stage::Staging);
+    let staged = job.source.stage(client, job.group, &path_json, &pb, &scope);
+    if !pb.is_finished() {
+        pb.finish_and_clear();
+    }
+    staged.map(|inputs| (inputs, pb.waited())).map_err(|e| {
+        let hint = match api::retry_after(&e) {
+            Some(wait) => format!(" (transitória; servidor sugere aguardar {}s)", wait.as_secs()),
+            None if api::is_retryable(&e) => " (transitória)".to_string(),
+            None => String::new(),
+        };
+        let err = job.fail(Stage::Staging, job.source.location(), e);
+        log::error!("Falha no Download: {}{}", err, hint);
+        err
+    })
+}
+
+/// Converte as saídas de um endpoint: ele mesmo ou, com `extract`, cada array
+/// do envelope separado em seu próprio staging. A falha de uma saída não
+/// impede as demais.
+///
+/// # Erros
+/// Falhas na separação dos arrays, como `ProcessorError::WithContext`; as de
+/// cada saída seguem em `Converted::outcome`.
+fn convert_job(
+    job: &Job,
+    inputs: &[PathBuf],
+    convert: ConvertOptions,
+    staging: StageTimings,
+    warnings: &Warnings,
+    dead_letter: Option<&Path>,
+) -> Result<Vec<Converted>, ProcessorError> {
+    if job.group.extract.is_empty() {
+        return Ok(vec![Converted {
+            endpoint: job.id(),
+            artifact: job.artifact_path(),
+            outcome: convert_output(job, job.group, inputs, convert, staging, warnings, dead_letter),
+        }]);
+    }
+
+    let split = extract::split(inputs, job.group).map_err(|e| {
+        let err = job.fail(Stage::Conversion, inputs[0].display(), e);
+        log::error!("Falha na Extração: {}", err);
+        if let Some(dir) = dead_letter {
+            send_to_dead_letter(job, inputs, &err, dir, convert.run_id, warnings);
+        }
+        err
+    })?;
+    let group = extract::records_group(job.group);
+
+    let outputs = split
+        .into_iter()
+        .map(|(name, pages)| {
+            let output = job.extracted(&name);
+            Converted {
+                endpoint: output.id(),
+                artifact: output.artifact_path(),
+                outcome: convert_output(&output, &group, &pages, convert, staging, warnings, dead_letter),
+            }
+        })
+        .collect();
+    Ok(outputs)
+}
+
+/// Move o staging de uma conversão que falhou para `{dir}/{api}/{grupo}/{run_id}/`;
+/// uma falha na movimentação vira aviso (o staging fica onde estava).
+fn send_to_dead_letter(
+    job: &Job,
+    inputs: &[PathBuf],
+    error: &ProcessorError,
+    dir: &Path,
+    run_id: &str,
+    warnings: &Warnings,
+) {
+    let target = dir.join(job.api_name).join(job.group_name).join(run_id);
+    let endpoint = job.id();
+    let failure = dead_letter::Failure {
+        endpoint: &endpoint,
+        source: &job.source.location(),
+        run_id,
+        error,
+    };
+    match dead_letter::store(&target, inputs, &failure) {
+        Ok(stored) if !stored.is_empty() => {
+            log::info!("Dead letter: {} arquivo(s) em {}", stored.len(), target.display())
+        }
+        Ok(_) => {}
+        Err(e) => warnings.endpoint(endpoint, Stage::Conversion).warn(format!(
+            "Aviso: staging não movido para '{}': {}",
+            target.display(),
+            e
+        )),
+    }
+}
+
+/// Conversão e etapas pós-escrita (JSON Schema, compressão) de uma saída.
+///
+/// `group` é o do job ou, com `extract`, o de `extract::records_group`.
+/// `staging` traz os tempos de conexão e download, somados ao tempo exibido no
+/// log e repassados nas estatísticas.
+///
+/// # Erros
+/// Toda falha retorna como `ProcessorError::WithContext` (endpoint, etapa e entrada).
+fn convert_output(
+    job: &Job,
+    group: &EndpointGroup,
+    inputs: &[PathBuf],
+    convert: ConvertOptions,
+    staging: StageTimings,
+    warnings: &Warnings,
+    dead_letter: Option<&Path>,
+) -> Result<ConvertStats, ProcessorError> {
+    let step_timer = Instant::now();
+    let path_output = job.output_path();
+    let scope = warnings.endpoint(job.id(), Stage::Conversion);
+    let convert = ConvertOptions {
+        warnings: Some(&scope),
+        origin: Some((job.api_name, job.group_name)),
+        ..convert
+    };
+
+    // Subdiretórios do `output_template` (o padrão já existe: data/{api}/{grupo})
+    if convert.stdout.is_none()
+        && let Err(e) = job.create_output_dir()
+    {
+        let err = job.fail(Stage::Conversion, path_output.display(), ProcessorError::Io(e));
+        log::error!("Falha na Transformação: {}", err);
+        return Err(err);
+    }
+
+    // Injeta os metadados específicos de cada órgão/grupo (root_path, formato)
+    let monitor = PeakMonitor::start();
+    let converted = processor::process_json_to_parquet(inputs, &path_output, group, convert);
+    log::info!("Pico de memória (RSS) em {}: {} MB", job.key, memory::to_mb(monitor.finish()));
+
+    let mut stats = match converted {
+        Ok(stats) => stats,
+        Err(e) => {
+            let input = match inputs {
+                [single] => single.display().to_string(),
+                pages => format!("{} (+{} página(s))", pages[0].display(), pages.len() - 1),
+            };
+            let err = job.fail(Stage::Conversion, input, e);
+            log::error!("Falha na Transformação: {}", err);
+            if let Some(dir) = dead_letter {
+                send_to_dead_letter(job, inputs, &err, dir, convert.run_id, warnings);
+            }
+            return Err(err);
+        }
+    };
+
+    if let Some(reason) = &stats.skipped {
+        scope.warn(format!("Ignorado: {} ({})", job.key, reason));
+        return Ok(stats);
+    }
+
+    for (column, coercion) in stats.coercions.iter().filter(|(_, c)| c.failed > 0) {
+        log::info!(
+            "Coerção: coluna '{}' — {} de {} valor(es) viraram nulo",
+            column, coercion.failed, coercion.attempted
+        );
+    }
+
+    if let Some(reconciliation) = stats.reconciliation {
+        match reconciliation.declared {
+            None => scope.warn(format!(
+                "Reconciliação: envelope sem {}; contagem não verificada",
+                processor::TOTAL_KEY
+            )),
+            Some(declared) if reconciliation.matches() => {
+                log::info!("Reconciliação: {} de {} registro(s)", reconciliation.actual, declared)
+            }
+            Some(declared) => scope.warn(format!(
+                "Reconciliação: {} declara {}, mas {} foram convertidos",
+                processor::TOTAL_KEY,
+                declared,
+                reconciliation.actual
+            )),
+        }
+    }
+
+    // Contrato de dados descrevendo a tabela gravada
+    let post_write = Instant::now();
+    if job.emit_json_schema {
+        let path = json_schema::schema_path(&path_output);
+        let document = json_schema::build(&job.id(), &stats.schema, &stats.nullable);
+        if let Err(e) = json_schema::write(&path, &document) {
+            let err = job.fail(Stage::Conversion, path.display(), e);
+            log::error!("Falha no JSON Schema: {}", err);
+            return Err(err);
+        }
+        log::info!("JSON Schema: {}", path.display());
+    }
+
+    // Compressão do arquivo inteiro para destinos de arquivamento
+    if let Some(codec) = job.group.post_compress
+        && convert.stdout.is_none()
+    {
+        match archive::compress_file(&path_output, codec, job.group.keep_uncompressed) {
+            Ok(path) => log::info!("Comprimido: {}", path.display()),
+            Err(e) => {
+                let err = job.fail(Stage::Compression, path_output.display(), e);
+                log::error!("Falha na Compressão: {}", err);
+                return Err(err);
+            }
+        }
+    }
+
+    stats.timings.connect = staging.connect;
+    stats.timings.download = staging.download;
+    stats.timings.write += post_write.elapsed();
+
+    let kind = if job.sample { " (amostra)" } else { "" };
+    let target = match convert.stdout {
+        Some(format) => format!("{} (stdout)", format.name()),
+        None => job.group.output_format.extension().to_string(),
+    };
+    log::info!(
+        "Sucesso: {} ({}{}) gerado ({:.2?})",
+        job.key,
+        target,
+        kind,
+        staging.total() + step_timer.elapsed()
+    );
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn group(toml: &str) -> EndpointGroup {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn job<'a>(group: &'a EndpointGroup, key: &str) -> Job<'a> {
+        Job {
+            api_name: "compras",
+            group_name: "itens",
+            group,
+            key: key.to_string(),
+            source: Source::File(PathBuf::from("in/pedidos.json")),
+            group_dir: PathBuf::from("data/compras/itens"),
+            depends_on: Vec::new(),
+            tags: Vec::new(),
+            sample: false,
+            emit_json_schema: false,
+            stamp: None,
+            data_root: PathBuf::from("data"),
+            run_date: "2024-01-15".to_string(),
+            run_id: "execucao-1".to_string(),
+        }
+    }
+
+    /// Diretório temporário exclusivo do teste, com `in/` para os arquivos locais.
+    fn sandbox(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("data_gov_pipeline_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("in")).unwrap();
+        dir
+    }
+
+    fn options(run_id: &str) -> RunOptions {
+        RunOptions {
+            strict: false,
+            quiet: true,
+            max_memory_mb: None,
+            run_id: run_id.to_string(),
+            manifest_path: None,
+            outputs_csv: None,
+            manifest_every: None,
+            sample: None,
+            emit_json_schema: false,
+            preview: false,
+            explain: false,
+            timings: false,
+            tags: Vec::new(),
+            stdout: None,
+        }
+    }
+
+    /// Carrega `config` (TOML, com `{dir}` trocado pelo sandbox) e executa o
+    /// pipeline completo, gravando em `{dir}/data`.
+    fn run(dir: &Path, config: &str, options: &RunOptions) -> Result<(), Box<dyn Error>> {
+        let path = dir.join("config.toml");
+        fs::write(&path, config.replace("{dir}", &dir.display().to_string())).unwrap();
+        let config = Config::load(path.to_str().unwrap(), None).unwrap();
+        let client = api::create_http_client(&config.http).unwrap();
+        run_pipeline(&config, &client, &dir.join("data"), options)
+    }
+
+    fn read_parquet(path: &Path) -> polars::prelude::DataFrame {
+        use polars::prelude::{ParquetReader, SerReader};
+        ParquetReader::new(fs::File::open(path).unwrap()).finish().unwrap()
+    }
+
+    fn read_manifest(path: &Path) -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn run_id_reaches_manifest_and_tagged_column() {
+        let dir = sandbox("run_id");
+        fs::write(dir.join("in/pedidos.json"), r#"[{"id": 1}, {"id": 2}]"#).unwrap();
+        let config = r#"
+            [compras.endpoints.itens]
+            source_glob = "{dir}/in/*.json"
+            run_id_column = "_run_id"
+        "#;
+        let mut options = options("execucao-42");
+        options.manifest_path = Some(dir.join("manifest.json"));
+
+        run(&dir, config, &options).unwrap();
+
+        let manifest = read_manifest(&dir.join("manifest.json"));
+        assert_eq!(manifest["run_id"], "execucao-42");
+        let output = read_parquet(&dir.join("data/compras/itens/pedidos.parquet"));
+        let tagged: Vec<_> = output.column("_run_id").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(tagged, [Some("execucao-42"); 2]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn each_globbed_file_becomes_an_output() {
+        let dir = sandbox("glob");
+        for (file, rows) in [("jan.json", 1), ("fev.json", 2), ("mar.json", 3)] {
+            let records: Vec<String> = (0..rows).map(|id| format!(r#"{{"id": {}}}"#, id)).collect();
+            fs::write(dir.join("in").join(file), format!("[{}]", records.join(","))).unwrap();
+        }
+        let mut options = options("execucao-1");
+        options.manifest_path = Some(dir.join("manifest.json"));
+
+        run(&dir, "[compras.endpoints.itens]\nsource_glob = \"{dir}/in/*.json\"", &options).unwrap();
+
+        for (key, rows) in [("jan", 1), ("fev", 2), ("mar", 3)] {
+            let output = dir.join(format!("data/compras/itens/{}.parquet", key));
+            assert_eq!(read_parquet(&output).height(), rows, "{key}");
+        }
+        let manifest = read_manifest(&dir.join("manifest.json"));
+        let mut endpoints: Vec<&str> = manifest["endpoints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| record["endpoint"].as_str().unwrap())
+            .collect();
+        endpoints.sort();
+        assert_eq!(endpoints, ["compras.itens.fev", "compras.itens.jan", "compras.itens.mar"]);
+        // Os originais não são consumidos pela conversão
+        assert!(dir.join("in/jan.json").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn strict_mode_aborts_on_the_first_failure() {
+        let dir = sandbox("strict");
+        fs::write(dir.join("in/a_vazio.json"), "").unwrap();
+        for file in ["b.json", "c.json"] {
+            fs::write(dir.join("in").join(file), r#"[{"id": 1}]"#).unwrap();
+        }
+        let config = "[compras.endpoints.itens]\nsource_glob = \"{dir}/in/*.json\"";
+        let mut options = options("execucao-1");
+        options.manifest_path = Some(dir.join("manifest.json"));
+
+        // Fora do modo strict, a falha fica no manifesto e os demais seguem
+        run(&dir, config, &options).unwrap();
+        assert!(dir.join("data/compras/itens/c.parquet").exists());
+
+        options.strict = true;
+        let error = run(&dir, config, &options).unwrap_err().to_string();
+        assert!(error.contains("compras.itens.a_vazio"), "{error}");
+        // O manifesto é gravado mesmo no abort, com a falha que o causou
+        let manifest = read_manifest(&dir.join("manifest.json"));
+        let endpoints = manifest["endpoints"].as_array().unwrap();
+        assert_eq!(endpoints[0]["endpoint"], "compras.itens.a_vazio");
+        assert_eq!(endpoints[0]["status"], "failed");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_outputs_always_proceed() {
+        for policy in [OnExists::Overwrite, OnExists::Error, OnExists::Skip, OnExists::Timestamped] {
+            assert_eq!(on_exists_action(policy, 0, 2), ExistingAction::Proceed, "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn overwrite_proceeds_over_existing_outputs() {
+        assert_eq!(on_exists_action(OnExists::Overwrite, 1, 1), ExistingAction::Proceed);
+    }
+
+    #[test]
+    fn error_fails_when_any_output_exists() {
+        assert_eq!(on_exists_action(OnExists::Error, 1, 1), ExistingAction::Fail);
+        assert_eq!(on_exists_action(OnExists::Error, 1, 3), ExistingAction::Fail);
+    }
+
+    #[test]
+    fn skip_requires_every_output() {
+        assert_eq!(on_exists_action(OnExists::Skip, 2, 2), ExistingAction::Skip);
+        assert_eq!(on_exists_action(OnExists::Skip, 1, 2), ExistingAction::Proceed);
+    }
+
+    #[test]
+    fn timestamped_writes_a_stamped_copy() {
+        assert_eq!(on_exists_action(OnExists::Timestamped, 1, 1), ExistingAction::Proceed);
+
+        let group = group(r#"on_exists = "timestamped""#);
+        let mut job = job(&group, "pedidos");
+        assert_eq!(job.output_path(), Path::new("data/compras/itens/pedidos.parquet"));
+        job.stamp = Some("20240115T103000Z".to_string());
+        assert_eq!(job.output_path(), Path::new("data/compras/itens/pedidos_20240115T103000Z.parquet"));
+        job.sample = true;
+        assert_eq!(
+            job.output_path(),
+            Path::new("data/compras/itens/pedidos_20240115T103000Z.sample.parquet")
+        );
+    }
+
+    #[test]
+    fn output_template_renders_path_and_creates_directories() {
+        let root = std::env::temp_dir().join(format!("data_gov_template_{}", std::process::id()));
+        let group = group(r#"output_template = "{api}/{date}/{group}_{key}_{run_id}.{ext}""#);
+        let mut job = job(&group, "pedidos");
+        job.data_root = root.clone();
+
+        let expected = root.join("compras/2024-01-15/itens_pedidos_execucao-1.parquet");
+        assert_eq!(job.output_path(), expected);
+        assert_eq!(job.check_template(), Ok(()));
+        job.sample = true;
+        assert_eq!(
+            job.output_path(),
+            root.join("compras/2024-01-15/itens_pedidos_execucao-1.sample.parquet")
+        );
+
+        job.create_output_dir().unwrap();
+        assert!(root.join("compras/2024-01-15").is_dir());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn output_template_rejects_values_leaving_the_data_root() {
+        let templated = group(r#"output_template = "{key}/{run_id}.{ext}""#);
+        for (key, run_id) in [("..", "execucao-1"), ("a/../../etc", "execucao-1"), ("pedidos", "../../tmp/x"), ("pedidos", r"..\x")] {
+            let mut job = job(&templated, key);
+            job.run_id = run_id.to_string();
+            assert!(job.check_template().is_err(), "{key} {run_id}");
+        }
+
+        // Sem template, o caminho padrão não passa pela verificação
+        let plain = group("");
+        assert_eq!(job(&plain, "..").check_template(), Ok(()));
+    }
+
+    #[test]
+    fn order_jobs_runs_dependencies_first() {
+        let group = group("");
+        let mut fatos = job(&group, "fatos");
+        fatos.depends_on = vec!["compras.itens.pontes".to_string()];
+        let mut pontes = job(&group, "pontes");
+        pontes.depends_on = vec!["compras.itens.dimensoes".to_string()];
+        let solto = job(&group, "solto");
+        let dimensoes = job(&group, "dimensoes");
+
+        let ordered = order_jobs(vec![fatos, solto, pontes, dimensoes]).unwrap();
+
+        // Kahn estável: sem dependências pendentes, vale a ordem original
+        let keys: Vec<&str> = ordered.iter().map(|job| job.key.as_str()).collect();
+        assert_eq!(keys, ["solto", "dimensoes", "pontes", "fatos"]);
+    }
+
+    #[test]
+    fn order_jobs_rejects_cycles_and_unknown_dependencies() {
+        let group = group("");
+        let mut a = job(&group, "a");
+        a.depends_on = vec!["compras.itens.b".to_string()];
+        let mut b = job(&group, "b");
+        b.depends_on = vec!["compras.itens.a".to_string()];
+        let error = order_jobs(vec![a, b]).err().unwrap().to_string();
+        assert!(error.contains("compras.itens.a, compras.itens.b"), "{error}");
+
+        let mut orfao = job(&group, "orfao");
+        orfao.depends_on = vec!["compras.itens.ausente".to_string()];
+        let error = order_jobs(vec![orfao]).err().unwrap().to_string();
+        assert!(error.contains("compras.itens.ausente"), "{error}");
+    }
+
+    #[test]
+    fn dependents_of_a_failed_endpoint_are_skipped() {
+        let dir = sandbox("depends_on");
+        fs::write(dir.join("in/dimensoes.json"), "").unwrap();
+        fs::write(dir.join("in/fatos.json"), r#"[{"id": 1}]"#).unwrap();
+        let config = r#"
+            [compras.endpoints.itens]
+            source_glob = "{dir}/in/*.json"
+            depends_on = { fatos = ["dimensoes"] }
+        "#;
+        let mut options = options("execucao-1");
+        options.manifest_path = Some(dir.join("manifest.json"));
+
+        run(&dir, config, &options).unwrap();
+
+        let manifest = read_manifest(&dir.join("manifest.json"));
+        let status = |endpoint: &str| {
+            manifest["endpoints"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|record| record["endpoint"] == endpoint)
+                .cloned()
+                .unwrap()
+        };
+        assert_eq!(status("compras.itens.dimensoes")["status"], "failed");
+        let fatos = status("compras.itens.fatos");
+        assert_eq!(fatos["status"], "skipped");
+        assert_eq!(fatos["error"], "dependência 'compras.itens.dimensoes' falhou");
+        assert!(!dir.join("data/compras/itens/fatos.parquet").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn gzipped_json_source_is_decompressed_in_staging() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let dir = sandbox("gzip");
+        // Dois membros concatenados, como em arquivos gerados em partes
+        let mut gzipped = Vec::new();
+        for part in [r#"[{"id": 1}, {"id": 2},"#, r#" {"id": 3}]"#] {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(part.as_bytes()).unwrap();
+            gzipped.extend(encoder.finish().unwrap());
+        }
+        fs::write(dir.join("in/pedidos.json.gz"), gzipped).unwrap();
+
+        run(&dir, "[compras.endpoints.itens]\nsource_glob = \"{dir}/in/*.json.gz\"", &options("execucao-1")).unwrap();
+
+        let output = read_parquet(&dir.join("data/compras/itens/pedidos.parquet"));
+        assert_eq!(output.height(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sample_mode_writes_exactly_n_rows() {
+        let dir = sandbox("sample");
+        let records: Vec<String> = (0..50).map(|id| format!(r#"{{"id": {}}}"#, id)).collect();
+        fs::write(dir.join("in/pedidos.json"), format!("[{}]", records.join(","))).unwrap();
+        let mut options = options("execucao-1");
+        options.sample = Some(7);
+        options.manifest_path = Some(dir.join("manifest.json"));
+
+        run(&dir, "[compras.endpoints.itens]\nsource_glob = \"{dir}/in/*.json\"", &options).unwrap();
+
+        let output = read_parquet(&dir.join("data/compras/itens/pedidos.sample.parquet"));
+        assert_eq!(output.height(), 7);
+        // A saída completa não é tocada por uma amostra
+        assert!(!dir.join("data/compras/itens/pedidos.parquet").exists());
+        let manifest = read_manifest(&dir.join("manifest.json"));
+        assert_eq!(manifest["sample"], 7);
+        assert_eq!(manifest["endpoints"][0]["rows"], 7);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn disabled_groups_are_reported_without_failing_the_run() {
+        let dir = sandbox("disabled");
+        for sub in ["dimensoes", "fatos"] {
+            fs::create_dir_all(dir.join("in").join(sub)).unwrap();
+            fs::write(dir.join("in").join(sub).join(format!("{}.json", sub)), r#"[{"id": 1}]"#).unwrap();
+        }
+        let config = r#"
+            [compras.endpoints.desligado]
+            enabled = false
+            source_glob = "{dir}/in/dimensoes/*.json"
+
+            [compras.endpoints.ativo]
+            source_glob = "{dir}/in/fatos/*.json"
+            depends_on = { fatos = ["desligado.dimensoes"] }
+        "#;
+        let mut options = options("execucao-1");
+        options.strict = true;
+        options.manifest_path = Some(dir.join("manifest.json"));
+
+        run(&dir, config, &options).unwrap();
+
+        let manifest = read_manifest(&dir.join("manifest.json"));
+        let statuses: BTreeMap<&str, &str> = manifest["endpoints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| (record["endpoint"].as_str().unwrap(), record["status"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            statuses,
+            BTreeMap::from([("compras.ativo.fatos", "success"), ("compras.desligado.dimensoes", "disabled")])
+        );
+        assert!(!dir.join("data/compras/desligado/dimensoes.parquet").exists());
+        // O dependente de um grupo desativado executa normalmente
+        assert!(dir.join("data/compras/ativo/fatos.parquet").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn select_by_tags_combines_and_within_a_filter_and_or_across_filters() {
+        let group = group("");
+        let tagged = |key: &str, tags: &[&str]| {
+            let mut job = job(&group, key);
+            job.tags = tags.iter().map(|t| t.to_string()).collect();
+            job
+        };
+        let jobs = || {
+            let mut fatos = tagged("fatos", &["diario", "financeiro"]);
+            fatos.depends_on = vec!["compras.itens.dimensoes".to_string(), "compras.itens.moedas".to_string()];
+            vec![
+                tagged("dimensoes", &["diario"]),
+                tagged("moedas", &["mensal", "financeiro"]),
+                fatos,
+                tagged("avulso", &[]),
+            ]
+        };
+        let keys = |jobs: &[Job]| jobs.iter().map(|job| job.key.clone()).collect::<Vec<_>>();
+
+        assert_eq!(keys(&select_by_tags(jobs(), &[])), ["dimensoes", "moedas", "fatos", "avulso"]);
+        assert_eq!(keys(&select_by_tags(jobs(), &["diario, financeiro".to_string()])), ["fatos"]);
+        let selected = select_by_tags(jobs(), &["diario".to_string(), "mensal".to_string()]);
+        assert_eq!(keys(&selected), ["dimensoes", "moedas", "fatos"]);
+
+        // Dependência planejada fora da seleção deixa de bloquear o job
+        let selected = select_by_tags(jobs(), &["financeiro".to_string()]);
+        assert_eq!(keys(&selected), ["moedas", "fatos"]);
+        assert_eq!(selected[1].depends_on, ["compras.itens.moedas"]);
+    }
+
+    #[test]
+    fn bounded_queue_holds_downloads_until_a_converter_is_free() {
+        let dir = sandbox("backpressure");
+        let group_dir = dir.join("data/compras/itens");
+        fs::create_dir_all(&group_dir).unwrap();
+        let group = group("");
+        let jobs: Vec<Job> = ["a", "b", "c"]
+            .into_iter()
+            .map(|key| {
+                let input = dir.join("in").join(format!("{}.json", key));
+                fs::write(&input, r#"[{"id": 1}]"#).unwrap();
+                Job {
+                    source: Source::File(input),
+                    group_dir: group_dir.clone(),
+                    data_root: dir.join("data"),
+                    ..job(&group, key)
+                }
+            })
+            .collect();
+        let client = api::create_http_client(&toml::from_str("").unwrap()).unwrap();
+        let progress = RunProgress::new(jobs.len(), true);
+        let warnings = Warnings::default();
+        let stop = AtomicBool::new(false);
+        let workers = Workers {
+            jobs: &jobs,
+            client: &client,
+            progress: &progress,
+            convert: ConvertOptions {
+                limit: None,
+                run_id: "execucao-1",
+                sample: None,
+                stdout: None,
+                write_retry: crate::atomic::Retry::default(),
+                stream_above: None,
+                warnings: None,
+                origin: None,
+            },
+            warnings: &warnings,
+            stop: &stop,
+            hosts: None,
+            dead_letter: None,
+        };
+        let staged_file = |key: &str| group_dir.join(format!("{}_temp.json", key));
+
+        let (queue_tx, queue_rx) = mpsc::channel();
+        let (staged_tx, staged_rx) = mpsc::sync_channel(1);
+        let (done_tx, done_rx) = mpsc::channel();
+        let (queue_rx, staged_rx) = (Mutex::new(queue_rx), Mutex::new(staged_rx));
+        for index in 0..jobs.len() {
+            queue_tx.send(index).unwrap();
+        }
+        drop(queue_tx);
+
+        thread::scope(|scope| {
+            let download_done = done_tx.clone();
+            scope.spawn(|| workers.download(&queue_rx, staged_tx, download_done));
+
+            // Sem conversores: um item na fila e o segundo bloqueado no envio
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while !staged_file("b").exists() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+            }
+            thread::sleep(Duration::from_millis(200));
+            assert!(staged_file("a").exists() && staged_file("b").exists());
+            assert!(!staged_file("c").exists(), "o terceiro download não deveria ter começado");
+
+            scope.spawn(|| workers.transform(&staged_rx, done_tx));
+        });
+
+        let mut finished: Vec<usize> = done_rx.iter().map(|f| {
+            assert!(f.outcome.is_ok());
+            f.index
+        }).collect();
+        finished.sort();
+        assert_eq!(finished, [0, 1, 2]);
+        for key in ["a", "b", "c"] {
+            assert!(group_dir.join(format!("{}.parquet", key)).exists(), "{key}");
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn warm_up_probes_each_enabled_origin_once() {
+        let (first, first_server) = api::test_server::serve(vec![api::test_server::response("200 OK", &[], b"")]);
+        let (second, second_server) = api::test_server::serve(vec![api::test_server::response("404 Not Found", &[], b"")]);
+        let http = |url: String| Source::Http {
+            url,
+            auth: Credentials::default(),
+            backoff: api::Backoff {
+                retries: 0,
+                strategy: crate::models::BackoffStrategy::Constant,
+                base_delay: Duration::ZERO,
+                max_delay: None,
+                jitter: 0.0,
+            },
+        };
+        let enabled = group("");
+        // Porta 1 recusa conexões: se o grupo desativado fosse aquecido, haveria aviso
+        let disabled = group("enabled = false");
+        let jobs = vec![
+            Job { source: http(format!("{}/pedidos", first)), ..job(&enabled, "pedidos") },
+            Job { source: http(format!("{}/itens?pagina=2", first)), ..job(&enabled, "itens") },
+            Job { source: http(format!("{}/", first)), ..job(&enabled, "raiz") },
+            Job { source: http(format!("{}/orgaos", second)), ..job(&enabled, "orgaos") },
+            job(&enabled, "local"),
+            Job { source: http("http://127.0.0.1:1/off".to_string()), ..job(&disabled, "off") },
+        ];
+        let client = api::create_http_client(&toml::from_str("").unwrap()).unwrap();
+        let warnings = Warnings::default();
+
+        warm_up(&client, &jobs, &warnings);
+
+        // Cada servidor atende uma única conexão: uma segunda sonda falharia com aviso
+        assert!(warnings.take().is_empty());
+        for server in [first_server, second_server] {
+            let requests = server.join().unwrap();
+            assert_eq!(requests.len(), 1);
+            assert_eq!(requests[0].line, "HEAD / HTTP/1.1");
+        }
+    }
+
+    #[test]
+    fn stage_timings_are_all_populated_and_add_up_to_the_run() {
+        let dir = sandbox("timings");
+        let rows: Vec<String> = (0..20_000).map(|id| format!(r#"{{"id": {}, "nome": "item {}"}}"#, id, id)).collect();
+        let body = format!("[{}]", rows.join(","));
+        let (base, server) = api::test_server::serve(vec![api::test_server::response("200 OK", &[], body.as_bytes())]);
+        let group = group("");
+        let job = Job {
+            source: Source::Http {
+                url: format!("{}/itens", base),
+                auth: Credentials::default(),
+                backoff: api::Backoff {
+                    retries: 0,
+                    strategy: crate::models::BackoffStrategy::Constant,
+                    base_delay: Duration::ZERO,
+                    max_delay: None,
+                    jitter: 0.0,
+                },
+            },
+            group_dir: dir.join("data/compras/itens"),
+            data_root: dir.join("data"),
+            ..job(&group, "itens")
+        };
+        fs::create_dir_all(&job.group_dir).unwrap();
+        let client = api::create_http_client(&toml::from_str("").unwrap()).unwrap();
+        let progress = RunProgress::new(1, true);
+        let warnings = Warnings::default();
+        let convert = ConvertOptions {
+            limit: None,
+            run_id: "execucao-1",
+            sample: None,
+            stdout: None,
+            write_retry: crate::atomic::Retry::default(),
+            stream_above: None,
+            warnings: None,
+            origin: None,
+        };
+
+        // Mesma sequência dos workers: staging, depois conversão
+        let timer = Instant::now();
+        let (inputs, connect) = stage_job(&job, &client, &progress, &warnings).unwrap();
+        let staging = StageTimings { connect, download: timer.elapsed().saturating_sub(connect), ..Default::default() };
+        let stats = convert_output(&job, &group, &inputs, convert, staging, &warnings, None).unwrap();
+        let wall = timer.elapsed();
+        server.join().unwrap();
+
+        let t = stats.timings;
+        for (stage, elapsed) in [("connect", t.connect), ("download", t.download), ("parse", t.parse), ("transform", t.transform), ("write", t.write)] {
+            assert!(elapsed > Duration::ZERO, "{stage} zerado");
+        }
+        // Fora das etapas sobra só a orquestração, dominada pela última amostra de RSS (50 ms)
+        assert!(t.total() <= wall, "{:?} > {:?}", t.total(), wall);
+        assert!(wall - t.total() < Duration::from_millis(150), "{:?} cobre pouco de {:?}", t.total(), wall);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn empty_download_is_recorded_as_a_warning_in_the_manifest() {
+        let dir = sandbox("empty_warning");
+        fs::write(dir.join("in/vazio.json"), "[]").unwrap();
+        let config = r#"
+            [compras.endpoints.itens]
+            source_glob = "{dir}/in/*.json"
+            write_empty = true
+        "#;
+        let mut options = options("execucao-1");
+        options.manifest_path = Some(dir.join("manifest.json"));
+
+        run(&dir, config, &options).unwrap();
+
+        let manifest = read_manifest(&dir.join("manifest.json"));
+        let warnings = manifest["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0]["endpoint"], "compras.itens.vazio");
+        assert_eq!(warnings[0]["stage"], "conversion");
+        let message = warnings[0]["message"].as_str().unwrap();
+        assert!(message.starts_with("Ignorado: vazio (sem registros; sem empty_schema"), "{message}");
+        assert!(!dir.join("data/compras/itens/vazio.parquet").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn extract_splits_a_two_array_envelope_into_two_outputs() {
+        let dir = sandbox("extract");
+        fs::write(
+            dir.join("in/envelope.json"),
+            r#"{"compras": [{"id": 1}, {"id": 2}], "dados": {"contratos": [{"numero": "C-1", "valor": 9.5}]}}"#,
+        )
+        .unwrap();
+        let config = r#"
+            [compras.endpoints.itens]
+            source_glob = "{dir}/in/*.json"
+            extract = { compras = "/compras", contratos = "/dados/contratos" }
+        "#;
+        let mut options = options("execucao-1");
+        options.strict = true;
+        options.manifest_path = Some(dir.join("manifest.json"));
+
+        run(&dir, config, &options).unwrap();
+
+        let compras = read_parquet(&dir.join("data/compras/itens/envelope_compras.parquet"));
+        assert_eq!(compras.height(), 2);
+        assert_eq!(compras.get_column_names(), ["id"]);
+        let contratos = read_parquet(&dir.join("data/compras/itens/envelope_contratos.parquet"));
+        assert_eq!(contratos.height(), 1);
+        assert_eq!(contratos.get_column_names(), ["numero", "valor"]);
+        // Uma saída por array, cada uma com o seu registro; o staging original é removido
+        let manifest = read_manifest(&dir.join("manifest.json"));
+        let endpoints: Vec<&str> = manifest["endpoints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| record["endpoint"].as_str().unwrap())
+            .collect();
+        assert_eq!(endpoints, ["compras.itens.envelope_compras", "compras.itens.envelope_contratos"]);
+        assert!(!dir.join("data/compras/itens/envelope_temp.json").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn checkpoint_lists_only_the_endpoints_finished_before_an_interruption() {
+        let dir = sandbox("checkpoint");
+        let group = group("");
+        let jobs: Vec<Job> = ["a", "b", "c"].into_iter().map(|key| job(&group, key)).collect();
+        let mut options = options("execucao-1");
+        options.manifest_every = Some(1);
+        options.manifest_path = Some(dir.join("manifest.json"));
+        let progress = RunProgress::new(jobs.len(), true);
+        let warnings = Warnings::default();
+        let mut manifest = Manifest::new("execucao-1", None);
+        let mut scheduler = Scheduler::new(&jobs, &options, &progress, &mut manifest, &warnings, false);
+        let (queue, dispatched) = mpsc::channel();
+        let converted = |index: usize| Finished {
+            index,
+            outcome: Ok(vec![Converted {
+                endpoint: jobs[index].id(),
+                artifact: jobs[index].artifact_path(),
+                outcome: Ok(ConvertStats::default()),
+            }]),
+            elapsed: Duration::from_millis(5),
+        };
+
+        scheduler.dispatch_ready(&queue).unwrap();
+        assert_eq!(dispatched.try_iter().count(), 3);
+        scheduler.complete(converted(1)).unwrap();
+        scheduler.complete(converted(0)).unwrap();
+        // Interrompido aqui: `c` segue em andamento e o manifesto final nunca é gravado
+        drop(scheduler);
+
+        let partial = read_manifest(&dir.join("manifest.json"));
+        let endpoints: Vec<&str> = partial["endpoints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| record["endpoint"].as_str().unwrap())
+            .collect();
+        assert_eq!(endpoints, ["compras.itens.b", "compras.itens.a"]);
+        assert!(partial["finished_at"].is_null());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn profile_trace_has_download_and_convert_spans() {
+        let dir = sandbox("profile");
+        fs::write(dir.join("in/pedidos.json"), r#"[{"id": 1}, {"id": 2}]"#).unwrap();
+        let config = r#"
+            [perfil.endpoints.itens]
+            source_glob = "{dir}/in/*.json"
+        "#;
+        profile::enable();
+
+        run(&dir, config, &options("execucao-1")).unwrap();
+        profile::write(&dir.join("trace.json")).unwrap();
+
+        let trace = read_manifest(&dir.join("trace.json"));
+        // O perfil é global: outros testes podem registrar spans no mesmo arquivo
+        let spans: Vec<(&str, &str)> = trace["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|event| event["args"]["endpoint"] == "perfil.itens.pedidos")
+            .map(|event| (event["cat"].as_str().unwrap(), event["name"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("endpoint", "staging"),
+                ("etapa", "download"),
+                ("endpoint", "convert"),
+                ("etapa", "parse"),
+                ("etapa", "transform"),
+                ("etapa", "write"),
+            ]
+        );
+        assert_eq!(trace["displayTimeUnit"], "ms");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn host_limit_holds_per_origin_under_a_high_global_cap() {
+        let http = |url: &str| Source::Http {
+            url: url.to_string(),
+            auth: Credentials::default(),
+            backoff: api::Backoff {
+                retries: 0,
+                strategy: crate::models::BackoffStrategy::Constant,
+                base_delay: Duration::ZERO,
+                max_delay: None,
+                jitter: 0.0,
+            },
+        };
+        let group = group("");
+        let jobs: Vec<Job> = ["https://a.gov.br/1", "https://a.gov.br/2", "https://a.gov.br/3", "https://b.gov.br/1", "https://b.gov.br/2", "https://b.gov.br/3"]
+            .iter()
+            .enumerate()
+            .map(|(i, url)| Job { source: http(url), ..job(&group, &format!("j{i}")) })
+            .collect();
+        let hosts = HostLimits::new(1, &jobs);
+        let (sender, receiver) = mpsc::channel();
+        for index in 0..jobs.len() {
+            sender.send(index).unwrap();
+        }
+        drop(sender);
+        let queue = Mutex::new(receiver);
+        // (ativos por origem, pico por origem, ativos no total, pico total)
+        let usage = Mutex::new((HashMap::<String, usize>::new(), HashMap::<String, usize>::new(), 0usize, 0usize));
+
+        // Quatro workers: o limite global não é o que segura as origens
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    while let Some(index) = hosts.next(&queue) {
+                        let origin = hosts.origins[index].clone().unwrap();
+                        {
+                            let mut usage = usage.lock().unwrap();
+                            let active = *usage.0.entry(origin.clone()).and_modify(|n| *n += 1).or_insert(1);
+                            let peak = usage.1.entry(origin.clone()).or_default();
+                            *peak = (*peak).max(active);
+                            usage.2 += 1;
+                            usage.3 = usage.3.max(usage.2);
+                        }
+                        thread::sleep(Duration::from_millis(20));
+                        {
+                            let mut usage = usage.lock().unwrap();
+                            *usage.0.get_mut(&origin).unwrap() -= 1;
+                            usage.2 -= 1;
+                        }
+                        hosts.release(index);
+                    }
+                });
+            }
+        });
+
+        let (_, peaks, _, total_peak) = usage.into_inner().unwrap();
+        assert_eq!(peaks["https://a.gov.br"], 1);
+        assert_eq!(peaks["https://b.gov.br"], 1);
+        // As duas origens correm em paralelo, uma vaga cada
+        assert_eq!(total_peak, 2);
+    }
+
+    #[test]
+    fn success_marker_is_written_only_when_the_whole_group_succeeds() {
+        let dir = sandbox("success_marker");
+        fs::write(dir.join("in/a.json"), r#"[{"id": 1}]"#).unwrap();
+        fs::write(dir.join("in/b.json"), r#"[{"id": 2}]"#).unwrap();
+        let config = r#"
+            [compras.endpoints.itens]
+            source_glob = "{dir}/in/*.json"
+            write_success_marker = true
+        "#;
+        let marker = dir.join("data/compras/itens/_SUCCESS");
+
+        run(&dir, config, &options("execucao-1")).unwrap();
+        assert!(marker.exists());
+        assert!(dir.join("data/compras/itens/b.parquet").exists());
+
+        // Uma falha no grupo remove o marcador da execução anterior
+        fs::write(dir.join("in/c.json"), "{não é json").unwrap();
+        let _ = run(&dir, config, &options("execucao-2"));
+        assert!(!dir.join("data/compras/itens/c.parquet").exists());
+        assert!(!marker.exists());
+
+        fs::remove_file(dir.join("in/c.json")).unwrap();
+        run(&dir, config, &options("execucao-3")).unwrap();
+        assert!(marker.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,50 +3,505 @@
 //! ## Engenharia de Dados
 //! Este módulo implementa a normalização dinâmica de JSON para Parquet.
 
-use polars::io::SerReader;
+use polars::io::avro::{AvroCompression, AvroReader, AvroWriter};
+use polars::io::parquet::write::get_column_write_options;
+use polars::io::{SerReader, SerWriter};
 use polars::prelude::StatisticsOptions;
 use polars::prelude::*;
+use polars_parquet::write::{FileWriter, RowGroupIterator, Version, WriteOptions};
 
+use serde::Serialize;
+use serde_json::Value;
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs::{self, File};
+use std::io::{self, Cursor, Write};
 use std::num::NonZeroUsize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use crate::atomic;
+use crate::batch;
+use crate::big_numbers;
+use crate::bloom;
+use crate::computed;
+use crate::delta;
 use crate::errors::ProcessorError;
+use crate::jq;
+use crate::jsonpath;
+use crate::memory::{self, MemoryLimit};
+use crate::pseudonymize;
+use crate::models::{
+    Compression, CsvOptions, CsvType, EndpointGroup, FloatSpecial, InputFormat, NullValues, NullsOrder,
+    OnDuplicateColumn, OutputFormat, ParquetVersion, ReconcileMode, SortKey,
+};
+use crate::warnings::{self, EndpointWarnings};
+use crate::xml;
+
+/// Chave do envelope com o total de registros declarado pela API.
+pub const TOTAL_KEY: &str = "totalRegistros";
+
+/// Campos de controle do envelope removidos de toda saída.
+pub const TECHNICAL_COLUMNS: [&str; 7] = [
+    "totalRegistros",
+    "totalPaginas",
+    "paginasRestantes",
+    "links",
+    "dataHoraConsulta",
+    "timeZoneAtual",
+    "dataHoraAtualizacao",
+];
+
+/// Coluna de `add_api_column`.
+pub const API_COLUMN: &str = "_api";
+
+/// Coluna de `add_group_column`.
+pub const GROUP_COLUMN: &str = "_group";
+
+/// Tamanho de lote usado quando o limite de memória força a conversão em lotes.
+const FALLBACK_BATCH_SIZE: NonZeroUsize = NonZeroUsize::new(10_000).unwrap();
+
+/// Linhas por row group na escrita v2 (padrão do `ParquetWriter` do Polars).
+const ROW_GROUP_ROWS: usize = 512 * 512;
+
+/// Parâmetros da execução que acompanham cada conversão.
+#[derive(Debug, Clone, Copy)]
+pub struct ConvertOptions<'a> {
+    /// Teto de RSS; excedê-lo aborta a conversão com `ErrorKind::OutOfMemory`.
+    pub limit: Option<MemoryLimit>,
+
+    /// Identificador da execução, gravado na coluna `run_id_column` quando configurada.
+    pub run_id: &'a str,
+
+    /// Grava apenas as primeiras N linhas (modo `--sample`).
+    pub sample: Option<usize>,
+
+    /// Escreve o resultado no stdout (`--output -`) em vez do arquivo final.
+    pub stdout: Option<StreamFormat>,
+
+    /// Novas tentativas da gravação do arquivo final em erros de I/O.
+    pub write_retry: atomic::Retry,
+
+    /// Staging acima deste tamanho (bytes) segue pela conversão em lotes (`stream_above_mb`).
+    pub stream_above: Option<u64>,
+
+    /// Avisos do endpoint em conversão (definido por job).
+    pub warnings: Option<&'a EndpointWarnings<'a>>,
+
+    /// API e grupo do endpoint em conversão (definidos por job), gravados
+    /// com `add_api_column` e `add_group_column`.
+    pub origin: Option<(&'a str, &'a str)>,
+}
 
-/// Converte JSON para Parquet utilizando metadados de root_path.
+/// Formatos aceitos por `--output -` (`--format`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// Um objeto JSON por linha (padrão; combina com `jq`).
+    #[default]
+    Ndjson,
+    Csv,
+    Parquet,
+    Avro,
+}
+
+impl StreamFormat {
+    /// Interpreta o valor de `--format`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ndjson" => Some(StreamFormat::Ndjson),
+            "csv" => Some(StreamFormat::Csv),
+            "parquet" => Some(StreamFormat::Parquet),
+            "avro" => Some(StreamFormat::Avro),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StreamFormat::Ndjson => "ndjson",
+            StreamFormat::Csv => "csv",
+            StreamFormat::Parquet => "parquet",
+            StreamFormat::Avro => "avro",
+        }
+    }
+
+    /// Formatos binários: permitidos, mas ilegíveis em um terminal.
+    pub fn is_binary(&self) -> bool {
+        matches!(self, StreamFormat::Parquet | StreamFormat::Avro)
+    }
+}
+
+/// Dimensões do arquivo gravado e coerções aplicadas.
+#[derive(Debug, Clone, Default)]
+pub struct ConvertStats {
+    pub rows: usize,
+    pub cols: usize,
+    pub coercions: CoercionReport,
+    /// Schema gravado (base do contrato JSON Schema).
+    pub schema: SchemaRef,
+    /// Colunas com ao menos um valor nulo gravado.
+    pub nullable: BTreeSet<String>,
+    /// Confronto com `totalRegistros` (apenas com `reconcile` configurado).
+    pub reconciliation: Option<Reconciliation>,
+    /// Valores distintos por coluna (apenas com `report_cardinality`).
+    pub cardinality: BTreeMap<String, usize>,
+    /// Variantes gravadas por codec (apenas com uma lista em `compression`).
+    pub variants: Vec<(Compression, PathBuf)>,
+    /// Tempo por etapa (a conversão preenche leitura, transformação e gravação).
+    pub timings: StageTimings,
+    /// Motivo quando nada foi gravado (resposta vazia sem schema conhecido).
+    pub skipped: Option<String>,
+}
+
+/// Tempo gasto em cada etapa de um endpoint.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StageTimings {
+    /// Até os cabeçalhos das respostas: DNS, conexão, TLS e espera do servidor.
+    pub connect: Duration,
+    /// Recebimento do corpo (e descompressão) no staging.
+    pub download: Duration,
+    /// Leitura do staging até a tabela de registros.
+    pub parse: Duration,
+    /// Normalização, coerções, reconciliação e ordenação.
+    pub transform: Duration,
+    /// Gravação da saída e etapas seguintes (JSON Schema, compressão).
+    pub write: Duration,
+}
+
+impl StageTimings {
+    /// Soma das etapas.
+    pub fn total(&self) -> Duration {
+        self.connect + self.download + self.parse + self.transform + self.write
+    }
+}
+
+/// Total declarado no envelope versus linhas convertidas.
+#[derive(Debug, Clone, Copy)]
+pub struct Reconciliation {
+    /// `totalRegistros` do envelope; None se ausente.
+    pub declared: Option<usize>,
+    pub actual: usize,
+}
+
+impl Reconciliation {
+    /// Verdadeiro se as contagens batem (ou se não há total declarado).
+    pub fn matches(&self) -> bool {
+        self.declared.is_none_or(|declared| declared == self.actual)
+    }
+}
+
+/// Resultado das coerções de tipo de uma coluna.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Coercion {
+    /// Valores não nulos submetidos à conversão.
+    pub attempted: usize,
+    /// Valores que viraram nulo na conversão (perda silenciosa).
+    pub failed: usize,
+}
+
+/// Coerções por coluna, acumuladas entre lotes.
+pub type CoercionReport = BTreeMap<String, Coercion>;
+
+/// Converte JSON (ou XML) para o formato de saída (Parquet/Avro) utilizando metadados do grupo.
+///
+/// `inputs` costuma ter um único arquivo; rotas paginadas entregam um por
+/// página, lidos na ordem e concatenados em uma única tabela.
+///
+/// Com `options.limit`, o RSS é verificado em pontos de controle e a
+/// conversão é abortada antes de exceder o teto.
+///
+/// # Returns
+/// Linhas e colunas gravadas.
 pub fn process_json_to_parquet(
-    json_path: &Path,
-    parquet_path: &Path,
-    root_path: Option<&str>,
-) -> Result<(), ProcessorError> {
-    // Abertura do arquivo original
-    let file = File::open(json_path).map_err(ProcessorError::Io)?;
-    let schema_len = NonZeroUsize::new(1000).unwrap();
+    inputs: &[PathBuf],
+    output_path: &Path,
+    group: &EndpointGroup,
+    options: ConvertOptions,
+) -> Result<ConvertStats, ProcessorError> {
+    let timer = Instant::now();
+    zstd_workers_fallback(group, options);
 
-    let mut dataframe = JsonReader::new(file)
-        .infer_schema_len(Some(schema_len))
-        .finish()
-        .map_err(|e| ProcessorError::Parquet(format!("Falha no parsing JSON: {}", e)))?;
+    // Números longos viram texto antes de qualquer leitura (lotes inclusive)
+    if group.input_format == InputFormat::Json && !group.big_number_columns.is_empty() {
+        for path in inputs {
+            big_numbers::quote_in_place(path, &group.big_number_columns)?;
+        }
+    }
 
-    if dataframe.height() == 0 {
+    let limit = options.limit;
+    let batch_size = match (group.batch_size, limit) {
+        (Some(size), _) => Some(size),
+        (None, _) if let Some(size) = oversized(inputs, group, options)? => Some(size),
+        (None, Some(limit)) => preflight(inputs, group, limit)?,
+        (None, None) => None,
+    };
+
+    // Arrays gigantes seguem pelo caminho em lotes (memória limitada)
+    if let Some(batch_size) = batch_size {
+        if options.stdout.is_some() {
+            return Err(ProcessorError::Parquet(
+                "a conversão em lotes (batch_size ou limite de memória) não escreve no stdout".to_string(),
+            ));
+        }
+        let mut stats = batch::process_json_in_batches(inputs, output_path, group, batch_size, options)?;
+        remove_inputs(inputs)?;
+        // Leitura e lotes se intercalam: a leitura é o restante do tempo
+        let timings = &mut stats.timings;
+        timings.parse = timer.elapsed().saturating_sub(timings.transform + timings.write);
+        return Ok(stats);
+    }
+
+    let mut frames = Vec::with_capacity(inputs.len());
+    let mut declared = None;
+    for (index, path) in inputs.iter().enumerate() {
+        let (frame, total) = load_records(path, group, options)?;
+        if index == 0 {
+            declared = total;
+        }
+        frames.push(frame);
+        if let Some(limit) = limit {
+            limit.check("leitura")?;
+        }
+    }
+
+    // Páginas vazias não contribuem; sem nenhum registro, a saída segue `write_empty`
+    frames.retain(|frame| frame.height() > 0);
+    if frames.is_empty() {
+        let reconciliation = reconcile(group, options, declared, 0)?;
+        let mut stats = empty_output(output_path, group, options, reconciliation)?;
+        remove_inputs(inputs)?;
+        stats.timings.parse = timer.elapsed().saturating_sub(stats.timings.write);
+        return Ok(stats);
+    }
+    let mut dataframe = concat_frames(frames)?;
+    let parse = timer.elapsed();
+
+    // Amostra: corta após o unnest, quando cada linha já é um registro
+    if let Some(rows) = options.sample {
+        dataframe = dataframe.head(Some(rows));
+    }
+
+    let mut coercions = CoercionReport::new();
+    dataframe = normalize(dataframe, group, options, &mut coercions)?;
+    absent_first_columns(&dataframe, group, options);
+    if let Some(limit) = limit {
+        limit.check("normalização")?;
+    }
+    let reconciliation = reconcile(group, options, declared, dataframe.height())?;
+
+    dataframe = sort_rows(dataframe, &group.sort_by)?;
+    if let Some(limit) = limit {
+        limit.check("ordenação")?;
+    }
+    let cardinality = column_cardinality(&dataframe, group)?;
+    let transform = timer.elapsed() - parse;
+
+    let variants = match options.stdout {
+        Some(format) => {
+            write_stdout(&mut dataframe, format, group)?;
+            Vec::new()
+        }
+        None => write_variants(&mut dataframe, output_path, group, options)?,
+    };
+
+    // Finalização
+    remove_inputs(inputs)?;
+
+    Ok(ConvertStats {
+        rows: dataframe.height(),
+        cols: dataframe.width(),
+        coercions,
+        schema: dataframe.schema().clone(),
+        nullable: nullable_columns(&dataframe),
+        reconciliation,
+        cardinality,
+        variants,
+        timings: StageTimings {
+            parse,
+            transform,
+            write: timer.elapsed() - parse - transform,
+            ..Default::default()
+        },
+        skipped: None,
+    })
+}
+
+/// Saída de uma resposta sem registros.
+///
+/// Com `write_empty`, grava uma saída sem linhas com o schema de
+/// `empty_schema` ou, na falta dele, o da saída anterior em `output_path`
+/// (tabelas Delta já existentes são mantidas como estão). Sem nenhum dos
+/// dois, nada é gravado e `skipped` traz o motivo.
+///
+/// # Erros
+/// `ProcessorError::Schema` sem `write_empty`: a resposta vazia é uma falha.
+pub(crate) fn empty_output(
+    output_path: &Path,
+    group: &EndpointGroup,
+    options: ConvertOptions,
+    reconciliation: Option<Reconciliation>,
+) -> Result<ConvertStats, ProcessorError> {
+    if !group.write_empty {
         return Err(ProcessorError::Schema(
             "Arquivo JSON sem registros ou vazio".to_string(),
         ));
     }
 
-    // Normalização Dinâmica
-    if let Some(path) = root_path
+    let timer = Instant::now();
+    let declared: Schema = group
+        .empty_schema
+        .iter()
+        .map(|entry| Field::new(entry.column.as_str().into(), polars_dtype(entry.dtype)))
+        .collect();
+    let schema = if declared.is_empty() {
+        // Com variantes, a anterior é a do primeiro codec
+        let previous = match group.compression_variants() {
+            [first, ..] => variant_path(output_path, *first),
+            [] => output_path.to_path_buf(),
+        };
+        previous_schema(&previous, group)?
+    } else {
+        Some(declared)
+    };
+    let Some(schema) = schema else {
+        return Ok(ConvertStats {
+            reconciliation,
+            skipped: Some(format!(
+                "sem registros; sem empty_schema nem saída anterior em '{}'",
+                output_path.display()
+            )),
+            ..Default::default()
+        });
+    };
+
+    let mut dataframe = DataFrame::empty_with_schema(&schema);
+    let mut variants = Vec::new();
+    match options.stdout {
+        Some(format) => write_stdout(&mut dataframe, format, group)?,
+        // Zero linhas não acrescentam nada a uma tabela Delta existente
+        None if group.output_format == OutputFormat::Delta && delta::is_table(output_path) => {}
+        None => variants = write_variants(&mut dataframe, output_path, group, options)?,
+    }
+
+    Ok(ConvertStats {
+        cols: dataframe.width(),
+        variants,
+        schema: dataframe.schema().clone(),
+        reconciliation,
+        timings: StageTimings {
+            write: timer.elapsed(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+}
+
+/// Valores distintos de cada coluna (`report_cardinality`), nulo incluído.
+///
+/// Com `approx_cardinality`, a contagem é estimada por HyperLogLog (memória
+/// constante por coluna). Listas e structs ficam de fora.
+fn column_cardinality(dataframe: &DataFrame, group: &EndpointGroup) -> PolarsResult<BTreeMap<String, usize>> {
+    if !group.report_cardinality {
+        return Ok(BTreeMap::new());
+    }
+    dataframe
+        .get_columns()
+        .iter()
+        .filter(|column| !column.dtype().is_nested())
+        .map(|column| {
+            let distinct = if group.approx_cardinality {
+                column.approx_n_unique()? as usize
+            } else {
+                column.n_unique()?
+            };
+            Ok((column.name().to_string(), distinct))
+        })
+        .collect()
+}
+
+/// Schema da saída gravada anteriormente em `output_path`, se houver.
+fn previous_schema(output_path: &Path, group: &EndpointGroup) -> Result<Option<Schema>, ProcessorError> {
+    let read_error =
+        |e: PolarsError| ProcessorError::Parquet(format!("Falha ao ler o schema de '{}': {}", output_path.display(), e));
+    match group.output_format {
+        OutputFormat::Delta if delta::is_table(output_path) => delta::schema(output_path).map(Some),
+        OutputFormat::Delta => Ok(None),
+        _ if !output_path.is_file() => Ok(None),
+        OutputFormat::Parquet => {
+            let file = File::open(output_path).map_err(ProcessorError::Io)?;
+            let schema = ParquetReader::new(file).schema().map_err(read_error)?;
+            Ok(Some(Schema::from_arrow_schema(&schema)))
+        }
+        OutputFormat::Avro => {
+            let file = File::open(output_path).map_err(ProcessorError::Io)?;
+            AvroReader::new(file).schema().map(Some).map_err(read_error)
+        }
+    }
+}
+
+/// Lê um arquivo de staging e extrai seus registros (envelope já desfeito).
+///
+/// # Returns
+/// Os registros e o `totalRegistros` declarado no envelope, se houver.
+fn load_records(
+    path: &Path,
+    group: &EndpointGroup,
+    options: ConvertOptions,
+) -> Result<(DataFrame, Option<usize>), ProcessorError> {
+    let mut dataframe = match group.input_format {
+        InputFormat::Json => match (group.jq_filter.as_deref(), group.json_path.as_deref()) {
+            (Some(filter), _) => read_json_filtered(path, "jq_filter", |input| jq::records(filter, input))?,
+            (None, Some(query)) => read_json_filtered(path, "json_path", |input| jsonpath::records(query, input))?,
+            (None, None) => read_json(path)?,
+        },
+        InputFormat::Xml => read_xml(path, group.record_element.as_deref().unwrap_or(""))?,
+        InputFormat::Csv => read_csv(path, group, options.sample)?,
+    };
+
+    // Sem registros: tratado por `empty_output` após a leitura de todas as páginas
+    if dataframe.height() == 0 {
+        return Ok((DataFrame::empty(), None));
+    }
+
+    // Lido antes do unnest e da limpeza das colunas técnicas
+    let declared = match group.input_format {
+        InputFormat::Json => declared_total(&dataframe),
+        _ => None,
+    };
+
+    // Status de negócio: conferido no envelope e descartado da saída
+    if let Some(field) = group.status_field.as_deref() {
+        let message_field = group.status_message_field.as_deref();
+        check_status(
+            group,
+            frame_text(&dataframe, field),
+            message_field.and_then(|f| frame_text(&dataframe, f)),
+        )?;
+        for column in [Some(field), message_field].into_iter().flatten() {
+            if dataframe.column(column).is_ok() {
+                dataframe = dataframe.drop(column)?;
+            }
+        }
+    }
+
+    // Normalização Dinâmica (registros XML já chegam extraídos)
+    if group.input_format == InputFormat::Json
+        && let Some(path) = group.root_path.as_deref()
         && !path.is_empty()
         && dataframe.column(path).is_ok()
     {
         let dtype = dataframe.column(path)?.dtype();
 
         match dtype {
+            // `resultado: []`: o explode geraria uma linha nula em vez de nenhuma
+            DataType::List(_) if dataframe.column(path)?.list()?.lst_lengths().sum().unwrap_or(0) == 0 => {
+                return Ok((DataFrame::empty(), declared));
+            }
             DataType::List(_) => {
-                dataframe = dataframe.explode([path])?.unnest([path])?;
+                dataframe = unnest_records(dataframe.explode([path])?, path, group.on_duplicate_column)?;
             }
             DataType::Struct(_) => {
-                dataframe = dataframe.unnest([path])?;
+                dataframe = unnest_records(dataframe, path, group.on_duplicate_column)?;
             }
             _ => {
                 dataframe = dataframe.unnest([path]).unwrap_or(dataframe);
@@ -54,50 +509,1110 @@ pub fn process_json_to_parquet(
         }
     }
 
-    // Limpeza de Colunas Técnicas
-    let technical_cols = [
-        "totalRegistros",
-        "totalPaginas",
-        "paginasRestantes",
-        "links",
-        "dataHoraConsulta",
-        "timeZoneAtual",
-        "dataHoraAtualizacao",
-    ];
+    Ok((dataframe, declared))
+}
+
+/// Achata a coluna struct `path`, resolvendo nomes repetidos conforme `policy`.
+///
+/// Campos do registro podem repetir colunas do envelope (ex: um `id` em cada
+/// nível), o que o `unnest` do Polars rejeita. A ordem das ocorrências é a
+/// ordem final das colunas: envelope antes de `path`, campos, envelope depois.
+fn unnest_records(
+    dataframe: DataFrame,
+    path: &str,
+    policy: OnDuplicateColumn,
+) -> Result<DataFrame, ProcessorError> {
+    let DataType::Struct(fields) = dataframe.column(path)?.dtype() else {
+        return Ok(dataframe.unnest([path])?);
+    };
+    let position = dataframe.get_column_index(path).unwrap_or_default();
+    let names = dataframe.get_column_names_owned();
+    let envelope = |range: &[PlSmallStr]| range.iter().map(|n| (n.to_string(), false)).collect::<Vec<_>>();
+
+    // (nome, é campo do registro), na ordem final
+    let mut flat = envelope(&names[..position]);
+    flat.extend(fields.iter().map(|f| (f.name().to_string(), true)));
+    flat.extend(envelope(&names[position + 1..]));
+
+    let mut taken: HashSet<String> = flat.iter().map(|(name, _)| name.clone()).collect();
+    let mut seen: HashSet<&str> = HashSet::new();
+    // Destino de cada ocorrência; None descarta
+    let mut targets: Vec<Option<String>> = Vec::with_capacity(flat.len());
+    let mut duplicates = BTreeSet::new();
+    for (name, _) in &flat {
+        if seen.insert(name) {
+            targets.push(Some(name.clone()));
+            continue;
+        }
+        duplicates.insert(name.as_str());
+        targets.push(match policy {
+            OnDuplicateColumn::Error | OnDuplicateColumn::First => None,
+            OnDuplicateColumn::Suffix => {
+                let mut n = 1;
+                while taken.contains(&format!("{}_{}", name, n)) {
+                    n += 1;
+                }
+                let renamed = format!("{}_{}", name, n);
+                taken.insert(renamed.clone());
+                Some(renamed)
+            }
+        });
+    }
+
+    if duplicates.is_empty() {
+        return Ok(dataframe.unnest([path])?);
+    }
+    if policy == OnDuplicateColumn::Error {
+        let names: Vec<&str> = duplicates.into_iter().collect();
+        return Err(ProcessorError::Schema(format!(
+            "root_path '{}': coluna(s) repetida(s) ao achatar os registros: {} \
+             (use on_duplicate_column = \"suffix\" ou \"first\")",
+            path,
+            names.join(", ")
+        )));
+    }
+
+    // Descartes recebem nomes temporários únicos, removidos após o unnest
+    let mut dropped = Vec::new();
+    let mut final_names: Vec<String> = Vec::with_capacity(flat.len());
+    for (index, target) in targets.into_iter().enumerate() {
+        final_names.push(target.unwrap_or_else(|| {
+            let temp = format!("__duplicate_{}", index);
+            dropped.push(temp.clone());
+            temp
+        }));
+    }
+
+    let field_names: Vec<String> = flat
+        .iter()
+        .zip(&final_names)
+        .filter(|((_, is_field), _)| *is_field)
+        .map(|(_, target)| target.clone())
+        .collect();
+    let envelope_renames = flat
+        .iter()
+        .zip(&final_names)
+        .filter(|((name, is_field), target)| !is_field && name != *target);
+    let (old, new): (Vec<&str>, Vec<&str>) = envelope_renames
+        .map(|((name, _), target)| (name.as_str(), target.as_str()))
+        .unzip();
+
+    let mut dataframe = dataframe
+        .lazy()
+        .rename(old, new, true)
+        .with_column(col(path).struct_().rename_fields(field_names))
+        .collect()?
+        .unnest([path])?;
+    for temp in &dropped {
+        dataframe.drop_in_place(temp)?;
+    }
+    Ok(dataframe)
+}
+
+/// Une os registros de várias páginas em uma única tabela.
+///
+/// Páginas inferem schemas próprios: colunas ausentes em uma página viram
+/// nulas e tipos divergentes são promovidos ao supertipo comum
+/// (ex: `Null` + `String` → `String`, `Int64` + `Float64` → `Float64`).
+fn concat_frames(mut frames: Vec<DataFrame>) -> Result<DataFrame, ProcessorError> {
+    if frames.len() == 1 {
+        return Ok(frames.remove(0));
+    }
+
+    let lazy: Vec<LazyFrame> = frames.into_iter().map(DataFrame::lazy).collect();
+    let args = UnionArgs {
+        rechunk: true,
+        to_supertypes: true,
+        diagonal: true,
+        ..Default::default()
+    };
+    concat(lazy, args)?
+        .collect()
+        .map_err(|e| ProcessorError::Schema(format!("Páginas com schemas incompatíveis: {}", e)))
+}
+
+/// Remove os arquivos de staging após a conversão bem-sucedida.
+fn remove_inputs(inputs: &[PathBuf]) -> Result<(), ProcessorError> {
+    for path in inputs {
+        fs::remove_file(path).map_err(ProcessorError::Io)?;
+    }
+    Ok(())
+}
+
+/// Confronta o total declarado com o convertido, conforme `reconcile`.
+///
+/// # Returns
+/// None se `reconcile` não estiver configurado ou em modo amostra (truncado de propósito).
+///
+/// # Erros
+/// `ProcessorError::Schema` em divergência com `reconcile = "fail"`.
+pub(crate) fn reconcile(
+    group: &EndpointGroup,
+    options: ConvertOptions,
+    declared: Option<usize>,
+    actual: usize,
+) -> Result<Option<Reconciliation>, ProcessorError> {
+    let Some(mode) = group.reconcile else {
+        return Ok(None);
+    };
+    if options.sample.is_some() {
+        return Ok(None);
+    }
+
+    let reconciliation = Reconciliation { declared, actual };
+    if mode == ReconcileMode::Fail && !reconciliation.matches() {
+        return Err(ProcessorError::Schema(format!(
+            "{} declara {} registro(s), mas {} foram convertidos",
+            TOTAL_KEY,
+            declared.unwrap_or_default(),
+            actual
+        )));
+    }
+    Ok(Some(reconciliation))
+}
+
+/// Ordena as linhas conforme `sort_by`, tornando as estatísticas por row group
+/// do Parquet seletivas para filtros por faixa.
+///
+/// A ordenação exige o DataFrame inteiro e uma cópia reordenada: em saídas
+/// grandes, o pico de memória pode dobrar.
+fn sort_rows(dataframe: DataFrame, keys: &[SortKey]) -> Result<DataFrame, ProcessorError> {
+    if keys.is_empty() {
+        return Ok(dataframe);
+    }
+    if let Some(missing) = keys.iter().find(|key| dataframe.column(&key.column).is_err()) {
+        return Err(ProcessorError::Schema(format!(
+            "sort_by: coluna '{}' não existe na saída",
+            missing.column
+        )));
+    }
+
+    let columns: Vec<PlSmallStr> = keys.iter().map(|key| key.column.as_str().into()).collect();
+    let options = SortMultipleOptions::default()
+        .with_order_descending_multi(keys.iter().map(|key| key.descending))
+        .with_nulls_last_multi(keys.iter().map(|key| key.nulls == NullsOrder::Last))
+        .with_maintain_order(true);
+    Ok(dataframe.sort(columns, options)?)
+}
+
+/// Confronta o status de negócio do envelope com `expected_status`.
+///
+/// Sem `status_field`, nada é verificado; campo ausente ou nulo conta como falha.
+///
+/// # Erros
+/// `ProcessorError::UpstreamStatus` com o status e a mensagem recebidos.
+pub(crate) fn check_status(
+    group: &EndpointGroup,
+    status: Option<String>,
+    message: Option<String>,
+) -> Result<(), ProcessorError> {
+    let Some(expected) = &group.expected_status else {
+        return Ok(());
+    };
+    let expected = expected.to_string();
+    if status.as_deref() == Some(expected.as_str()) {
+        return Ok(());
+    }
+    Err(ProcessorError::UpstreamStatus {
+        status,
+        expected,
+        message,
+    })
+}
+
+/// Texto de um campo do envelope (strings sem aspas; nulo vira None).
+pub(crate) fn envelope_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Texto do campo `name` na primeira linha (o envelope antes do unnest).
+fn frame_text(dataframe: &DataFrame, name: &str) -> Option<String> {
+    match dataframe.column(name).ok()?.get(0).ok()? {
+        AnyValue::Null => None,
+        AnyValue::String(text) => Some(text.to_string()),
+        AnyValue::StringOwned(text) => Some(text.to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// `totalRegistros` da primeira linha (o envelope antes do unnest).
+fn declared_total(dataframe: &DataFrame) -> Option<usize> {
+    let total = dataframe.column(TOTAL_KEY).ok()?.cast(&DataType::Int64).ok()?;
+    let value = total.get(0).ok()?.extract::<i64>()?;
+    usize::try_from(value).ok()
+}
+
+/// Nomes das colunas que contêm ao menos um nulo.
+pub(crate) fn nullable_columns(dataframe: &DataFrame) -> BTreeSet<String> {
+    dataframe
+        .get_columns()
+        .iter()
+        .filter(|c| c.null_count() > 0)
+        .map(|c| c.name().to_string())
+        .collect()
+}
 
-    for col in technical_cols {
+/// Desvia para a conversão em lotes o staging JSON maior que `stream_above_mb`.
+///
+/// # Returns
+/// `Some(lote)` acima do limite; None sem limite ou abaixo dele.
+///
+/// # Erros
+/// `ProcessorError::Schema` se o grupo exigir a leitura completa (ver `batch_fallback`):
+/// carregar a resposta inteira é justamente o que o limite impede.
+fn oversized(
+    inputs: &[PathBuf],
+    group: &EndpointGroup,
+    options: ConvertOptions,
+) -> Result<Option<NonZeroUsize>, ProcessorError> {
+    // CSV e XML não passam pela leitura do envelope JSON
+    let Some(threshold) = options.stream_above.filter(|_| group.input_format == InputFormat::Json) else {
+        return Ok(None);
+    };
+    let input_bytes = staged_bytes(inputs)?;
+    if input_bytes <= threshold {
+        return Ok(None);
+    }
+
+    if !batch_fallback(group) || options.stdout.is_some() {
+        return Err(ProcessorError::Schema(format!(
+            "staging de {} MB acima de stream_above_mb = {}, mas o endpoint exige a leitura completa \
+             (sort_by, report_cardinality, field_ids, bloom_filter_columns, parquet v2, variantes de compressão, jq_filter, json_path, \
+             saída diferente de Parquet ou --output -)",
+            memory::to_mb(input_bytes),
+            memory::to_mb(threshold)
+        )));
+    }
+    log::info!(
+        "Staging de {} MB acima de stream_above_mb = {}; convertendo em lotes de {}",
+        memory::to_mb(input_bytes),
+        memory::to_mb(threshold),
+        FALLBACK_BATCH_SIZE
+    );
+    Ok(Some(FALLBACK_BATCH_SIZE))
+}
+
+/// Tamanho somado dos arquivos de staging.
+fn staged_bytes(inputs: &[PathBuf]) -> Result<u64, ProcessorError> {
+    let mut input_bytes = 0;
+    for path in inputs {
+        input_bytes += fs::metadata(path).map_err(ProcessorError::Io)?.len();
+    }
+    Ok(input_bytes)
+}
+
+/// Estima o custo da leitura completa antes de carregar o arquivo.
+///
+/// # Returns
+/// `Some(lote)` quando o endpoint deve migrar para a conversão em lotes.
+///
+/// # Erros
+/// Erro de recurso se a estimativa exceder o limite e não houver caminho em lotes.
+fn preflight(
+    inputs: &[PathBuf],
+    group: &EndpointGroup,
+    limit: MemoryLimit,
+) -> Result<Option<NonZeroUsize>, ProcessorError> {
+    let estimate = memory::estimated_load(staged_bytes(inputs)?);
+    if limit.allows(estimate) {
+        return Ok(None);
+    }
+
+    if batch_fallback(group) {
+        log::info!(
+            "Estimativa de memória ({} MB) acima do limite; convertendo em lotes de {}",
+            memory::to_mb(estimate),
+            FALLBACK_BATCH_SIZE
+        );
+        return Ok(Some(FALLBACK_BATCH_SIZE));
+    }
+    Err(limit.exceeded("pré-checagem", estimate))
+}
+
+/// Se o limite de memória pode converter o grupo em lotes em vez de falhar.
+///
+/// Em lotes, cada lote seria ordenado isoladamente (sort_by), a cardinalidade
+/// seria a de cada lote e o writer em lotes do Polars grava um único arquivo,
+/// só com páginas v1, sem field IDs nem filtros de Bloom: todos exigem a
+/// leitura completa, assim como o `jq_filter` e o `json_path`, que avaliam a
+/// resposta inteira.
+pub(crate) fn batch_fallback(group: &EndpointGroup) -> bool {
+    group.input_format == InputFormat::Json
+        && group.jq_filter.is_none()
+        && group.json_path.is_none()
+        && group.output_format == OutputFormat::Parquet
+        && group.parquet_version == ParquetVersion::V1
+        && group.field_ids.is_empty()
+        && group.bloom_filter_columns.is_empty()
+        && !group.report_cardinality
+        && group.compression_variants().is_empty()
+        && group.sort_by.is_empty()
+}
+
+/// Etapas de limpeza comuns a todos os caminhos de leitura.
+///
+/// Valores anulados pelas coerções de tipo são somados em `coercions`.
+pub(crate) fn normalize(
+    mut dataframe: DataFrame,
+    group: &EndpointGroup,
+    options: ConvertOptions,
+    coercions: &mut CoercionReport,
+) -> Result<DataFrame, ProcessorError> {
+    // Limpeza de Colunas Técnicas
+    for col in TECHNICAL_COLUMNS {
         if dataframe.column(col).is_ok() {
             dataframe = dataframe.drop(col)?;
         }
     }
 
     // Sanitização de Encodings
-    dataframe = byte_arrays(dataframe)?;
+    dataframe = byte_arrays(dataframe, group.byte_array_columns.as_deref(), coercions)?;
+
+    // NaN/Infinity quebram as estatísticas do Parquet e o SQL a jusante
+    dataframe = float_specials(dataframe, group.float_special, coercions)?;
+
+    // Espaços nas bordas atrapalham joins e deduplicação por chave
+    dataframe = trim_strings(dataframe, group)?;
+
+    // Marcadores textuais de ausência ("", "N/A") viram nulos reais
+    if let Some(null_values) = &group.null_values {
+        dataframe = replace_null_values(dataframe, null_values)?;
+    }
 
-    // Escrita Parquet (Escopo corrigido)
-    let file_out = File::create(parquet_path).map_err(ProcessorError::Io)?;
+    // Listas de escalares viram texto para destinos sem suporte a listas
+    if group.stringify_lists {
+        dataframe = stringify_lists(dataframe, group.list_separator())?;
+    }
 
-    let stats_options = StatisticsOptions {
+    // Rastreabilidade: liga cada linha à execução e à API/grupo que a produziram
+    let tags = [
+        (group.run_id_column.as_deref(), Some(options.run_id)),
+        (
+            group.add_api_column.then_some(API_COLUMN),
+            options.origin.map(|(api, _)| api),
+        ),
+        (
+            group.add_group_column.then_some(GROUP_COLUMN),
+            options.origin.map(|(_, group)| group),
+        ),
+    ];
+    for (name, value) in tags {
+        if let (Some(name), Some(value)) = (name, value) {
+            let tag = Column::new_scalar(
+                name.into(),
+                Scalar::new(DataType::String, AnyValue::StringOwned(value.into())),
+                dataframe.height(),
+            );
+            dataframe.with_column(tag)?;
+        }
+    }
+
+    // Colunas derivadas das normalizadas (`total = quantidade * preco`)
+    dataframe = computed::apply(dataframe, &group.computed_columns, coercions)?;
+
+    // Transformação definida pelo usuário, já sobre as colunas normalizadas
+    #[cfg(feature = "scripting")]
+    if let Some(script) = group.transform_script.as_deref() {
+        dataframe = crate::scripting::transform(dataframe, Path::new(script))?;
+    }
+
+    // Pseudonimização: nenhum valor original das colunas de PII chega à saída
+    dataframe = pseudonymize::hash_columns(dataframe, group)?;
+
+    // Proteção contra schemas explodidos por payloads aninhados malformados
+    if let Some(max) = group.max_columns
+        && dataframe.width() > max.get()
+    {
+        return Err(ProcessorError::Schema(format!(
+            "{} colunas após a normalização excedem max_columns = {}",
+            dataframe.width(),
+            max
+        )));
+    }
+
+    // Contrato leve: presença das colunas, independente do tipo
+    let missing: Vec<&str> = group
+        .required_columns
+        .iter()
+        .map(String::as_str)
+        .filter(|name| dataframe.column(name).is_err())
+        .collect();
+    if !missing.is_empty() {
+        return Err(ProcessorError::Schema(format!(
+            "{} coluna(s) de required_columns ausente(s): {}",
+            missing.len(),
+            missing.join(", ")
+        )));
+    }
+
+    columns_first(dataframe, &group.columns_first)
+}
+
+/// Move as colunas de `first` para o início, na ordem da lista; as demais
+/// mantêm a ordem de origem. Colunas ausentes são ignoradas (ver `absent_first_columns`).
+fn columns_first(dataframe: DataFrame, first: &[String]) -> Result<DataFrame, ProcessorError> {
+    if first.is_empty() {
+        return Ok(dataframe);
+    }
+    let names: Vec<PlSmallStr> = dataframe.get_column_names_owned();
+    let ordered: Vec<PlSmallStr> = first
+        .iter()
+        .filter_map(|name| names.iter().find(|n| n.as_str() == name.as_str()).cloned())
+        .chain(names.iter().filter(|n| !first.iter().any(|f| f.as_str() == n.as_str())).cloned())
+        .collect();
+    Ok(dataframe.select(ordered)?)
+}
+
+/// Avisa sobre as colunas de `columns_first` ausentes da saída normalizada.
+pub(crate) fn absent_first_columns(dataframe: &DataFrame, group: &EndpointGroup, options: ConvertOptions) {
+    let absent: Vec<&str> = group
+        .columns_first
+        .iter()
+        .map(String::as_str)
+        .filter(|name| dataframe.column(name).is_err())
+        .collect();
+    if !absent.is_empty() {
+        warnings::warn(
+            options.warnings,
+            format!("Aviso: columns_first ignorou coluna(s) ausente(s): {}", absent.join(", ")),
+        );
+    }
+}
+
+/// Aplica `float_special` aos NaN e ±Infinity das colunas de ponto flutuante do
+/// nível superior. Os valores anulados entram em `coercions`.
+///
+/// # Erros
+/// `ProcessorError::Schema` com `FloatSpecial::Error` e algum valor não finito.
+fn float_specials(
+    dataframe: DataFrame,
+    policy: FloatSpecial,
+    coercions: &mut CoercionReport,
+) -> Result<DataFrame, ProcessorError> {
+    if policy == FloatSpecial::Keep {
+        return Ok(dataframe);
+    }
+    let floats: Vec<(PlSmallStr, DataType)> = dataframe
+        .get_columns()
+        .iter()
+        .filter(|column| column.dtype().is_float())
+        .map(|column| (column.name().clone(), column.dtype().clone()))
+        .collect();
+    if floats.is_empty() {
+        return Ok(dataframe);
+    }
+
+    // Nulos ficam fora da contagem: `is_finite` os mantém nulos
+    let counts = dataframe
+        .clone()
+        .lazy()
+        .select(
+            floats
+                .iter()
+                .map(|(name, _)| col(name.clone()).is_finite().not().sum().alias(name.clone()))
+                .collect::<Vec<_>>(),
+        )
+        .collect()?;
+    let mut found = Vec::new();
+    for (name, _) in &floats {
+        let count = counts.column(name)?.cast(&DataType::UInt64)?.u64()?.get(0).unwrap_or(0) as usize;
+        if count > 0 {
+            found.push((name, count));
+        }
+    }
+    if found.is_empty() {
+        return Ok(dataframe);
+    }
+
+    if policy == FloatSpecial::Error {
+        let columns: Vec<String> = found.iter().map(|(name, count)| format!("{} ({})", name, count)).collect();
+        return Err(ProcessorError::Schema(format!(
+            "NaN/Infinity em coluna(s) numérica(s) com float_special = \"error\": {}",
+            columns.join(", ")
+        )));
+    }
+
+    for (name, count) in &found {
+        let column = dataframe.column(name)?;
+        let stats = coercions.entry(name.to_string()).or_default();
+        stats.attempted += column.len() - column.null_count();
+        stats.failed += count;
+    }
+    let exprs: Vec<Expr> = floats
+        .iter()
+        .filter(|(name, _)| found.iter().any(|(found, _)| *found == name))
+        .map(|(name, dtype)| {
+            when(col(name.clone()).is_finite())
+                .then(col(name.clone()))
+                .otherwise(lit(NULL).cast(dtype.clone()))
+                .alias(name.clone())
+        })
+        .collect();
+    Ok(dataframe.lazy().with_columns(exprs).collect()?)
+}
+
+/// Remove espaços nas bordas das colunas de `trim_columns` (ou de todas as
+/// colunas de texto, com `trim_all_strings`), antes de `null_values`: `" N/A "`
+/// também vira nulo.
+///
+/// # Erros
+/// `ProcessorError::Schema` se uma coluna de `trim_columns` não existir ou não for texto.
+fn trim_strings(dataframe: DataFrame, group: &EndpointGroup) -> Result<DataFrame, ProcessorError> {
+    let names: Vec<PlSmallStr> = if group.trim_all_strings {
+        dataframe
+            .get_columns()
+            .iter()
+            .filter(|column| column.dtype() == &DataType::String)
+            .map(|column| column.name().clone())
+            .collect()
+    } else {
+        for name in &group.trim_columns {
+            match dataframe.column(name).map(Column::dtype) {
+                Ok(DataType::String) => {}
+                Ok(dtype) => {
+                    return Err(ProcessorError::Schema(format!(
+                        "trim_columns: coluna '{}' ({}) não é texto",
+                        name, dtype
+                    )));
+                }
+                Err(_) => {
+                    return Err(ProcessorError::Schema(format!(
+                        "trim_columns: coluna '{}' não existe na saída",
+                        name
+                    )));
+                }
+            }
+        }
+        group.trim_columns.iter().map(|name| name.as_str().into()).collect()
+    };
+
+    if names.is_empty() {
+        return Ok(dataframe);
+    }
+    let exprs: Vec<Expr> = names
+        .iter()
+        .map(|name| {
+            let value = col(name.clone());
+            // Espaços internos repetidos (inclusive tabulações e quebras) viram um só
+            let value = if group.collapse_whitespace {
+                value.str().replace_all(lit(r"\s+"), lit(" "), false)
+            } else {
+                value
+            };
+            value.str().strip_chars(lit(NULL)).alias(name.clone())
+        })
+        .collect();
+    Ok(dataframe.lazy().with_columns(exprs).collect()?)
+}
+
+/// Substitui por nulo os textos de `null_values` nas colunas `String` do nível superior.
+fn replace_null_values(
+    dataframe: DataFrame,
+    null_values: &NullValues,
+) -> Result<DataFrame, ProcessorError> {
+    let exprs: Vec<Expr> = dataframe
+        .get_columns()
+        .iter()
+        .filter(|column| column.dtype() == &DataType::String)
+        .filter_map(|column| {
+            let name = column.name().as_str();
+            let matches = null_values
+                .for_column(name)?
+                .iter()
+                .map(|value| col(name).eq(lit(value.as_str())))
+                .reduce(Expr::or)?;
+            Some(
+                when(matches)
+                    .then(lit(NULL).cast(DataType::String))
+                    .otherwise(col(name))
+                    .alias(name),
+            )
+        })
+        .collect();
+
+    if exprs.is_empty() {
+        return Ok(dataframe);
+    }
+    Ok(dataframe.lazy().with_columns(exprs).collect()?)
+}
+
+/// Grava o DataFrame no formato de saída configurado no grupo.
+pub(crate) fn write_output(
+    dataframe: &mut DataFrame,
+    output_path: &Path,
+    group: &EndpointGroup,
+) -> Result<(), ProcessorError> {
+    // Tabelas Delta ganham uma versão nova em vez de serem substituídas
+    if group.output_format == OutputFormat::Delta {
+        return delta::append(output_path, dataframe, group).map(|_| ());
+    }
+
+    let bloom_columns = &group.bloom_filter_columns;
+    if !bloom_columns.is_empty() {
+        bloom::check(dataframe, bloom_columns)?;
+    }
+
+    // Gravado em `.tmp` e renomeado: leitores nunca veem um arquivo parcial
+    let tmp = atomic::temp_path(output_path);
+    atomic::write(output_path, |file_out| {
+        write_format(&mut *file_out, dataframe, group.output_format, group)?;
+        if !bloom_columns.is_empty() {
+            bloom::write(&tmp, dataframe, bloom_columns)?;
+        }
+        // Conferido ainda como `.tmp`: uma falha preserva a saída anterior
+        if group.verify_after_write {
+            verify_written(&tmp, group.output_format, dataframe.height())?;
+        }
+        Ok(())
+    })
+}
+
+/// Relê a saída em `path` por completo e confere o número de linhas (`verify_after_write`).
+///
+/// # Erros
+/// `ProcessorError::Parquet` se o arquivo não puder ser lido ou tiver outro número de linhas.
+pub(crate) fn verify_written(path: &Path, format: OutputFormat, expected: usize) -> Result<(), ProcessorError> {
+    let file = File::open(path).map_err(ProcessorError::Io)?;
+    let read = match format {
+        OutputFormat::Avro => AvroReader::new(file).finish(),
+        OutputFormat::Parquet | OutputFormat::Delta => ParquetReader::new(file).finish(),
+    };
+    let rows = read
+        .map_err(|e| ProcessorError::Parquet(format!("Verificação após a gravação: saída ilegível: {}", e)))?
+        .height();
+    if rows != expected {
+        return Err(ProcessorError::Parquet(format!(
+            "Verificação após a gravação: a saída tem {} linha(s), esperadas {}",
+            rows, expected
+        )));
+    }
+    Ok(())
+}
+
+/// Grava a saída em `output_path` ou, com uma lista em `compression`, uma
+/// variante por codec (`variant_path`): a conversão é a mesma, só a escrita
+/// se repete.
+///
+/// # Returns
+/// As variantes gravadas, na ordem da lista (vazio sem lista).
+fn write_variants(
+    dataframe: &mut DataFrame,
+    output_path: &Path,
+    group: &EndpointGroup,
+    options: ConvertOptions,
+) -> Result<Vec<(Compression, PathBuf)>, ProcessorError> {
+    let codecs = group.compression_variants();
+    if codecs.is_empty() {
+        options
+            .write_retry
+            .run(output_path, options.warnings, || write_output(dataframe, output_path, group))?;
+        return Ok(Vec::new());
+    }
+
+    let mut variants = Vec::with_capacity(codecs.len());
+    for &codec in codecs {
+        let path = variant_path(output_path, codec);
+        let variant = group.with_compression(codec);
+        options
+            .write_retry
+            .run(&path, options.warnings, || write_output(dataframe, &path, &variant))?;
+        variants.push((codec, path));
+    }
+    Ok(variants)
+}
+
+/// Caminho da variante de `codec` (`{chave}.{codec}.{formato}`).
+pub fn variant_path(output_path: &Path, codec: Compression) -> PathBuf {
+    let extension = output_path.extension().unwrap_or_default().to_string_lossy();
+    output_path.with_extension(format!("{}.{}", codec.name(), extension))
+}
+
+/// Escreve o DataFrame no stdout (`--output -`).
+fn write_stdout(
+    dataframe: &mut DataFrame,
+    format: StreamFormat,
+    group: &EndpointGroup,
+) -> Result<(), ProcessorError> {
+    write_stream(io::stdout().lock(), dataframe, format, group)
+}
+
+/// Serializa o DataFrame em `format` no destino de `--output -`.
+///
+/// Parquet e Avro usam as mesmas opções do grupo que a gravação em arquivo.
+fn write_stream<W: Write>(
+    out: W,
+    dataframe: &mut DataFrame,
+    format: StreamFormat,
+    group: &EndpointGroup,
+) -> Result<(), ProcessorError> {
+    // Checado antes de escrever: o cabeçalho não chega ao stdout de uma saída inválida
+    if format == StreamFormat::Csv
+        && let Some(column) = dataframe.get_columns().iter().find(|c| c.dtype().is_nested())
+    {
+        return Err(ProcessorError::Parquet(format!(
+            "CSV não suporta a coluna aninhada '{}' ({}); use stringify_lists ou --format ndjson",
+            column.name(),
+            column.dtype()
+        )));
+    }
+
+    let mut out = io::BufWriter::new(out);
+    match format {
+        StreamFormat::Ndjson => JsonWriter::new(&mut out)
+            .with_json_format(JsonFormat::JsonLines)
+            .finish(dataframe)
+            .map_err(|e| ProcessorError::Parquet(format!("Erro ao escrever NDJSON: {}", e)))?,
+        StreamFormat::Csv => CsvWriter::new(&mut out)
+            .finish(dataframe)
+            .map_err(|e| ProcessorError::Parquet(format!("Erro ao escrever CSV: {}", e)))?,
+        StreamFormat::Parquet => write_format(&mut out, dataframe, OutputFormat::Parquet, group)?,
+        StreamFormat::Avro => write_format(&mut out, dataframe, OutputFormat::Avro, group)?,
+    }
+    out.flush().map_err(ProcessorError::Io)
+}
+
+/// Serializa o DataFrame em `format` com o codec e a versão do grupo.
+///
+/// Os arquivos de dados de tabelas Delta são Parquet.
+pub(crate) fn write_format<W: Write>(
+    writer: W,
+    dataframe: &mut DataFrame,
+    format: OutputFormat,
+    group: &EndpointGroup,
+) -> Result<(), ProcessorError> {
+    let mut writer = IoTracker { inner: writer, error: None };
+    let (label, result) = match format {
+        OutputFormat::Parquet | OutputFormat::Delta
+            if group.parquet_version == ParquetVersion::V2 || !group.field_ids.is_empty() =>
+        {
+            ("Parquet", write_parquet_direct(&mut writer, dataframe, group))
+        }
+        OutputFormat::Parquet | OutputFormat::Delta => {
+            let written = parquet_writer(&mut writer, group)
+                .finish(dataframe) // Referência mutável para a variável 'dataframe'
+                .map(|_| ());
+            ("Parquet", written)
+        }
+        OutputFormat::Avro => {
+            // O writer Avro emite um cabeçalho por chunk: exige chunk único
+            dataframe.as_single_chunk_par();
+
+            let compression = match group.compression() {
+                Some(Compression::Snappy) => Some(AvroCompression::Snappy),
+                Some(Compression::Deflate) => Some(AvroCompression::Deflate),
+                _ => None,
+            };
+
+            let written = AvroWriter::new(&mut writer)
+                .with_compression(compression)
+                .with_name("registro".to_string())
+                .finish(dataframe);
+            ("Avro", written)
+        }
+    };
+
+    // Falhas de I/O seguem como `Io`, sujeitas a novas tentativas (`atomic::Retry`)
+    result.map_err(|e| match writer.error.take() {
+        Some(error) => ProcessorError::Io(error),
+        None => ProcessorError::Parquet(format!("Erro ao gravar {}: {}", label, e)),
+    })
+}
+
+/// Guarda o primeiro erro de I/O do destino: os writers do Polars o
+/// convertem em texto, perdendo o tipo.
+struct IoTracker<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> IoTracker<W> {
+    fn track<T>(&mut self, result: io::Result<T>) -> io::Result<T> {
+        // `Interrupted` é repetido pelo próprio `write_all`
+        if let Err(e) = &result
+            && e.kind() != io::ErrorKind::Interrupted
+            && self.error.is_none()
+        {
+            self.error = Some(io::Error::new(e.kind(), e.to_string()));
+        }
+        result
+    }
+}
+
+impl<W: Write> Write for IoTracker<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let result = self.inner.write(buf);
+        self.track(result)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.inner.flush();
+        self.track(result)
+    }
+}
+
+/// Writer Parquet com as opções padrão do pipeline.
+pub(crate) fn parquet_writer<W: Write>(writer: W, group: &EndpointGroup) -> ParquetWriter<W> {
+    ParquetWriter::new(writer)
+        .with_compression(parquet_compression(group))
+        .with_statistics(statistics_options())
+}
+
+/// Grava com páginas de dados v2 (`parquet_version = "v2"`) ou com os
+/// `field_ids` do grupo.
+///
+/// O `ParquetWriter` do Polars fixa páginas v1 e não expõe os field IDs; aqui
+/// o mesmo encoder é acionado diretamente pelo `polars-parquet`, com as opções
+/// do pipeline.
+fn write_parquet_direct<W: Write>(
+    writer: W,
+    dataframe: &mut DataFrame,
+    group: &EndpointGroup,
+) -> PolarsResult<()> {
+    dataframe.align_chunks_par();
+
+    let schema = dataframe.schema().to_arrow(CompatLevel::newest());
+    let mut column_options = get_column_write_options(&schema, &[]);
+    for (column, &id) in &group.field_ids {
+        let Some(index) = schema.index_of(column.as_str()) else {
+            polars_bail!(ColumnNotFound: "field_ids: coluna '{}' não existe na saída", column);
+        };
+        column_options[index].field_id = Some(id);
+    }
+    let options = WriteOptions {
+        statistics: statistics_options(),
+        compression: parquet_compression(group).into(),
+        version: match group.parquet_version {
+            ParquetVersion::V1 => Version::V1,
+            ParquetVersion::V2 => Version::V2,
+        },
+        data_page_size: None,
+    };
+
+    // Row groups do mesmo tamanho usado pelo `ParquetWriter`
+    let batches: Vec<_> = (0..dataframe.height())
+        .step_by(ROW_GROUP_ROWS)
+        .flat_map(|offset| {
+            dataframe
+                .slice(offset as i64, ROW_GROUP_ROWS)
+                .iter_chunks(CompatLevel::newest(), false)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let row_groups =
+        RowGroupIterator::try_new(batches.into_iter().map(Ok), &schema, options, column_options.clone())?;
+
+    let mut file = FileWriter::try_new(writer, schema, options, &column_options)?;
+    for group in row_groups {
+        file.write(group?)?;
+    }
+    file.end(None, &column_options)?;
+    Ok(())
+}
+
+/// Estatísticas gravadas por coluna (min/max/nulos), base do pruning das engines.
+fn statistics_options() -> StatisticsOptions {
+    StatisticsOptions {
         min_value: true,
         max_value: true,
         null_count: true,
         distinct_count: false,
+    }
+}
+
+/// Compressão ZSTD multithread no backend do Parquet. O polars-parquet comprime
+/// cada página com `zstd::bulk` (sem parâmetro de threads) e o zstd é compilado
+/// sem `zstdmt`: enquanto for `false`, `zstd_workers` é ignorado e as colunas
+/// seguem comprimidas em paralelo pelo próprio writer.
+pub(crate) const ZSTD_MULTITHREAD: bool = false;
+
+/// Avisa que `zstd_workers` não tem efeito sem suporte no backend.
+fn zstd_workers_fallback(group: &EndpointGroup, options: ConvertOptions) {
+    if let Some(workers) = group.zstd_workers
+        && !ZSTD_MULTITHREAD
+    {
+        warnings::warn(
+            options.warnings,
+            format!(
+                "Aviso: zstd_workers = {} ignorado: o writer Parquet não suporta ZSTD multithread; \
+                 usando a compressão padrão (uma thread por página)",
+                workers
+            ),
+        );
+    }
+}
+
+fn parquet_compression(group: &EndpointGroup) -> ParquetCompression {
+    match group.compression() {
+        Some(Compression::Uncompressed) => ParquetCompression::Uncompressed,
+        Some(Compression::Zstd) => ParquetCompression::Zstd(None),
+        Some(Compression::Gzip) => ParquetCompression::Gzip(None),
+        Some(Compression::Lz4) => ParquetCompression::Lz4Raw,
+        // Deflate é rejeitado na validação; Snappy é o padrão histórico
+        Some(Compression::Snappy | Compression::Deflate) | None => ParquetCompression::Snappy,
+    }
+}
+
+/// Leitura direta do JSON bruto pela engine Polars.
+fn read_json(json_path: &Path) -> Result<DataFrame, ProcessorError> {
+    // Abertura do arquivo original
+    let file = File::open(json_path).map_err(ProcessorError::Io)?;
+    let schema_len = NonZeroUsize::new(1000).unwrap();
+
+    JsonReader::new(file)
+        .infer_schema_len(Some(schema_len))
+        .finish()
+        .map_err(|e| ProcessorError::Parquet(format!("Falha no parsing JSON: {}", e)))
+}
+
+/// Aplica `jq_filter` ou `json_path` (`option`) à resposta e entrega os
+/// registros ao leitor JSON do Polars.
+fn read_json_filtered(
+    json_path: &Path,
+    option: &str,
+    select: impl FnOnce(&Value) -> Result<Vec<Value>, String>,
+) -> Result<DataFrame, ProcessorError> {
+    let file = File::open(json_path).map_err(ProcessorError::Io)?;
+    let input: Value = serde_json::from_reader(io::BufReader::new(file))?;
+    let records = select(&input).map_err(|e| ProcessorError::Schema(format!("{}: {}", option, e)))?;
+    drop(input);
+    if records.is_empty() {
+        return Ok(DataFrame::empty());
+    }
+
+    let bytes = serde_json::to_vec(&records)?;
+    let schema_len = NonZeroUsize::new(1000).unwrap();
+
+    JsonReader::new(Cursor::new(bytes))
+        .infer_schema_len(Some(schema_len))
+        .finish()
+        .map_err(|e| ProcessorError::Parquet(format!("Falha na leitura da saída do {}: {}", option, e)))
+}
+
+/// Extrai os registros XML e os entrega ao mesmo leitor JSON do Polars.
+fn read_xml(xml_path: &Path, record_element: &str) -> Result<DataFrame, ProcessorError> {
+    let records = xml::read_xml_records(xml_path, record_element)?;
+    if records.is_empty() {
+        return Ok(DataFrame::empty());
+    }
+
+    let bytes = serde_json::to_vec(&records)?;
+    let schema_len = NonZeroUsize::new(1000).unwrap();
+
+    JsonReader::new(Cursor::new(bytes))
+        .infer_schema_len(Some(schema_len))
+        .finish()
+        .map_err(|e| ProcessorError::Parquet(format!("Falha na conversão XML: {}", e)))
+}
+
+/// Tipo Polars de `csv.schema` e `empty_schema`.
+fn polars_dtype(dtype: CsvType) -> DataType {
+    match dtype {
+        CsvType::String => DataType::String,
+        CsvType::Int64 => DataType::Int64,
+        CsvType::Float64 => DataType::Float64,
+        CsvType::Boolean => DataType::Boolean,
+    }
+}
+
+/// Leitura do CSV pela engine Polars, com delimitador, cabeçalho e tipos configuráveis.
+///
+/// Colunas de `big_number_columns` são lidas como `String`. Com `n_rows`, a
+/// leitura para após as primeiras linhas.
+fn read_csv(
+    csv_path: &Path,
+    group: &EndpointGroup,
+    n_rows: Option<usize>,
+) -> Result<DataFrame, ProcessorError> {
+    let defaults = CsvOptions::default();
+    let options = group.csv.as_ref().unwrap_or(&defaults);
+
+    let overrides: Schema = options
+        .schema
+        .iter()
+        .map(|(name, dtype)| Field::new(name.as_str().into(), polars_dtype(*dtype)))
+        .chain(
+            group
+                .big_number_columns
+                .iter()
+                .map(|name| Field::new(name.as_str().into(), DataType::String)),
+        )
+        .collect();
+
+    CsvReadOptions::default()
+        .with_has_header(options.has_header.unwrap_or(true))
+        .with_infer_schema_length(Some(1000))
+        .with_n_rows(n_rows)
+        .with_schema_overwrite(Some(Arc::new(overrides)))
+        .with_parse_options(
+            CsvParseOptions::default()
+                .with_separator(options.delimiter())
+                .with_quote_char(Some(b'"')),
+        )
+        .try_into_reader_with_file_path(Some(csv_path.to_path_buf()))?
+        .finish()
+        .map_err(|e| ProcessorError::Parquet(format!("Falha no parsing CSV: {}", e)))
+}
+
+/// Converte as colunas List<Int64> (bytes UTF-8) para String legível,
+/// contabilizando as perdas. Com `columns`, só as listadas são convertidas e
+/// as demais listas numéricas são mantidas; sem, todas são convertidas.
+fn byte_arrays(
+    mut df_internal: DataFrame,
+    columns: Option<&[String]>,
+    coercions: &mut CoercionReport,
+) -> Result<DataFrame, ProcessorError> {
+    let col_names: Vec<String> = match columns {
+        Some(columns) => columns.to_vec(),
+        None => df_internal.get_column_names().iter().map(|name| name.to_string()).collect(),
     };
 
-    ParquetWriter::new(file_out)
-        .with_compression(ParquetCompression::Snappy)
-        .with_statistics(stats_options)
-        .finish(&mut dataframe) // Referência mutável para a variável 'dataframe'
-        .map_err(|e| ProcessorError::Parquet(format!("Erro ao gravar Parquet: {}", e)))?;
+    for name_str in &col_names {
+        let name_str = name_str.as_str();
+        let Ok(s) = df_internal.column(name_str) else {
+            continue;
+        };
 
-    // Finalização
-    fs::remove_file(json_path).map_err(ProcessorError::Io)?;
+        if let DataType::List(inner_type) = s.dtype()
+            && matches!(**inner_type, DataType::Int64 | DataType::Float64)
+        {
+            // Cada lista vira bytes; fora de 0-255 ou UTF-8 inválido vira nulo
+            let clean_series = s
+                .as_materialized_series()
+                .list()?
+                .into_iter()
+                .map(|bytes| bytes.and_then(|inner| decode_bytes(&inner)))
+                .collect::<StringChunked>()
+                .with_name(name_str.into())
+                .into_series();
 
-    Ok(())
+            let stats = coercions.entry(name_str.to_string()).or_default();
+            stats.attempted += s.len() - s.null_count();
+            stats.failed += clean_series.null_count().saturating_sub(s.null_count());
+
+            df_internal.replace(name_str, clean_series)?;
+        }
+    }
+    Ok(df_internal)
+}
+
+/// Interpreta uma lista numérica como bytes UTF-8.
+fn decode_bytes(inner: &Series) -> Option<String> {
+    let values = inner.cast(&DataType::Int64).ok()?;
+    let bytes = values
+        .i64()
+        .ok()?
+        .into_iter()
+        .map(|v| v.and_then(|b| u8::try_from(b).ok()))
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
 }
 
-/// Converte List<Int64> (ASCII) para String legível.
-fn byte_arrays(mut df_internal: DataFrame) -> Result<DataFrame, ProcessorError> {
+/// Une List<escalar> em uma String com `separator` (List<Struct> é preservada).
+fn stringify_lists(mut df_internal: DataFrame, separator: &str) -> Result<DataFrame, ProcessorError> {
     let col_names = df_internal.get_column_names_owned();
 
     for name_smart in col_names {
@@ -105,19 +1620,687 @@ fn byte_arrays(mut df_internal: DataFrame) -> Result<DataFrame, ProcessorError>
         let s = df_internal.column(name_str)?;
 
         if let DataType::List(inner_type) = s.dtype()
-            && matches!(**inner_type, DataType::Int64 | DataType::Float64)
+            && !inner_type.is_nested()
         {
-            let clean_series = s
-                .clone()
-                .cast(&DataType::List(Box::new(DataType::UInt8)))?
+            let joined = s
+                .cast(&DataType::List(Box::new(DataType::String)))?
                 .list()?
-                .apply_to_inner(&|inner: Series| -> PolarsResult<Series> {
-                    inner.cast(&DataType::Binary)
-                })?
-                .cast(&DataType::String)?;
+                .join_literal(separator, true)?
+                .into_series();
 
-            df_internal.replace(name_str, clean_series)?;
+            df_internal.replace(name_str, joined)?;
         }
     }
     Ok(df_internal)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_column(name: &str, rows: &[&[i64]]) -> Column {
+        let lists: Vec<Series> = rows.iter().map(|row| Series::new("".into(), *row)).collect();
+        Series::new(name.into(), lists).into_column()
+    }
+
+    #[test]
+    fn byte_arrays_decodes_only_declared_columns() {
+        let dataframe = DataFrame::new(vec![
+            list_column("anexo", &[&[65, 66], &[300], &[0xC3, 0xA9]]),
+            list_column("nums", &[&[1, 2, 300], &[65, 66], &[7]]),
+        ])
+        .unwrap();
+        let mut coercions = CoercionReport::new();
+
+        let declared = ["anexo".to_string(), "ausente".to_string()];
+        let out = byte_arrays(dataframe, Some(&declared), &mut coercions).unwrap();
+
+        let decoded: Vec<Option<&str>> = out.column("anexo").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(decoded, [Some("AB"), None, Some("é")]);
+        assert_eq!(out.column("nums").unwrap().dtype(), &DataType::List(Box::new(DataType::Int64)));
+        assert_eq!((coercions["anexo"].attempted, coercions["anexo"].failed), (3, 1));
+        assert!(!coercions.contains_key("nums"));
+    }
+
+    #[test]
+    fn byte_arrays_decodes_every_numeric_list_by_default() {
+        let dataframe = DataFrame::new(vec![
+            list_column("anexo", &[&[65, 66], &[0xC3, 0xA9]]),
+            list_column("nums", &[&[1, 2, 300], &[67]]),
+        ])
+        .unwrap();
+        let mut coercions = CoercionReport::new();
+
+        let out = byte_arrays(dataframe, None, &mut coercions).unwrap();
+
+        let text = |name: &str| out.column(name).unwrap().str().unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(text("anexo"), [Some("AB"), Some("é")]);
+        assert_eq!(text("nums"), [None, Some("C")]);
+        assert_eq!((coercions["anexo"].attempted, coercions["anexo"].failed), (2, 0));
+        assert_eq!((coercions["nums"].attempted, coercions["nums"].failed), (2, 1));
+    }
+
+    #[test]
+    fn stringify_lists_joins_text_and_numeric_lists() {
+        let tags: Vec<Series> = vec![Series::new("".into(), ["a", "b"]), Series::new("".into(), ["c"])];
+        let dataframe = DataFrame::new(vec![
+            Series::new("tags".into(), tags).into_column(),
+            list_column("nums", &[&[1, 2, 300], &[65, 66]]),
+        ])
+        .unwrap();
+        let mut coercions = CoercionReport::new();
+
+        // Mesma ordem de `normalize`: `byte_array_columns = []` não decodifica nada
+        let dataframe = byte_arrays(dataframe, Some(&[]), &mut coercions).unwrap();
+        let out = stringify_lists(dataframe, ",").unwrap();
+
+        let text = |name: &str| out.column(name).unwrap().str().unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(text("tags"), [Some("a,b"), Some("c")]);
+        assert_eq!(text("nums"), [Some("1,2,300"), Some("65,66")]);
+        assert!(coercions.is_empty());
+    }
+
+    fn group(toml: &str) -> EndpointGroup {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn options() -> ConvertOptions<'static> {
+        ConvertOptions {
+            limit: None,
+            run_id: "execucao-1",
+            sample: None,
+            stdout: None,
+            write_retry: atomic::Retry::default(),
+            stream_above: None,
+            warnings: None,
+            origin: None,
+        }
+    }
+
+    /// Diretório temporário exclusivo do teste.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("data_gov_processor_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Converte `json` com `group` e lê de volta o Parquet gravado.
+    fn convert(dir: &Path, json: &str, group: &EndpointGroup, options: ConvertOptions) -> (ConvertStats, DataFrame) {
+        let input = dir.join("pedidos.json");
+        let output = dir.join("pedidos.parquet");
+        fs::write(&input, json).unwrap();
+        let stats = process_json_to_parquet(&[input], &output, group, options).unwrap();
+        let dataframe = ParquetReader::new(File::open(&output).unwrap()).finish().unwrap();
+        (stats, dataframe)
+    }
+
+    #[test]
+    fn failed_date_parses_are_counted_in_coercions() {
+        let dataframe = df!(
+            "data" => [Some("2024-03-15"), Some("ontem"), Some("2024-13-40"), None, Some("2024-01-01T10:00:00"), Some("15/03/2024")]
+        )
+        .unwrap();
+        let group = group("[computed_columns]\nano = \"year(data)\"");
+        let mut coercions = CoercionReport::new();
+
+        let out = normalize(dataframe, &group, options(), &mut coercions).unwrap();
+
+        let years: Vec<_> = out.column("ano").unwrap().cast(&DataType::Int32).unwrap().i32().unwrap().into_iter().collect();
+        assert_eq!(years, [Some(2024), None, None, None, Some(2024), None]);
+        // Três datas inválidas: exatamente três falhas entre os cinco valores não nulos
+        assert_eq!((coercions["data"].attempted, coercions["data"].failed), (5, 3));
+    }
+
+    #[test]
+    fn zstd_workers_output_reads_back_identically() {
+        let dir = temp_dir("zstd_workers");
+        let json = r#"[{"id": 1, "nome": "Ana", "valor": 1.5}, {"id": 2, "nome": null, "valor": 2.25}]"#;
+
+        let (_, plain) = convert(&dir, json, &group(r#"compression = "zstd""#), options());
+        let (stats, threaded) = convert(&dir, json, &group("compression = \"zstd\"\nzstd_workers = 4"), options());
+
+        // Sem suporte no backend, a opção cai no writer padrão (ver `zstd_workers_fallback`)
+        const { assert!(!ZSTD_MULTITHREAD) };
+        assert_eq!(stats.rows, 2);
+        assert_eq!(threaded, plain);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn trim_strings_trims_padded_values() {
+        let dataframe = df!("nome" => [Some("  Ana  "), Some("\tJoão\n"), None], "id" => [1i64, 2, 3]).unwrap();
+
+        let out = trim_strings(dataframe, &group(r#"trim_columns = ["nome"]"#)).unwrap();
+
+        let names: Vec<_> = out.column("nome").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(names, [Some("Ana"), Some("João"), None]);
+    }
+
+    #[test]
+    fn trim_strings_collapses_internal_whitespace() {
+        let dataframe = df!("a" => [" São   Paulo\t SP "], "b" => ["  x  y "]).unwrap();
+
+        let out = trim_strings(dataframe, &group("trim_all_strings = true\ncollapse_whitespace = true")).unwrap();
+
+        assert_eq!(out.column("a").unwrap().str().unwrap().get(0), Some("São Paulo SP"));
+        assert_eq!(out.column("b").unwrap().str().unwrap().get(0), Some("x y"));
+    }
+
+    #[test]
+    fn trim_strings_rejects_missing_or_non_text_columns() {
+        let dataframe = df!("id" => [1i64]).unwrap();
+        for toml in [r#"trim_columns = ["id"]"#, r#"trim_columns = ["ausente"]"#] {
+            assert!(matches!(trim_strings(dataframe.clone(), &group(toml)), Err(ProcessorError::Schema(_))));
+        }
+    }
+
+    #[test]
+    fn xml_records_become_columns() {
+        let dir = temp_dir("xml");
+        let path = dir.join("itens.xml");
+        fs::write(&path, r#"<itens><item id="1"><nome>Ana</nome><uf>SP</uf></item><item id="2"><nome>Bia</nome></item></itens>"#).unwrap();
+
+        let dataframe = read_xml(&path, "item").unwrap();
+
+        assert_eq!(dataframe.get_column_names(), ["id", "nome", "uf"]);
+        assert_eq!(dataframe.height(), 2);
+        assert_eq!(dataframe.column("uf").unwrap().null_count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn avro_round_trip_keeps_nullable_columns() {
+        let mut dataframe = df!(
+            "id" => [Some(1i64), None, Some(3)],
+            "nome" => [Some("Ana"), Some("Bia"), None],
+            "valor" => [None, Some(2.5f64), Some(-1.0)],
+            "ativo" => [Some(true), None, Some(false)]
+        )
+        .unwrap();
+        let expected = dataframe.clone();
+
+        for toml in [r#"output_format = "avro""#, "output_format = \"avro\"\ncompression = \"snappy\""] {
+            let mut bytes = Vec::new();
+            write_format(&mut bytes, &mut dataframe, OutputFormat::Avro, &group(toml)).unwrap();
+
+            let read = AvroReader::new(Cursor::new(bytes)).finish().unwrap();
+            assert_eq!(read, expected);
+            for column in read.get_columns() {
+                assert_eq!(column.null_count(), 1, "coluna {}", column.name());
+            }
+        }
+    }
+
+    #[test]
+    fn memory_limit_below_the_estimate_aborts_the_conversion() {
+        let dir = temp_dir("memory_limit");
+        let input = dir.join("pedidos.json");
+        let output = dir.join("pedidos.parquet");
+        let records: Vec<String> = (0..20_000).map(|i| format!(r#"{{"id": {}, "nome": "pessoa {}"}}"#, i, i)).collect();
+        fs::write(&input, format!("[{}]", records.join(","))).unwrap();
+        // `sort_by` exige a leitura completa: sem conversão em lotes como saída
+        let group = group(r#"sort_by = [{ column = "id" }]"#);
+        let options = ConvertOptions {
+            limit: Some(MemoryLimit::from_mb(1)),
+            ..options()
+        };
+
+        let error = process_json_to_parquet(&[input], &output, &group, options).unwrap_err();
+
+        let ProcessorError::Io(error) = error else {
+            panic!("esperado erro de recurso, obtido {error:?}");
+        };
+        assert_eq!(error.kind(), io::ErrorKind::OutOfMemory);
+        assert!(error.to_string().contains("pré-checagem"), "{error}");
+        assert!(!output.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn max_columns_rejects_wider_outputs() {
+        let dataframe = df!("id" => [1i64], "nome" => ["Ana"], "uf" => ["SP"]).unwrap();
+
+        let error = normalize(dataframe.clone(), &group("max_columns = 2"), options(), &mut CoercionReport::new()).unwrap_err();
+        let ProcessorError::Schema(message) = error else {
+            panic!("esperado erro de schema, obtido {error:?}");
+        };
+        assert!(message.contains("3 colunas") && message.contains("max_columns = 2"), "{message}");
+
+        // No limite exato, a saída passa
+        assert!(normalize(dataframe, &group("max_columns = 3"), options(), &mut CoercionReport::new()).is_ok());
+    }
+
+    #[test]
+    fn csv_fixture_reads_with_schema_and_row_count() {
+        let dir = temp_dir("csv");
+        let path = dir.join("fornecedores.csv");
+        fs::write(
+            &path,
+            "cnpj;nome;valor;ativo\n00123;\"Ana; Filhos\";10.5;true\n04567;\"Bia\nLtda\";2;false\n08910;Caio;;true\n",
+        )
+        .unwrap();
+        let group = group("input_format = \"csv\"\n[csv]\ndelimiter = \";\"\nschema = { cnpj = \"string\" }");
+
+        let dataframe = read_csv(&path, &group, None).unwrap();
+
+        assert_eq!(dataframe.height(), 3);
+        let dtypes: Vec<(&str, &DataType)> =
+            dataframe.get_columns().iter().map(|c| (c.name().as_str(), c.dtype())).collect();
+        assert_eq!(dtypes, [
+            ("cnpj", &DataType::String),
+            ("nome", &DataType::String),
+            ("valor", &DataType::Float64),
+            ("ativo", &DataType::Boolean),
+        ]);
+        // Zeros à esquerda preservados; aspas protegem o separador e a quebra de linha
+        let text = |name: &str| dataframe.column(name).unwrap().str().unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(text("cnpj"), [Some("00123"), Some("04567"), Some("08910")]);
+        assert_eq!(text("nome"), [Some("Ana; Filhos"), Some("Bia\nLtda"), Some("Caio")]);
+        assert_eq!(dataframe.column("valor").unwrap().null_count(), 1);
+
+        assert_eq!(read_csv(&path, &group, Some(2)).unwrap().height(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reconcile_detects_a_total_mismatch() {
+        let dir = temp_dir("reconcile");
+        let json = r#"{"totalRegistros": 5, "resultado": [{"id": 1}, {"id": 2}, {"id": 3}]}"#;
+
+        let (stats, dataframe) = convert(&dir, json, &group("root_path = \"resultado\"\nreconcile = \"warn\""), options());
+        let reconciliation = stats.reconciliation.unwrap();
+        assert_eq!((reconciliation.declared, reconciliation.actual), (Some(5), 3));
+        assert!(!reconciliation.matches());
+        // O total do envelope não vira coluna da saída
+        assert_eq!(dataframe.get_column_names(), ["id"]);
+
+        fs::remove_file(dir.join("pedidos.parquet")).unwrap();
+        fs::write(dir.join("pedidos.json"), json).unwrap();
+        let error = process_json_to_parquet(
+            &[dir.join("pedidos.json")],
+            &dir.join("pedidos.parquet"),
+            &group("root_path = \"resultado\"\nreconcile = \"fail\""),
+            options(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("totalRegistros declara 5 registro(s), mas 3 foram convertidos"), "{error}");
+        assert!(!dir.join("pedidos.parquet").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sort_rows_orders_by_every_key() {
+        let dataframe = df!(
+            "uf" => [Some("SP"), Some("RJ"), None, Some("SP"), Some("RJ")],
+            "valor" => [Some(1i64), Some(5), Some(3), None, Some(2)],
+            "id" => [1i64, 2, 3, 4, 5]
+        )
+        .unwrap();
+        let sorted = group(
+            r#"sort_by = [{ column = "uf", nulls = "last" }, { column = "valor", descending = true }]"#,
+        );
+
+        let out = sort_rows(dataframe, &sorted.sort_by).unwrap();
+
+        // Nulos de `valor` vêm primeiro (padrão do Polars), mesmo em ordem decrescente
+        let ids: Vec<_> = out.column("id").unwrap().i64().unwrap().into_no_null_iter().collect();
+        assert_eq!(ids, [2, 5, 4, 1, 3]);
+
+        let missing = group(r#"sort_by = [{ column = "ausente" }]"#);
+        assert!(matches!(sort_rows(out, &missing.sort_by), Err(ProcessorError::Schema(_))));
+    }
+
+    #[test]
+    fn business_error_envelope_fails_the_conversion() {
+        let dir = temp_dir("check_status");
+        let json = r#"{"codigo": 500, "mensagem": "Token expirado", "resultado": [{"id": 1}]}"#;
+        let envelope = group("root_path = \"resultado\"\nstatus_field = \"codigo\"\nstatus_message_field = \"mensagem\"\nexpected_status = 200");
+        fs::write(dir.join("pedidos.json"), json).unwrap();
+
+        let error = process_json_to_parquet(&[dir.join("pedidos.json")], &dir.join("pedidos.parquet"), &envelope, options())
+            .unwrap_err();
+
+        let ProcessorError::UpstreamStatus { status, expected, message } = &error else {
+            panic!("esperado status de negócio, obtido {error:?}");
+        };
+        assert_eq!((status.as_deref(), expected.as_str(), message.as_deref()), (Some("500"), "200", Some("Token expirado")));
+        assert_eq!(error.to_string(), "API sinalizou falha no envelope: status '500' (esperado '200'): Token expirado");
+        assert!(!dir.join("pedidos.parquet").exists());
+
+        // Status esperado passa; sem `expected_status` nada é conferido; ausente conta como falha
+        assert!(check_status(&envelope, Some("200".to_string()), None).is_ok());
+        assert!(check_status(&group(""), None, None).is_ok());
+        assert!(check_status(&envelope, None, None).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn concat_frames_unions_three_pages_with_divergent_schemas() {
+        let pages = vec![
+            df!("id" => [1i64, 2], "valor" => [10i64, 20]).unwrap(),
+            df!("id" => [3i64], "valor" => [2.5f64], "uf" => ["SP"]).unwrap(),
+            df!("id" => [4i64], "uf" => [None::<&str>]).unwrap(),
+        ];
+
+        let out = concat_frames(pages).unwrap();
+
+        assert_eq!(out.height(), 4);
+        assert_eq!(out.get_column_names(), ["id", "valor", "uf"]);
+        let valor: Vec<_> = out.column("valor").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(valor, [Some(10.0), Some(20.0), Some(2.5), None]);
+        let uf: Vec<_> = out.column("uf").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(uf, [None, None, Some("SP"), None]);
+    }
+
+    #[test]
+    fn null_values_become_nulls() {
+        let dataframe = df!(
+            "nome" => [Some("Ana"), Some("N/A"), Some(""), None],
+            "uf" => [Some("N/A"), Some("SP"), Some(""), Some("RJ")],
+            "id" => [1i64, 2, 3, 4]
+        )
+        .unwrap();
+
+        let all = group(r#"null_values = ["N/A", ""]"#).null_values.unwrap();
+        let out = replace_null_values(dataframe.clone(), &all).unwrap();
+        assert_eq!(out.column("nome").unwrap().null_count(), 3);
+        assert_eq!(out.column("uf").unwrap().null_count(), 2);
+        let nome: Vec<_> = out.column("nome").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(nome, [Some("Ana"), None, None, None]);
+
+        // Por coluna, as demais ficam intocadas
+        let per_column = group(r#"null_values = { nome = ["N/A"] }"#).null_values.unwrap();
+        let out = replace_null_values(dataframe, &per_column).unwrap();
+        assert_eq!(out.column("nome").unwrap().null_count(), 2);
+        assert_eq!(out.column("uf").unwrap().null_count(), 0);
+    }
+
+    #[test]
+    fn direct_parquet_writes_round_trip_with_the_footer_version() {
+        use polars_parquet::parquet::read::read_metadata;
+
+        let expected = df!("id" => [Some(1i64), None, Some(3)], "nome" => [Some("Ana"), Some("Bia"), None]).unwrap();
+        // v1 passa pelo writer direto por causa dos field IDs
+        let cases = [("parquet_version = \"v1\"\nfield_ids = { id = 1 }", 1), ("parquet_version = \"v2\"", 2)];
+        for (toml, version) in cases {
+            let mut dataframe = expected.clone();
+            let mut bytes = Vec::new();
+            write_parquet_direct(&mut bytes, &mut dataframe, &group(toml)).unwrap();
+
+            let metadata = read_metadata(&mut Cursor::new(&bytes)).unwrap();
+            assert_eq!(metadata.version, version, "{toml}");
+            let read = ParquetReader::new(Cursor::new(bytes)).finish().unwrap();
+            assert_eq!(read, expected, "{toml}");
+        }
+    }
+
+    #[test]
+    fn stream_output_writes_ndjson_lines() {
+        let mut dataframe = df!("id" => [1i64, 2], "nome" => [Some("Ana"), None]).unwrap();
+        let mut sink = Vec::new();
+
+        write_stream(&mut sink, &mut dataframe, StreamFormat::Ndjson, &group("")).unwrap();
+
+        let lines: Vec<Value> = String::from_utf8(sink)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, [serde_json::json!({"id": 1, "nome": "Ana"}), serde_json::json!({"id": 2, "nome": null})]);
+
+        // CSV recusa colunas aninhadas antes de escrever qualquer byte
+        let tags: Vec<Series> = vec![Series::new("".into(), ["a"])];
+        let mut nested = DataFrame::new(vec![Series::new("tags".into(), tags).into_column()]).unwrap();
+        let mut sink = Vec::new();
+        assert!(write_stream(&mut sink, &mut nested, StreamFormat::Csv, &group("")).is_err());
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn unnest_collisions_follow_the_duplicate_column_policy() {
+        let dir = temp_dir("duplicate_column");
+        // O `id` do envelope vem antes de `resultado` e colide com o `id` de cada registro
+        let json = r#"{"id": 9, "resultado": [{"id": 1, "nome": "Ana"}, {"id": 2, "nome": "Bia"}]}"#;
+        let policy = |name: &str| group(&format!("root_path = \"resultado\"\non_duplicate_column = \"{}\"", name));
+        let ids = |dataframe: &DataFrame, name: &str| {
+            let column = dataframe.column(name).unwrap().cast(&DataType::Int64).unwrap();
+            column.i64().unwrap().into_iter().collect::<Vec<_>>()
+        };
+
+        fs::write(dir.join("pedidos.json"), json).unwrap();
+        let error = process_json_to_parquet(&[dir.join("pedidos.json")], &dir.join("pedidos.parquet"), &policy("error"), options())
+            .unwrap_err();
+        assert!(matches!(&error, ProcessorError::Schema(message) if message.contains(": id (")), "{error}");
+
+        let (_, suffixed) = convert(&dir, json, &policy("suffix"), options());
+        assert_eq!(suffixed.get_column_names(), ["id", "id_1", "nome"]);
+        assert_eq!(ids(&suffixed, "id"), [Some(9), Some(9)]);
+        assert_eq!(ids(&suffixed, "id_1"), [Some(1), Some(2)]);
+
+        let (_, first) = convert(&dir, json, &policy("first"), options());
+        assert_eq!(first.get_column_names(), ["id", "nome"]);
+        assert_eq!(ids(&first, "id"), [Some(9), Some(9)]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_required_columns_fail_the_endpoint() {
+        let dataframe = df!("id" => [1i64], "nome" => [None::<&str>]).unwrap();
+        let required = |columns: &str| group(&format!("required_columns = [{}]", columns));
+
+        let error = normalize(dataframe.clone(), &required(r#""id", "cnpj", "valor""#), options(), &mut CoercionReport::new())
+            .unwrap_err();
+        let ProcessorError::Schema(message) = error else {
+            panic!("esperado erro de schema, obtido {error:?}");
+        };
+        assert_eq!(message, "2 coluna(s) de required_columns ausente(s): cnpj, valor");
+
+        // Basta a presença: uma coluna só de nulos satisfaz o contrato
+        assert!(normalize(dataframe, &required(r#""id", "nome""#), options(), &mut CoercionReport::new()).is_ok());
+    }
+
+    #[test]
+    fn empty_response_writes_a_valid_parquet_with_the_declared_schema() {
+        let dir = temp_dir("empty_schema");
+        let declared = group(
+            r#"write_empty = true
+            empty_schema = [{ column = "id", type = "int64" }, { column = "nome", type = "string" }, { column = "ativo", type = "boolean" }]"#,
+        );
+
+        let (stats, dataframe) = convert(&dir, "[]", &declared, options());
+
+        assert_eq!((stats.rows, stats.cols), (0, 3));
+        assert_eq!(dataframe.height(), 0);
+        let columns: Vec<(&str, &DataType)> = dataframe.schema().iter().map(|(name, dtype)| (name.as_str(), dtype)).collect();
+        assert_eq!(columns, [("id", &DataType::Int64), ("nome", &DataType::String), ("ativo", &DataType::Boolean)]);
+
+        // Sem empty_schema nem saída anterior, nada é gravado e o motivo é informado
+        let output = dir.join("outra.parquet");
+        fs::write(dir.join("pedidos.json"), "[]").unwrap();
+        let stats = process_json_to_parquet(&[dir.join("pedidos.json")], &output, &group("write_empty = true"), options()).unwrap();
+        assert!(stats.skipped.is_some_and(|reason| reason.contains("sem empty_schema")));
+        assert!(!output.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn origin_columns_carry_the_api_and_group_names() {
+        let dir = temp_dir("origin_columns");
+        let tagged = group("add_api_column = true\nadd_group_column = true");
+        let options = ConvertOptions {
+            origin: Some(("compras", "itens")),
+            ..options()
+        };
+
+        let (_, dataframe) = convert(&dir, r#"[{"id": 1}, {"id": 2}]"#, &tagged, options);
+
+        assert_eq!(dataframe.get_column_names(), ["id", API_COLUMN, GROUP_COLUMN]);
+        let text = |name: &str| dataframe.column(name).unwrap().str().unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(text(API_COLUMN), [Some("compras"), Some("compras")]);
+        assert_eq!(text(GROUP_COLUMN), [Some("itens"), Some("itens")]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn field_ids_are_written_to_the_footer_schema() {
+        use polars_parquet::parquet::read::read_metadata;
+
+        let dir = temp_dir("field_ids");
+        let json = r#"[{"id": 1, "nome": "Ana", "uf": "SP"}]"#;
+
+        convert(&dir, json, &group("field_ids = { id = 1, nome = 7 }"), options());
+
+        let metadata = read_metadata(&mut File::open(dir.join("pedidos.parquet")).unwrap()).unwrap();
+        let ids: Vec<(&str, Option<i32>)> = metadata
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| (field.name(), field.get_field_info().id))
+            .collect();
+        // Colunas fora de `field_ids` seguem sem ID
+        assert_eq!(ids, [("id", Some(1)), ("nome", Some(7)), ("uf", None)]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn column_cardinality_counts_distinct_values_with_null() {
+        let tags: Vec<Series> = vec![Series::new("".into(), ["a"]), Series::new("".into(), ["b"]), Series::new("".into(), ["a"])];
+        let dataframe = DataFrame::new(vec![
+            Column::new("uf".into(), [Some("SP"), Some("RJ"), None]),
+            Column::new("ano".into(), [2024i64, 2024, 2024]),
+            Series::new("tags".into(), tags).into_column(),
+        ])
+        .unwrap();
+
+        // Desligado por padrão; o nulo conta como valor e listas ficam de fora
+        assert!(column_cardinality(&dataframe, &group("")).unwrap().is_empty());
+        let exact = column_cardinality(&dataframe, &group("report_cardinality = true")).unwrap();
+        assert_eq!(exact, BTreeMap::from([("ano".to_string(), 1), ("uf".to_string(), 3)]));
+        let approx = column_cardinality(&dataframe, &group("report_cardinality = true\napprox_cardinality = true")).unwrap();
+        assert_eq!(approx.keys().collect::<Vec<_>>(), ["ano", "uf"]);
+        assert_eq!(approx["ano"], 1);
+    }
+
+    #[test]
+    fn write_variants_two_codecs_read_back_identically() {
+        use polars_parquet::parquet::compression::Compression as Codec;
+        use polars_parquet::parquet::read::read_metadata;
+
+        let dir = temp_dir("variants");
+        let input = dir.join("pedidos.json");
+        fs::write(&input, r#"[{"id": 1, "nome": "Ana"}, {"id": 2, "nome": null}]"#).unwrap();
+        let variants = group(r#"compression = ["zstd", "snappy"]"#);
+
+        let stats = process_json_to_parquet(&[input], &dir.join("pedidos.parquet"), &variants, options()).unwrap();
+
+        let expected = [
+            (Compression::Zstd, dir.join("pedidos.zstd.parquet")),
+            (Compression::Snappy, dir.join("pedidos.snappy.parquet")),
+        ];
+        assert_eq!(stats.variants, expected);
+        assert!(!dir.join("pedidos.parquet").exists());
+        let read = |path: &Path| ParquetReader::new(File::open(path).unwrap()).finish().unwrap();
+        assert_eq!(read(&expected[0].1), read(&expected[1].1));
+        assert_eq!(read(&expected[0].1).height(), 2);
+        // Mesmo conteúdo, codecs diferentes no rodapé
+        let codec = |path: &Path| {
+            let metadata = read_metadata(&mut File::open(path).unwrap()).unwrap();
+            metadata.row_groups[0].parquet_columns()[0].compression()
+        };
+        assert_eq!((codec(&expected[0].1), codec(&expected[1].1)), (Codec::Zstd, Codec::Snappy));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn float_specials_turn_nan_and_infinity_into_nulls() {
+        let dataframe = df!(
+            "valor" => [Some(1.5f64), Some(f64::NAN), Some(f64::INFINITY), None, Some(f64::NEG_INFINITY)],
+            "id" => [1i64, 2, 3, 4, 5]
+        )
+        .unwrap();
+        let mut coercions = CoercionReport::new();
+
+        let out = float_specials(dataframe.clone(), FloatSpecial::Null, &mut coercions).unwrap();
+
+        let valor: Vec<_> = out.column("valor").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(valor, [Some(1.5), None, None, None, None]);
+        assert_eq!(out.column("id").unwrap(), dataframe.column("id").unwrap());
+        // Quatro valores não nulos, três deles não finitos
+        assert_eq!((coercions["valor"].attempted, coercions["valor"].failed), (4, 3));
+
+        let error = float_specials(dataframe.clone(), FloatSpecial::Error, &mut CoercionReport::new()).unwrap_err();
+        assert!(error.to_string().contains("valor (3)"), "{error}");
+        let kept = float_specials(dataframe, FloatSpecial::Keep, &mut CoercionReport::new()).unwrap();
+        assert!(kept.column("valor").unwrap().f64().unwrap().get(1).unwrap().is_nan());
+    }
+
+    #[test]
+    fn verify_written_detects_truncated_and_corrupt_outputs() {
+        let dir = temp_dir("verify_written");
+        let group = group("verify_after_write = true");
+        let (_, dataframe) = convert(&dir, r#"[{"id": 1}, {"id": 2}, {"id": 3}]"#, &group, options());
+        assert_eq!(dataframe.height(), 3);
+        let output = dir.join("pedidos.parquet");
+        let bytes = fs::read(&output).unwrap();
+
+        verify_written(&output, OutputFormat::Parquet, 3).unwrap();
+        let error = verify_written(&output, OutputFormat::Parquet, 4).unwrap_err().to_string();
+        assert!(error.contains("a saída tem 3 linha(s), esperadas 4"), "{error}");
+
+        // Cortado ao meio: sem rodapé
+        let truncated = dir.join("cortado.parquet");
+        fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+        let error = verify_written(&truncated, OutputFormat::Parquet, 3).unwrap_err().to_string();
+        assert!(error.contains("saída ilegível"), "{error}");
+
+        // Tamanho certo, mas o rodapé não fecha com o `PAR1` final
+        let mut damaged = bytes.clone();
+        let end = damaged.len();
+        damaged[end - 4..].copy_from_slice(b"XXXX");
+        let corrupt = dir.join("corrompido.parquet");
+        fs::write(&corrupt, &damaged).unwrap();
+        let error = verify_written(&corrupt, OutputFormat::Parquet, 3).unwrap_err().to_string();
+        assert!(error.contains("saída ilegível"), "{error}");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn staging_above_stream_above_is_converted_in_batches() {
+        crate::logging::capture();
+        let dir = temp_dir("stream_above");
+        let json = r#"[{"id": 1}, {"id": 2}, {"id": 3}]"#;
+        let low = ConvertOptions { stream_above: Some(16), ..options() };
+
+        let (stats, dataframe) = convert(&dir, json, &group(""), low);
+        assert_eq!((stats.rows, dataframe.height()), (3, 3));
+        let marker = "acima de stream_above_mb = 0; convertendo em lotes";
+        assert_eq!(crate::logging::captured(marker).len(), 1);
+
+        // Abaixo do limite, a leitura completa de sempre
+        let high = ConvertOptions { stream_above: Some(1 << 20), ..options() };
+        convert(&dir, json, &group(""), high);
+        assert_eq!(crate::logging::captured(marker).len(), 1);
+
+        // sort_by exige o DataFrame inteiro: acima do limite, falha em vez de carregar
+        let input = dir.join("pedidos.json");
+        fs::write(&input, json).unwrap();
+        let sorted = group(r#"sort_by = [{ column = "id" }]"#);
+        let error = process_json_to_parquet(&[input], &dir.join("ordenado.parquet"), &sorted, low).unwrap_err();
+        assert!(matches!(&error, ProcessorError::Schema(m) if m.contains("exige a leitura completa")), "{error}");
+        assert!(!dir.join("ordenado.parquet").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn columns_first_leads_in_list_order_and_keeps_the_rest() {
+        let dir = temp_dir("columns_first");
+        let group = group(r#"columns_first = ["nome", "id", "ausente"]"#);
+
+        let (_, dataframe) = convert(&dir, r#"[{"a": 1, "b": 2, "id": 3, "nome": "x", "c": 4}]"#, &group, options());
+
+        // Ausentes são ignoradas; as demais seguem a ordem de origem
+        assert_eq!(dataframe.get_column_names(), ["nome", "id", "a", "b", "c"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+//! # Perfil da Execução (Chrome Tracing)
+//!
+//! Com `--profile ARQUIVO`, cada endpoint registra spans de staging e de
+//! conversão, com as etapas (`connect`, `download`, `parse`, `transform`,
+//! `write`) aninhadas, e o arquivo é gravado no formato Trace Event do Chrome
+//! (`chrome://tracing`, Perfetto, speedscope).
+//!
+//! ## Custo
+//! Sem a flag, registrar um span é apenas a consulta a um `OnceLock` vazio:
+//! nada é alocado nem cronometrado além dos tempos que o pipeline já mede.
+//!
+//! ## Etapas
+//! As etapas vêm de `StageTimings` e são dispostas em sequência a partir do
+//! início do span do endpoint. Em lotes e na paginação elas se intercalam de
+//! fato: o span mostra a soma de cada etapa, não os intervalos reais.
+
+use serde::Serialize;
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+static PROFILE: OnceLock<Profile> = OnceLock::new();
+
+/// Spans acumulados desde o último `write`.
+struct Profile {
+    origin: Instant,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    events: Vec<Event>,
+    /// Threads numeradas na ordem do primeiro span (`tid` do trace).
+    threads: HashMap<ThreadId, u64>,
+}
+
+/// Evento completo (`ph = "X"`) do formato Trace Event; tempos em microssegundos.
+#[derive(Serialize)]
+struct Event {
+    name: &'static str,
+    cat: &'static str,
+    ph: &'static str,
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: u64,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    args: HashMap<&'static str, String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+    trace_events: &'a [Event],
+    display_time_unit: &'static str,
+}
+
+/// Liga o registro de spans (`--profile`).
+pub fn enable() {
+    let _ = PROFILE.set(Profile {
+        origin: Instant::now(),
+        state: Mutex::default(),
+    });
+}
+
+/// Verdadeiro com `--profile`.
+pub fn enabled() -> bool {
+    PROFILE.get().is_some()
+}
+
+/// Registra o span `name` iniciado em `start`, na thread atual, e as etapas
+/// `stages` em sequência dentro dele (etapas zeradas são omitidas).
+pub fn record(
+    name: &'static str,
+    endpoint: Option<&str>,
+    start: Instant,
+    duration: Duration,
+    stages: &[(&'static str, Duration)],
+) {
+    let Some(profile) = PROFILE.get() else {
+        return;
+    };
+    let mut state = profile.state.lock().unwrap_or_else(PoisonError::into_inner);
+    let next = state.threads.len() as u64 + 1;
+    let tid = *state.threads.entry(thread::current().id()).or_insert(next);
+    let args: HashMap<&'static str, String> = endpoint
+        .map(|endpoint| HashMap::from([("endpoint", endpoint.to_string())]))
+        .unwrap_or_default();
+
+    let category = if endpoint.is_some() { "endpoint" } else { "execução" };
+    let mut offset = start.saturating_duration_since(profile.origin);
+    state.events.push(event(name, category, offset, duration, tid, args.clone()));
+    for (stage, elapsed) in stages.iter().filter(|(_, elapsed)| !elapsed.is_zero()) {
+        state.events.push(event(stage, "etapa", offset, *elapsed, tid, args.clone()));
+        offset += *elapsed;
+    }
+}
+
+fn event(
+    name: &'static str,
+    cat: &'static str,
+    offset: Duration,
+    duration: Duration,
+    tid: u64,
+    args: HashMap<&'static str, String>,
+) -> Event {
+    Event {
+        name,
+        cat,
+        ph: "X",
+        ts: offset.as_secs_f64() * 1e6,
+        dur: duration.as_secs_f64() * 1e6,
+        pid: 1,
+        tid,
+        args,
+    }
+}
+
+/// Grava os spans acumulados em `path` e os descarta (no modo daemon, cada
+/// ciclo regrava o arquivo apenas com os seus spans). Sem `--profile`, não faz nada.
+pub fn write(path: &Path) -> io::Result<()> {
+    let Some(profile) = PROFILE.get() else {
+        return Ok(());
+    };
+    let events = std::mem::take(&mut profile.state.lock().unwrap_or_else(PoisonError::into_inner).events);
+    let trace = Trace {
+        trace_events: &events,
+        display_time_unit: "ms",
+    };
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, &trace)?;
+    writer.flush()
+}
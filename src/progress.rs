@@ -0,0 +1,365 @@
+//! # Progresso Global da Execução
+//!
+//! Linha de resumo fixa ("3/20 endpoints concluídos, 1 falhou, ~4m restantes"),
+//! distinta das barras de bytes de cada download.
+//!
+//! Uma barra agregada soma os bytes de todos os downloads (vazão combinada):
+//! cada leitura de rede alimenta a barra local e o total compartilhado, um
+//! contador atômico seguro para downloads simultâneos.
+//!
+//! ## Estilos
+//! Todos os estilos passam por [`style`]: um template inválido vira nota de
+//! depuração e um spinner simples, nunca um panic. Com `NO_COLOR` definido, as
+//! cores dos templates são removidas.
+//!
+//! ## Estimativa
+//! O tempo restante é a média por endpoint concluído multiplicada pelos
+//! endpoints pendentes. A lógica de cálculo é pura (sem relógio) para
+//! permitir verificação isolada.
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+use std::cell::Cell;
+use std::env;
+use std::io::{self, Read};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Estima o tempo restante a partir da média dos endpoints concluídos.
+///
+/// Retorna `None` enquanto nenhum endpoint terminou (sem base para a média).
+pub fn estimate_remaining(completed: usize, total: usize, elapsed: Duration) -> Option<Duration> {
+    if completed == 0 {
+        return None;
+    }
+    let pending = total.saturating_sub(completed) as u32;
+    Some(elapsed / completed as u32 * pending)
+}
+
+/// Formata durações de forma compacta (ex: "45s", "4m", "1h12m").
+pub fn format_eta(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+/// Monta a linha de resumo exibida no rodapé.
+pub fn summary_line(completed: usize, failed: usize, total: usize, eta: Option<Duration>) -> String {
+    let eta_text = match eta {
+        Some(d) if completed < total => format!(", ~{} restantes", format_eta(d)),
+        _ => String::new(),
+    };
+    format!(
+        "{}/{} endpoints concluídos, {} falhou{}",
+        completed, total, failed, eta_text
+    )
+}
+
+/// Caracteres da barra de download (preenchido, ponta, vazio).
+const PROGRESS_CHARS: &str = "#>-";
+
+/// Estilo a partir de `template`, sem cores quando `NO_COLOR` está definido.
+///
+/// Templates inválidos não derrubam a execução: caem para um spinner simples.
+pub fn style(template: &str) -> ProgressStyle {
+    let template = match env::var_os("NO_COLOR") {
+        Some(value) if !value.is_empty() => plain_template(template),
+        _ => template.to_string(),
+    };
+    match ProgressStyle::with_template(&template) {
+        Ok(style) => style,
+        Err(e) => {
+            log::debug!("Template de progresso inválido '{}': {}", template, e);
+            ProgressStyle::default_spinner()
+        }
+    }
+}
+
+/// Estilo de barra de bytes (`{bar}` com os caracteres do pipeline).
+pub fn bar_style(template: &str) -> ProgressStyle {
+    style(template).progress_chars(PROGRESS_CHARS)
+}
+
+/// Remove as cores dos campos do template (`{bar:40.cyan/blue}` → `{bar:40}`).
+fn plain_template(template: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let field = &rest[start + 1..start + len];
+        out.push_str(&rest[..start]);
+        out.push('{');
+        match field.split_once(':') {
+            Some((key, spec)) => {
+                let spec = spec.split_once('.').map_or(spec, |(layout, _)| layout);
+                out.push_str(key);
+                if !spec.is_empty() {
+                    out.push(':');
+                    out.push_str(spec);
+                }
+            }
+            None => out.push_str(field),
+        }
+        out.push('}');
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Total de bytes baixados na execução, compartilhado entre os downloads.
+#[derive(Clone)]
+pub struct ByteTotal {
+    bytes: Arc<AtomicU64>,
+    bar: ProgressBar,
+}
+
+impl ByteTotal {
+    fn new(bar: ProgressBar) -> Self {
+        Self {
+            bytes: Arc::new(AtomicU64::new(0)),
+            bar,
+        }
+    }
+
+    /// Soma `n` bytes; seguro entre threads sem lock.
+    pub fn add(&self, n: u64) {
+        self.bytes.fetch_add(n, Ordering::Relaxed);
+        self.bar.inc(n);
+    }
+
+    /// Bytes acumulados até o momento.
+    pub fn get(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+}
+
+/// Barra de um download que também alimenta o total agregado.
+///
+/// Derefera para a `ProgressBar` local (mensagens, estilo, tamanho).
+#[derive(Clone)]
+pub struct DownloadBar {
+    bar: ProgressBar,
+    total: ByteTotal,
+    /// Espera pelos cabeçalhos das respostas deste endpoint.
+    waited: Cell<Duration>,
+}
+
+impl DownloadBar {
+    /// Envolve um leitor: cada leitura avança a barra local e o total.
+    pub fn wrap_read<R: Read>(&self, inner: R) -> TrackedRead<R> {
+        TrackedRead {
+            inner,
+            bar: self.bar.clone(),
+            total: self.total.clone(),
+        }
+    }
+
+    /// Soma ao total bytes recebidos fora de um leitor (ex: páginas em memória).
+    pub fn add_bytes(&self, n: u64) {
+        self.total.add(n);
+    }
+
+    /// Soma o tempo até os cabeçalhos de uma resposta (DNS, conexão, TLS e servidor).
+    pub fn add_wait(&self, elapsed: Duration) {
+        self.waited.set(self.waited.get() + elapsed);
+    }
+
+    /// Espera total pelos cabeçalhos, somada entre as páginas.
+    pub fn waited(&self) -> Duration {
+        self.waited.get()
+    }
+}
+
+impl Deref for DownloadBar {
+    type Target = ProgressBar;
+
+    fn deref(&self) -> &ProgressBar {
+        &self.bar
+    }
+}
+
+/// Leitor que contabiliza os bytes lidos na barra local e no total agregado.
+pub struct TrackedRead<R> {
+    inner: R,
+    bar: ProgressBar,
+    total: ByteTotal,
+}
+
+impl<R: Read> Read for TrackedRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bar.inc(n as u64);
+        self.total.add(n as u64);
+        Ok(n)
+    }
+}
+
+/// Agrega as barras de download e o rodapé com o resumo global.
+///
+/// Compartilhado por referência entre os workers de download e conversão.
+pub struct RunProgress {
+    multi: MultiProgress,
+    footer: ProgressBar,
+    bytes: ByteTotal,
+    started: Instant,
+    total: usize,
+    completed: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl RunProgress {
+    /// Cria o rodapé; com `quiet`, nenhuma barra é desenhada.
+    pub fn new(total: usize, quiet: bool) -> Self {
+        let multi = if quiet {
+            MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+        } else {
+            MultiProgress::new()
+        };
+
+        let footer = multi.add(ProgressBar::new_spinner());
+        footer.set_style(style("{spinner:.yellow} {msg}"));
+        footer.enable_steady_tick(Duration::from_millis(250));
+
+        let aggregate = multi.insert_before(&footer, ProgressBar::new_spinner());
+        aggregate.set_style(style("{spinner:.cyan} Total baixado: {bytes} ({bytes_per_sec})"));
+
+        let progress = Self {
+            multi,
+            footer,
+            bytes: ByteTotal::new(aggregate),
+            started: Instant::now(),
+            total,
+            completed: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+        };
+        progress.refresh();
+        progress
+    }
+
+    /// Nova barra de download, inserida acima do rodapé e ligada ao total agregado.
+    pub fn download_bar(&self) -> DownloadBar {
+        DownloadBar {
+            bar: self.multi.insert_before(&self.footer, ProgressBar::new(0)),
+            total: self.bytes.clone(),
+            waited: Cell::new(Duration::ZERO),
+        }
+    }
+
+    /// Bytes baixados por todos os endpoints até o momento.
+    pub fn bytes_downloaded(&self) -> u64 {
+        self.bytes.get()
+    }
+
+    /// Registra o término de um endpoint e atualiza a estimativa.
+    pub fn record(&self, success: bool) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+        self.refresh();
+    }
+
+    /// Fixa a linha final do resumo.
+    pub fn finish(&self) {
+        self.bytes.bar.finish();
+        self.footer.finish_with_message(summary_line(
+            self.completed.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed),
+            self.total,
+            None,
+        ));
+    }
+
+    fn refresh(&self) {
+        let completed = self.completed.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+        let eta = estimate_remaining(completed, self.total, self.started.elapsed());
+        self.footer
+            .set_message(summary_line(completed, failed, self.total, eta));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn estimate_uses_the_mean_per_completed_endpoint() {
+        assert_eq!(estimate_remaining(0, 20, secs(90)), None);
+        // 3 endpoints em 90s: 30s cada, 17 pendentes
+        assert_eq!(estimate_remaining(3, 20, secs(90)), Some(secs(510)));
+        assert_eq!(estimate_remaining(20, 20, secs(600)), Some(Duration::ZERO));
+        // Mais concluídos que o total (re-contagem) não estoura
+        assert_eq!(estimate_remaining(5, 4, secs(10)), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn eta_is_compact() {
+        assert_eq!(format_eta(secs(45)), "45s");
+        assert_eq!(format_eta(secs(59)), "59s");
+        assert_eq!(format_eta(secs(60)), "1m");
+        assert_eq!(format_eta(secs(3599)), "59m");
+        assert_eq!(format_eta(secs(4320)), "1h12m");
+        assert_eq!(format_eta(secs(36_000)), "10h00m");
+    }
+
+    #[test]
+    fn summary_shows_eta_only_while_pending() {
+        assert_eq!(
+            summary_line(3, 1, 20, estimate_remaining(3, 20, secs(90))),
+            "3/20 endpoints concluídos, 1 falhou, ~8m restantes"
+        );
+        assert_eq!(summary_line(20, 0, 20, Some(secs(5))), "20/20 endpoints concluídos, 0 falhou");
+        assert_eq!(summary_line(0, 0, 20, None), "0/20 endpoints concluídos, 0 falhou");
+    }
+
+    #[test]
+    fn concurrent_downloads_add_up_in_the_total() {
+        let progress = RunProgress::new(8, true);
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    let bar = progress.download_bar();
+                    for _ in 0..1000 {
+                        bar.add_bytes(3);
+                    }
+                    // Leituras acompanhadas somam no mesmo total
+                    let mut reader = bar.wrap_read(&[0u8; 100][..]);
+                    std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+                });
+            }
+        });
+
+        assert_eq!(progress.bytes_downloaded(), 8 * (1000 * 3 + 100));
+    }
+
+    #[test]
+    fn invalid_templates_fall_back_to_a_usable_style() {
+        crate::logging::capture();
+        assert!(ProgressStyle::with_template("{msg:>x}").is_err());
+
+        let bar = ProgressBar::hidden().with_style(style("{msg:>x}"));
+        bar.set_message("Página 1");
+        bar.inc(5);
+        bar.finish_with_message("Concluído");
+
+        assert_eq!(bar.position(), 5);
+        assert!(bar.is_finished());
+        assert_eq!(crate::logging::captured("Template de progresso inválido '{msg:>x}'").len(), 1);
+        // Cores removidas com NO_COLOR, layout preservado
+        assert_eq!(plain_template("{spinner:.green} {bar:40.cyan/blue} {msg}"), "{spinner} {bar:40} {msg}");
+    }
+}
@@ -0,0 +1,106 @@
+//! # Pseudonimização de Colunas
+//!
+//! Colunas com dados pessoais (CPF, e-mail) listadas em `hash_columns` têm
+//! cada valor substituído pelo digest hexadecimal de `salt + valor` antes da
+//! gravação: a saída mantém a cardinalidade e permite joins entre tabelas
+//! pseudonimizadas com o mesmo salt, sem expor o valor original.
+//!
+//! ## Regras
+//! - Valores não textuais são convertidos para texto antes do hash (`123` → `"123"`).
+//! - Nulos continuam nulos.
+//! - O salt (`hash_salt`, com referências `${VAR}`) é resolvido a cada conversão
+//!   e nunca aparece em logs nem em mensagens de erro.
+
+use polars::prelude::*;
+use ring::digest;
+
+use crate::errors::ProcessorError;
+use crate::models::{EndpointGroup, HashAlgorithm};
+
+/// Substitui os valores das colunas de `hash_columns` pelos seus digests.
+///
+/// # Erros
+/// `ProcessorError::Schema` se uma coluna não existir na saída ou não puder
+/// ser convertida para texto (ex: listas), ou se o salt não puder ser resolvido.
+pub fn hash_columns(mut dataframe: DataFrame, group: &EndpointGroup) -> Result<DataFrame, ProcessorError> {
+    if group.hash_columns.is_empty() {
+        return Ok(dataframe);
+    }
+    let salt = group.hash_salt().map_err(ProcessorError::Schema)?;
+    let algorithm = ring_algorithm(group.hash_algorithm);
+
+    for name in &group.hash_columns {
+        let column = dataframe.column(name).map_err(|_| {
+            ProcessorError::Schema(format!("hash_columns: coluna '{}' não existe na saída", name))
+        })?;
+        let text = column.cast(&DataType::String).map_err(|_| {
+            ProcessorError::Schema(format!(
+                "hash_columns: coluna '{}' ({}) não pode ser convertida para texto",
+                name,
+                column.dtype()
+            ))
+        })?;
+        let hashed: StringChunked = text
+            .str()?
+            .into_iter()
+            .map(|value| value.map(|v| hex_digest(algorithm, &salt, v)))
+            .collect();
+        dataframe.with_column(hashed.with_name(name.as_str().into()))?;
+    }
+    Ok(dataframe)
+}
+
+/// Digest hexadecimal (minúsculo) de `salt + value`.
+fn hex_digest(algorithm: &'static digest::Algorithm, salt: &str, value: &str) -> String {
+    let mut context = digest::Context::new(algorithm);
+    context.update(salt.as_bytes());
+    context.update(value.as_bytes());
+    context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn ring_algorithm(algorithm: HashAlgorithm) -> &'static digest::Algorithm {
+    match algorithm {
+        HashAlgorithm::Sha256 => &digest::SHA256,
+        HashAlgorithm::Sha384 => &digest::SHA384,
+        HashAlgorithm::Sha512 => &digest::SHA512,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(toml: &str) -> EndpointGroup {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn hashes_match_an_independent_salted_sha() {
+        let dataframe = df!(
+            "cpf" => [Some("12345678900"), None],
+            "id" => [42i64, 7],
+            "uf" => ["SP", "RJ"]
+        )
+        .unwrap();
+
+        // Digests de referência calculados fora do Rust: sha256("s4l" + valor)
+        let out = hash_columns(dataframe.clone(), &group("hash_columns = [\"cpf\", \"id\"]\nhash_salt = \"s4l\"")).unwrap();
+
+        let text = |name: &str| out.column(name).unwrap().str().unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(text("cpf"), [Some("e457dde41353a86bad635097d490602439c8aa41ddaca66de47acc54c45b2c81"), None]);
+        assert_eq!(text("id")[0], Some("4e6188453173d1cdca3f529588aa41897d760844e150717c89daf12137a310dc"));
+        assert_eq!(text("uf"), [Some("SP"), Some("RJ")]);
+
+        let sha512 = group("hash_columns = [\"id\"]\nhash_salt = \"s4l\"\nhash_algorithm = \"sha512\"");
+        let out = hash_columns(dataframe, &sha512).unwrap();
+        assert_eq!(
+            out.column("id").unwrap().str().unwrap().get(0),
+            Some("f76be7b1fd2d22213024827a7ee932503b4a567552900b191323a680a44cb09a401f0f9287740d20628df69cddd08edef430ae404de79937f9d100186687e39e")
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! # Transformações via Script (Rhai)
+//!
+//! Válvula de escape para limpezas pontuais sem recompilar: o grupo aponta
+//! `transform_script` para um arquivo `.rhai` que define `fn transform(row)`.
+//! Disponível apenas com a feature `scripting`.
+//!
+//! ## Contrato
+//! - `row` é um mapa coluna → valor (structs viram mapas, listas viram arrays).
+//! - Retornar o mapa (modificado ou não) mantém a linha; retornar `()` a descarta.
+//! - Colunas novas são inferidas a partir dos valores retornados.
+//! - Se todas as linhas forem descartadas, a saída fica vazia com o schema de entrada.
+//!
+//! ## Custo
+//! As linhas passam por JSON entre o Polars e o Rhai: adequado a ajustes
+//! pontuais, não a transformações pesadas em volumes grandes.
+
+use polars::prelude::*;
+use rhai::{AST, Dynamic, Engine, Scope};
+use serde_json::Value;
+
+use std::io::Cursor;
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+use crate::errors::ProcessorError;
+
+/// Função que o script precisa definir.
+const ENTRY_POINT: &str = "transform";
+
+/// Compila o script e verifica a presença de `fn transform(row)`.
+///
+/// Usado na validação da configuração; erros de sintaxe trazem a linha.
+pub fn check(path: &Path) -> Result<(), String> {
+    compile(&Engine::new(), path).map(|_| ())
+}
+
+/// Aplica `transform` a cada linha do DataFrame.
+///
+/// # Erros
+/// `ProcessorError::Schema` com a linha do script em falhas de execução.
+pub fn transform(dataframe: DataFrame, path: &Path) -> Result<DataFrame, ProcessorError> {
+    let engine = Engine::new();
+    let ast = compile(&engine, path).map_err(ProcessorError::Schema)?;
+
+    let mut dataframe = dataframe;
+    let mut bytes = Vec::new();
+    JsonWriter::new(&mut bytes)
+        .with_json_format(JsonFormat::Json)
+        .finish(&mut dataframe)
+        .map_err(|e| ProcessorError::Parquet(format!("Falha ao preparar linhas do script: {}", e)))?;
+    let rows: Vec<Value> = serde_json::from_slice(&bytes)?;
+    drop(bytes);
+
+    let mut scope = Scope::new();
+    let mut output = Vec::with_capacity(rows.len());
+    for row in rows {
+        let row = rhai::serde::to_dynamic(&row).map_err(|e| script_error(path, &e))?;
+        let result: Dynamic = engine
+            .call_fn(&mut scope, &ast, ENTRY_POINT, (row,))
+            .map_err(|e| script_error(path, &e))?;
+
+        if result.is_unit() {
+            continue;
+        }
+        let value: Value = rhai::serde::from_dynamic(&result).map_err(|e| script_error(path, &e))?;
+        output.push(value);
+    }
+
+    if output.is_empty() {
+        return Ok(dataframe.clear());
+    }
+
+    let bytes = serde_json::to_vec(&output)?;
+    JsonReader::new(Cursor::new(bytes))
+        .infer_schema_len(NonZeroUsize::new(1000))
+        .finish()
+        .map_err(|e| ProcessorError::Parquet(format!("Falha ao ler o retorno do script: {}", e)))
+}
+
+fn compile(engine: &Engine, path: &Path) -> Result<AST, String> {
+    let ast = engine
+        .compile_file(path.to_path_buf())
+        .map_err(|e| format!("Script '{}': {}", path.display(), e))?;
+
+    let has_entry = ast
+        .iter_functions()
+        .any(|f| f.name == ENTRY_POINT && f.params.len() == 1);
+    if !has_entry {
+        return Err(format!(
+            "Script '{}' não define fn {}(row)",
+            path.display(),
+            ENTRY_POINT
+        ));
+    }
+    Ok(ast)
+}
+
+/// Erro do Rhai (já inclui `line N, position M`) com o caminho do script.
+fn script_error(path: &Path, error: &rhai::EvalAltResult) -> ProcessorError {
+    ProcessorError::Schema(format!("Script '{}': {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn script(name: &str, source: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("data_gov_scripting_{}_{}.rhai", std::process::id(), name));
+        fs::write(&path, source).unwrap();
+        path
+    }
+
+    #[test]
+    fn script_adds_a_computed_column_and_drops_rows() {
+        let path = script(
+            "total",
+            r#"
+            fn transform(row) {
+                if row.qtd == 0 { return (); }
+                row.total = row.qtd * row.preco;
+                row
+            }
+            "#,
+        );
+        let dataframe = df!("qtd" => [2i64, 0, 3], "preco" => [1.5f64, 9.0, 2.0]).unwrap();
+
+        let out = transform(dataframe, &path).unwrap();
+
+        assert_eq!(out.height(), 2);
+        let total: Vec<_> = out.column("total").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(total, [Some(3.0), Some(6.0)]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn scripts_without_transform_are_rejected() {
+        let path = script("sem_entrada", "fn outra(row) { row }");
+        let error = check(&path).unwrap_err();
+        assert!(error.contains("não define fn transform(row)"), "{error}");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,408 @@
+//! # Fontes de Dados
+//!
+//! Abstrai a origem do conteúdo bruto de um endpoint antes do staging:
+//! - **Http**: download remoto via `api::fetch_data_to_disk`, ou página a
+//!   página via `pagination` quando o grupo define `pagination`.
+//! - **File**: arquivo local (ex: arquivos históricos), copiado para o staging
+//!   para que a limpeza pós-conversão nunca apague o original.
+//!
+//! Arquivos locais são descobertos por glob (`source_glob`), e cada arquivo
+//! casado vira um endpoint nomeado por `name_template`.
+//!
+//! Conteúdo em gzip (`.json.gz`, local ou remoto) é descomprimido no staging,
+//! de forma transparente para os leitores.
+
+use flate2::bufread::MultiGzDecoder;
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Seek};
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::api::{self, Backoff, Credentials, HttpClient, Jitter, Payload, RequestBody};
+use crate::atomic;
+use crate::errors::{ApiError, ProcessorError};
+use crate::models::EndpointGroup;
+use crate::multipart;
+use crate::open_files;
+use crate::pagination;
+use crate::progress::DownloadBar;
+use crate::warnings::EndpointWarnings;
+
+/// Assinatura de arquivos gzip.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Extensão removida ao nomear arquivos locais comprimidos.
+const GZIP_EXTENSION: &str = "gz";
+
+/// Template padrão de nome: o nome do arquivo sem extensão.
+pub const DEFAULT_NAME_TEMPLATE: &str = "{stem}";
+
+/// Placeholders aceitos em `name_template`.
+const TEMPLATE_PLACEHOLDERS: [&str; 2] = ["{stem}", "{parent}"];
+
+/// Origem do conteúdo bruto de um endpoint.
+#[derive(Debug, Clone)]
+pub enum Source {
+    /// URL remota já resolvida, com as credenciais da API (se houver) e a
+    /// política de novas tentativas da API.
+    Http { url: String, auth: Credentials, backoff: Backoff },
+    /// Arquivo local casado por glob.
+    File(PathBuf),
+}
+
+impl Source {
+    /// URL (sem senha) ou caminho de origem, para logs e contexto de erros.
+    pub fn location(&self) -> String {
+        match self {
+            Source::Http { url, .. } => api::redact_url(url),
+            Source::File(path) => path.display().to_string(),
+        }
+    }
+
+    /// Materializa o conteúdo no caminho de staging (descomprimindo gzip).
+    ///
+    /// # Returns
+    /// Arquivos de staging: um por página em fontes paginadas, senão o próprio `destination`.
+    pub fn stage(
+        &self,
+        client: &HttpClient,
+        group: &EndpointGroup,
+        destination: &Path,
+        pb: &DownloadBar,
+        warnings: &EndpointWarnings,
+    ) -> Result<Vec<PathBuf>, ApiError> {
+        let bytes = match self {
+            Source::Http { url, auth, backoff } => match &group.pagination {
+                Some(p) => {
+                    return pagination::fetch_pages(
+                        client,
+                        url,
+                        auth,
+                        p,
+                        group,
+                        destination,
+                        pb,
+                        warnings,
+                    );
+                }
+                None => download(client, url, auth, backoff, group, destination, pb, warnings)?,
+            },
+            Source::File(path) => {
+                pb.finish_and_clear();
+                let bytes = fs::copy(path, destination).map_err(ApiError::FileSystemError)?;
+                if bytes == 0 {
+                    let _ = fs::remove_file(destination);
+                    return Err(ApiError::EmptyResponse);
+                }
+                bytes
+            }
+        };
+        decompress_if_gzip(destination, bytes, group.max_decompress_ratio)?;
+        Ok(vec![destination.to_path_buf()])
+    }
+}
+
+/// Download único, repetido conforme `backoff` após falhas transitórias
+/// (`api::is_retryable`) e, com `retry_on_empty`, respostas vazias.
+#[allow(clippy::too_many_arguments)]
+fn download(
+    client: &HttpClient,
+    url: &str,
+    auth: &Credentials,
+    backoff: &Backoff,
+    group: &EndpointGroup,
+    destination: &Path,
+    pb: &DownloadBar,
+    warnings: &EndpointWarnings,
+) -> Result<u64, ApiError> {
+    let mut jitter = Jitter::from_entropy();
+    let payload = match (&group.multipart, &group.body, &group.body_file) {
+        (Some(form), _, _) => Some(Payload::Form(form)),
+        (None, Some(content), Some(path)) => Some(Payload::Raw {
+            content,
+            content_type: multipart::content_type(Path::new(path)),
+        }),
+        _ => None,
+    };
+    let body = payload.map(|payload| RequestBody {
+        payload,
+        gzip: group.compress_request,
+    });
+    let mut attempt = 0;
+    loop {
+        match api::fetch_data_to_disk(
+            client,
+            url,
+            auth,
+            body,
+            &group.accept_status,
+            group.resume_downloads,
+            destination,
+            pb,
+        ) {
+            Err(e) if attempt < backoff.retries && retryable(&e, group) => {
+                attempt += 1;
+                let delay = backoff.delay(attempt, &e, &mut jitter);
+                warnings.warn(format!(
+                    "Aviso: '{}' falhou ({}); nova tentativa ({}/{}) em {:.2?}",
+                    api::redact_url(url),
+                    e,
+                    attempt,
+                    backoff.retries,
+                    delay
+                ));
+                thread::sleep(delay);
+                pb.reset();
+            }
+            result => return result,
+        }
+    }
+}
+
+fn retryable(error: &ApiError, group: &EndpointGroup) -> bool {
+    api::is_retryable(error) || (group.retry_on_empty && matches!(error, ApiError::EmptyResponse))
+}
+
+/// Descomprime o staging em streaming se ele for gzip (detectado pelos magic bytes).
+///
+/// Vale para downloads (ex: APIs que servem `.json.gz`) e arquivos locais.
+///
+/// Com `max_ratio`, a leitura para assim que a saída passa de `max_ratio` vezes
+/// o tamanho comprimido: um gzip bomb nunca chega a ocupar o disco.
+///
+/// # Returns
+/// Número de bytes do staging após a descompressão (inalterado se não for gzip).
+///
+/// # Erros
+/// `ApiError::DecompressionRatio` ao exceder a razão (staging e parcial são removidos).
+fn decompress_if_gzip(staging: &Path, bytes: u64, max_ratio: Option<NonZeroU64>) -> Result<u64, ApiError> {
+    let mut magic = [0u8; 2];
+    let mut file = File::open(staging).map_err(ApiError::FileSystemError)?;
+    if file.read_exact(&mut magic).is_err() || magic != GZIP_MAGIC {
+        return Ok(bytes);
+    }
+    file.rewind().map_err(ApiError::FileSystemError)?;
+
+    // Sem limite, a razão é infinita na prática; um byte além do limite basta para detectar o excesso
+    let ratio = max_ratio.map_or(u64::MAX, NonZeroU64::get);
+    let limit = bytes.saturating_mul(ratio);
+    let tmp = atomic::temp_path(staging);
+    let _permit = open_files::acquire();
+    let decompressed = File::create(&tmp).and_then(|mut out| {
+        let decoder = MultiGzDecoder::new(BufReader::new(file));
+        io::copy(&mut decoder.take(limit.saturating_add(1)), &mut out)
+    });
+
+    match decompressed {
+        Ok(written) if written > limit => {
+            let _ = fs::remove_file(&tmp);
+            let _ = fs::remove_file(staging);
+            Err(ApiError::DecompressionRatio { compressed: bytes, ratio })
+        }
+        Ok(0) => {
+            let _ = fs::remove_file(&tmp);
+            let _ = fs::remove_file(staging);
+            Err(ApiError::EmptyResponse)
+        }
+        Ok(written) => {
+            fs::rename(&tmp, staging).map_err(ApiError::FileSystemError)?;
+            Ok(written)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(ApiError::FileSystemError(e))
+        }
+    }
+}
+
+/// Valida o padrão glob e o template de nome no momento da carga.
+pub fn validate_glob(pattern: &str, template: &str) -> Result<(), String> {
+    glob::Pattern::new(pattern).map_err(|e| format!("Glob inválido '{}': {}", pattern, e))?;
+
+    let mut rest = template.to_string();
+    for placeholder in TEMPLATE_PLACEHOLDERS {
+        rest = rest.replace(placeholder, "");
+    }
+    if rest.contains('{') || rest.contains('}') {
+        return Err(format!(
+            "Placeholder inválido em name_template '{}' (aceitos: {})",
+            template,
+            TEMPLATE_PLACEHOLDERS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Expande o glob em pares `(nome, caminho)`, ordenados pelo caminho.
+///
+/// Suporta globs recursivos (`**`). Nomes repetidos após o template
+/// geram erro, pois sobrescreveriam a mesma saída.
+pub fn expand_glob(pattern: &str, template: &str) -> Result<Vec<(String, PathBuf)>, ProcessorError> {
+    let entries = glob::glob(pattern)
+        .map_err(|e| ProcessorError::Schema(format!("Glob inválido '{}': {}", pattern, e)))?;
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| ProcessorError::Io(e.into_error()))?;
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    // Ordem determinística independente do sistema de arquivos
+    paths.sort();
+
+    let mut named: Vec<(String, PathBuf)> = Vec::with_capacity(paths.len());
+    for path in paths {
+        let name = render_name(template, &path);
+        if let Some((_, previous)) = named.iter().find(|(n, _)| *n == name) {
+            return Err(ProcessorError::Schema(format!(
+                "Nome '{}' repetido para '{}' e '{}'; ajuste name_template",
+                name,
+                previous.display(),
+                path.display()
+            )));
+        }
+        named.push((name, path));
+    }
+    Ok(named)
+}
+
+fn render_name(template: &str, path: &Path) -> String {
+    // `raw.json.gz` nomeia como `raw`, igual ao arquivo descomprimido
+    let path = match path.extension() {
+        Some(ext) if ext == GZIP_EXTENSION => path.with_extension(""),
+        _ => path.to_path_buf(),
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let parent = path
+        .parent()
+        .and_then(Path::file_name)
+        .unwrap_or_default()
+        .to_string_lossy();
+
+    template
+        .replace("{stem}", &stem)
+        .replace("{parent}", &parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("data_gov_sources_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("2023")).unwrap();
+        fs::create_dir_all(dir.join("2024")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn expand_glob_names_every_matching_file() {
+        let dir = temp_dir("expand");
+        for file in ["2023/jan.json", "2024/fev.json", "2024/mar.json.gz", "2024/notas.txt"] {
+            fs::write(dir.join(file), "[]").unwrap();
+        }
+        let pattern = format!("{}/**/*.json*", dir.display());
+
+        let named = expand_glob(&pattern, DEFAULT_NAME_TEMPLATE).unwrap();
+
+        let names: Vec<&str> = named.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["jan", "fev", "mar"]);
+        assert_eq!(named[2].1, dir.join("2024/mar.json.gz"));
+
+        let named = expand_glob(&pattern, "{parent}_{stem}").unwrap();
+        let names: Vec<&str> = named.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["2023_jan", "2024_fev", "2024_mar"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn expand_glob_rejects_repeated_names() {
+        let dir = temp_dir("repeated");
+        fs::write(dir.join("2023/itens.json"), "[]").unwrap();
+        fs::write(dir.join("2024/itens.json"), "[]").unwrap();
+
+        let result = expand_glob(&format!("{}/*/itens.json", dir.display()), DEFAULT_NAME_TEMPLATE);
+
+        assert!(matches!(result, Err(ProcessorError::Schema(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn validate_glob_rejects_unknown_placeholders() {
+        assert!(validate_glob("in/*.json", "{parent}-{stem}").is_ok());
+        assert!(validate_glob("in/*.json", "{nome}").is_err());
+        assert!(validate_glob("in/[.json", DEFAULT_NAME_TEMPLATE).is_err());
+    }
+
+    #[test]
+    fn high_ratio_gzip_aborts_before_filling_the_disk() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let dir = temp_dir("ratio");
+        let staging = dir.join("itens_temp.json");
+        // 10 MB de zeros comprimem para poucos KB: razão bem acima de 100
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![0u8; 10 * 1024 * 1024]).unwrap();
+        let compressed = encoder.finish().unwrap();
+        fs::write(&staging, &compressed).unwrap();
+        let bytes = compressed.len() as u64;
+
+        let result = decompress_if_gzip(&staging, bytes, NonZeroU64::new(100));
+
+        assert!(matches!(
+            result,
+            Err(ApiError::DecompressionRatio { compressed, ratio: 100 }) if compressed == bytes
+        ));
+        assert!(!staging.exists());
+        assert!(!atomic::temp_path(&staging).exists());
+
+        // Sem limite, o mesmo arquivo descomprime por inteiro
+        fs::write(&staging, &compressed).unwrap();
+        assert_eq!(decompress_if_gzip(&staging, bytes, None).unwrap(), 10 * 1024 * 1024);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn retry_on_empty_recovers_when_the_second_response_has_records() {
+        use crate::api::test_server::{response, serve};
+
+        let dir = temp_dir("retry_on_empty");
+        let destination = dir.join("itens_temp.json");
+        let backoff = Backoff {
+            retries: 1,
+            strategy: crate::models::BackoffStrategy::Constant,
+            base_delay: std::time::Duration::ZERO,
+            max_delay: None,
+            jitter: 0.0,
+        };
+        let client = api::create_http_client(&toml::from_str("").unwrap()).unwrap();
+        let progress = crate::progress::RunProgress::new(1, true);
+        let warnings = crate::warnings::Warnings::default();
+        let scope = warnings.endpoint("compras.itens.pedidos".to_string(), crate::errors::Stage::Staging);
+        let stage = |group: &str| {
+            let (base, server) = serve(vec![response("200 OK", &[], b""), response("200 OK", &[], br#"[{"id": 1}]"#)]);
+            let source = Source::Http { url: base, auth: Credentials::default(), backoff };
+            let group: EndpointGroup = toml::from_str(group).unwrap();
+            let staged = source.stage(&client, &group, &destination, &progress.download_bar(), &scope);
+            (staged, server)
+        };
+
+        let (staged, server) = stage("retry_on_empty = true");
+        assert_eq!(staged.unwrap(), std::slice::from_ref(&destination));
+        assert_eq!(fs::read_to_string(&destination).unwrap(), r#"[{"id": 1}]"#);
+        assert_eq!(server.join().unwrap().len(), 2);
+        let retries = warnings.take();
+        assert_eq!(retries.len(), 1);
+        assert!(retries[0].message.contains("nova tentativa (1/1)"), "{}", retries[0].message);
+
+        // Sem a opção, a resposta vazia falha na primeira tentativa
+        let (staged, _server) = stage("");
+        assert!(matches!(staged, Err(ApiError::EmptyResponse)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+//! # Metadados de Build
+//!
+//! Identifica exatamente qual binário produziu um arquivo de saída:
+//! versão do crate, commit git, versões de Polars/reqwest e target.
+//! Os valores de git/target são capturados pelo `build.rs`.
+
+use serde::Serialize;
+
+use std::fmt;
+
+/// Informações de reprodutibilidade embutidas no binário.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub crate_version: &'static str,
+    pub git_sha: &'static str,
+    pub polars_version: &'static str,
+    pub reqwest_version: &'static str,
+    pub target: &'static str,
+}
+
+impl BuildInfo {
+    /// Metadados do binário em execução.
+    pub fn current() -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION"),
+            git_sha: env!("GIT_SHA"),
+            polars_version: polars::VERSION,
+            reqwest_version: env!("REQWEST_VERSION"),
+            target: env!("BUILD_TARGET"),
+        }
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} (git {}, polars {}, reqwest {}, {})",
+            env!("CARGO_PKG_NAME"),
+            self.crate_version,
+            self.git_sha,
+            self.polars_version,
+            self.reqwest_version,
+            self.target
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_string_names_the_crate_version() {
+        let info = BuildInfo::current();
+        let text = info.to_string();
+
+        assert!(!text.is_empty());
+        assert!(text.contains(env!("CARGO_PKG_VERSION")), "{text}");
+        assert!(text.starts_with(env!("CARGO_PKG_NAME")), "{text}");
+        for field in [info.git_sha, info.polars_version, info.reqwest_version, info.target] {
+            assert!(!field.is_empty());
+            assert!(text.contains(field), "{text}");
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! # Avisos da Execução
+//!
+//! Avisos (páginas ignoradas, novas tentativas, divergências, endpoints
+//! ignorados...) vão para o log e também são acumulados com o endpoint e a
+//! etapa de origem. Ao final, são resumidos no log e gravados no manifesto
+//! (`warnings`), onde o CI pode contá-los e alertar acima de um limite.
+//!
+//! ## Coleta
+//! `Warnings` é compartilhado pelos workers; cada etapa de um endpoint recebe
+//! um `EndpointWarnings`, que preenche endpoint e etapa de cada aviso.
+//! Totais derivados do manifesto (coerções, reconciliação) são apenas logados:
+//! os avisos de origem já estão na lista.
+
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::{Mutex, PoisonError};
+
+use crate::errors::Stage;
+
+/// Aviso registrado durante a execução.
+#[derive(Debug, Clone, Serialize)]
+pub struct Warning {
+    /// Identificador `api.grupo.chave`; None para avisos da execução (ex: warm-up).
+    pub endpoint: Option<String>,
+    /// Etapa de origem; None para avisos da execução.
+    pub stage: Option<Stage>,
+    pub message: String,
+}
+
+/// Coletor compartilhado da execução.
+#[derive(Debug, Default)]
+pub struct Warnings {
+    list: Mutex<Vec<Warning>>,
+}
+
+impl Warnings {
+    /// Loga e registra um aviso da execução como um todo.
+    pub fn warn(&self, message: String) {
+        self.push(None, None, message);
+    }
+
+    /// Coletor de uma etapa de um endpoint.
+    pub fn endpoint(&self, endpoint: String, stage: Stage) -> EndpointWarnings<'_> {
+        EndpointWarnings {
+            warnings: self,
+            endpoint,
+            stage,
+        }
+    }
+
+    /// Retira os avisos acumulados, na ordem de registro.
+    pub fn take(&self) -> Vec<Warning> {
+        mem::take(&mut *self.list.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn push(&self, endpoint: Option<String>, stage: Option<Stage>, message: String) {
+        log::warn!("{}", message);
+        self.list
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Warning {
+                endpoint,
+                stage,
+                message,
+            });
+    }
+}
+
+/// Avisos de uma etapa de um endpoint.
+#[derive(Debug)]
+pub struct EndpointWarnings<'w> {
+    warnings: &'w Warnings,
+    endpoint: String,
+    stage: Stage,
+}
+
+impl EndpointWarnings<'_> {
+    /// Loga e registra um aviso com o endpoint e a etapa.
+    pub fn warn(&self, message: String) {
+        self.warnings
+            .push(Some(self.endpoint.clone()), Some(self.stage), message);
+    }
+}
+
+/// Avisos pelo nome da etapa (`execução` para os da execução como um todo).
+pub fn count_by_stage(warnings: &[Warning]) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for warning in warnings {
+        let stage = warning.stage.map_or_else(|| "execução".to_string(), |s| s.to_string());
+        *counts.entry(stage).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Loga com `EndpointWarnings` quando disponível; sem ele, apenas no log.
+pub fn warn(warnings: Option<&EndpointWarnings>, message: String) {
+    match warnings {
+        Some(warnings) => warnings.warn(message),
+        None => log::warn!("{}", message),
+    }
+}
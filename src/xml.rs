@@ -0,0 +1,248 @@
+//! # Leitor de Fontes XML
+//!
+//! Converte documentos XML em registros JSON para reaproveitar o mesmo
+//! pipeline de transformação e escrita Parquet das fontes JSON.
+//!
+//! ## Mapeamento
+//! - Cada ocorrência do `record_element` (em qualquer profundidade) vira um registro.
+//! - Atributos e filhos viram chaves do objeto; filhos repetidos viram listas.
+//! - Elementos aninhados viram objetos (colunas Struct no Polars).
+//! - Elementos folha viram `String`, pois XML não possui tipagem nativa.
+
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::{BytesStart, Event};
+use serde_json::{Map, Value};
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::errors::ProcessorError;
+
+/// Nó em construção durante a leitura de um registro.
+struct Node {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+impl Node {
+    fn from_start(start: &BytesStart) -> Result<Self, ProcessorError> {
+        let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+        let mut fields = Map::new();
+
+        for attr in start.attributes() {
+            let attr = attr.map_err(|e| ProcessorError::Xml(e.to_string()))?;
+            let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            let value = attr
+                .unescape_value()
+                .map_err(|e| ProcessorError::Xml(e.to_string()))?;
+            insert_field(&mut fields, key, Value::String(value.into_owned()));
+        }
+
+        Ok(Self {
+            name,
+            fields,
+            text: String::new(),
+        })
+    }
+
+    /// Folhas viram texto; nós com filhos viram objetos (texto misto em `#text`).
+    fn into_value(self) -> Value {
+        let text = self.text.trim();
+
+        if self.fields.is_empty() {
+            return if text.is_empty() {
+                Value::Null
+            } else {
+                Value::String(text.to_string())
+            };
+        }
+
+        let mut fields = self.fields;
+        if !text.is_empty() {
+            fields.insert("#text".to_string(), Value::String(text.to_string()));
+        }
+        Value::Object(fields)
+    }
+}
+
+/// Insere um campo, promovendo para lista quando a chave se repete.
+fn insert_field(fields: &mut Map<String, Value>, key: String, value: Value) {
+    match fields.get_mut(&key) {
+        Some(Value::Array(items)) => items.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            fields.insert(key, value);
+        }
+    }
+}
+
+/// Extrai todos os registros `record_element` de um arquivo XML.
+///
+/// # Erros
+/// Retorna `ProcessorError::Xml` para documentos malformados ou
+/// `ProcessorError::Io` se o arquivo não puder ser aberto.
+pub fn read_xml_records(path: &Path, record_element: &str) -> Result<Vec<Value>, ProcessorError> {
+    let file = File::open(path).map_err(ProcessorError::Io)?;
+    let mut reader = Reader::from_reader(BufReader::new(file));
+
+    let mut records = Vec::new();
+    let mut stack: Vec<Node> = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| ProcessorError::Xml(format!("posição {}: {}", reader.error_position(), e)))?;
+
+        match event {
+            // Elementos fora de um registro são ignorados até achar o `record_element`
+            Event::Start(start)
+                if !stack.is_empty() || start.name().as_ref() == record_element.as_bytes() =>
+            {
+                stack.push(Node::from_start(&start)?);
+            }
+            Event::Empty(start)
+                if !stack.is_empty() || start.name().as_ref() == record_element.as_bytes() =>
+            {
+                let node = Node::from_start(&start)?;
+                close_node(node, &mut stack, &mut records);
+            }
+            Event::End(_) => {
+                if let Some(node) = stack.pop() {
+                    close_node(node, &mut stack, &mut records);
+                }
+            }
+            Event::Text(text) => {
+                if let Some(node) = stack.last_mut() {
+                    let decoded = text
+                        .xml_content()
+                        .map_err(|e| ProcessorError::Xml(e.to_string()))?;
+                    node.text.push_str(&decoded);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(node) = stack.last_mut() {
+                    let decoded = data
+                        .decode()
+                        .map_err(|e| ProcessorError::Xml(e.to_string()))?;
+                    node.text.push_str(&decoded);
+                }
+            }
+            Event::GeneralRef(reference) => {
+                if let Some(node) = stack.last_mut() {
+                    if let Some(ch) = reference
+                        .resolve_char_ref()
+                        .map_err(|e| ProcessorError::Xml(e.to_string()))?
+                    {
+                        node.text.push(ch);
+                    } else {
+                        let name = reference
+                            .decode()
+                            .map_err(|e| ProcessorError::Xml(e.to_string()))?;
+                        let resolved = resolve_predefined_entity(&name).ok_or_else(|| {
+                            ProcessorError::Xml(format!("Entidade desconhecida: &{};", name))
+                        })?;
+                        node.text.push_str(resolved);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        buf.clear();
+    }
+
+    if !stack.is_empty() {
+        return Err(ProcessorError::Xml(
+            "Documento terminou com elementos abertos".to_string(),
+        ));
+    }
+
+    Ok(records)
+}
+
+/// Fecha um nó: registros completos vão para a saída, filhos para o nó pai.
+fn close_node(node: Node, stack: &mut [Node], records: &mut Vec<Value>) {
+    let name = node.name.clone();
+    let value = node.into_value();
+
+    match (stack.last_mut(), value) {
+        (Some(parent), value) => insert_field(&mut parent.fields, name, value),
+        (None, record @ Value::Object(_)) => records.push(record),
+        // Registros folha viram uma coluna única com o nome do elemento
+        (None, leaf) => {
+            let mut fields = Map::new();
+            fields.insert(name, leaf);
+            records.push(Value::Object(fields));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FIXTURE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<resposta total="2">
+  <itens>
+    <item id="1">
+      <nome>Caneta &amp; Lápis</nome>
+      <fornecedor><cnpj>00.000.000/0001-91</cnpj><uf>SP</uf></fornecedor>
+      <tag>a</tag>
+      <tag>b</tag>
+    </item>
+    <item id="2">
+      <nome><![CDATA[Papel <A4>]]></nome>
+      <fornecedor><cnpj>11.111.111/0001-11</cnpj><uf>RJ</uf></fornecedor>
+    </item>
+  </itens>
+</resposta>"#;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("data_gov_xml_{}_{}.xml", std::process::id(), name));
+        std::fs::write(&path, FIXTURE).unwrap();
+        path
+    }
+
+    #[test]
+    fn records_map_attributes_children_and_repeats() {
+        let path = fixture("records");
+
+        let records = read_xml_records(&path, "item").unwrap();
+
+        assert_eq!(records, [
+            json!({
+                "id": "1",
+                "nome": "Caneta & Lápis",
+                "fornecedor": { "cnpj": "00.000.000/0001-91", "uf": "SP" },
+                "tag": ["a", "b"]
+            }),
+            json!({
+                "id": "2",
+                "nome": "Papel <A4>",
+                "fornecedor": { "cnpj": "11.111.111/0001-11", "uf": "RJ" }
+            }),
+        ]);
+        let columns: Vec<&String> = records[0].as_object().unwrap().keys().collect();
+        assert_eq!(columns, ["fornecedor", "id", "nome", "tag"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unknown_element_yields_no_records_and_malformed_documents_fail() {
+        let path = fixture("errors");
+        assert!(read_xml_records(&path, "contrato").unwrap().is_empty());
+
+        std::fs::write(&path, "<itens><item><nome>x</item></itens>").unwrap();
+        assert!(matches!(read_xml_records(&path, "item"), Err(ProcessorError::Xml(_))));
+        std::fs::remove_file(&path).unwrap();
+    }
+}